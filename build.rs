@@ -0,0 +1,50 @@
+// Generates the C header for the native library from src/ffi.rs and the error
+// codes in src/errors.rs when the c-header feature is on. It is written to
+// OUT_DIR, and copied to TSS_WASM_HEADER_DIR if that is set so a C build can
+// find it. The wasm build has no C ABI, so there is nothing to generate there.
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    #[cfg(feature = "c-header")]
+    c_header();
+}
+
+#[cfg(feature = "c-header")]
+fn c_header() {
+    use std::path::PathBuf;
+
+    println!("cargo:rerun-if-changed=src/ffi.rs");
+    println!("cargo:rerun-if-changed=src/errors.rs");
+    println!("cargo:rerun-if-env-changed=TSS_WASM_HEADER_DIR");
+    if std::env::var("CARGO_CFG_TARGET_ARCH").as_deref() == Ok("wasm32") {
+        return;
+    }
+
+    // both are always set for build scripts
+    let crate_dir = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default());
+    let out_dir = PathBuf::from(std::env::var("OUT_DIR").unwrap_or_default());
+    let bindings = match cbindgen::Builder::new()
+        .with_language(cbindgen::Language::C)
+        .with_include_guard("TSS_WASM_H")
+        .with_autogen_warning("/* Generated from src/ffi.rs by build.rs, do not edit. */")
+        .with_cpp_compat(true)
+        .with_src(crate_dir.join("src/ffi.rs"))
+        .with_src(crate_dir.join("src/errors.rs"))
+        .generate()
+    {
+        Ok(bindings) => bindings,
+        Err(e) => {
+            // a broken header must not break the library build
+            println!("cargo:warning=tss_wasm.h was not generated: {}", e);
+            return;
+        }
+    };
+    bindings.write_to_file(out_dir.join("tss_wasm.h"));
+    if let Ok(dir) = std::env::var("TSS_WASM_HEADER_DIR") {
+        let dir = crate_dir.join(dir);
+        if std::fs::create_dir_all(&dir).is_err() || !dir.is_dir() {
+            println!("cargo:warning=cannot create {}", dir.display());
+            return;
+        }
+        bindings.write_to_file(dir.join("tss_wasm.h"));
+    }
+}
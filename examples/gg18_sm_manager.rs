@@ -1,7 +1,7 @@
 #[cfg(not(target_arch = "wasm32"))]
 use reqwest;
 #[cfg(not(target_arch = "wasm32"))]
-use rocket::http::Status;
+use rocket::http::{ContentType, Status};
 #[cfg(not(target_arch = "wasm32"))]
 use rocket::request::{FromRequest, Outcome};
 #[cfg(not(target_arch = "wasm32"))]
@@ -9,7 +9,7 @@ use rocket::serde::json::Json;
 #[cfg(not(target_arch = "wasm32"))]
 use rocket::Request;
 #[cfg(not(target_arch = "wasm32"))]
-use rocket::{post, routes, State};
+use rocket::{get, post, routes, State};
 #[cfg(not(target_arch = "wasm32"))]
 use rocket_cors::{AllowedOrigins, CorsOptions};
 #[cfg(not(target_arch = "wasm32"))]
@@ -19,7 +19,11 @@ use std::collections::HashMap;
 #[cfg(not(target_arch = "wasm32"))]
 use std::fs;
 #[cfg(not(target_arch = "wasm32"))]
-use std::sync::RwLock;
+use std::fmt::Write;
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::{Mutex, RwLock};
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{Duration, Instant};
 #[cfg(not(target_arch = "wasm32"))]
 use tss_wasm::common::{Entry, Index, Key, Params, PartySignup, TaskRequest};
 #[cfg(not(target_arch = "wasm32"))]
@@ -92,6 +96,232 @@ async fn get_task(task_id: &str) -> Result<Task, reqwest::Error> {
     Ok(task)
 }
 
+/// ラウンド所要時間のヒストグラムのバケット (秒)
+#[cfg(not(target_arch = "wasm32"))]
+const LATENCY_BUCKETS: [f64; 8] = [0.1, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0];
+
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Default)]
+struct Histogram {
+    // LATENCY_BUCKETS の各バケット以下の件数 (累積ではない)
+    buckets: [u64; 8],
+    count: u64,
+    sum: f64,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Histogram {
+    fn observe(&mut self, seconds: f64) {
+        if let Some(i) = LATENCY_BUCKETS.iter().position(|le| seconds <= *le) {
+            self.buckets[i] += 1;
+        }
+        self.count += 1;
+        self.sum += seconds;
+    }
+}
+
+/// ラベルにするラウンド番号の上限 (round0〜round16, echo0〜echo16)
+#[cfg(not(target_arch = "wasm32"))]
+const MAX_ROUND_LABEL: u16 = 16;
+/// 最後のメッセージからこの時間が経ったセッションは終了したものとして集計を閉じる
+#[cfg(not(target_arch = "wasm32"))]
+const SESSION_IDLE: Duration = Duration::from_secs(120);
+/// 同時に追跡するセッション数の上限。超えたら最も古いセッションの集計を閉じる
+#[cfg(not(target_arch = "wasm32"))]
+const MAX_TRACKED_SESSIONS: usize = 1024;
+
+/// クライアントが送るラウンド名を固定の名前 ("identity", "round<N>", "echo<N>") に絞り、
+/// それ以外は "other" とする。ラベルの種類が有限になり、エスケープも要らない。
+#[cfg(not(target_arch = "wasm32"))]
+fn round_label(round: &str) -> String {
+    if round == "identity" {
+        return round.to_string();
+    }
+    for prefix in ["round", "echo"] {
+        if let Some(number) = round.strip_prefix(prefix) {
+            match number.parse::<u16>() {
+                Ok(n) if n <= MAX_ROUND_LABEL && n.to_string() == number => {
+                    return round.to_string()
+                }
+                _ => {}
+            }
+        }
+    }
+    "other".to_string()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn observe(round_latency: &mut HashMap<String, Histogram>, round: &str, elapsed: Duration) {
+    round_latency
+        .entry(round.to_string())
+        .or_default()
+        .observe(elapsed.as_secs_f64());
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+struct CurrentRound {
+    round: String,
+    // 最初と最後のメッセージが届いた時刻
+    started: Instant,
+    last: Instant,
+}
+
+/// /metrics で公開する運用メトリクス。メッセージ本体や識別鍵・トークンは保持しない。
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Default)]
+struct Metrics {
+    sessions: HashMap<&'static str, u64>,
+    signups: HashMap<&'static str, u64>,
+    messages_stored: u64,
+    message_bytes_stored: u64,
+    // 進行中のセッションUUIDごとの現在のラウンド
+    current_rounds: HashMap<String, CurrentRound>,
+    round_latency: HashMap<String, Histogram>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Metrics {
+    fn signup(&mut self, kind: &'static str, party_signup: &PartySignup) {
+        *self.signups.entry(kind).or_default() += 1;
+        if party_signup.number == 1 {
+            *self.sessions.entry(kind).or_default() += 1;
+        }
+    }
+
+    /// /set で新しく保存されたメッセージを数える。キーは "<from>-<round>-<uuid>" または
+    /// "<from>-<to>-<round>-<uuid>" で、ラウンドの最初のメッセージから次のラウンドの最初の
+    /// メッセージまでの時間をそのラウンドの所要時間とする。最後のラウンドは、セッションが
+    /// 終了した (SESSION_IDLE の間メッセージがない) ときに最後のメッセージまでの時間で数える。
+    fn stored(&mut self, key: &str, value: &str) {
+        self.messages_stored += 1;
+        self.message_bytes_stored += value.len() as u64;
+        let now = Instant::now();
+        self.finish_idle(now);
+
+        // UUIDはハイフンを含む36文字
+        if key.len() <= 37 || !key.is_char_boundary(key.len() - 37) {
+            return;
+        }
+        let (rest, uuid) = key.split_at(key.len() - 36);
+        let round = match rest.trim_end_matches('-').rsplit('-').next() {
+            Some(round) if !round.is_empty() => round_label(round),
+            _ => return,
+        };
+        let next = CurrentRound {
+            round,
+            started: now,
+            last: now,
+        };
+        match self.current_rounds.get_mut(uuid) {
+            Some(current) if current.round == next.round => current.last = now,
+            Some(current) => {
+                let elapsed = now.duration_since(current.started);
+                observe(&mut self.round_latency, &current.round, elapsed);
+                *current = next;
+            }
+            None => {
+                if self.current_rounds.len() >= MAX_TRACKED_SESSIONS {
+                    self.finish_oldest();
+                }
+                self.current_rounds.insert(uuid.to_string(), next);
+            }
+        }
+    }
+
+    /// SESSION_IDLE の間メッセージがないセッションを終了したものとし、最後のラウンドを数えて外す。
+    fn finish_idle(&mut self, now: Instant) {
+        let round_latency = &mut self.round_latency;
+        self.current_rounds.retain(|_, current| {
+            if now.duration_since(current.last) < SESSION_IDLE {
+                return true;
+            }
+            let elapsed = current.last.duration_since(current.started);
+            observe(round_latency, &current.round, elapsed);
+            false
+        });
+    }
+
+    fn finish_oldest(&mut self) {
+        let oldest = self
+            .current_rounds
+            .iter()
+            .min_by_key(|(_, current)| current.last)
+            .map(|(uuid, _)| uuid.clone());
+        if let Some(current) = oldest.and_then(|uuid| self.current_rounds.remove(&uuid)) {
+            let elapsed = current.last.duration_since(current.started);
+            observe(&mut self.round_latency, &current.round, elapsed);
+        }
+    }
+
+    /// Prometheusのテキスト形式で出力する。
+    fn render(&mut self, stored_messages: usize) -> String {
+        self.finish_idle(Instant::now());
+        let mut out = String::new();
+        let _ = writeln!(out, "# HELP tss_sessions_total Sessions started.");
+        let _ = writeln!(out, "# TYPE tss_sessions_total counter");
+        for kind in ["keygen", "sign"] {
+            let value = self.sessions.get(kind).copied().unwrap_or(0);
+            let _ = writeln!(out, "tss_sessions_total{{kind=\"{}\"}} {}", kind, value);
+        }
+        let _ = writeln!(out, "# HELP tss_signups_total Parties signed up.");
+        let _ = writeln!(out, "# TYPE tss_signups_total counter");
+        for kind in ["keygen", "sign"] {
+            let value = self.signups.get(kind).copied().unwrap_or(0);
+            let _ = writeln!(out, "tss_signups_total{{kind=\"{}\"}} {}", kind, value);
+        }
+        let _ = writeln!(out, "# HELP tss_messages_stored_total Messages stored by /set.");
+        let _ = writeln!(out, "# TYPE tss_messages_stored_total counter");
+        let _ = writeln!(out, "tss_messages_stored_total {}", self.messages_stored);
+        let _ = writeln!(
+            out,
+            "# HELP tss_message_bytes_stored_total Bytes of the messages stored by /set."
+        );
+        let _ = writeln!(out, "# TYPE tss_message_bytes_stored_total counter");
+        let _ = writeln!(
+            out,
+            "tss_message_bytes_stored_total {}",
+            self.message_bytes_stored
+        );
+        let _ = writeln!(out, "# HELP tss_stored_messages Entries held in memory.");
+        let _ = writeln!(out, "# TYPE tss_stored_messages gauge");
+        let _ = writeln!(out, "tss_stored_messages {}", stored_messages);
+        let _ = writeln!(
+            out,
+            "# HELP tss_round_latency_seconds Time from the first message of a round to the first of the next, or to its last for the final round."
+        );
+        let _ = writeln!(out, "# TYPE tss_round_latency_seconds histogram");
+        let mut rounds: Vec<_> = self.round_latency.iter().collect();
+        rounds.sort_by(|a, b| a.0.cmp(b.0));
+        for (round, histogram) in rounds {
+            let mut cumulative = 0;
+            for (le, count) in LATENCY_BUCKETS.iter().zip(histogram.buckets.iter()) {
+                cumulative += count;
+                let _ = writeln!(
+                    out,
+                    "tss_round_latency_seconds_bucket{{round=\"{}\",le=\"{}\"}} {}",
+                    round, le, cumulative
+                );
+            }
+            let _ = writeln!(
+                out,
+                "tss_round_latency_seconds_bucket{{round=\"{}\",le=\"+Inf\"}} {}",
+                round, histogram.count
+            );
+            let _ = writeln!(
+                out,
+                "tss_round_latency_seconds_sum{{round=\"{}\"}} {}",
+                round, histogram.sum
+            );
+            let _ = writeln!(
+                out,
+                "tss_round_latency_seconds_count{{round=\"{}\"}} {}",
+                round, histogram.count
+            );
+        }
+        out
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 #[post("/get", format = "json", data = "<request>")]
 fn get(
@@ -118,12 +348,22 @@ fn get(
 fn set(
     _auth: ApiKey, // Authorizationチェック済み
     db_mtx: &State<RwLock<HashMap<Key, String>>>,
+    metrics: &State<Mutex<Metrics>>,
     request: Json<Entry>,
 ) -> Json<Result<(), ()>> {
     let entry: Entry = request.0;
     let mut hm = db_mtx.write().unwrap();
-    hm.insert(entry.key.clone(), entry.value);
-    Json(Ok(()))
+    // 書き込みは一度きり: 再送された同じ値は成功として扱い、異なる値での上書きは拒否する
+    // (クライアントがチェックポイントからラウンドを再開しても状態が変わらないように)
+    match hm.get(&entry.key) {
+        Some(v) if *v != entry.value => Json(Err(())),
+        Some(_) => Json(Ok(())),
+        None => {
+            metrics.lock().unwrap().stored(&entry.key, &entry.value);
+            hm.insert(entry.key.clone(), entry.value);
+            Json(Ok(()))
+        }
+    }
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -131,6 +371,7 @@ fn set(
 async fn signup_keygen(
     _auth: ApiKey, // Authorizationチェック済み
     db_mtx: &State<RwLock<HashMap<Key, String>>>,
+    metrics: &State<Mutex<Metrics>>,
     request: Json<TaskRequest>,
 ) -> Json<Result<PartySignup, ()>> {
     // 1. POSTされたJSONから task_id を取得
@@ -140,6 +381,9 @@ async fn signup_keygen(
     if party_type != "wallet_side" && party_type != "server_side" {
         return Json(Err(()));
     }
+    if request.identity_key.is_empty() {
+        return Json(Err(()));
+    }
 
     // 2. 取得した task_id を用いて get_task を呼び出す
     let task = get_task(task_id).await.map_err(|_| ());
@@ -180,6 +424,12 @@ async fn signup_keygen(
     };
 
     hm.insert(key, serde_json::to_string(&party_signup).unwrap());
+    // 割り当てたパーティ番号に識別鍵を登録する (各クライアントはピン留めした鍵と照合する)
+    hm.insert(
+        format!("{}-identity-{}", party_signup.number, party_signup.uuid),
+        request.identity_key.clone(),
+    );
+    metrics.lock().unwrap().signup("keygen", &party_signup);
 
     if (party_type == "wallet_side") {
         let _child = tokio::process::Command::new("node")
@@ -198,6 +448,7 @@ async fn signup_keygen(
 async fn signup_sign(
     _auth: ApiKey, // Authorizationチェック済み
     db_mtx: &State<RwLock<HashMap<Key, String>>>,
+    metrics: &State<Mutex<Metrics>>,
     request: Json<TaskRequest>,
 ) -> Json<Result<PartySignup, ()>> {
     // 1. POSTされたJSONから task_id を取得
@@ -210,6 +461,9 @@ async fn signup_sign(
     if party_type != "wallet_side" && party_type != "server_side" {
         return Json(Err(()));
     }
+    if request.identity_key.is_empty() {
+        return Json(Err(()));
+    }
 
     if task.is_err() {
         return Json(Err(()));
@@ -249,6 +503,12 @@ async fn signup_sign(
     };
 
     hm.insert(key, serde_json::to_string(&party_signup).unwrap());
+    // 割り当てたパーティ番号に識別鍵を登録する (各クライアントはピン留めした鍵と照合する)
+    hm.insert(
+        format!("{}-identity-{}", party_signup.number, party_signup.uuid),
+        request.identity_key.clone(),
+    );
+    metrics.lock().unwrap().signup("sign", &party_signup);
 
     if (party_type == "wallet_side") {
         let _output = tokio::process::Command::new("node")
@@ -262,6 +522,21 @@ async fn signup_sign(
     Json(Ok(party_signup))
 }
 
+/// Prometheus向けのメトリクス。認証なしで公開するため、集計値以外は含めない。
+#[cfg(not(target_arch = "wasm32"))]
+#[get("/metrics")]
+fn metrics(
+    db_mtx: &State<RwLock<HashMap<Key, String>>>,
+    metrics: &State<Mutex<Metrics>>,
+) -> (ContentType, String) {
+    let stored_messages = db_mtx.read().unwrap().len();
+    let body = metrics.lock().unwrap().render(stored_messages);
+    (
+        ContentType::new("text", "plain").with_params(("version", "0.0.4")),
+        body,
+    )
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 #[tokio::main]
 async fn main() {
@@ -308,9 +583,10 @@ async fn main() {
 
     /////////////////////////////////////////////////////////////////
     rocket::build()
-        .mount("/", routes![get, set, signup_keygen, signup_sign])
+        .mount("/", routes![get, set, signup_keygen, signup_sign, metrics])
         .attach(cors.to_cors().unwrap())
         .manage(db_mtx)
+        .manage(Mutex::new(Metrics::default()))
         .launch()
         .await
         .unwrap();
@@ -7,6 +7,8 @@ const gg18 = require('../pkg');
 const GG18_KEYGEN_ADDR = "http://192.168.10.17:8000";
 const GG18_SIGN_ADDR = "http://192.168.10.17:8000";
 const MGT_SERVER_ADDR = "http://192.168.10.17:3000";
+// サーバー側パーティの長期識別鍵 (16進数)。公開鍵はウォレット側にピン留めしておくこと
+const IDENTITY_KEY = process.env.GG18_IDENTITY_KEY;
 
 // MGT_SERVER_ADDRとパス部分を結合するヘルパー関数
 function buildManagementServerUrl(path) {
@@ -14,9 +16,9 @@ function buildManagementServerUrl(path) {
 }
 
 
-async function keygen(addr, t, n, delay, token, taskId) {
+async function keygen(addr, t, n, delay, token, taskId, peerIdentityKeys, curve) {
     console.log(`Executing key generation:  ${addr}:${t}:${n}:${delay}:${token}:${taskId}`);
-    let context = await gg18.gg18_keygen_client_new_context(addr, t, n, delay, token, taskId, "server_side");
+    let context = await gg18.gg18_keygen_client_new_context(addr, t, n, delay, token, taskId, "server_side", IDENTITY_KEY, JSON.stringify(peerIdentityKeys), curve);
     console.log('keygen new context: ', context);
     context = await gg18.gg18_keygen_client_round1(context, delay, token);
     console.log('keygen round1: ', context);
@@ -32,9 +34,9 @@ async function keygen(addr, t, n, delay, token, taskId) {
 }
 
 
-async function sign(addr, t, n, message, key_store, delay, token, task_id) {
+async function sign(addr, t, n, message, key_store, delay, token, task_id, peerIdentityKeys, curve) {
     console.log(`creating signature for : ${message}`);
-    let context = await gg18.gg18_sign_client_new_context(addr, t, n, key_store, message, token, task_id, "server_side");
+    let context = await gg18.gg18_sign_client_new_context(addr, t, n, key_store, message, token, task_id, "server_side", IDENTITY_KEY, JSON.stringify(peerIdentityKeys), curve);
     console.log('sign new context: ', context);
     context = await gg18.gg18_sign_client_round0(context, delay, token);
     console.log('sign round0: ', context);
@@ -66,10 +68,10 @@ async function sign(addr, t, n, message, key_store, delay, token, task_id) {
  * 3. keygen の結果を管理サーバーへPUTで永続化（PUT先: /internal/generated_user_key/{user_id}）
  */
 async function processKeyGeneration(task, params, delay, token) {
-    if (!("t" in params) || !("n" in params)) {
-        throw new Error("Parameters for keygeneration must include 't' and 'n'");
+    if (!("t" in params) || !("n" in params) || !("identity_keys" in params)) {
+        throw new Error("Parameters for keygeneration must include 't', 'n', and 'identity_keys'");
     }
-    const result = await keygen(GG18_KEYGEN_ADDR, params.t, params.n, delay, token, task.id);
+    const result = await keygen(GG18_KEYGEN_ADDR, params.t, params.n, delay, token, task.id, params.identity_keys, params.curve || "");
     console.log("Key generation result:", result);
 
     // PUTで結果を永続化
@@ -91,8 +93,8 @@ async function processKeyGeneration(task, params, delay, token) {
  * 3. sign の実行
  */
 async function processSigning(task, params, delay, token) {
-    if (!("t" in params) || !("n" in params) || !("message" in params)) {
-        throw new Error("Parameters for signing must include 't', 'n', and 'message'");
+    if (!("t" in params) || !("n" in params) || !("message" in params) || !("identity_keys" in params)) {
+        throw new Error("Parameters for signing must include 't', 'n', 'message', and 'identity_keys'");
     }
     const keyUrl = buildManagementServerUrl(`/internal/generated_user_key/${task.created_by}`);
     let keyResponse;
@@ -113,7 +115,7 @@ async function processSigning(task, params, delay, token) {
     if (!("key_data" in keyData)) {
         throw new Error("Generated user key JSON does not contain 'key_data'");
     }
-    const result = await sign(GG18_SIGN_ADDR, params.t, params.n, params.message, keyData.key_data, delay, token, task.id);
+    const result = await sign(GG18_SIGN_ADDR, params.t, params.n, params.message, keyData.key_data, delay, token, task.id, params.identity_keys, params.curve || "");
     console.log("Signing result:", result);
 }
 
@@ -177,6 +179,10 @@ async function main() {
     }
     const taskId = args[0];
     const token = args[1];
+    if (!IDENTITY_KEY) {
+        console.error("GG18_IDENTITY_KEY is not set");
+        process.exit(1);
+    }
     const task = await getTask(taskId);
 
     if (!task) {
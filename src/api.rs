@@ -1,516 +1,571 @@
-#![cfg(any(target_arch = "wasm32", target_arch = "aarch64"))]
 #![allow(non_snake_case)]
 use crate::common::{
-    aes_decrypt, aes_encrypt, broadcast, check_sig, poll_for_broadcasts, poll_for_p2p, postb,
-    public_key_address, sendp2p, PartySignup, AEAD, AES_KEY_BYTES_LEN, TaskRequest
+    broadcast, poll_for_broadcasts, poll_for_p2p, postb_once, sendp2p, ManagerClient,
+    PartySignup, TaskRequest, TokenProvider,
 };
+use crate::curv::arithmetic::num_bigint::BigInt;
+use crate::curv::arithmetic::traits::Converter;
 use crate::curv::elliptic::curves::traits::{ECPoint, ECScalar};
-use crate::curv::{
-    arithmetic::num_bigint::BigInt,
-    arithmetic::traits::Converter,
-    cryptographic_primitives::{
-        proofs::sigma_correct_homomorphic_elgamal_enc::HomoELGamalProof,
-        proofs::sigma_dlog::DLogProof, secret_sharing::feldman_vss::VerifiableSS,
-    },
-    elliptic::curves::secp256_k1::{Secp256k1Point as Point, Secp256k1Scalar as Scalar},
-};
-use crate::errors::Result;
-use crate::gg_2018::mta::*;
+use crate::curv::elliptic::curves::{secp256_k1, secp256_r1};
+use crate::eddsa;
+use crate::errors::{Result, TssError};
 use crate::gg_2018::party_i::*;
-use crate::paillier::EncryptionKey;
-use reqwest::header::{HeaderMap, HeaderValue};
-use reqwest::Client;
+use crate::gg_2018::messages::{EchoMessage, KeygenMessage, Outgoing, RoundMessage, SignMessage};
+use crate::gg_2018::session::{KeygenSession, SignSession};
+use crate::identity::{gg18_identity_public_key, PartyIdentities, PayloadKind};
+use crate::keystore::{EddsaKeyStore, KeyStore};
+use crate::logging::{RoundTrace, Stopwatch};
+use crate::paillier::PrimeKind;
+use crate::sealed_context::{
+    open_context, open_context_at, peek_context, seal_context, RoundContext,
+};
+use num_traits::Num;
 use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 #[cfg(target_arch = "wasm32")]
 use wasm_bindgen::prelude::*;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct GG18KeygenClientContext {
-    addr: String,
-    params: Parameters,
-    party_num_int: u16,
-    uuid: String,
-    bc1_vec: Option<Vec<KeyGenBroadcastMessage1>>,
-    decom_i: Option<KeyGenDecommitMessage1>,
-    party_keys: Option<Keys>,
-    y_sum: Option<crate::curv::elliptic::curves::secp256_k1::Secp256k1Point>,
-    vss_scheme: Option<VerifiableSS>,
-    secret_shares: Option<Vec<crate::curv::elliptic::curves::secp256_k1::Secp256k1Scalar>>,
-    enc_keys: Option<Vec<Vec<u8>>>,
-    party_shares: Option<Vec<Scalar>>,
-    point_vec: Option<Vec<Point>>,
-    dlog_proof: Option<DLogProof>,
-    shared_keys: Option<SharedKeys>,
-    vss_scheme_vec: Option<Vec<VerifiableSS>>,
-    public_key_address: Option<String>,
-}
-
-fn new_client_with_headers(token: &str) -> Result<Client> {
-    let mut headers = HeaderMap::new();
-    headers.insert(
-        "Content-Type",
-        HeaderValue::from_static("Content-Type:application/json; charset=utf-8"),
-    );
-    headers.insert(
-        "Accept",
-        HeaderValue::from_static("application/json; charset=utf-8"),
-    );
-    headers.insert(
-        "Authorization",
-        HeaderValue::from_str(&format!("Bearer {}", token)).unwrap(),
-    );
-
-    Ok(reqwest::Client::builder()
-        .default_headers(headers)
-        .build()?)
-}
-
-#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
-pub async fn gg18_keygen_client_new_context(
-    addr: String,
-    t: usize,
-    n: usize,
-    _delay: u32,
-    token: String,
-    task_id: String,
-    party_type: String
-) -> Result<String> {
-    let client = new_client_with_headers(&token)?;
-    let params = Parameters {
-        threshold: t,
-        share_count: n,
-    };
+// the sign context is created without a delay; poll for the identity keys at this one
+const IDENTITY_POLL_DELAY: u32 = 100;
 
-    let (party_num_int, uuid) = match signup_keygen(&client, &addr, &task_id, &party_type).await? {
-        PartySignup { number, uuid } => (number, uuid),
-    };
+// whether contexts created from now on follow broadcast rounds with an echo round
+static ECHO_BROADCAST: AtomicBool = AtomicBool::new(false);
 
-    Ok(serde_json::to_string(&GG18KeygenClientContext {
-        addr,
-        params,
-        party_num_int,
-        uuid,
-        bc1_vec: None,
-        decom_i: None,
-        party_keys: None,
-        y_sum: None,
-        vss_scheme: None,
-        secret_shares: None,
-        enc_keys: None,
-        party_shares: None,
-        point_vec: None,
-        dlog_proof: None,
-        shared_keys: None,
-        vss_scheme_vec: None,
-        public_key_address: None,
-    })?)
+/// Follows every broadcast round with an echo round in which the parties
+/// compare hashes of the messages they received, so the manager cannot show
+/// different values to different parties. Every party of a session must
+/// enable it, otherwise the others wait for echoes that never come.
+///
+/// The setting is stored in the contexts created afterwards, so contexts that
+/// already exist, including persisted ones, keep the one they were made with.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn gg18_enable_echo_broadcast() {
+    ECHO_BROADCAST.store(true, Ordering::SeqCst);
 }
 
 #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
-pub async fn gg18_keygen_client_round1(context: String, delay: u32, token: String) -> Result<String> {
-    let mut context = serde_json::from_str::<GG18KeygenClientContext>(&context)?;
+pub fn gg18_disable_echo_broadcast() {
+    ECHO_BROADCAST.store(false, Ordering::SeqCst);
+}
 
-    let mut headers = reqwest::header::HeaderMap::new();
-    headers.insert(
-        "Authorization",
-        HeaderValue::from_str(&format!("Bearer {}", token)).unwrap(),
-    );
+/// Progress of a session run by one of the `*_client_run` functions, e.g. to
+/// drive a progress bar in the app.
+pub trait SessionProgress {
+    /// Round `round` of `rounds` is starting, counted from 1.
+    fn on_round(&self, round: u16, rounds: u16);
+    /// Waiting for the message of `party` in the current round.
+    fn on_waiting(&self, party: u16);
+    /// A step of the session is done, see `RoundTiming`.
+    fn on_timing(&self, _timing: &RoundTiming) {}
+}
 
-    let client = reqwest::Client::builder()
-        .default_headers(headers)
-        .build()
-        .expect("Failed to build client");
+/// Time a step of a session took, split into computing our messages (Paillier
+/// operations, range proofs, ...) and waiting on the manager and the other
+/// parties.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RoundTiming {
+    // "setup" for creating the context (signup, identity exchange and our
+    // first messages), else the round function, e.g. "round0"
+    pub step: String,
+    pub compute_ms: u64,
+    pub network_ms: u64,
+}
 
-    let party_keys = Keys::create(context.party_num_int as usize);
-    let (bc_i, decom_i) = party_keys.phase1_broadcast_phase3_proof_of_correct_key();
+impl RoundTiming {
+    fn new(step: String, started: &Stopwatch, network_ms: u64) -> RoundTiming {
+        RoundTiming {
+            step,
+            compute_ms: started.elapsed_ms().saturating_sub(network_ms),
+            network_ms,
+        }
+    }
+}
 
-    broadcast(
-        &client,
-        &context.addr,
-        context.party_num_int,
-        "round1",
-        serde_json::to_string(&bc_i)?,
-        context.uuid.clone(),
-    )
-    .await?;
+// forwards to the caller's progress and keeps the timings for the result
+struct TimingRecorder<'a> {
+    progress: &'a dyn SessionProgress,
+    timings: RefCell<Vec<RoundTiming>>,
+}
 
-    let round1_ans_vec = poll_for_broadcasts(
-        &client,
-        &context.addr,
-        context.party_num_int,
-        context.params.share_count as u16,
-        "round1",
-        context.uuid.clone(),
-        delay,
-    )
-    .await?;
+impl<'a> TimingRecorder<'a> {
+    fn new(progress: &'a dyn SessionProgress) -> Self {
+        TimingRecorder {
+            progress,
+            timings: RefCell::new(Vec::new()),
+        }
+    }
 
-    let mut bc1_vec: Vec<_> = round1_ans_vec
-        .into_iter()
-        .map(|m| serde_json::from_str::<KeyGenBroadcastMessage1>(&m))
-        .collect::<std::result::Result<Vec<KeyGenBroadcastMessage1>, serde_json::Error>>()?;
+    fn into_timings(self) -> Vec<RoundTiming> {
+        self.timings.into_inner()
+    }
+}
 
-    bc1_vec.insert(context.party_num_int as usize - 1, bc_i);
+impl SessionProgress for TimingRecorder<'_> {
+    fn on_round(&self, round: u16, rounds: u16) {
+        self.progress.on_round(round, rounds);
+    }
 
-    context.bc1_vec = Some(bc1_vec);
-    context.party_keys = Some(party_keys);
-    context.decom_i = Some(decom_i);
+    fn on_waiting(&self, party: u16) {
+        self.progress.on_waiting(party);
+    }
 
-    Ok(serde_json::to_string(&context)?)
+    fn on_timing(&self, timing: &RoundTiming) {
+        self.timings.borrow_mut().push(timing.clone());
+        self.progress.on_timing(timing);
+    }
 }
 
-#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
-pub async fn gg18_keygen_client_round2(context: String, delay: u32, token: String) -> Result<String> {
-    let mut context = serde_json::from_str::<GG18KeygenClientContext>(&context)?;
-
-    let mut headers = reqwest::header::HeaderMap::new();
-    headers.insert(
-        "Authorization",
-        HeaderValue::from_str(&format!("Bearer {}", token)).unwrap(),
-    );
+// used when the caller drives the rounds itself
+struct NoProgress;
 
-    let client = reqwest::Client::builder()
-        .default_headers(headers)
-        .build()
-        .expect("Failed to build client");
+impl SessionProgress for NoProgress {
+    fn on_round(&self, _round: u16, _rounds: u16) {}
+    fn on_waiting(&self, _party: u16) {}
+}
 
-    // send ephemeral public keys and check commitments correctness
-    broadcast(
-        &client,
-        &context.addr,
-        context.party_num_int,
-        "round2",
-        serde_json::to_string(&context.decom_i.as_ref().unwrap())?,
-        context.uuid.clone(),
-    )
-    .await?;
+/// Curve a key is generated and used on. Contexts created before the curve
+/// could be chosen are secp256k1 ones.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Curve {
+    #[default]
+    Secp256k1,
+    P256,
+}
 
-    let round2_ans_vec = poll_for_broadcasts(
-        &client,
-        &context.addr,
-        context.party_num_int,
-        context.params.share_count as u16,
-        "round2",
-        context.uuid.clone(),
-        delay,
-    )
-    .await?;
+impl std::str::FromStr for Curve {
+    type Err = TssError;
 
-    let mut j = 0;
-    let mut point_vec: Vec<Point> = Vec::new();
-    let mut decom_vec: Vec<KeyGenDecommitMessage1> = Vec::new();
-    let mut enc_keys: Vec<Vec<u8>> = Vec::new();
-    for i in 1..=context.params.share_count as u16 {
-        if i == context.party_num_int {
-            point_vec.push(context.decom_i.as_ref().unwrap().y_i.clone());
-            decom_vec.push(context.decom_i.as_ref().unwrap().clone());
-        } else {
-            let decom_j: KeyGenDecommitMessage1 = serde_json::from_str(&round2_ans_vec[j])?;
-            point_vec.push(decom_j.y_i.clone());
-            decom_vec.push(decom_j.clone());
-            let key_bn: BigInt = (decom_j.y_i.clone()
-                * context.party_keys.as_ref().unwrap().u_i.clone())
-            .x_coor()
-            .unwrap();
-            let key_bytes = BigInt::to_vec(&key_bn);
-            let mut template: Vec<u8> = vec![0u8; AES_KEY_BYTES_LEN - key_bytes.len()];
-            template.extend_from_slice(&key_bytes[..]);
-            enc_keys.push(template);
-            j += 1;
+    fn from_str(curve: &str) -> Result<Curve> {
+        match curve {
+            "" | "secp256k1" => Ok(Curve::Secp256k1),
+            "p256" => Ok(Curve::P256),
+            _ => Err(TssError::UnsupportedCurve),
         }
     }
+}
 
-    let (head, tail) = point_vec.split_at(1);
-    let y_sum = tail.iter().fold(head[0].clone(), |acc, x| acc + x);
+// reads only the curve of a round context, to pick the types to open it as
+#[derive(Deserialize)]
+struct ContextCurve {
+    #[serde(default)]
+    curve: Curve,
+}
 
-    let (vss_scheme, secret_shares, _index) = context
-        .party_keys
-        .as_ref()
-        .unwrap()
-        .phase1_verify_com_phase3_verify_correct_key_phase2_distribute(
-            &context.params,
-            &decom_vec,
-            &(context.bc1_vec.as_ref().unwrap()),
-        )?;
+fn context_curve(context: &str, kind: &str) -> Result<Curve> {
+    Ok(peek_context::<ContextCurve>(context, kind)?.curve)
+}
 
-    context.y_sum = Some(y_sum.clone());
-    let pubkey_a = y_sum.get_element().serialize();
-    let pubkey = secp256k1::PublicKey::parse(&pubkey_a)?;
-    context.public_key_address = Some(hex::encode(public_key_address(&pubkey)));
-    context.vss_scheme = Some(vss_scheme);
-    context.secret_shares = Some(secret_shares);
-    context.enc_keys = Some(enc_keys);
-    context.point_vec = Some(point_vec);
+const GG18_KEYGEN_CONTEXT: &str = "gg18-keygen";
+const GG18_SIGN_CONTEXT: &str = "gg18-sign";
 
-    Ok(serde_json::to_string(&context)?)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(bound = "")]
+pub struct GG18KeygenClientContext<P: ECPoint = secp256_k1::GE> {
+    addr: String,
+    uuid: String,
+    #[serde(default)]
+    curve: Curve,
+    identities: PartyIdentities,
+    // whether broadcast rounds are followed by an echo round, fixed when
+    // the context is created
+    #[serde(default)]
+    echo_broadcast: bool,
+    session: KeygenSession<P>,
+    // messages of the current round, produced by the previous one. They are
+    // kept so a round restarted from this checkpoint re-sends the same values.
+    outgoing: Outgoing<KeygenMessage<P>>,
 }
 
-#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
-pub async fn gg18_keygen_client_round3(context: String, delay: u32, token: String) -> Result<String> {
-    let mut context = serde_json::from_str::<GG18KeygenClientContext>(&context)?;
-
-    let mut headers = reqwest::header::HeaderMap::new();
-    headers.insert(
-        "Authorization",
-        HeaderValue::from_str(&format!("Bearer {}", token)).unwrap(),
-    );
-
-    let client = reqwest::Client::builder()
-        .default_headers(headers)
-        .build()
-        .expect("Failed to build client");
-
-    let mut j = 0;
-    for (k, i) in (1..=context.params.share_count as u16).enumerate() {
-        if i != context.party_num_int {
-            // prepare encrypted ss for party i:
-            let key_i = &context.enc_keys.as_ref().unwrap()[j];
-            let plaintext =
-                BigInt::to_vec(&context.secret_shares.as_ref().unwrap()[k].to_big_int());
-            let aead_pack_i = aes_encrypt(key_i, &plaintext)?;
-            sendp2p(
-                &client,
-                &context.addr,
-                context.party_num_int,
-                i,
-                "round3",
-                serde_json::to_string(&aead_pack_i)?,
-                context.uuid.clone(),
-            )
-            .await?;
-            j += 1;
-        }
-    }
+impl<P: ECPoint> RoundContext for GG18KeygenClientContext<P> {
+    const KIND: &'static str = GG18_KEYGEN_CONTEXT;
 
-    let round3_ans_vec = poll_for_p2p(
-        &client,
-        &context.addr,
-        context.party_num_int,
-        context.params.share_count as u16,
-        delay,
-        "round3",
-        context.uuid.clone(),
-    )
-    .await?;
+    fn uuid(&self) -> &str {
+        &self.uuid
+    }
 
-    let mut j = 0;
-    let mut party_shares: Vec<Scalar> = Vec::new();
-    for i in 1..=context.params.share_count as u16 {
-        if i == context.party_num_int {
-            party_shares.push(context.secret_shares.as_ref().unwrap()[(i - 1) as usize].clone());
-        } else {
-            let aead_pack: AEAD = serde_json::from_str(&round3_ans_vec[j]).unwrap();
-            let key_i = &context.enc_keys.as_ref().unwrap()[j];
-            let out = aes_decrypt(key_i, aead_pack)?;
-            let out_bn = BigInt::from_bytes_be(&out[..]);
-            let out_fe = ECScalar::from(&out_bn);
-            party_shares.push(out_fe);
-
-            j += 1;
-        }
+    fn round(&self) -> u16 {
+        self.session.round()
     }
+}
 
-    context.party_shares = Some(party_shares);
+/// Creates the context of a GG18 keygen on secp256k1, see
+/// `gg18_keygen_client_new_context_with_curve` for the other curves.
+#[allow(clippy::too_many_arguments)]
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub async fn gg18_keygen_client_new_context(
+    addr: String,
+    t: usize,
+    n: usize,
+    delay: u32,
+    token: String,
+    task_id: String,
+    party_type: String,
+    identity_key: String,
+    peer_identity_keys: String,
+) -> Result<String> {
+    gg18_keygen_client_new_context_with_curve(
+        addr,
+        t,
+        n,
+        delay,
+        token,
+        task_id,
+        party_type,
+        identity_key,
+        peer_identity_keys,
+        "secp256k1".to_string(),
+    )
+    .await
+}
 
-    Ok(serde_json::to_string(&context)?)
+/// `gg18_keygen_client_new_context` on `curve`, "secp256k1" or "p256".
+#[allow(clippy::too_many_arguments)]
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub async fn gg18_keygen_client_new_context_with_curve(
+    addr: String,
+    t: usize,
+    n: usize,
+    delay: u32,
+    token: String,
+    task_id: String,
+    party_type: String,
+    identity_key: String,
+    peer_identity_keys: String,
+    curve: String,
+) -> Result<String> {
+    keygen_setup(
+        addr,
+        t,
+        n,
+        delay,
+        &ManagerClient::new(&token, None)?,
+        task_id,
+        party_type,
+        identity_key,
+        peer_identity_keys,
+        curve,
+        DEFAULT_PAILLIER_MODULUS_BITS,
+        false,
+        &NoProgress,
+    )
+    .await
 }
 
+/// `gg18_keygen_client_new_context_with_curve` with a Paillier modulus of
+/// `paillier_modulus_bits` (2048 to 8192, 2048 by default), built from safe
+/// primes instead of Blum primes if `safe_primes` is set. The other parties
+/// must use at least the same size, smaller moduli are rejected in round1.
+#[allow(clippy::too_many_arguments)]
 #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
-pub async fn gg18_keygen_client_round4(context: String, delay: u32, token: String) -> Result<String> {
-    let mut context = serde_json::from_str::<GG18KeygenClientContext>(&context)?;
-
-    let mut headers = reqwest::header::HeaderMap::new();
-    headers.insert(
-        "Authorization",
-        HeaderValue::from_str(&format!("Bearer {}", token)).unwrap(),
-    );
-
-    let client = reqwest::Client::builder()
-        .default_headers(headers)
-        .build()
-        .expect("Failed to build client");
-    broadcast(
-        &client,
-        &context.addr,
-        context.party_num_int,
-        "round4",
-        serde_json::to_string(&context.vss_scheme.as_ref().unwrap())?,
-        context.uuid.clone(),
+pub async fn gg18_keygen_client_new_context_with_paillier(
+    addr: String,
+    t: usize,
+    n: usize,
+    delay: u32,
+    token: String,
+    task_id: String,
+    party_type: String,
+    identity_key: String,
+    peer_identity_keys: String,
+    curve: String,
+    paillier_modulus_bits: usize,
+    safe_primes: bool,
+) -> Result<String> {
+    keygen_setup(
+        addr,
+        t,
+        n,
+        delay,
+        &ManagerClient::new(&token, None)?,
+        task_id,
+        party_type,
+        identity_key,
+        peer_identity_keys,
+        curve,
+        paillier_modulus_bits,
+        safe_primes,
+        &NoProgress,
     )
-    .await?;
-    let round4_ans_vec = poll_for_broadcasts(
-        &client,
-        &context.addr,
-        context.party_num_int,
-        context.params.share_count as u16,
-        "round4",
-        context.uuid.clone(),
+    .await
+}
+
+// gg18_keygen_client_new_context, reporting the time it took to `progress`
+#[allow(clippy::too_many_arguments)]
+async fn keygen_setup(
+    addr: String,
+    t: usize,
+    n: usize,
+    delay: u32,
+    client: &ManagerClient<'_>,
+    task_id: String,
+    party_type: String,
+    identity_key: String,
+    peer_identity_keys: String,
+    curve: String,
+    paillier_modulus_bits: usize,
+    safe_primes: bool,
+    progress: &dyn SessionProgress,
+) -> Result<String> {
+    let started = Stopwatch::start();
+    let curve: Curve = curve.parse()?;
+    let params = keygen_parameters(t, n, paillier_modulus_bits, safe_primes)?;
+    let public_key = gg18_identity_public_key(identity_key.clone())?;
+
+    let network = Stopwatch::start();
+    let (party_num_int, uuid) =
+        match signup_keygen(client, &addr, &task_id, &party_type, &public_key).await? {
+            PartySignup { number, uuid } => (number, uuid),
+        };
+    let identities = exchange_identities(
+        client,
+        &addr,
+        party_num_int,
+        n as u16,
+        &uuid,
+        &identity_key,
+        &peer_identity_keys,
         delay,
     )
     .await?;
+    let network_ms = network.elapsed_ms();
+
+    let context = match curve {
+        Curve::Secp256k1 => new_keygen_context::<secp256_k1::GE>(
+            addr,
+            uuid,
+            curve,
+            identities,
+            params,
+            party_num_int,
+        ),
+        Curve::P256 => new_keygen_context::<secp256_r1::GE>(
+            addr,
+            uuid,
+            curve,
+            identities,
+            params,
+            party_num_int,
+        ),
+    }?;
+    progress.on_timing(&RoundTiming::new("setup".to_string(), &started, network_ms));
+    Ok(context)
+}
+
+fn keygen_parameters(
+    t: usize,
+    n: usize,
+    paillier_modulus_bits: usize,
+    safe_primes: bool,
+) -> Result<Parameters> {
+    let prime_kind = match safe_primes {
+        true => PrimeKind::Safe,
+        false => PrimeKind::Blum,
+    };
+    Parameters::with_paillier(t, n, paillier_modulus_bits, prime_kind)
+}
 
-    let mut j = 0;
-    let mut vss_scheme_vec: Vec<VerifiableSS> = Vec::new();
-    for i in 1..=context.params.share_count as u16 {
-        if i == context.party_num_int {
-            vss_scheme_vec.push(context.vss_scheme.as_ref().unwrap().clone());
-        } else {
-            let vss_scheme_j: VerifiableSS = serde_json::from_str(&round4_ans_vec[j]).unwrap();
-            vss_scheme_vec.push(vss_scheme_j);
-            j += 1;
+fn new_keygen_context<P: ECPoint>(
+    addr: String,
+    uuid: String,
+    curve: Curve,
+    identities: PartyIdentities,
+    params: Parameters,
+    party_num_int: u16,
+) -> Result<String> {
+    let (outgoing, session) =
+        KeygenSession::<P>::new(params, party_num_int, uuid.clone()).advance(Vec::new())?;
+
+    seal_context(&GG18KeygenClientContext {
+        addr,
+        uuid,
+        curve,
+        identities,
+        echo_broadcast: ECHO_BROADCAST.load(Ordering::SeqCst),
+        session,
+        outgoing,
+    })
+}
+
+/// Round to call next with this context, e.g. after the app was restarted
+/// from a persisted checkpoint; 6 once the key store has been produced.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn gg18_keygen_client_next_round(context: String) -> Result<u16> {
+    match context_curve(&context, GG18_KEYGEN_CONTEXT)? {
+        Curve::Secp256k1 => {
+            let context = open_context::<GG18KeygenClientContext<secp256_k1::GE>>(&context)?;
+            Ok(context.session.round())
+        }
+        Curve::P256 => {
+            let context = open_context::<GG18KeygenClientContext<secp256_r1::GE>>(&context)?;
+            Ok(context.session.round())
         }
     }
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub async fn gg18_keygen_client_round1(context: String, delay: u32, token: String) -> Result<String> {
+    keygen_round(context, 1, delay, &ManagerClient::new(&token, None)?, &NoProgress).await
+}
 
-    let (shared_keys, dlog_proof) = context
-        .party_keys
-        .as_ref()
-        .unwrap()
-        .phase2_verify_vss_construct_keypair_phase3_pok_dlog(
-            &context.params,
-            &context.point_vec.as_ref().unwrap(),
-            &context.party_shares.as_ref().unwrap(),
-            &vss_scheme_vec,
-            &(context.party_num_int.clone() as usize), // FIXME
-        )?;
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub async fn gg18_keygen_client_round2(context: String, delay: u32, token: String) -> Result<String> {
+    keygen_round(context, 2, delay, &ManagerClient::new(&token, None)?, &NoProgress).await
+}
 
-    context.shared_keys = Some(shared_keys);
-    context.dlog_proof = Some(dlog_proof);
-    context.vss_scheme_vec = Some(vss_scheme_vec);
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub async fn gg18_keygen_client_round3(context: String, delay: u32, token: String) -> Result<String> {
+    keygen_round(context, 3, delay, &ManagerClient::new(&token, None)?, &NoProgress).await
+}
 
-    Ok(serde_json::to_string(&context)?)
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub async fn gg18_keygen_client_round4(context: String, delay: u32, token: String) -> Result<String> {
+    keygen_round(context, 4, delay, &ManagerClient::new(&token, None)?, &NoProgress).await
 }
 
 #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
 pub async fn gg18_keygen_client_round5(context: String, delay: u32, token: String) -> Result<String> {
-    let context = serde_json::from_str::<GG18KeygenClientContext>(&context)?;
-    let mut headers = reqwest::header::HeaderMap::new();
-    headers.insert(
-        "Authorization",
-        HeaderValue::from_str(&format!("Bearer {}", token)).unwrap(),
-    );
-
-    let client = reqwest::Client::builder()
-        .default_headers(headers)
-        .build()
-        .expect("Failed to build client");
-    broadcast(
-        &client,
-        &context.addr,
-        context.party_num_int,
-        "round5",
-        serde_json::to_string(&context.dlog_proof.as_ref().unwrap())?,
-        context.uuid.clone(),
-    )
-    .await?;
-    let round5_ans_vec = poll_for_broadcasts(
-        &client,
-        &context.addr,
-        context.party_num_int,
-        context.params.share_count as u16,
-        "round5",
-        context.uuid.clone(),
-        delay,
-    )
-    .await?;
+    keygen_round(context, 5, delay, &ManagerClient::new(&token, None)?, &NoProgress).await
+}
 
-    let mut j = 0;
-    let mut dlog_proof_vec: Vec<DLogProof> = Vec::new();
-    for i in 1..=context.params.share_count as u16 {
-        if i == context.party_num_int {
-            dlog_proof_vec.push(context.dlog_proof.as_ref().unwrap().clone());
-        } else {
-            let dlog_proof_j: DLogProof = serde_json::from_str(&round5_ans_vec[j])?;
-            dlog_proof_vec.push(dlog_proof_j);
-            j += 1;
-        }
+// runs a round on the curve of the context; returns the sealed context for the
+// next round, or the key store once the last round is done
+async fn keygen_round(
+    context: String,
+    round: u16,
+    delay: u32,
+    client: &ManagerClient<'_>,
+    progress: &dyn SessionProgress,
+) -> Result<String> {
+    match context_curve(&context, GG18_KEYGEN_CONTEXT)? {
+        Curve::Secp256k1 => {
+            keygen_round_on::<secp256_k1::GE>(context, round, delay, client, progress).await
+        },
+        Curve::P256 => {
+            keygen_round_on::<secp256_r1::GE>(context, round, delay, client, progress).await
+        },
     }
-    Keys::verify_dlog_proofs(
-        &context.params,
-        &dlog_proof_vec,
-        &context.point_vec.as_ref().unwrap(),
-    )?;
-
-    //save key to file:
-    let paillier_key_vec = (0..context.params.share_count as u16)
-        .map(|i| context.bc1_vec.as_ref().unwrap()[i as usize].e.clone())
-        .collect::<Vec<EncryptionKey>>();
-
-    let keygen_json = serde_json::to_string(&(
-        context.party_keys.as_ref().unwrap(),
-        context.shared_keys.as_ref().unwrap(),
-        context.party_num_int,
-        context.vss_scheme_vec.as_ref().unwrap(),
-        paillier_key_vec,
-        context.y_sum.as_ref().unwrap(),
-    ))?;
+}
 
-    Ok(keygen_json)
+async fn keygen_round_on<P: ECPoint>(
+    context: String,
+    round: u16,
+    delay: u32,
+    client: &ManagerClient<'_>,
+    progress: &dyn SessionProgress,
+) -> Result<String> {
+    let started = Stopwatch::start();
+    let GG18KeygenClientContext {
+        addr,
+        uuid,
+        curve,
+        identities,
+        echo_broadcast,
+        session,
+        outgoing,
+    } = open_context_at::<GG18KeygenClientContext<P>>(&context, round)?;
+    session.check_round(round)?;
+
+    let network = Stopwatch::start();
+    let incoming = exchange_round(
+        client,
+        &addr,
+        session.party_num_int,
+        session.params.share_count as u16,
+        &uuid,
+        &identities,
+        &outgoing,
+        echo_broadcast,
+        delay,
+        progress,
+    )
+    .await?;
+    let network_ms = network.elapsed_ms();
+    let (outgoing, session) = session.advance(incoming)?;
+    let context = match session.key_store() {
+        Some(key_store) => serde_json::to_string(key_store)?,
+        None => seal_context(&GG18KeygenClientContext {
+            addr,
+            uuid,
+            curve,
+            identities,
+            echo_broadcast,
+            session,
+            outgoing,
+        })?,
+    };
+    progress.on_timing(&RoundTiming::new(format!("round{}", round), &started, network_ms));
+    Ok(context)
 }
 
-pub async fn signup_keygen(client: &Client, addr: &str, task_id: &str, party_type: &str) -> Result<PartySignup> {
+pub async fn signup_keygen(
+    client: &ManagerClient<'_>,
+    addr: &str,
+    task_id: &str,
+    party_type: &str,
+    identity_key: &str,
+) -> Result<PartySignup> {
     let request = TaskRequest {
         task_id: task_id.to_string(),
         party_type: party_type.to_string(),
+        identity_key: identity_key.to_string(),
     };
-    let res_body = postb(client, addr, "signupkeygen", request).await?;
+    let res_body = postb_once(client, addr, "signupkeygen", request).await?;
     let u: std::result::Result<PartySignup, ()> = serde_json::from_str(&res_body)?;
     Ok(u.unwrap())
 }
 
-pub async fn signup_sign(client: &Client, addr: &str, task_id: &str, party_type: &str) -> Result<PartySignup> {
+pub async fn signup_sign(
+    client: &ManagerClient<'_>,
+    addr: &str,
+    task_id: &str,
+    party_type: &str,
+    identity_key: &str,
+) -> Result<PartySignup> {
     let request = TaskRequest {
         task_id: task_id.to_string(),
         party_type: party_type.to_string(),
+        identity_key: identity_key.to_string(),
     };
-    let res_body = postb(client, addr, "signupsign", request).await?;
+    let res_body = postb_once(client, addr, "signupsign", request).await?;
     let u: std::result::Result<PartySignup, ()> = serde_json::from_str(&res_body)?;
     Ok(u.unwrap())
 }
 
-#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
 #[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct GG18SignClientContext {
+#[serde(bound = "")]
+pub struct GG18SignClientContext<P: ECPoint = secp256_k1::GE> {
     addr: String,
-    party_keys: Keys,
-    shared_keys: SharedKeys,
-    party_id: u16,
-    vss_scheme_vec: Vec<VerifiableSS>,
-    paillier_key_vector: Vec<EncryptionKey>,
-    y_sum: Point,
-    threshould: u16,
-    party_num_int: u16,
     uuid: String,
-    sign_keys: Option<SignKeys>,
-    com: Option<SignBroadcastPhase1>,
-    decommit: Option<SignDecommitPhase1>,
-    round1_ans_vec: Option<Vec<String>>,
-    signers_vec: Option<Vec<usize>>,
-    round2_ans_vec: Option<Vec<String>>,
-    xi_com_vec: Option<Vec<crate::curv::elliptic::curves::secp256_k1::Secp256k1Point>>,
-    beta_vec: Option<Vec<Scalar>>,
-    ni_vec: Option<Vec<Scalar>>,
-    bc1_vec: Option<Vec<SignBroadcastPhase1>>,
-    m_b_gamma_rec_vec: Option<Vec<MessageB>>,
-    delta_inv: Option<crate::curv::elliptic::curves::secp256_k1::Secp256k1Scalar>,
-    sigma: Option<crate::curv::elliptic::curves::secp256_k1::Secp256k1Scalar>,
-    message: Vec<u8>,
-    phase5_com: Option<Phase5Com1>,
-    phase_5a_decom: Option<Phase5ADecom1>,
-    helgamal_proof: Option<HomoELGamalProof>,
-    dlog_proof_rho: Option<DLogProof>,
-    commit5a_vec: Option<Vec<Phase5Com1>>,
-    local_sig: Option<LocalSignature>,
-    r: Option<crate::curv::elliptic::curves::secp256_k1::Secp256k1Point>,
-    phase5_com2: Option<Phase5Com2>,
-    phase_5d_decom2: Option<Phase5DDecom2>,
-    decommit5a_and_elgamal_and_dlog_vec: Option<Vec<(Phase5ADecom1, HomoELGamalProof, DLogProof)>>,
-    decommit5a_and_elgamal_and_dlog_vec_includes_i:
-        Option<Vec<(Phase5ADecom1, HomoELGamalProof, DLogProof)>>,
-    s_i: Option<crate::curv::elliptic::curves::secp256_k1::Secp256k1Scalar>,
-    commit5c_vec: Option<Vec<Phase5Com2>>,
+    #[serde(default)]
+    curve: Curve,
+    identities: PartyIdentities,
+    #[serde(default)]
+    echo_broadcast: bool,
+    session: SignSession<P>,
+    // messages of the current round, produced by the previous one. They are
+    // kept so a round restarted from this checkpoint re-sends the same values.
+    outgoing: Outgoing<SignMessage<P>>,
+}
+
+impl<P: ECPoint> RoundContext for GG18SignClientContext<P> {
+    const KIND: &'static str = GG18_SIGN_CONTEXT;
+
+    fn uuid(&self) -> &str {
+        &self.uuid
+    }
+
+    fn round(&self) -> u16 {
+        self.session.round()
+    }
 }
 
+/// Creates the context of a GG18 sign with a secp256k1 key store, see
+/// `gg18_sign_client_new_context_with_curve` for the other curves.
+#[allow(clippy::too_many_arguments)]
 #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
 pub async fn gg18_sign_client_new_context(
     addr: String,
@@ -520,707 +575,1274 @@ pub async fn gg18_sign_client_new_context(
     message_str: String,
     token: String,
     task_id: String,
-    party_type: String
+    party_type: String,
+    identity_key: String,
+    peer_identity_keys: String,
 ) -> Result<String> {
-    let message = match hex::decode(message_str.clone()) {
-        Ok(x) => x,
-        Err(_e) => message_str.as_bytes().to_vec(),
-    };
-    // let message = &message[..];
-    let client = new_client_with_headers(&token)?;
-
-    let (party_keys, shared_keys, party_id, vss_scheme_vec, paillier_key_vector, y_sum): (
-        Keys,
-        SharedKeys,
-        u16,
-        Vec<VerifiableSS>,
-        Vec<EncryptionKey>,
-        Point,
-    ) = serde_json::from_str(&key_store)?;
-
-    //signup:
-    let (party_num_int, uuid) = match signup_sign(&client, &addr, &task_id, &party_type).await? {
-        PartySignup { number, uuid } => (number, uuid),
-    };
-
-    Ok(serde_json::to_string(&GG18SignClientContext {
+    gg18_sign_client_new_context_with_curve(
         addr,
-        party_keys,
-        shared_keys,
-        party_id,
-        vss_scheme_vec,
-        paillier_key_vector,
-        y_sum,
-        threshould: t as u16,
-        party_num_int,
-        uuid,
-        sign_keys: None,
-        com: None,
-        decommit: None,
-        round1_ans_vec: None,
-        signers_vec: None,
-        round2_ans_vec: None,
-        xi_com_vec: None,
-        beta_vec: None,
-        ni_vec: None,
-        bc1_vec: None,
-        m_b_gamma_rec_vec: None,
-        delta_inv: None,
-        message, // TODO: The message is plain now
-        sigma: None,
-        phase5_com: None,
-        phase_5a_decom: None,
-        helgamal_proof: None,
-        dlog_proof_rho: None,
-        commit5a_vec: None,
-        local_sig: None,
-        r: None,
-        phase5_com2: None,
-        phase_5d_decom2: None,
-        decommit5a_and_elgamal_and_dlog_vec: None,
-        decommit5a_and_elgamal_and_dlog_vec_includes_i: None,
-        s_i: None,
-        commit5c_vec: None,
-    })?)
+        t,
+        _n,
+        key_store,
+        message_str,
+        token,
+        task_id,
+        party_type,
+        identity_key,
+        peer_identity_keys,
+        "secp256k1".to_string(),
+    )
+    .await
 }
 
+/// `gg18_sign_client_new_context` with a key store of `curve`, "secp256k1" or
+/// "p256"; it has to be the curve the key was generated on.
+#[allow(clippy::too_many_arguments)]
 #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
-pub async fn gg18_sign_client_round0(context: String, delay: u32, token: String) -> Result<String> {
-    let mut context = serde_json::from_str::<GG18SignClientContext>(&context)?;
-    let client = new_client_with_headers(&token)?;
-    // round 0: collect signers IDs
-    broadcast(
-        &client,
-        &context.addr,
-        context.party_num_int,
-        "round0",
-        serde_json::to_string(&context.party_id)?,
-        context.uuid.clone(),
+pub async fn gg18_sign_client_new_context_with_curve(
+    addr: String,
+    t: usize,
+    _n: usize,
+    key_store: String,
+    message_str: String,
+    token: String,
+    task_id: String,
+    party_type: String,
+    identity_key: String,
+    peer_identity_keys: String,
+    curve: String,
+) -> Result<String> {
+    sign_setup(
+        addr,
+        t,
+        _n,
+        key_store,
+        message_str,
+        &ManagerClient::new(&token, None)?,
+        task_id,
+        party_type,
+        identity_key,
+        peer_identity_keys,
+        curve,
+        &NoProgress,
     )
-    .await?;
-    let round0_ans_vec = poll_for_broadcasts(
-        &client,
-        &context.addr,
-        context.party_num_int,
-        context.threshould + 1,
-        "round0",
-        context.uuid.clone(),
-        delay,
+    .await
+}
+
+// gg18_sign_client_new_context, reporting the time it took to `progress`
+#[allow(clippy::too_many_arguments)]
+async fn sign_setup(
+    addr: String,
+    t: usize,
+    _n: usize,
+    key_store: String,
+    message_str: String,
+    client: &ManagerClient<'_>,
+    task_id: String,
+    party_type: String,
+    identity_key: String,
+    peer_identity_keys: String,
+    curve: String,
+    progress: &dyn SessionProgress,
+) -> Result<String> {
+    let started = Stopwatch::start();
+    let curve: Curve = curve.parse()?;
+    let message = message_bytes(&message_str);
+
+    let public_key = gg18_identity_public_key(identity_key.clone())?;
+
+    let network = Stopwatch::start();
+    //signup:
+    let (party_num_int, uuid) =
+        match signup_sign(client, &addr, &task_id, &party_type, &public_key).await? {
+            PartySignup { number, uuid } => (number, uuid),
+        };
+    let identities = exchange_identities(
+        client,
+        &addr,
+        party_num_int,
+        t as u16 + 1,
+        &uuid,
+        &identity_key,
+        &peer_identity_keys,
+        IDENTITY_POLL_DELAY,
     )
     .await?;
+    let network_ms = network.elapsed_ms();
 
-    let mut j = 0;
-    let mut signers_vec: Vec<usize> = Vec::new();
-    for i in 1..=context.threshould + 1 {
-        if i == context.party_num_int {
-            signers_vec.push((context.party_id - 1).into());
-        } else {
-            let signer_j: u16 = serde_json::from_str(&round0_ans_vec[j])?;
-            signers_vec.push((signer_j - 1).into());
-            j += 1;
-        }
-    }
-
-    let private =
-        PartyPrivate::set_private(context.party_keys.clone(), context.shared_keys.clone());
+    let context = NewSignContext {
+        addr,
+        uuid,
+        curve,
+        identities,
+        t: t as u16,
+        party_num_int,
+        message,
+    };
+    let context = match curve {
+        Curve::Secp256k1 => context.seal::<secp256_k1::GE>(&key_store),
+        Curve::P256 => context.seal::<secp256_r1::GE>(&key_store),
+    }?;
+    progress.on_timing(&RoundTiming::new("setup".to_string(), &started, network_ms));
+    Ok(context)
+}
 
-    let sign_keys = SignKeys::create(
-        &private,
-        &context.vss_scheme_vec[usize::from(signers_vec[usize::from(context.party_num_int - 1)])],
-        signers_vec[usize::from(context.party_num_int - 1)].into(),
-        &signers_vec,
-    );
+// everything a sign context is made of that does not depend on the curve
+struct NewSignContext {
+    addr: String,
+    uuid: String,
+    curve: Curve,
+    identities: PartyIdentities,
+    t: u16,
+    party_num_int: u16,
+    message: Vec<u8>,
+}
 
-    let xi_com_vec = Keys::get_commitments_to_xi(&context.vss_scheme_vec);
+impl NewSignContext {
+    fn seal<P: ECPoint>(self, key_store: &str) -> Result<String> {
+        let key_store: KeyStore<P> = serde_json::from_str(key_store)?;
+        // TODO: The message is plain now
+        let (outgoing, session) =
+            SignSession::new(key_store, self.t, self.party_num_int, self.uuid.clone(), self.message)
+                .advance(Vec::new())?;
+
+        seal_context(&GG18SignClientContext {
+            addr: self.addr,
+            uuid: self.uuid,
+            curve: self.curve,
+            identities: self.identities,
+            echo_broadcast: ECHO_BROADCAST.load(Ordering::SeqCst),
+            session,
+            outgoing,
+        })
+    }
+}
 
-    context.sign_keys = Some(sign_keys);
-    context.signers_vec = Some(signers_vec);
-    context.xi_com_vec = Some(xi_com_vec);
+/// Round to call next with this context, e.g. after the app was restarted
+/// from a persisted checkpoint; 10 once the signature has been produced.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn gg18_sign_client_next_round(context: String) -> Result<u16> {
+    match context_curve(&context, GG18_SIGN_CONTEXT)? {
+        Curve::Secp256k1 => {
+            let context = open_context::<GG18SignClientContext<secp256_k1::GE>>(&context)?;
+            Ok(context.session.round())
+        }
+        Curve::P256 => {
+            let context = open_context::<GG18SignClientContext<secp256_r1::GE>>(&context)?;
+            Ok(context.session.round())
+        }
+    }
+}
 
-    Ok(serde_json::to_string(&context)?)
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub async fn gg18_sign_client_round0(context: String, delay: u32, token: String) -> Result<String> {
+    sign_round(context, 0, delay, &ManagerClient::new(&token, None)?, &NoProgress).await
 }
 
 #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
 pub async fn gg18_sign_client_round1(context: String, delay: u32, token: String) -> Result<String> {
-    let mut context = serde_json::from_str::<GG18SignClientContext>(&context)?;
-    let client = new_client_with_headers(&token)?;
-    let (com, decommit) = context.sign_keys.as_ref().unwrap().phase1_broadcast();
-    let (m_a_k, _) = MessageA::a(
-        &context.sign_keys.as_ref().unwrap().k_i,
-        &context.party_keys.ek,
-        &[],
-    );
-    broadcast(
-        &client,
-        &context.addr,
-        context.party_num_int,
-        "round1",
-        serde_json::to_string(&(com.clone(), m_a_k))?,
-        context.uuid.clone(),
-    )
-    .await?;
-    let round1_ans_vec = poll_for_broadcasts(
-        &client,
-        &context.addr,
-        context.party_num_int,
-        context.threshould + 1,
-        "round1",
-        context.uuid.clone(),
-        delay,
-    )
-    .await?;
+    sign_round(context, 1, delay, &ManagerClient::new(&token, None)?, &NoProgress).await
+}
 
-    context.com = Some(com);
-    context.decommit = Some(decommit);
-    context.round1_ans_vec = Some(round1_ans_vec);
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub async fn gg18_sign_client_round2(context: String, delay: u32, token: String) -> Result<String> {
+    sign_round(context, 2, delay, &ManagerClient::new(&token, None)?, &NoProgress).await
+}
 
-    Ok(serde_json::to_string(&context)?)
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub async fn gg18_sign_client_round3(context: String, delay: u32, token: String) -> Result<String> {
+    sign_round(context, 3, delay, &ManagerClient::new(&token, None)?, &NoProgress).await
 }
 
 #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
-pub async fn gg18_sign_client_round2(context: String, delay: u32, token: String) -> Result<String> {
-    let mut context = serde_json::from_str::<GG18SignClientContext>(&context)?;
-    let client = new_client_with_headers(&token)?;
-    let mut j = 0;
-    let mut bc1_vec: Vec<SignBroadcastPhase1> = Vec::new();
-    let mut m_a_vec: Vec<MessageA> = Vec::new();
-
-    for i in 1..context.threshould + 2 {
-        if i == context.party_num_int {
-            bc1_vec.push(context.com.as_ref().unwrap().clone());
-        //   m_a_vec.push(m_a_k.clone());
-        } else {
-            //     if signers_vec.contains(&(i as usize)) {
-            let (bc1_j, m_a_party_j): (SignBroadcastPhase1, MessageA) =
-                serde_json::from_str(&context.round1_ans_vec.as_ref().unwrap()[j])?;
-            bc1_vec.push(bc1_j);
-            m_a_vec.push(m_a_party_j);
-
-            j += 1;
-            //       }
-        }
-    }
-    assert_eq!(context.signers_vec.as_ref().unwrap().len(), bc1_vec.len());
-
-    //////////////////////////////////////////////////////////////////////////////
-    let mut m_b_gamma_send_vec: Vec<MessageB> = Vec::new();
-    let mut beta_vec: Vec<Scalar> = Vec::new();
-    let mut m_b_w_send_vec: Vec<MessageB> = Vec::new();
-    let mut ni_vec: Vec<Scalar> = Vec::new();
-    let mut j = 0;
-    for i in 1..context.threshould + 2 {
-        if i != context.party_num_int {
-            let (m_b_gamma, beta_gamma, _, _) = MessageB::b(
-                &context.sign_keys.as_ref().unwrap().gamma_i,
-                &context.paillier_key_vector
-                    [usize::from(context.signers_vec.as_ref().unwrap()[usize::from(i - 1)])],
-                m_a_vec[j].clone(),
-                &[],
-            )
-            .unwrap();
-            let (m_b_w, beta_wi, _, _) = MessageB::b(
-                &context.sign_keys.as_ref().unwrap().w_i,
-                &context.paillier_key_vector
-                    [usize::from(context.signers_vec.as_ref().unwrap()[usize::from(i - 1)])],
-                m_a_vec[j].clone(),
-                &[],
-            )
-            .unwrap();
-            m_b_gamma_send_vec.push(m_b_gamma);
-            m_b_w_send_vec.push(m_b_w);
-            beta_vec.push(beta_gamma);
-            ni_vec.push(beta_wi);
-            j += 1;
-        }
-    }
+pub async fn gg18_sign_client_round4(context: String, delay: u32, token: String) -> Result<String> {
+    sign_round(context, 4, delay, &ManagerClient::new(&token, None)?, &NoProgress).await
+}
 
-    let mut j = 0;
-    for i in 1..context.threshould + 2 {
-        if i != context.party_num_int {
-            sendp2p(
-                &client,
-                &context.addr,
-                context.party_num_int,
-                i,
-                "round2",
-                serde_json::to_string(&(m_b_gamma_send_vec[j].clone(), m_b_w_send_vec[j].clone()))?,
-                context.uuid.clone(),
-            )
-            .await?;
-            j += 1;
-        }
-    }
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub async fn gg18_sign_client_round5(context: String, delay: u32, token: String) -> Result<String> {
+    sign_round(context, 5, delay, &ManagerClient::new(&token, None)?, &NoProgress).await
+}
 
-    let round2_ans_vec = poll_for_p2p(
-        &client,
-        &context.addr,
-        context.party_num_int,
-        context.threshould + 1,
-        delay,
-        "round2",
-        context.uuid.clone(),
-    )
-    .await?;
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub async fn gg18_sign_client_round6(context: String, delay: u32, token: String) -> Result<String> {
+    sign_round(context, 6, delay, &ManagerClient::new(&token, None)?, &NoProgress).await
+}
 
-    context.round2_ans_vec = Some(round2_ans_vec);
-    context.beta_vec = Some(beta_vec);
-    context.ni_vec = Some(ni_vec);
-    context.bc1_vec = Some(bc1_vec);
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub async fn gg18_sign_client_round7(context: String, delay: u32, token: String) -> Result<String> {
+    sign_round(context, 7, delay, &ManagerClient::new(&token, None)?, &NoProgress).await
+}
 
-    Ok(serde_json::to_string(&context)?)
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub async fn gg18_sign_client_round8(context: String, delay: u32, token: String) -> Result<String> {
+    sign_round(context, 8, delay, &ManagerClient::new(&token, None)?, &NoProgress).await
 }
 
 #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
-pub async fn gg18_sign_client_round3(context: String, delay: u32, token: String) -> Result<String> {
-    let mut context = serde_json::from_str::<GG18SignClientContext>(&context)?;
-    let client = new_client_with_headers(&token)?;
-    let mut m_b_gamma_rec_vec: Vec<MessageB> = Vec::new();
-    let mut m_b_w_rec_vec: Vec<MessageB> = Vec::new();
-
-    for i in 0..context.threshould {
-        //  if signers_vec.contains(&(i as usize)) {
-        let (m_b_gamma_i, m_b_w_i): (MessageB, MessageB) =
-            serde_json::from_str(&context.round2_ans_vec.as_ref().unwrap()[i as usize])?;
-        m_b_gamma_rec_vec.push(m_b_gamma_i);
-        m_b_w_rec_vec.push(m_b_w_i);
-        //     }
-    }
+pub async fn gg18_sign_client_round9(context: String, delay: u32, token: String) -> Result<String> {
+    sign_round(context, 9, delay, &ManagerClient::new(&token, None)?, &NoProgress).await
+}
 
-    let mut alpha_vec: Vec<Scalar> = Vec::new();
-    let mut miu_vec: Vec<Scalar> = Vec::new();
+// r, s and v of the signature as hex, hex and decimal strings
+fn signature_json<P: ECPoint>(sig: &Signature<P>) -> Result<String> {
+    let sign_json = serde_json::to_string(&vec![
+        //"r",
+        sig.r.to_big_int().to_hex(),
+        //"s",
+        sig.s.to_big_int().to_hex(),
+        //"v"
+        sig.recid.to_string(),
+    ])?;
 
-    let mut j = 0;
-    for i in 1..context.threshould + 2 {
-        if i != context.party_num_int {
-            let m_b = m_b_gamma_rec_vec[j].clone();
+    Ok(sign_json)
+}
 
-            let alpha_ij_gamma = m_b.verify_proofs_get_alpha(
-                &context.party_keys.dk,
-                &context.sign_keys.as_ref().unwrap().k_i,
-            )?;
-            let m_b = m_b_w_rec_vec[j].clone();
-            let alpha_ij_wi = m_b.verify_proofs_get_alpha(
-                &context.party_keys.dk,
-                &context.sign_keys.as_ref().unwrap().k_i,
-            )?;
-            alpha_vec.push(alpha_ij_gamma.0);
-            miu_vec.push(alpha_ij_wi.0);
-            let g_w_i = Keys::update_commitments_to_xi(
-                &context.xi_com_vec.as_ref().unwrap()
-                    [usize::from(context.signers_vec.as_ref().unwrap()[usize::from(i - 1)])],
-                &context.vss_scheme_vec
-                    [usize::from(context.signers_vec.as_ref().unwrap()[usize::from(i - 1)])],
-                context.signers_vec.as_ref().unwrap()[usize::from(i - 1)],
-                &context.signers_vec.as_ref().unwrap(),
-            );
-            assert_eq!(m_b.b_proof.pk, g_w_i);
-            j += 1;
-        }
+// runs a round on the curve of the context; returns the sealed context for the
+// next round, or the signature once the last round is done
+async fn sign_round(
+    context: String,
+    round: u16,
+    delay: u32,
+    client: &ManagerClient<'_>,
+    progress: &dyn SessionProgress,
+) -> Result<String> {
+    match context_curve(&context, GG18_SIGN_CONTEXT)? {
+        Curve::Secp256k1 => {
+            sign_round_on::<secp256_k1::GE>(context, round, delay, client, progress).await
+        },
+        Curve::P256 => {
+            sign_round_on::<secp256_r1::GE>(context, round, delay, client, progress).await
+        },
     }
-    //////////////////////////////////////////////////////////////////////////////
-    let delta_i = context
-        .sign_keys
-        .as_ref()
-        .unwrap()
-        .phase2_delta_i(&alpha_vec, &context.beta_vec.as_ref().unwrap());
-    let sigma = context
-        .sign_keys
-        .as_ref()
-        .unwrap()
-        .phase2_sigma_i(&miu_vec, &context.ni_vec.as_ref().unwrap());
-
-    broadcast(
-        &client,
-        &context.addr,
-        context.party_num_int,
-        "round3",
-        serde_json::to_string(&delta_i)?,
-        context.uuid.clone(),
-    )
-    .await?;
-    let round3_ans_vec = poll_for_broadcasts(
-        &client,
-        &context.addr,
-        context.party_num_int,
-        context.threshould + 1,
-        "round3",
-        context.uuid.clone(),
+}
+
+async fn sign_round_on<P: ECPoint>(
+    context: String,
+    round: u16,
+    delay: u32,
+    client: &ManagerClient<'_>,
+    progress: &dyn SessionProgress,
+) -> Result<String> {
+    let started = Stopwatch::start();
+    let GG18SignClientContext {
+        addr,
+        uuid,
+        curve,
+        identities,
+        echo_broadcast,
+        session,
+        outgoing,
+    } = open_context_at::<GG18SignClientContext<P>>(&context, round)?;
+    session.check_round(round)?;
+
+    let network = Stopwatch::start();
+    let incoming = exchange_round(
+        client,
+        &addr,
+        session.party_num_int,
+        session.threshould + 1,
+        &uuid,
+        &identities,
+        &outgoing,
+        echo_broadcast,
         delay,
+        progress,
     )
     .await?;
-    let mut delta_vec: Vec<Scalar> = Vec::new();
-    format_vec_from_reads(
-        &round3_ans_vec,
-        context.party_num_int as usize,
-        delta_i,
-        &mut delta_vec,
-    )?;
-    let delta_inv = SignKeys::phase3_reconstruct_delta(&delta_vec);
+    let network_ms = network.elapsed_ms();
+    let (outgoing, session) = session.advance(incoming)?;
+    let context = match session.signature() {
+        Some(sig) => signature_json(sig)?,
+        None => seal_context(&GG18SignClientContext {
+            addr,
+            uuid,
+            curve,
+            identities,
+            echo_broadcast,
+            session,
+            outgoing,
+        })?,
+    };
+    progress.on_timing(&RoundTiming::new(format!("round{}", round), &started, network_ms));
+    Ok(context)
+}
 
-    context.m_b_gamma_rec_vec = Some(m_b_gamma_rec_vec);
-    context.delta_inv = Some(delta_inv);
-    context.sigma = Some(sigma);
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EddsaKeygenClientContext {
+    addr: String,
+    uuid: String,
+    identities: PartyIdentities,
+    #[serde(default)]
+    echo_broadcast: bool,
+    session: eddsa::session::KeygenSession,
+    // messages of the current round, see GG18KeygenClientContext
+    outgoing: Outgoing<eddsa::messages::KeygenMessage>,
+}
+
+impl RoundContext for EddsaKeygenClientContext {
+    const KIND: &'static str = "eddsa-keygen";
 
-    Ok(serde_json::to_string(&context)?)
+    fn uuid(&self) -> &str {
+        &self.uuid
+    }
+
+    fn round(&self) -> u16 {
+        self.session.round()
+    }
 }
 
+/// Threshold EdDSA keygen over Ed25519. Driven like the GG18 keygen, with the
+/// same manager and identity keys, but finishes after round3.
+#[allow(clippy::too_many_arguments)]
 #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
-pub async fn gg18_sign_client_round4(context: String, delay: u32, token: String) -> Result<String> {
-    let mut context = serde_json::from_str::<GG18SignClientContext>(&context)?;
-    let client = new_client_with_headers(&token)?;
-    // decommit to gamma_i
-    broadcast(
-        &client,
-        &context.addr,
-        context.party_num_int,
-        "round4",
-        serde_json::to_string(&context.decommit.as_ref().unwrap())?,
-        context.uuid.clone(),
+pub async fn eddsa_keygen_client_new_context(
+    addr: String,
+    t: usize,
+    n: usize,
+    delay: u32,
+    token: String,
+    task_id: String,
+    party_type: String,
+    identity_key: String,
+    peer_identity_keys: String,
+) -> Result<String> {
+    eddsa_keygen_setup(
+        addr,
+        t,
+        n,
+        delay,
+        &ManagerClient::new(&token, None)?,
+        task_id,
+        party_type,
+        identity_key,
+        peer_identity_keys,
+        &NoProgress,
     )
-    .await?;
-    let round4_ans_vec = poll_for_broadcasts(
-        &client,
-        &context.addr,
-        context.party_num_int,
-        context.threshould + 1,
-        "round4",
-        context.uuid.clone(),
+    .await
+}
+
+// eddsa_keygen_client_new_context, reporting the time it took to `progress`
+#[allow(clippy::too_many_arguments)]
+async fn eddsa_keygen_setup(
+    addr: String,
+    t: usize,
+    n: usize,
+    delay: u32,
+    client: &ManagerClient<'_>,
+    task_id: String,
+    party_type: String,
+    identity_key: String,
+    peer_identity_keys: String,
+    progress: &dyn SessionProgress,
+) -> Result<String> {
+    let started = Stopwatch::start();
+    let params = Parameters::new(t, n);
+    let public_key = gg18_identity_public_key(identity_key.clone())?;
+
+    let network = Stopwatch::start();
+    let PartySignup {
+        number: party_num_int,
+        uuid,
+    } = signup_keygen(client, &addr, &task_id, &party_type, &public_key).await?;
+    let identities = exchange_identities(
+        client,
+        &addr,
+        party_num_int,
+        n as u16,
+        &uuid,
+        &identity_key,
+        &peer_identity_keys,
         delay,
     )
     .await?;
+    let network_ms = network.elapsed_ms();
 
-    let mut decommit_vec: Vec<SignDecommitPhase1> = Vec::new();
-    format_vec_from_reads(
-        &round4_ans_vec,
-        context.party_num_int as usize,
-        context.decommit.clone().unwrap(),
-        &mut decommit_vec,
-    )?;
+    let (outgoing, session) =
+        eddsa::session::KeygenSession::new(params, party_num_int, uuid.clone())
+            .advance(Vec::new())?;
+    let context = seal_context(&EddsaKeygenClientContext {
+        addr,
+        uuid,
+        identities,
+        echo_broadcast: ECHO_BROADCAST.load(Ordering::SeqCst),
+        session,
+        outgoing,
+    })?;
+    progress.on_timing(&RoundTiming::new("setup".to_string(), &started, network_ms));
+    Ok(context)
+}
 
-    let decomm_i = decommit_vec.remove(usize::from(context.party_num_int - 1));
-    let _ = &context
-        .bc1_vec
-        .as_mut()
-        .unwrap()
-        .remove(usize::from(context.party_num_int - 1));
-    let b_proof_vec = (0..context.m_b_gamma_rec_vec.as_ref().unwrap().len())
-        .map(|i| &context.m_b_gamma_rec_vec.as_ref().unwrap()[i].b_proof)
-        .collect::<Vec<&DLogProof>>();
-
-    let R = SignKeys::phase4(
-        &context.delta_inv.as_ref().unwrap(),
-        &b_proof_vec,
-        decommit_vec,
-        &context.bc1_vec.as_ref().unwrap(),
-    )?;
+/// Round to call next with this context; 4 once the key store has been produced.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn eddsa_keygen_client_next_round(context: String) -> Result<u16> {
+    let context = open_context::<EddsaKeygenClientContext>(&context)?;
+    Ok(context.session.round())
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub async fn eddsa_keygen_client_round1(context: String, delay: u32, token: String) -> Result<String> {
+    eddsa_keygen_round(context, 1, delay, &ManagerClient::new(&token, None)?, &NoProgress).await
+}
 
-    // adding local g_gamma_i
-    let R = R + decomm_i.g_gamma_i * context.delta_inv.as_ref().unwrap();
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub async fn eddsa_keygen_client_round2(context: String, delay: u32, token: String) -> Result<String> {
+    eddsa_keygen_round(context, 2, delay, &ManagerClient::new(&token, None)?, &NoProgress).await
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub async fn eddsa_keygen_client_round3(context: String, delay: u32, token: String) -> Result<String> {
+    eddsa_keygen_round(context, 3, delay, &ManagerClient::new(&token, None)?, &NoProgress).await
+}
+
+// returns the sealed context for the next round, or the key store once the
+// last round is done
+async fn eddsa_keygen_round(
+    context: String,
+    round: u16,
+    delay: u32,
+    client: &ManagerClient<'_>,
+    progress: &dyn SessionProgress,
+) -> Result<String> {
+    let started = Stopwatch::start();
+    let EddsaKeygenClientContext {
+        addr,
+        uuid,
+        identities,
+        echo_broadcast,
+        session,
+        outgoing,
+    } = open_context_at::<EddsaKeygenClientContext>(&context, round)?;
+    session.check_round(round)?;
+
+    let network = Stopwatch::start();
+    let incoming = exchange_round(
+        client,
+        &addr,
+        session.party_num_int,
+        session.params.share_count as u16,
+        &uuid,
+        &identities,
+        &outgoing,
+        echo_broadcast,
+        delay,
+        progress,
+    )
+    .await?;
+    let network_ms = network.elapsed_ms();
+    let (outgoing, session) = session.advance(incoming)?;
+    let context = match session.key_store() {
+        Some(key_store) => serde_json::to_string(key_store)?,
+        None => seal_context(&EddsaKeygenClientContext {
+            addr,
+            uuid,
+            identities,
+            echo_broadcast,
+            session,
+            outgoing,
+        })?,
+    };
+    progress.on_timing(&RoundTiming::new(format!("round{}", round), &started, network_ms));
+    Ok(context)
+}
 
-    // we assume the message is already hashed (by the signer).
-    let message = &context.message[..];
-    let message_bn = BigInt::from_bytes_be(message);
-    let local_sig = LocalSignature::phase5_local_sig(
-        &context.sign_keys.as_ref().unwrap().k_i,
-        &message_bn,
-        &R,
-        &context.sigma.as_ref().unwrap(),
-        &context.y_sum,
-    );
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EddsaSignClientContext {
+    addr: String,
+    uuid: String,
+    identities: PartyIdentities,
+    #[serde(default)]
+    echo_broadcast: bool,
+    session: eddsa::session::SignSession,
+    // messages of the current round, see GG18SignClientContext
+    outgoing: Outgoing<eddsa::messages::SignMessage>,
+}
 
-    let (phase5_com, phase_5a_decom, helgamal_proof, dlog_proof_rho) =
-        local_sig.phase5a_broadcast_5b_zkproof();
+impl RoundContext for EddsaSignClientContext {
+    const KIND: &'static str = "eddsa-sign";
 
-    context.phase5_com = Some(phase5_com);
-    context.phase_5a_decom = Some(phase_5a_decom);
-    context.helgamal_proof = Some(helgamal_proof);
-    context.dlog_proof_rho = Some(dlog_proof_rho);
-    context.local_sig = Some(local_sig);
-    context.r = Some(R);
+    fn uuid(&self) -> &str {
+        &self.uuid
+    }
 
-    Ok(serde_json::to_string(&context)?)
+    fn round(&self) -> u16 {
+        self.session.round()
+    }
 }
 
+/// Signs `message_str` with a key store from `eddsa_keygen_client_round3`.
+/// Unlike GG18 the message is not expected to be hashed already, EdDSA hashes
+/// it as part of signing.
+#[allow(clippy::too_many_arguments)]
 #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
-pub async fn gg18_sign_client_round5(context: String, delay: u32, token: String) -> Result<String> {
-    let mut context = serde_json::from_str::<GG18SignClientContext>(&context)?;
-    let client = new_client_with_headers(&token)?;
-    //phase (5A)  broadcast commit
-    broadcast(
-        &client,
-        &context.addr,
-        context.party_num_int,
-        "round5",
-        serde_json::to_string(&context.phase5_com.as_ref().unwrap())?,
-        context.uuid.clone(),
+pub async fn eddsa_sign_client_new_context(
+    addr: String,
+    t: usize,
+    _n: usize,
+    key_store: String,
+    message_str: String,
+    token: String,
+    task_id: String,
+    party_type: String,
+    identity_key: String,
+    peer_identity_keys: String,
+) -> Result<String> {
+    eddsa_sign_setup(
+        addr,
+        t,
+        _n,
+        key_store,
+        message_str,
+        &ManagerClient::new(&token, None)?,
+        task_id,
+        party_type,
+        identity_key,
+        peer_identity_keys,
+        &NoProgress,
     )
-    .await?;
-    let round5_ans_vec = poll_for_broadcasts(
-        &client,
-        &context.addr,
-        context.party_num_int,
-        context.threshould + 1,
-        "round5",
-        context.uuid.clone(),
-        delay,
+    .await
+}
+
+// eddsa_sign_client_new_context, reporting the time it took to `progress`
+#[allow(clippy::too_many_arguments)]
+async fn eddsa_sign_setup(
+    addr: String,
+    t: usize,
+    _n: usize,
+    key_store: String,
+    message_str: String,
+    client: &ManagerClient<'_>,
+    task_id: String,
+    party_type: String,
+    identity_key: String,
+    peer_identity_keys: String,
+    progress: &dyn SessionProgress,
+) -> Result<String> {
+    let started = Stopwatch::start();
+    let key_store: EddsaKeyStore = serde_json::from_str(&key_store)?;
+    let message = message_bytes(&message_str);
+
+    let public_key = gg18_identity_public_key(identity_key.clone())?;
+
+    let network = Stopwatch::start();
+    //signup:
+    let PartySignup {
+        number: party_num_int,
+        uuid,
+    } = signup_sign(client, &addr, &task_id, &party_type, &public_key).await?;
+    let identities = exchange_identities(
+        client,
+        &addr,
+        party_num_int,
+        t as u16 + 1,
+        &uuid,
+        &identity_key,
+        &peer_identity_keys,
+        IDENTITY_POLL_DELAY,
     )
     .await?;
+    let network_ms = network.elapsed_ms();
 
-    let mut commit5a_vec: Vec<Phase5Com1> = Vec::new();
-    format_vec_from_reads(
-        &round5_ans_vec,
-        context.party_num_int as usize,
-        context.phase5_com.clone().unwrap(),
-        &mut commit5a_vec,
-    )?;
+    let (outgoing, session) =
+        eddsa::session::SignSession::new(key_store, t as u16, party_num_int, uuid.clone(), message)
+            .advance(Vec::new())?;
+    let context = seal_context(&EddsaSignClientContext {
+        addr,
+        uuid,
+        identities,
+        echo_broadcast: ECHO_BROADCAST.load(Ordering::SeqCst),
+        session,
+        outgoing,
+    })?;
+    progress.on_timing(&RoundTiming::new("setup".to_string(), &started, network_ms));
+    Ok(context)
+}
 
-    context.commit5a_vec = Some(commit5a_vec);
+/// Round to call next with this context; 5 once the signature has been produced.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn eddsa_sign_client_next_round(context: String) -> Result<u16> {
+    let context = open_context::<EddsaSignClientContext>(&context)?;
+    Ok(context.session.round())
+}
 
-    Ok(serde_json::to_string(&context)?)
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub async fn eddsa_sign_client_round0(context: String, delay: u32, token: String) -> Result<String> {
+    eddsa_sign_round(context, 0, delay, &ManagerClient::new(&token, None)?, &NoProgress).await
 }
 
 #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
-pub async fn gg18_sign_client_round6(context: String, delay: u32, token: String) -> Result<String> {
-    let mut context = serde_json::from_str::<GG18SignClientContext>(&context)?;
-    let client = new_client_with_headers(&token)?;
-    //phase (5B)  broadcast decommit and (5B) ZK proof
-    broadcast(
-        &client,
-        &context.addr,
-        context.party_num_int,
-        "round6",
-        serde_json::to_string(&(
-            context.phase_5a_decom.clone().unwrap(),
-            context.helgamal_proof.clone().unwrap(),
-            context.dlog_proof_rho.clone().unwrap(),
-        ))?,
-        context.uuid.clone(),
-    )
-    .await?;
-    let round6_ans_vec = poll_for_broadcasts(
-        &client,
-        &context.addr,
-        context.party_num_int,
-        context.threshould + 1,
-        "round6",
-        context.uuid.clone(),
+pub async fn eddsa_sign_client_round1(context: String, delay: u32, token: String) -> Result<String> {
+    eddsa_sign_round(context, 1, delay, &ManagerClient::new(&token, None)?, &NoProgress).await
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub async fn eddsa_sign_client_round2(context: String, delay: u32, token: String) -> Result<String> {
+    eddsa_sign_round(context, 2, delay, &ManagerClient::new(&token, None)?, &NoProgress).await
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub async fn eddsa_sign_client_round3(context: String, delay: u32, token: String) -> Result<String> {
+    eddsa_sign_round(context, 3, delay, &ManagerClient::new(&token, None)?, &NoProgress).await
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub async fn eddsa_sign_client_round4(context: String, delay: u32, token: String) -> Result<String> {
+    eddsa_sign_round(context, 4, delay, &ManagerClient::new(&token, None)?, &NoProgress).await
+}
+
+// returns the sealed context for the next round, or once the last round is
+// done the 64 byte signature as a hex JSON string
+async fn eddsa_sign_round(
+    context: String,
+    round: u16,
+    delay: u32,
+    client: &ManagerClient<'_>,
+    progress: &dyn SessionProgress,
+) -> Result<String> {
+    let started = Stopwatch::start();
+    let EddsaSignClientContext {
+        addr,
+        uuid,
+        identities,
+        echo_broadcast,
+        session,
+        outgoing,
+    } = open_context_at::<EddsaSignClientContext>(&context, round)?;
+    session.check_round(round)?;
+
+    let network = Stopwatch::start();
+    let incoming = exchange_round(
+        client,
+        &addr,
+        session.party_num_int,
+        session.threshold + 1,
+        &uuid,
+        &identities,
+        &outgoing,
+        echo_broadcast,
         delay,
+        progress,
     )
     .await?;
+    let network_ms = network.elapsed_ms();
+    let (outgoing, session) = session.advance(incoming)?;
+    let context = match session.signature() {
+        Some(sig) => serde_json::to_string(&hex::encode(sig.to_bytes()))?,
+        None => seal_context(&EddsaSignClientContext {
+            addr,
+            uuid,
+            identities,
+            echo_broadcast,
+            session,
+            outgoing,
+        })?,
+    };
+    progress.on_timing(&RoundTiming::new(format!("round{}", round), &started, network_ms));
+    Ok(context)
+}
 
-    let mut decommit5a_and_elgamal_and_dlog_vec: Vec<(Phase5ADecom1, HomoELGamalProof, DLogProof)> =
-        Vec::new();
-    format_vec_from_reads(
-        &round6_ans_vec,
-        context.party_num_int as usize,
-        (
-            context.phase_5a_decom.clone().unwrap(),
-            context.helgamal_proof.clone().unwrap(),
-            context.dlog_proof_rho.clone().unwrap(),
-        ),
-        &mut decommit5a_and_elgamal_and_dlog_vec,
-    )?;
-    let decommit5a_and_elgamal_and_dlog_vec_includes_i =
-        decommit5a_and_elgamal_and_dlog_vec.clone();
-    decommit5a_and_elgamal_and_dlog_vec.remove(usize::from(context.party_num_int - 1));
-    context
-        .commit5a_vec
-        .as_mut()
-        .unwrap()
-        .remove(usize::from(context.party_num_int - 1));
-    let phase_5a_decomm_vec = (0..context.threshould)
-        .map(|i| decommit5a_and_elgamal_and_dlog_vec[i as usize].0.clone())
-        .collect::<Vec<Phase5ADecom1>>();
-    let phase_5a_elgamal_vec = (0..context.threshould)
-        .map(|i| decommit5a_and_elgamal_and_dlog_vec[i as usize].1.clone())
-        .collect::<Vec<HomoELGamalProof>>();
-    let phase_5a_dlog_vec = (0..context.threshould)
-        .map(|i| decommit5a_and_elgamal_and_dlog_vec[i as usize].2.clone())
-        .collect::<Vec<DLogProof>>();
-    let (phase5_com2, phase_5d_decom2) = context.local_sig.clone().unwrap().phase5c(
-        &phase_5a_decomm_vec,
-        &context.commit5a_vec.as_ref().unwrap(),
-        &phase_5a_elgamal_vec,
-        &phase_5a_dlog_vec,
-        &context.phase_5a_decom.as_ref().unwrap().V_i,
-        &context.r.as_ref().unwrap(),
-    )?;
+/// Hex of the compressed public key of a GG18 key store.
+pub fn gg18_public_key(key_store: &str, curve: Curve) -> Result<String> {
+    let public_key = match curve {
+        Curve::Secp256k1 => serde_json::from_str::<KeyStore<secp256_k1::GE>>(key_store)?
+            .5
+            .to_bytes(true),
+        Curve::P256 => serde_json::from_str::<KeyStore<secp256_r1::GE>>(key_store)?
+            .5
+            .to_bytes(true),
+    };
+    Ok(hex::encode(public_key))
+}
+
+/// Hex of the 32 byte RFC 8032 public key of an EdDSA key store.
+pub fn eddsa_public_key(key_store: &str) -> Result<String> {
+    let key_store: EddsaKeyStore = serde_json::from_str(key_store)?;
+    Ok(hex::encode(key_store.4.to_bytes(true)))
+}
+
+// the message as the sign contexts take it: hex if it decodes, else the raw bytes
+fn message_bytes(message_str: &str) -> Vec<u8> {
+    match hex::decode(message_str) {
+        Ok(x) => x,
+        Err(_e) => message_str.as_bytes().to_vec(),
+    }
+}
 
-    context.phase5_com2 = Some(phase5_com2);
-    context.phase_5d_decom2 = Some(phase_5d_decom2);
-    context.decommit5a_and_elgamal_and_dlog_vec_includes_i =
-        Some(decommit5a_and_elgamal_and_dlog_vec_includes_i);
+// a signature scalar as produced by signature_json, in [1, q)
+fn signature_scalar<S: ECScalar>(hex: &str) -> Result<S> {
+    let n = BigInt::from_str_radix(hex, 16).map_err(|_e| TssError::InvalidSig)?;
+    if n == BigInt::from(0u32) || n >= S::q() {
+        return Err(TssError::InvalidSig);
+    }
+    Ok(ECScalar::from(&n))
+}
 
-    Ok(serde_json::to_string(&context)?)
+fn gg18_verify_on<P: ECPoint>(y: P, message: &[u8], r: &str, s: &str) -> Result<bool> {
+    let sig = Signature::<P> {
+        r: signature_scalar(r)?,
+        s: signature_scalar(s)?,
+        recid: 0,
+    };
+    // a hash that is 0 mod q has no scalar, nothing can have signed it
+    let message = BigInt::from_bytes_be(message);
+    if &message % P::Scalar::q() == BigInt::from(0u32) {
+        return Ok(false);
+    }
+    Ok(verify(&sig, &y, &message).is_ok())
 }
 
+/// Checks a GG18 signature, r and s in hex as returned by the last sign round,
+/// against the compressed public key in hex. Like signing, expects
+/// `message_str` to be the hash of the message. Malformed input is an error,
+/// a well formed signature that does not verify is `false`.
 #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
-pub async fn gg18_sign_client_round7(context: String, delay: u32, token: String) -> Result<String> {
-    let mut context = serde_json::from_str::<GG18SignClientContext>(&context)?;
-    let client = new_client_with_headers(&token)?;
-    //////////////////////////////////////////////////////////////////////////////
-    broadcast(
+pub fn gg18_verify_signature(
+    public_key: String,
+    message_str: String,
+    r: String,
+    s: String,
+    curve: String,
+) -> Result<bool> {
+    let public_key = hex::decode(public_key).map_err(|_e| TssError::InvalidPublicKey)?;
+    let message = message_bytes(&message_str);
+    match curve.parse()? {
+        Curve::Secp256k1 => {
+            // the curv point parser does not take SEC1 encodings
+            let pk = secp256k1::PublicKey::parse_slice(&public_key, None)
+                .map_err(|_e| TssError::InvalidPublicKey)?
+                .serialize();
+            let y = secp256_k1::GE::from_coor(
+                &BigInt::from_bytes_be(&pk[1..33]),
+                &BigInt::from_bytes_be(&pk[33..]),
+            );
+            gg18_verify_on(y, &message, &r, &s)
+        }
+        Curve::P256 => {
+            let y = secp256_r1::GE::from_bytes(&public_key)?;
+            gg18_verify_on(y, &message, &r, &s)
+        }
+    }
+}
+
+/// Checks a 64 byte EdDSA signature in hex against the RFC 8032 public key in
+/// hex. `message_str` is decoded as `eddsa_sign_client_new_context` does.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn eddsa_verify_signature(
+    public_key: String,
+    message_str: String,
+    signature: String,
+) -> Result<bool> {
+    let public_key = hex::decode(public_key).map_err(|_e| TssError::InvalidPublicKey)?;
+    let y = crate::curv::elliptic::curves::ed25519::GE::from_bytes(&public_key)?;
+    let signature = hex::decode(signature).map_err(|_e| TssError::InvalidSig)?;
+    let signature = eddsa::party_i::Signature::from_bytes(&signature)?;
+    Ok(eddsa::party_i::verify(&signature, &y, &message_bytes(&message_str)).is_ok())
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct KeygenResult {
+    pub key_store: String,
+    // hex of the compressed public key of the group
+    pub public_key: String,
+    // compute and network time of each step, in order
+    #[serde(default)]
+    pub timings: Vec<RoundTiming>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SignResult {
+    pub r: String,
+    pub s: String,
+    pub v: String,
+    #[serde(default)]
+    pub timings: Vec<RoundTiming>,
+}
+
+impl SignResult {
+    fn from_json(signature: &str) -> Result<SignResult> {
+        let [r, s, v]: [String; 3] = serde_json::from_str(signature)?;
+        Ok(SignResult {
+            r,
+            s,
+            v,
+            timings: Vec::new(),
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct EddsaSignResult {
+    // hex of the 64 byte signature
+    pub signature: String,
+    #[serde(default)]
+    pub timings: Vec<RoundTiming>,
+}
+
+/// Runs a whole GG18 keygen, from signup to the key store, instead of the
+/// caller driving `gg18_keygen_client_round1` to `round5` itself. If the
+/// manager rejects `token` partway, `token_provider` is asked for a new one
+/// and the request is sent again. The Paillier settings are those of
+/// `gg18_keygen_client_new_context_with_paillier`.
+#[allow(clippy::too_many_arguments)]
+pub async fn gg18_keygen_client_run(
+    addr: String,
+    t: usize,
+    n: usize,
+    delay: u32,
+    token: String,
+    task_id: String,
+    party_type: String,
+    identity_key: String,
+    peer_identity_keys: String,
+    curve: String,
+    paillier_modulus_bits: usize,
+    safe_primes: bool,
+    progress: &dyn SessionProgress,
+    token_provider: Option<&dyn TokenProvider>,
+) -> Result<KeygenResult> {
+    let recorder = TimingRecorder::new(progress);
+    // one client for the whole session, so a refreshed token is kept for the later rounds
+    let client = ManagerClient::new(&token, token_provider)?;
+    let mut context = keygen_setup(
+        addr,
+        t,
+        n,
+        delay,
         &client,
-        &context.addr,
-        context.party_num_int,
-        "round7",
-        serde_json::to_string(&context.phase5_com2.as_ref().unwrap())?,
-        context.uuid.clone(),
+        task_id,
+        party_type,
+        identity_key,
+        peer_identity_keys,
+        curve.clone(),
+        paillier_modulus_bits,
+        safe_primes,
+        &recorder,
     )
     .await?;
-    let round7_ans_vec = poll_for_broadcasts(
+    for round in 1..=5 {
+        recorder.on_round(round, 5);
+        context = keygen_round(context, round, delay, &client, &recorder).await?;
+    }
+
+    Ok(KeygenResult {
+        public_key: gg18_public_key(&context, curve.parse()?)?,
+        key_store: context,
+        timings: recorder.into_timings(),
+    })
+}
+
+/// Runs a whole GG18 sign, from signup to the signature, instead of the
+/// caller driving `gg18_sign_client_round0` to `round9` itself.
+#[allow(clippy::too_many_arguments)]
+pub async fn gg18_sign_client_run(
+    addr: String,
+    t: usize,
+    n: usize,
+    delay: u32,
+    key_store: String,
+    message_str: String,
+    token: String,
+    task_id: String,
+    party_type: String,
+    identity_key: String,
+    peer_identity_keys: String,
+    curve: String,
+    progress: &dyn SessionProgress,
+    token_provider: Option<&dyn TokenProvider>,
+) -> Result<SignResult> {
+    let recorder = TimingRecorder::new(progress);
+    let client = ManagerClient::new(&token, token_provider)?;
+    let mut context = sign_setup(
+        addr,
+        t,
+        n,
+        key_store,
+        message_str,
         &client,
-        &context.addr,
-        context.party_num_int,
-        context.threshould + 1,
-        "round7",
-        context.uuid.clone(),
-        delay,
+        task_id,
+        party_type,
+        identity_key,
+        peer_identity_keys,
+        curve,
+        &recorder,
     )
     .await?;
-
-    let mut commit5c_vec: Vec<Phase5Com2> = Vec::new();
-    format_vec_from_reads(
-        &round7_ans_vec,
-        context.party_num_int as usize,
-        context.phase5_com2.clone().unwrap(),
-        &mut commit5c_vec,
-    )?;
-
-    context.commit5c_vec = Some(commit5c_vec);
-
-    Ok(serde_json::to_string(&context)?)
+    for round in 0..=9 {
+        recorder.on_round(round + 1, 10);
+        context = sign_round(context, round, delay, &client, &recorder).await?;
+    }
+    Ok(SignResult {
+        timings: recorder.into_timings(),
+        ..SignResult::from_json(&context)?
+    })
 }
 
-#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
-pub async fn gg18_sign_client_round8(context: String, delay: u32, token: String) -> Result<String> {
-    let mut context = serde_json::from_str::<GG18SignClientContext>(&context)?;
-    let client = new_client_with_headers(&token)?;
-    //phase (5B)  broadcast decommit and (5B) ZK proof
-    broadcast(
+/// Runs a whole EdDSA keygen, see `gg18_keygen_client_run`.
+#[allow(clippy::too_many_arguments)]
+pub async fn eddsa_keygen_client_run(
+    addr: String,
+    t: usize,
+    n: usize,
+    delay: u32,
+    token: String,
+    task_id: String,
+    party_type: String,
+    identity_key: String,
+    peer_identity_keys: String,
+    progress: &dyn SessionProgress,
+    token_provider: Option<&dyn TokenProvider>,
+) -> Result<KeygenResult> {
+    let recorder = TimingRecorder::new(progress);
+    let client = ManagerClient::new(&token, token_provider)?;
+    let mut context = eddsa_keygen_setup(
+        addr,
+        t,
+        n,
+        delay,
         &client,
-        &context.addr,
-        context.party_num_int,
-        "round8",
-        serde_json::to_string(&context.phase_5d_decom2.as_ref().unwrap())?,
-        context.uuid.clone(),
+        task_id,
+        party_type,
+        identity_key,
+        peer_identity_keys,
+        &recorder,
     )
     .await?;
-    let round8_ans_vec = poll_for_broadcasts(
+    for round in 1..=3 {
+        recorder.on_round(round, 3);
+        context = eddsa_keygen_round(context, round, delay, &client, &recorder).await?;
+    }
+
+    Ok(KeygenResult {
+        public_key: eddsa_public_key(&context)?,
+        key_store: context,
+        timings: recorder.into_timings(),
+    })
+}
+
+/// Runs a whole EdDSA sign, see `gg18_sign_client_run`.
+#[allow(clippy::too_many_arguments)]
+pub async fn eddsa_sign_client_run(
+    addr: String,
+    t: usize,
+    n: usize,
+    delay: u32,
+    key_store: String,
+    message_str: String,
+    token: String,
+    task_id: String,
+    party_type: String,
+    identity_key: String,
+    peer_identity_keys: String,
+    progress: &dyn SessionProgress,
+    token_provider: Option<&dyn TokenProvider>,
+) -> Result<EddsaSignResult> {
+    let recorder = TimingRecorder::new(progress);
+    let client = ManagerClient::new(&token, token_provider)?;
+    let mut context = eddsa_sign_setup(
+        addr,
+        t,
+        n,
+        key_store,
+        message_str,
         &client,
-        &context.addr,
-        context.party_num_int,
-        context.threshould + 1,
-        "round8",
-        context.uuid.clone(),
-        delay,
+        task_id,
+        party_type,
+        identity_key,
+        peer_identity_keys,
+        &recorder,
     )
     .await?;
+    for round in 0..=4 {
+        recorder.on_round(round + 1, 5);
+        context = eddsa_sign_round(context, round, delay, &client, &recorder).await?;
+    }
+    Ok(EddsaSignResult {
+        signature: serde_json::from_str(&context)?,
+        timings: recorder.into_timings(),
+    })
+}
 
-    let mut decommit5d_vec: Vec<Phase5DDecom2> = Vec::new();
-    format_vec_from_reads(
-        &round8_ans_vec,
-        context.party_num_int as usize,
-        context.phase_5d_decom2.clone().unwrap(),
-        &mut decommit5d_vec,
-    )?;
+// sends our messages for the round and waits for those of the other n - 1 parties
+#[allow(clippy::too_many_arguments)]
+async fn exchange_round<M: RoundMessage>(
+    client: &ManagerClient<'_>,
+    addr: &str,
+    party_num_int: u16,
+    n: u16,
+    uuid: &str,
+    identities: &PartyIdentities,
+    outgoing: &Outgoing<M>,
+    echo_broadcast: bool,
+    delay: u32,
+    progress: &dyn SessionProgress,
+) -> Result<Vec<(u16, M)>> {
+    let round = format!("round{}", outgoing.round);
+    let trace = RoundTrace::start(uuid, party_num_int, &round);
+    let mut own_broadcast = None;
+    // broadcasts are signed for recipient 0
+    let (ans_vec, to) = match &outgoing.broadcast {
+        Some(msg) => {
+            let payload = msg.to_json()?;
+            let data = identities.sign(
+                PayloadKind::Round,
+                uuid,
+                party_num_int,
+                0,
+                outgoing.round,
+                payload.clone(),
+            )?;
+            broadcast(client, addr, party_num_int, &round, data, uuid.to_string()).await?;
+            let ans_vec = poll_for_broadcasts(
+                client,
+                addr,
+                party_num_int,
+                n,
+                &round,
+                uuid.to_string(),
+                delay,
+                &trace,
+                &|party| progress.on_waiting(party),
+            )
+            .await?;
+            own_broadcast = Some(payload);
+            (ans_vec, 0)
+        }
+        None => {
+            for (to, msg) in outgoing.p2p.iter() {
+                let data = identities.sign(
+                    PayloadKind::Round,
+                    uuid,
+                    party_num_int,
+                    *to,
+                    outgoing.round,
+                    msg.to_json()?,
+                )?;
+                sendp2p(client, addr, party_num_int, *to, &round, data, uuid.to_string()).await?;
+            }
+            let ans_vec = poll_for_p2p(
+                client,
+                addr,
+                party_num_int,
+                n,
+                delay,
+                &round,
+                uuid.to_string(),
+                &trace,
+                &|party| progress.on_waiting(party),
+            )
+            .await?;
+            (ans_vec, party_num_int)
+        }
+    };
 
-    let phase_5a_decomm_vec_includes_i = (0..=context.threshould)
-        .map(|i| {
-            context
-                .decommit5a_and_elgamal_and_dlog_vec_includes_i
-                .clone()
-                .unwrap()[i as usize]
-                .0
-                .clone()
+    // the manager answers in party order, skipping ourselves
+    let payloads = (1..=n)
+        .filter(|i| *i != party_num_int)
+        .zip(ans_vec.iter())
+        .map(|(from, data)| {
+            let payload = identities.open(PayloadKind::Round, uuid, from, to, outgoing.round, data)?;
+            Ok((from, payload))
         })
-        .collect::<Vec<Phase5ADecom1>>();
-    let s_i = context.local_sig.clone().unwrap().phase5d(
-        &decommit5d_vec,
-        &context.commit5c_vec.as_ref().unwrap(),
-        &phase_5a_decomm_vec_includes_i,
-    )?;
-
-    context.s_i = Some(s_i);
+        .collect::<Result<Vec<(u16, String)>>>()?;
+    trace.finish();
+
+    if let Some(payload) = own_broadcast.filter(|_| echo_broadcast) {
+        let mut seen = payloads.clone();
+        seen.push((party_num_int, payload));
+        let echo = EchoMessage::new(uuid, outgoing.round, &seen);
+        echo_round(
+            client,
+            addr,
+            party_num_int,
+            n,
+            uuid,
+            identities,
+            echo,
+            delay,
+            progress,
+        )
+        .await?;
+    }
 
-    Ok(serde_json::to_string(&context)?)
+    payloads
+        .into_iter()
+        .map(|(from, payload)| Ok((from, M::from_json(outgoing.round, &payload)?)))
+        .collect()
 }
 
-#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
-pub async fn gg18_sign_client_round9(context: String, delay: u32, token: String) -> Result<String> {
-    let context = serde_json::from_str::<GG18SignClientContext>(&context)?;
-    let client = new_client_with_headers(&token)?;
-    //////////////////////////////////////////////////////////////////////////////
-    broadcast(
-        &client,
-        &context.addr,
-        context.party_num_int,
-        "round9",
-        serde_json::to_string(&context.s_i.as_ref().unwrap())?,
-        context.uuid.clone(),
+// sends the hash of what we saw in a broadcast round and checks that every
+// other party saw the same
+#[allow(clippy::too_many_arguments)]
+async fn echo_round(
+    client: &ManagerClient<'_>,
+    addr: &str,
+    party_num_int: u16,
+    n: u16,
+    uuid: &str,
+    identities: &PartyIdentities,
+    echo: EchoMessage,
+    delay: u32,
+    progress: &dyn SessionProgress,
+) -> Result<()> {
+    let round = format!("echo{}", echo.round);
+    let trace = RoundTrace::start(uuid, party_num_int, &round);
+    let data = identities.sign(
+        PayloadKind::Echo,
+        uuid,
+        party_num_int,
+        0,
+        echo.round,
+        serde_json::to_string(&echo)?,
+    )?;
+    broadcast(client, addr, party_num_int, &round, data, uuid.to_string()).await?;
+    let ans_vec = poll_for_broadcasts(
+        client,
+        addr,
+        party_num_int,
+        n,
+        &round,
+        uuid.to_string(),
+        delay,
+        &trace,
+        &|party| progress.on_waiting(party),
     )
     .await?;
-    let round9_ans_vec = poll_for_broadcasts(
-        &client,
-        &context.addr,
-        context.party_num_int,
-        context.threshould + 1,
-        "round9",
-        context.uuid.clone(),
+
+    let echoes = (1..=n)
+        .filter(|i| *i != party_num_int)
+        .zip(ans_vec.iter())
+        .map(|(from, data)| {
+            let payload = identities.open(PayloadKind::Echo, uuid, from, 0, echo.round, data)?;
+            Ok((from, serde_json::from_str(&payload)?))
+        })
+        .collect::<Result<Vec<(u16, EchoMessage)>>>()?;
+    echo.check(&echoes)?;
+    trace.finish();
+    Ok(())
+}
+
+// collects the identity keys the other parties registered at signup and
+// checks them against the pinned ones (a JSON array of hex public keys)
+#[allow(clippy::too_many_arguments)]
+async fn exchange_identities(
+    client: &ManagerClient<'_>,
+    addr: &str,
+    party_num_int: u16,
+    n: u16,
+    uuid: &str,
+    identity_key: &str,
+    peer_identity_keys: &str,
+    delay: u32,
+) -> Result<PartyIdentities> {
+    let pinned: Vec<String> = serde_json::from_str(peer_identity_keys)?;
+    let trace = RoundTrace::start(uuid, party_num_int, "identity");
+    let ans_vec = poll_for_broadcasts(
+        client,
+        addr,
+        party_num_int,
+        n,
+        "identity",
+        uuid.to_string(),
         delay,
+        &trace,
+        &|_| {},
     )
     .await?;
+    let roster = (1..=n).filter(|i| *i != party_num_int).zip(ans_vec).collect();
+    let identities = PartyIdentities::new(identity_key, &pinned, roster)?;
+    trace.finish();
+    Ok(identities)
+}
 
-    let mut s_i_vec: Vec<Scalar> = Vec::new();
-    format_vec_from_reads(
-        &round9_ans_vec,
-        context.party_num_int as usize,
-        context.s_i.unwrap(),
-        &mut s_i_vec,
-    )?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::identity::gg18_identity_key_new;
 
-    s_i_vec.remove(usize::from(context.party_num_int - 1));
-    let sig = context
-        .local_sig
-        .clone()
-        .unwrap()
-        .output_signature(&s_i_vec)?;
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
 
-    let sign_json = serde_json::to_string(&vec![
-        //"r",
-        sig.r.to_big_int().to_hex(),
-        //"s",
-        sig.s.to_big_int().to_hex(),
-        //"v"
-        sig.recid.to_string(),
-    ])?;
-    // crate::console_log!("sign_json: {:?}", sign_json);
+    struct CountingProgress(RefCell<Vec<String>>);
 
-    check_sig(
-        &sig.r,
-        &sig.s,
-        &context.local_sig.clone().unwrap().m,
-        &context.y_sum.clone(),
-    )?;
+    impl SessionProgress for CountingProgress {
+        fn on_round(&self, _round: u16, _rounds: u16) {}
+        fn on_waiting(&self, _party: u16) {}
+        fn on_timing(&self, timing: &RoundTiming) {
+            self.0.borrow_mut().push(timing.step.clone());
+        }
+    }
 
-    Ok(sign_json)
-}
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_timing_recorder() {
+        let progress = CountingProgress(RefCell::new(Vec::new()));
+        let recorder = TimingRecorder::new(&progress);
+        let started = Stopwatch::start();
+        recorder.on_timing(&RoundTiming::new("setup".to_string(), &started, 0));
+        // the network time is never more than the whole step
+        recorder.on_timing(&RoundTiming::new("round1".to_string(), &started, u64::MAX));
+
+        let timings = recorder.into_timings();
+        assert_eq!(*progress.0.borrow(), vec!["setup", "round1"]);
+        assert_eq!(timings.len(), 2);
+        assert_eq!(timings[1].compute_ms, 0);
+
+        let json = serde_json::to_value(&timings[1]).unwrap();
+        assert_eq!(json["step"], "round1");
+        assert!(json.get("computeMs").is_some() && json.get("networkMs").is_some());
+
+        // results saved before the timings were added still parse
+        let result: EddsaSignResult = serde_json::from_str(r#"{"signature":"00"}"#).unwrap();
+        assert!(result.timings.is_empty());
+    }
 
-fn format_vec_from_reads<'a, T: serde::Deserialize<'a> + Clone>(
-    ans_vec: &'a [String],
-    party_num: usize,
-    value_i: T,
-    new_vec: &'a mut Vec<T>,
-) -> Result<()> {
-    let mut j = 0;
-    for i in 1..ans_vec.len() + 2 {
-        if i == party_num {
-            new_vec.push(value_i.clone());
-        } else {
-            let value_j: T = serde_json::from_str(&ans_vec[j])?;
-            new_vec.push(value_j);
-            j += 1;
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_keygen_paillier_modulus_bits() {
+        let params = keygen_parameters(1, 2, 3072, false).unwrap();
+        let (outgoing, _) = KeygenSession::<secp256_k1::GE>::new(params, 1, "uuid".to_string())
+            .advance(Vec::new())
+            .unwrap();
+        match outgoing.broadcast {
+            Some(KeygenMessage::Round1(bc1)) => assert_eq!(bc1.e.n.bits(), 3072),
+            _ => panic!("round1 should broadcast the Paillier key"),
+        }
+
+        // below the protocol minimum, above what a phone can generate, or odd
+        for bits in [1024, 16384, 3073] {
+            assert!(keygen_parameters(1, 2, bits, false).is_err());
         }
     }
-    Ok(())
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_context_echo_broadcast() {
+        let key = gg18_identity_key_new().unwrap();
+        let peer = gg18_identity_public_key(gg18_identity_key_new().unwrap()).unwrap();
+        let roster = vec![(2, peer.clone())];
+        let identities = PartyIdentities::new(&key, &[peer], roster).unwrap();
+        let (outgoing, session) =
+            eddsa::session::KeygenSession::new(Parameters::new(1, 2), 1, "uuid".to_string())
+                .advance(Vec::new())
+                .unwrap();
+        let context = EddsaKeygenClientContext {
+            addr: String::new(),
+            uuid: "uuid".to_string(),
+            identities,
+            echo_broadcast: true,
+            session,
+            outgoing,
+        };
+
+        // the setting travels with the context, whatever the process default
+        let mut json = serde_json::to_value(&context).unwrap();
+        let opened: EddsaKeygenClientContext = serde_json::from_value(json.clone()).unwrap();
+        assert!(opened.echo_broadcast);
+
+        // contexts saved before it was stored there run without echo rounds
+        json.as_object_mut().unwrap().remove("echo_broadcast");
+        let opened: EddsaKeygenClientContext = serde_json::from_value(json).unwrap();
+        assert!(!opened.echo_broadcast);
+    }
 }
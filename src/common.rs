@@ -2,11 +2,13 @@
 
 use crate::curv::elliptic::curves::traits::{ECPoint, ECScalar};
 use crate::errors::TssError;
+use crate::logging::{self, RoundTrace};
 #[cfg(target_arch = "wasm32")]
-use crate::log;
+use wasm_bindgen::prelude::*;
 
-use aes_gcm::aead::{Aead, NewAead};
+use aes_gcm::aead::{Aead, NewAead, Payload};
 use aes_gcm::{Aes256Gcm, Nonce};
+use hmac::{Hmac, Mac, NewMac};
 use rand::{rngs::OsRng, RngCore};
 
 use crate::curv::{
@@ -15,9 +17,15 @@ use crate::curv::{
     elliptic::curves::secp256_k1::{Secp256k1Point as Point, Secp256k1Scalar as Scalar},
 };
 
-use reqwest::Client;
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
+use reqwest::{Client, StatusCode};
 use serde::{Deserialize, Serialize};
+use sha2::Sha256;
 use sha3::{Digest, Keccak256};
+use std::cell::RefCell;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::RwLock;
 
 use crate::errors::Result;
 
@@ -25,11 +33,20 @@ pub type Key = String;
 
 #[allow(dead_code)]
 pub const AES_KEY_BYTES_LEN: usize = 32;
+pub const AES_NONCE_BYTES_LEN: usize = 12;
+
+/// Version written by `aes_encrypt`: ciphertext authenticated together with
+/// associated data. Version 0 packs predate it and carry no associated data.
+pub const AEAD_VERSION: u32 = 1;
 
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub struct AEAD {
+    #[serde(default)]
+    pub version: u32,
     pub ciphertext: Vec<u8>,
-    pub tag: Vec<u8>,
+    // older packs called the nonce "tag"; the GCM tag is part of the ciphertext
+    #[serde(alias = "tag")]
+    pub nonce: Vec<u8>,
 }
 
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
@@ -59,39 +76,61 @@ pub struct Params {
 #[derive(Debug, serde_derive::Deserialize, serde_derive::Serialize)]
 pub struct TaskRequest {
     pub task_id: String,
-    pub party_type: String
+    pub party_type: String,
+    // hex encoded public identity key, registered for the assigned party number
+    pub identity_key: String,
 }
 
+/// Encrypts `plaintext` under `key`, authenticating `aad` along with it.
 #[allow(dead_code)]
-pub fn aes_encrypt(key: &[u8], plaintext: &[u8]) -> Result<AEAD> {
+pub fn aes_encrypt(key: &[u8], plaintext: &[u8], aad: &[u8]) -> Result<AEAD> {
     let aes_key = aes_gcm::Key::from_slice(key);
     let cipher = Aes256Gcm::new(aes_key);
 
-    let mut nonce = [0u8; 12];
+    let mut nonce = [0u8; AES_NONCE_BYTES_LEN];
     let mut rng = OsRng::new()?;
     rng.fill_bytes(&mut nonce);
     let nonce = Nonce::from_slice(&nonce);
 
     let ciphertext = cipher
-        .encrypt(nonce, plaintext)
+        .encrypt(nonce, Payload { msg: plaintext, aad })
         .map_err(|_e| TssError::UnknownError {
             msg: ("encryption failure!").to_string(),
             line: (line!()),
         })?;
     Ok(AEAD {
+        version: AEAD_VERSION,
         ciphertext: ciphertext,
-        tag: nonce.to_vec(),
+        nonce: nonce.to_vec(),
     })
 }
 
+/// Decrypts a pack made by `aes_encrypt` with the same `aad`. Version 0 packs
+/// are only accepted where no associated data is expected.
 #[allow(dead_code)]
-pub fn aes_decrypt(key: &[u8], aead_pack: AEAD) -> Result<Vec<u8>> {
+pub fn aes_decrypt(key: &[u8], aead_pack: AEAD, aad: &[u8]) -> Result<Vec<u8>> {
+    let supported = match aead_pack.version {
+        0 => aad.is_empty(),
+        v => v == AEAD_VERSION,
+    };
+    if !supported || aead_pack.nonce.len() != AES_NONCE_BYTES_LEN {
+        return Err(TssError::UnknownError {
+            msg: ("aes_decrypt: unsupported pack").to_string(),
+            line: (line!()),
+        });
+    }
     let aes_key = aes_gcm::Key::from_slice(key);
-    let nonce = Nonce::from_slice(&aead_pack.tag);
+    let nonce = Nonce::from_slice(&aead_pack.nonce);
     let gcm = Aes256Gcm::new(aes_key);
 
     let out = gcm
-        .decrypt(nonce, aead_pack.ciphertext.as_slice())
+        .decrypt(
+            nonce,
+            Payload {
+                msg: aead_pack.ciphertext.as_slice(),
+                aad,
+            },
+        )
         .map_err(|_e| TssError::UnknownError {
             msg: ("aes_decrypt").to_string(),
             line: (line!()),
@@ -99,47 +138,280 @@ pub fn aes_decrypt(key: &[u8], aead_pack: AEAD) -> Result<Vec<u8>> {
     out
 }
 
+/// HKDF-SHA256 (RFC 5869), filling `okm` with key material.
+pub fn hkdf_sha256(salt: &[u8], ikm: &[u8], info: &[u8], okm: &mut [u8]) {
+    let hmac = |key: &[u8], parts: &[&[u8]]| {
+        let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC takes keys of any length");
+        for part in parts {
+            mac.update(part);
+        }
+        mac.finalize().into_bytes()
+    };
+    assert!(okm.len() <= 255 * 32, "hkdf_sha256: output too long");
+
+    let prk = hmac(salt, &[ikm]);
+    let mut t: Vec<u8> = Vec::new();
+    for (i, chunk) in okm.chunks_mut(32).enumerate() {
+        t = hmac(&prk, &[&t, info, &[i as u8 + 1]]).to_vec();
+        chunk.copy_from_slice(&t[..chunk.len()]);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+extern "C" {
+    // the global setTimeout, present in windows, Web Workers and Node alike,
+    // unlike web_sys::window() which only exists on the main thread of a page
+    #[wasm_bindgen(js_name = setTimeout)]
+    fn set_timeout(handler: &js_sys::Function, timeout: i32) -> JsValue;
+}
+
 #[cfg(target_arch = "wasm32")]
 pub async fn sleep(ms: u32) {
     let promise = js_sys::Promise::new(&mut |resolve, _| {
-        web_sys::window()
-            .unwrap()
-            .set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, ms as i32)
-            .unwrap();
+        set_timeout(&resolve, ms as i32);
     });
     wasm_bindgen_futures::JsFuture::from(promise).await;
 }
 
+// needs a tokio runtime, see runtime::block_on
 #[cfg(not(target_arch = "wasm32"))]
 pub async fn sleep(ms: u32) {
-    std::thread::sleep(core::time::Duration::from_millis(ms as u64));
+    tokio::time::sleep(core::time::Duration::from_millis(ms as u64)).await;
+}
+
+/// How `postb` retries requests to the manager.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RetryPolicy {
+    // attempts in total, the first one included
+    pub max_attempts: u32,
+    pub base_delay_ms: u32,
+    pub max_delay_ms: u32,
+}
+
+impl RetryPolicy {
+    pub const DEFAULT: RetryPolicy = RetryPolicy {
+        max_attempts: 5,
+        base_delay_ms: 200,
+        max_delay_ms: 5000,
+    };
+
+    // the delay before attempt `attempt` + 1: exponential up to the maximum,
+    // with the upper half randomized so parties that failed together do not
+    // retry together
+    fn backoff_ms(&self, attempt: u32) -> u32 {
+        let exp = self
+            .base_delay_ms
+            .saturating_mul(1u32.checked_shl(attempt - 1).unwrap_or(u32::MAX))
+            .min(self.max_delay_ms);
+        let jitter = OsRng::new().map(|mut rng| rng.next_u32()).unwrap_or(0);
+        exp / 2 + jitter % (exp - exp / 2 + 1)
+    }
+}
+
+static RETRY_POLICY: RwLock<RetryPolicy> = RwLock::new(RetryPolicy::DEFAULT);
+
+/// Sets how requests to the manager are retried by the sessions started from
+/// now on (a `ManagerClient` takes the policy when it is created): up to
+/// `max_attempts` in total, waiting `base_delay_ms` doubled after each failure, at most
+/// `max_delay_ms`, and randomized. Only requests that failed to reach the
+/// manager or got a 5xx, 408 or 429 are retried; a rejected token is reported
+/// at once as `AuthFailed`, unless the session has a `TokenProvider` that
+/// gives a new one.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn set_retry_policy(max_attempts: u32, base_delay_ms: u32, max_delay_ms: u32) -> Result<()> {
+    if max_attempts == 0 {
+        return Err(TssError::InvalidArgument {
+            name: "max_attempts".to_string(),
+        });
+    }
+    *RETRY_POLICY.write().unwrap_or_else(|e| e.into_inner()) = RetryPolicy {
+        max_attempts,
+        base_delay_ms,
+        max_delay_ms,
+    };
+    Ok(())
+}
+
+// the default of every new ManagerClient
+fn retry_policy() -> RetryPolicy {
+    *RETRY_POLICY.read().unwrap_or_else(|e| e.into_inner())
 }
 
-pub async fn postb<T>(client: &Client, addr: &str, path: &str, body: T) -> Result<String>
+/// Gives a new bearer token when the manager rejected the current one, so a
+/// session outliving its token goes on instead of failing with `AuthFailed`.
+pub trait TokenProvider {
+    fn refresh_token(&self) -> Pin<Box<dyn Future<Output = Result<String>> + '_>>;
+}
+
+/// The HTTP client of a session and its bearer token, which is renewed through
+/// the token provider, if any, when the manager rejects it, and how its
+/// requests are retried.
+pub struct ManagerClient<'a> {
+    http: Client,
+    authorization: RefCell<HeaderValue>,
+    provider: Option<&'a dyn TokenProvider>,
+    policy: RetryPolicy,
+}
+
+fn bearer(token: &str) -> Result<HeaderValue> {
+    HeaderValue::from_str(&format!("Bearer {}", token)).map_err(|_e| TssError::InvalidArgument {
+        name: "token".to_string(),
+    })
+}
+
+impl<'a> ManagerClient<'a> {
+    pub fn new(token: &str, provider: Option<&'a dyn TokenProvider>) -> Result<ManagerClient<'a>> {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "Content-Type",
+            HeaderValue::from_static("Content-Type:application/json; charset=utf-8"),
+        );
+        headers.insert(
+            "Accept",
+            HeaderValue::from_static("application/json; charset=utf-8"),
+        );
+        let http = reqwest::Client::builder()
+            .default_headers(headers)
+            .build()?;
+        Ok(ManagerClient {
+            http,
+            authorization: RefCell::new(bearer(token)?),
+            provider,
+            policy: retry_policy(),
+        })
+    }
+
+    /// Retries the requests of this client as `policy` says instead of as set
+    /// by `set_retry_policy`.
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> ManagerClient<'a> {
+        self.policy = policy;
+        self
+    }
+
+    // asks the provider for a new token; false if there is none to try
+    async fn refresh_token(&self) -> bool {
+        let provider = match self.provider {
+            Some(provider) => provider,
+            None => return false,
+        };
+        match provider.refresh_token().await.and_then(|token| bearer(&token)) {
+            Ok(authorization) => {
+                *self.authorization.borrow_mut() = authorization;
+                logging::token_refreshed();
+                true
+            }
+            Err(e) => {
+                logging::token_refresh_failed(e.code());
+                false
+            }
+        }
+    }
+}
+
+// whether a request that got `status` may succeed if sent again
+fn is_transient(status: StatusCode) -> bool {
+    status.is_server_error()
+        || status == StatusCode::REQUEST_TIMEOUT
+        || status == StatusCode::TOO_MANY_REQUESTS
+}
+
+/// Posts `body` as JSON to `path` of the manager and returns the response
+/// body, retrying as the retry policy of `client` says. Only for requests the
+/// manager may see twice, like `set` (writes are once only) and `get`.
+pub async fn postb<T>(
+    client: &ManagerClient<'_>,
+    addr: &str,
+    path: &str,
+    body: T,
+) -> Result<String>
+where
+    T: serde::ser::Serialize,
+{
+    post_with_retry(client, addr, path, &body, true).await
+}
+
+/// Like `postb`, but a request that may have reached the manager is not sent
+/// again, e.g. a signup, where that would take a second party number.
+pub async fn postb_once<T>(
+    client: &ManagerClient<'_>,
+    addr: &str,
+    path: &str,
+    body: T,
+) -> Result<String>
+where
+    T: serde::ser::Serialize,
+{
+    post_with_retry(client, addr, path, &body, false).await
+}
+
+async fn post_with_retry<T>(
+    client: &ManagerClient<'_>,
+    addr: &str,
+    path: &str,
+    body: &T,
+    resend: bool,
+) -> Result<String>
 where
     T: serde::ser::Serialize,
 {
     let url = format!("{}/{}", addr, path);
-    let retries = 3;
-    for _i in 1..retries {
+    let mut attempt = 1;
+    let mut refreshed = false;
+    loop {
+        let authorization = client.authorization.borrow().clone();
         let res = client
+            .http
             .post(url.clone())
             .header("Content-Type", "application/json; charset=utf-8")
-            .json(&body)
+            .header(AUTHORIZATION, authorization)
+            .json(body)
             .send()
             .await;
-        if let Ok(res) = res {
-            return Ok(res.text().await?);
+        // the error to return if this was the last attempt, and whether
+        // another one may help
+        let (err, retry) = match res {
+            Ok(res) if res.status().is_success() => return Ok(res.text().await?),
+            Ok(res) => {
+                let status = res.status();
+                if status == StatusCode::UNAUTHORIZED || status == StatusCode::FORBIDDEN {
+                    // the manager did not handle the request, so it is sent
+                    // again with a new token once
+                    if !refreshed && client.refresh_token().await {
+                        refreshed = true;
+                        continue;
+                    }
+                    return Err(TssError::AuthFailed {
+                        status: status.as_u16(),
+                    });
+                }
+                let err = TssError::ManagerError {
+                    status: status.as_u16(),
+                };
+                (err, is_transient(status) && resend)
+            }
+            Err(e) => {
+                let retry = resend || e.is_connect();
+                let err = TssError::ManagerUnreachable {
+                    attempts: attempt,
+                    msg: e.to_string(),
+                };
+                (err, retry)
+            }
+        };
+        if !retry || attempt >= client.policy.max_attempts {
+            logging::request_failed(path, attempt, err.code());
+            return Err(err);
         }
+        let delay = client.policy.backoff_ms(attempt);
+        logging::request_retry(path, attempt, err.code(), delay);
+        sleep(delay).await;
+        attempt += 1;
     }
-    Err(TssError::UnknownError {
-        msg: ("postb").to_string(),
-        line: (line!()),
-    })
 }
 
 pub async fn broadcast(
-    client: &Client,
+    client: &ManagerClient<'_>,
     addr: &str,
     party_num: u16,
     round: &str,
@@ -150,11 +422,15 @@ pub async fn broadcast(
     let entry = Entry { key, value: data };
     let res_body = postb(client, addr, "set", entry).await?;
     let u: std::result::Result<(), ()> = serde_json::from_str(&res_body)?;
-    Ok(u.unwrap())
+    // the manager refuses to overwrite a key with a different value
+    u.map_err(|_e| TssError::UnknownError {
+        msg: ("set rejected").to_string(),
+        line: (line!()),
+    })
 }
 
 pub async fn sendp2p(
-    client: &Client,
+    client: &ManagerClient<'_>,
     addr: &str,
     party_from: u16,
     party_to: u16,
@@ -168,23 +444,33 @@ pub async fn sendp2p(
 
     let res_body = postb(client, addr, "set", entry).await?;
     let u: std::result::Result<(), ()> = serde_json::from_str(&res_body)?;
-    Ok(u.unwrap())
+    // the manager refuses to overwrite a key with a different value
+    u.map_err(|_e| TssError::UnknownError {
+        msg: ("set rejected").to_string(),
+        line: (line!()),
+    })
 }
 
+// `waiting` is called with each party before polling for its message, `trace`
+// logs the arrivals
+#[allow(clippy::too_many_arguments)]
 pub async fn poll_for_broadcasts(
-    client: &Client,
+    client: &ManagerClient<'_>,
     addr: &str,
     party_num: u16,
     n: u16,
     round: &str,
     sender_uuid: String,
     delay: u32,
+    trace: &RoundTrace,
+    waiting: &dyn Fn(u16),
 ) -> Result<Vec<String>> {
     let mut ans_vec = Vec::new();
     for i in 1..=n {
         if i != party_num {
             let key = format!("{}-{}-{}", i, round, sender_uuid);
             let index = Index { key };
+            waiting(i);
             loop {
                 sleep(delay).await;
                 // add delay to allow the server to process request:
@@ -192,7 +478,7 @@ pub async fn poll_for_broadcasts(
                 let answer: std::result::Result<Entry, ()> = serde_json::from_str(&res_body)?;
                 if let Ok(answer) = answer {
                     ans_vec.push(answer.value);
-                    println!("[{:?}] party {:?} => party {:?}", round, i, party_num);
+                    trace.received(i);
                     break;
                 }
             }
@@ -201,20 +487,24 @@ pub async fn poll_for_broadcasts(
     return Ok(ans_vec);
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn poll_for_p2p(
-    client: &Client,
+    client: &ManagerClient<'_>,
     addr: &str,
     party_num: u16,
     n: u16,
     delay: u32,
     round: &str,
     sender_uuid: String,
+    trace: &RoundTrace,
+    waiting: &dyn Fn(u16),
 ) -> Result<Vec<String>> {
     let mut ans_vec = Vec::new();
     for i in 1..=n {
         if i != party_num {
             let key = format!("{}-{}-{}-{}", i, party_num, round, sender_uuid);
             let index = Index { key };
+            waiting(i);
             loop {
                 // add delay to allow the server to process request:
                 sleep(delay).await;
@@ -222,7 +512,7 @@ pub async fn poll_for_p2p(
                 let answer: std::result::Result<Entry, ()> = serde_json::from_str(&res_body)?;
                 if let Ok(answer) = answer {
                     ans_vec.push(answer.value);
-                    println!("[{:?}] party {:?} => party {:?}", round, i, party_num);
+                    trace.received(i);
                     break;
                 }
             }
@@ -253,13 +543,6 @@ pub fn check_sig(r: &Scalar, s: &Scalar, msg: &BigInt, pk: &Point) -> Result<boo
 
     let pubkey = secp256k1::PublicKey::parse(&pubkey_a)?;
 
-    #[cfg(target_arch = "wasm32")]
-    crate::console_log!("pubkey: {:?}", pubkey);
-    #[cfg(target_arch = "wasm32")]
-    crate::console_log!(
-        "address: {:?}",
-        checksum(&hex::encode(public_key_address(&pubkey)))
-    );
     Ok(secp256k1::verify(&message, &signature, &pubkey))
 }
 
@@ -308,3 +591,221 @@ pub fn checksum(address: &str) -> Result<String> {
 
     Ok(checksum)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_hkdf_sha256() {
+        // RFC 5869, test case 1
+        let ikm = [0x0bu8; 22];
+        let salt: Vec<u8> = (0x00..=0x0c).collect();
+        let info: Vec<u8> = (0xf0..=0xf9).collect();
+        let mut okm = [0u8; 42];
+        hkdf_sha256(&salt, &ikm, &info, &mut okm);
+        assert_eq!(
+            hex::encode(okm),
+            "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865"
+        );
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_aead_associated_data() {
+        let key = [7u8; AES_KEY_BYTES_LEN];
+        let pack = aes_encrypt(&key, b"share", b"context").unwrap();
+        assert_eq!(pack.version, AEAD_VERSION);
+        assert_eq!(aes_decrypt(&key, pack.clone(), b"context").unwrap(), b"share");
+        assert!(aes_decrypt(&key, pack.clone(), b"other").is_err());
+        assert!(aes_decrypt(&key, pack, &[]).is_err());
+
+        // packs written before versioning still open where no associated data is used
+        let legacy = aes_encrypt(&key, b"keystore", &[]).unwrap();
+        let json = format!(
+            "{{\"ciphertext\":{:?},\"tag\":{:?}}}",
+            legacy.ciphertext, legacy.nonce
+        );
+        let legacy: AEAD = serde_json::from_str(&json).unwrap();
+        assert_eq!(legacy.version, 0);
+        assert_eq!(aes_decrypt(&key, legacy.clone(), &[]).unwrap(), b"keystore");
+        assert!(aes_decrypt(&key, legacy, b"context").is_err());
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_backoff() {
+        let policy = RetryPolicy {
+            max_attempts: 10,
+            base_delay_ms: 100,
+            max_delay_ms: 1000,
+        };
+        for _ in 0..20 {
+            let first = policy.backoff_ms(1);
+            assert!((50..=100).contains(&first));
+            let third = policy.backoff_ms(3);
+            assert!((200..=400).contains(&third));
+            // capped, also where the doubling overflows
+            assert!((500..=1000).contains(&policy.backoff_ms(40)));
+        }
+        assert!(set_retry_policy(0, 100, 1000).is_err());
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_client_retry_policy() {
+        // the global policy is only the default of a new client
+        let client = ManagerClient::new("token", None).unwrap();
+        assert_eq!(client.policy, retry_policy());
+        let policy = RetryPolicy {
+            max_attempts: 2,
+            base_delay_ms: 1,
+            max_delay_ms: 1,
+        };
+        let client = client.with_retry_policy(policy);
+        assert_eq!(client.policy, policy);
+
+        let (addr, requests) = serve(vec![503, 503]);
+        let err = crate::runtime::block_on(postb(&client, &addr, "set", ())).unwrap_err();
+        assert!(matches!(err, TssError::ManagerError { status: 503 }));
+        assert_eq!(requests.lock().unwrap().len(), 2);
+    }
+
+    // serves one canned response per connection and keeps the requests
+    #[cfg(not(target_arch = "wasm32"))]
+    fn serve(statuses: Vec<u16>) -> (String, std::sync::Arc<std::sync::Mutex<Vec<String>>>) {
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = format!("http://{}", listener.local_addr().unwrap());
+        let requests = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let received = requests.clone();
+        std::thread::spawn(move || {
+            for status in statuses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut buf = [0u8; 4096];
+                let len = stream.read(&mut buf).unwrap_or(0);
+                let request = String::from_utf8_lossy(&buf[..len]).to_string();
+                received.lock().unwrap().push(request);
+                let body = "{\"Ok\":null}";
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 {} X\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+            }
+        });
+        (addr, requests)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_post_with_retry() {
+        let policy = RetryPolicy {
+            max_attempts: 3,
+            base_delay_ms: 1,
+            max_delay_ms: 10,
+        };
+        let client = ManagerClient::new("token", None)
+            .unwrap()
+            .with_retry_policy(policy);
+        let post = |addr: String, resend: bool| {
+            crate::runtime::block_on(post_with_retry(&client, &addr, "set", &(), resend))
+        };
+
+        let (addr, requests) = serve(vec![503, 200]);
+        assert_eq!(post(addr, true).unwrap(), "{\"Ok\":null}");
+        assert_eq!(requests.lock().unwrap().len(), 2);
+
+        // an expired token is not retried
+        let (addr, requests) = serve(vec![403]);
+        let err = post(addr, true).unwrap_err();
+        assert!(matches!(err, TssError::AuthFailed { status: 403 }));
+        assert_eq!(err.code(), 204);
+        assert_eq!(requests.lock().unwrap().len(), 1);
+
+        let (addr, requests) = serve(vec![500, 500, 500]);
+        let err = post(addr, true).unwrap_err();
+        assert!(matches!(err, TssError::ManagerError { status: 500 }));
+        assert_eq!(requests.lock().unwrap().len(), 3);
+
+        // a request the manager may have handled is not sent again
+        let (addr, requests) = serve(vec![500]);
+        assert!(matches!(
+            post(addr, false).unwrap_err(),
+            TssError::ManagerError { status: 500 }
+        ));
+        assert_eq!(requests.lock().unwrap().len(), 1);
+
+        let (addr, requests) = serve(vec![404]);
+        assert_eq!(post(addr, true).unwrap_err().code(), 203);
+        assert_eq!(requests.lock().unwrap().len(), 1);
+
+        // nothing listens on a port just released
+        let addr = {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            format!("http://{}", listener.local_addr().unwrap())
+        };
+        let err = post(addr, false).unwrap_err();
+        assert!(matches!(err, TssError::ManagerUnreachable { attempts: 3, .. }));
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    struct NextToken(RefCell<Vec<&'static str>>);
+
+    #[cfg(not(target_arch = "wasm32"))]
+    impl TokenProvider for NextToken {
+        fn refresh_token(&self) -> Pin<Box<dyn Future<Output = Result<String>> + '_>> {
+            let token = self.0.borrow_mut().pop().map(|token| token.to_string());
+            Box::pin(async move {
+                token.ok_or(TssError::InvalidArgument {
+                    name: "token".to_string(),
+                })
+            })
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_token_refresh() {
+        let policy = RetryPolicy {
+            max_attempts: 3,
+            base_delay_ms: 1,
+            max_delay_ms: 10,
+        };
+        let provider = NextToken(RefCell::new(vec!["fresh"]));
+        let client = ManagerClient::new("expired", Some(&provider))
+            .unwrap()
+            .with_retry_policy(policy);
+        let post = |addr: String| {
+            crate::runtime::block_on(post_with_retry(&client, &addr, "get", &(), false))
+        };
+
+        // the rejected request is sent again with the new token, which is kept
+        let (addr, requests) = serve(vec![403, 200, 200]);
+        assert!(post(addr.clone()).is_ok());
+        assert!(post(addr).is_ok());
+        let requests = requests.lock().unwrap();
+        assert!(requests[0].contains("Bearer expired"));
+        assert!(requests[1].contains("Bearer fresh"));
+        assert!(requests[2].contains("Bearer fresh"));
+
+        // a provider without a new token, or a new token rejected as well, fails
+        let (addr, requests) = serve(vec![403]);
+        assert!(matches!(post(addr).unwrap_err(), TssError::AuthFailed { status: 403 }));
+        assert_eq!(requests.lock().unwrap().len(), 1);
+
+        provider.0.borrow_mut().push("also-expired");
+        let (addr, requests) = serve(vec![401, 401]);
+        assert_eq!(post(addr).unwrap_err().code(), 204);
+        assert_eq!(requests.lock().unwrap().len(), 2);
+
+        assert!(ManagerClient::new("bad\ntoken", None).is_err());
+    }
+}
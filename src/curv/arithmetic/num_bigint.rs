@@ -42,14 +42,26 @@ impl NumberTests for BigUint {
 }
 
 impl Modulo for BigUint {
+    #[cfg(not(feature = "fast-bigint"))]
     fn mod_pow(base: &Self, exponent: &Self, modulus: &Self) -> Self {
         base.modpow(&exponent, &modulus)
     }
 
+    #[cfg(feature = "fast-bigint")]
+    fn mod_pow(base: &Self, exponent: &Self, modulus: &Self) -> Self {
+        fast::mod_pow(base, exponent, modulus)
+    }
+
+    #[cfg(not(feature = "fast-bigint"))]
     fn mod_mul(a: &Self, b: &Self, modulus: &Self) -> Self {
         (a.mod_floor(modulus) * b.mod_floor(modulus)).mod_floor(modulus)
     }
 
+    #[cfg(feature = "fast-bigint")]
+    fn mod_mul(a: &Self, b: &Self, modulus: &Self) -> Self {
+        fast::mod_mul(a, b, modulus)
+    }
+
     fn mod_sub(a: &Self, b: &Self, modulus: &Self) -> Self {
         let a_m = a.mod_floor(modulus);
         let b_m = b.mod_floor(modulus);
@@ -63,6 +75,12 @@ impl Modulo for BigUint {
     }
 
     fn mod_inv(a: &Self, modulus: &Self) -> Self {
+        #[cfg(feature = "fast-bigint")]
+        {
+            if let Some(x) = fast::mod_inv(a, modulus) {
+                return x;
+            }
+        }
         let x = egcd(a, modulus).1; //[d,x,y]
         let x_ubn = x
             .mod_floor(&modulus.to_bigint().unwrap())
@@ -86,6 +104,43 @@ impl Modulo for BigUint {
     }
 }
 
+// Modular arithmetic on ibig, whose sliding window exponentiation and
+// Karatsuba multiplication are much faster than num-bigint 0.2 at Paillier
+// sizes. Values are still stored and serialized as num-bigint, the conversion
+// is linear and negligible next to an exponentiation.
+#[cfg(feature = "fast-bigint")]
+mod fast {
+    use ibig::modular::ModuloRing;
+    use ibig::UBig;
+    use num_bigint::BigUint;
+
+    fn to_ubig(x: &BigUint) -> UBig {
+        UBig::from_le_bytes(&x.to_bytes_le())
+    }
+
+    fn from_ubig(x: &UBig) -> BigUint {
+        BigUint::from_bytes_le(&x.to_le_bytes())
+    }
+
+    pub fn mod_pow(base: &BigUint, exponent: &BigUint, modulus: &BigUint) -> BigUint {
+        let ring = ModuloRing::new(&to_ubig(modulus));
+        from_ubig(&ring.from(to_ubig(base)).pow(&to_ubig(exponent)).residue())
+    }
+
+    pub fn mod_mul(a: &BigUint, b: &BigUint, modulus: &BigUint) -> BigUint {
+        let ring = ModuloRing::new(&to_ubig(modulus));
+        from_ubig(&(ring.from(to_ubig(a)) * ring.from(to_ubig(b))).residue())
+    }
+
+    // None if a is not invertible, the caller then keeps the egcd result
+    pub fn mod_inv(a: &BigUint, modulus: &BigUint) -> Option<BigUint> {
+        let ring = ModuloRing::new(&to_ubig(modulus));
+        ring.from(to_ubig(a))
+            .inverse()
+            .map(|x| from_ubig(&x.residue()))
+    }
+}
+
 fn egcd(a: &BigUint, b: &BigUint) -> (BN, BN, BN) {
     let mut a = a.clone().to_bigint().unwrap();
     let mut b = b.clone().to_bigint().unwrap();
@@ -285,3 +340,28 @@ impl BasicOps for BigInt {
     }
 }
 */
+
+#[cfg(all(test, feature = "fast-bigint"))]
+mod tests {
+    use super::*;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_fast_backend_matches_num_bigint() {
+        for bits in [64, 1024, 4096] {
+            let modulus = BigUint::sample(bits).set_bit(0, true);
+            let a = BigUint::sample_below(&modulus);
+            let b = BigUint::sample(bits + 7);
+            assert_eq!(BigUint::mod_pow(&a, &b, &modulus), a.modpow(&b, &modulus));
+            assert_eq!(BigUint::mod_mul(&a, &b, &modulus), (&a * &b) % &modulus);
+
+            let inv = BigUint::mod_inv(&a, &modulus);
+            if a.gcd(&modulus).is_one() {
+                assert!(BigUint::mod_mul(&a, &inv, &modulus).is_one());
+            }
+        }
+    }
+}
@@ -5,15 +5,51 @@ use digest::Digest;
 
 use crate::curv::arithmetic::num_bigint::BigInt;
 // use crate::curv::arithmetic::*;
-use crate::curv::elliptic::curves::secp256_k1::{
-    Secp256k1Point as Point, Secp256k1Scalar as Scalar,
-};
 use crate::curv::elliptic::curves::traits::{ECPoint, ECScalar};
 
+const PROOF_CONTEXT_TAG: &[u8] = b"tss-wasm/fiat-shamir/v1";
+
+/// Transcript context of a non-interactive proof. It is hashed into the
+/// Fiat-Shamir challenge ahead of the statement, so a proof only verifies in
+/// the session, for the party and at the protocol step it was made for.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ProofContext<'a> {
+    pub session_id: &'a str,
+    pub party_index: u16,
+    pub label: &'a str,
+}
+
+impl<'a> ProofContext<'a> {
+    pub fn new(session_id: &'a str, party_index: u16, label: &'a str) -> Self {
+        Self {
+            session_id,
+            party_index,
+            label,
+        }
+    }
+
+    /// Same session and step, proven by another party.
+    pub fn for_party(&self, party_index: u16) -> Self {
+        Self {
+            party_index,
+            ..*self
+        }
+    }
+}
+
 pub trait DigestExt {
     fn input_bigint(&mut self, n: &BigInt);
-    fn input_point(&mut self, point: &Point);
-    fn input_scalar(&mut self, scalar: &Scalar);
+    fn input_point<P: ECPoint>(&mut self, point: &P);
+    fn input_scalar<S: ECScalar>(&mut self, scalar: &S);
+    fn input_context(&mut self, ctx: &ProofContext);
+
+    fn chain_context(mut self, ctx: &ProofContext) -> Self
+    where
+        Self: Sized,
+    {
+        self.input_context(ctx);
+        self
+    }
 
     fn chain_bigint(mut self, n: &BigInt) -> Self
     where
@@ -22,14 +58,17 @@ pub trait DigestExt {
         self.input_bigint(n);
         self
     }
-    fn chain_point(mut self, point: &Point) -> Self
+    fn chain_point<P: ECPoint>(mut self, point: &P) -> Self
     where
         Self: Sized,
     {
         self.input_point(point);
         self
     }
-    fn chain_points<'p>(mut self, points: impl IntoIterator<Item = &'p Point>) -> Self
+    fn chain_points<'p, P: ECPoint + 'p>(
+        mut self,
+        points: impl IntoIterator<Item = &'p P>,
+    ) -> Self
     where
         Self: Sized,
     {
@@ -38,14 +77,17 @@ pub trait DigestExt {
         }
         self
     }
-    fn chain_scalar(mut self, scalar: &Scalar) -> Self
+    fn chain_scalar<S: ECScalar>(mut self, scalar: &S) -> Self
     where
         Self: Sized,
     {
         self.input_scalar(scalar);
         self
     }
-    fn chain_scalars<'s>(mut self, scalars: impl IntoIterator<Item = &'s Scalar>) -> Self
+    fn chain_scalars<'s, S: ECScalar + 's>(
+        mut self,
+        scalars: impl IntoIterator<Item = &'s S>,
+    ) -> Self
     where
         Self: Sized,
     {
@@ -69,14 +111,25 @@ where
         self.update(&n.to_bytes_be())
     }
 
-    fn input_point(&mut self, point: &Point) {
+    fn input_point<P: ECPoint>(&mut self, point: &P) {
         self.update(&point.to_bytes(false)[..])
     }
 
-    fn input_scalar(&mut self, scalar: &Scalar) {
+    fn input_scalar<S: ECScalar>(&mut self, scalar: &S) {
         self.update(&scalar.to_big_int().to_bytes_be())
     }
 
+    // variable length fields are length prefixed so that no two contexts
+    // share an encoding
+    fn input_context(&mut self, ctx: &ProofContext) {
+        self.update(PROOF_CONTEXT_TAG);
+        self.update((ctx.session_id.len() as u64).to_be_bytes());
+        self.update(ctx.session_id.as_bytes());
+        self.update(ctx.party_index.to_be_bytes());
+        self.update((ctx.label.len() as u64).to_be_bytes());
+        self.update(ctx.label.as_bytes());
+    }
+
     fn result_bigint(self) -> BigInt {
         let result = self.finalize();
         BigInt::from_bytes_be(&result)
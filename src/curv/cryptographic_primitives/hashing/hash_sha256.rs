@@ -7,7 +7,6 @@
 
 use super::traits::Hash;
 use crate::curv::arithmetic::num_bigint::{from, BigInt};
-use crate::curv::elliptic::curves::secp256_k1::{FE, GE};
 use crate::curv::elliptic::curves::traits::{ECPoint, ECScalar};
 use cryptoxide::digest::Digest;
 use cryptoxide::sha2::Sha256;
@@ -28,7 +27,7 @@ impl Hash for HSha256 {
         from(result.as_ref())
     }
 
-    fn create_hash_from_ge(ge_vec: &[&GE]) -> FE {
+    fn create_hash_from_ge<P: ECPoint>(ge_vec: &[&P]) -> P::Scalar {
         let mut hasher = Sha256::new();
 
         for value in ge_vec {
@@ -48,6 +47,7 @@ mod tests {
     use super::Hash;
     use crate::curv::arithmetic::num_bigint::BigInt;
     use crate::curv::elliptic::curves::secp256_k1::GE;
+    use crate::curv::elliptic::curves::secp256_r1;
     use crate::curv::elliptic::curves::traits::ECPoint;
     use crate::curv::elliptic::curves::traits::ECScalar;
     use num_traits::{One, Zero};
@@ -76,4 +76,15 @@ mod tests {
         let result3 = HSha256::create_hash_from_ge(&vec![&GE::generator(), &point]);
         assert_eq!(result2, result3);
     }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn create_hash_from_p256_ge_test() {
+        let g = secp256_r1::GE::generator();
+        let point = secp256_r1::GE::random_point();
+        let result1 = HSha256::create_hash_from_ge(&[&point, &g]);
+        let result2 = HSha256::create_hash_from_ge(&[&g, &point]);
+        assert_ne!(result1, result2);
+        assert!(result1.to_big_int() < secp256_r1::FE::q());
+    }
 }
@@ -7,10 +7,10 @@
 */
 
 use super::ProofError;
-use crate::curv::cryptographic_primitives::hashing::hash_sha256::HSha256;
-use crate::curv::cryptographic_primitives::hashing::traits::Hash;
-use crate::curv::elliptic::curves::secp256_k1::{FE, GE};
+use crate::curv::cryptographic_primitives::hashing::ext::{DigestExt, ProofContext};
+use crate::curv::elliptic::curves::secp256_k1::GE;
 use crate::curv::elliptic::curves::traits::*;
+use sha2::{Digest, Sha256};
 use zeroize::Zeroize;
 
 /// This is a proof of knowledge that a pair of group elements {D, E}
@@ -18,43 +18,62 @@ use zeroize::Zeroize;
 /// (HEG is defined in B. Schoenmakers and P. Tuyls. Practical Two-Party Computation Based on the Conditional Gate)
 /// Specifically, the witness is ω = (x, r), the statement is δ = (G, H, Y, D, E).
 /// The relation R outputs 1 if D = xH+rY , E = rG (for the case of G=H this is ElGamal)
+/// The challenge is bound to the `ProofContext` of the prover.
 ///
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
-pub struct HomoELGamalProof {
-    pub T: GE,
-    pub A3: GE,
-    pub z1: FE,
-    pub z2: FE,
+#[serde(bound = "")]
+pub struct HomoELGamalProof<P: ECPoint = GE> {
+    pub T: P,
+    pub A3: P,
+    pub z1: P::Scalar,
+    pub z2: P::Scalar,
 }
 
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
-pub struct HomoElGamalWitness {
-    pub r: FE,
-    pub x: FE,
+#[serde(bound = "")]
+pub struct HomoElGamalWitness<P: ECPoint = GE> {
+    pub r: P::Scalar,
+    pub x: P::Scalar,
 }
 
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
-pub struct HomoElGamalStatement {
-    pub G: GE,
-    pub H: GE,
-    pub Y: GE,
-    pub D: GE,
-    pub E: GE,
+#[serde(bound = "")]
+pub struct HomoElGamalStatement<P: ECPoint = GE> {
+    pub G: P,
+    pub H: P,
+    pub Y: P,
+    pub D: P,
+    pub E: P,
 }
 
-impl HomoELGamalProof {
-    pub fn prove(w: &HomoElGamalWitness, delta: &HomoElGamalStatement) -> HomoELGamalProof {
-        let mut s1: FE = ECScalar::new_random();
-        let mut s2: FE = ECScalar::new_random();
+fn challenge<P: ECPoint>(
+    ctx: &ProofContext,
+    T: &P,
+    A3: &P,
+    delta: &HomoElGamalStatement<P>,
+) -> P::Scalar {
+    let e = Sha256::new()
+        .chain_context(ctx)
+        .chain_points([T, A3, &delta.G, &delta.H, &delta.Y, &delta.D, &delta.E])
+        .result_bigint();
+    ECScalar::from(&e)
+}
+
+impl<P: ECPoint> HomoELGamalProof<P> {
+    pub fn prove(
+        w: &HomoElGamalWitness<P>,
+        delta: &HomoElGamalStatement<P>,
+        ctx: &ProofContext,
+    ) -> HomoELGamalProof<P> {
+        let mut s1 = P::Scalar::new_random();
+        let mut s2 = P::Scalar::new_random();
         let mut A1 = delta.H.clone() * s1.clone();
         let mut A2 = delta.Y.clone() * s2.clone();
         let A3 = delta.G.clone() * s2.clone();
         let T = A1.clone() + A2.clone();
-        let e = HSha256::create_hash_from_ge(&[
-            &T, &A3, &delta.G, &delta.H, &delta.Y, &delta.D, &delta.E,
-        ]);
+        let e = challenge(ctx, &T, &A3, delta);
         // dealing with zero field element
-        let z1 = if w.x.clone() != FE::zero() {
+        let z1 = if w.x.clone() != P::Scalar::zero() {
             s1.clone() + w.x.clone() * e.clone()
         } else {
             s1.clone()
@@ -66,16 +85,12 @@ impl HomoELGamalProof {
         A2.zeroize();
         HomoELGamalProof { T, A3, z1, z2 }
     }
-    pub fn verify(&self, delta: &HomoElGamalStatement) -> Result<(), ProofError> {
-        let e = HSha256::create_hash_from_ge(&[
-            &self.T.clone(),
-            &self.A3.clone(),
-            &delta.G.clone(),
-            &delta.H.clone(),
-            &delta.Y.clone(),
-            &delta.D.clone(),
-            &delta.E.clone(),
-        ]);
+    pub fn verify(
+        &self,
+        delta: &HomoElGamalStatement<P>,
+        ctx: &ProofContext,
+    ) -> Result<(), ProofError> {
+        let e = challenge(ctx, &self.T, &self.A3, delta);
         let z1H_plus_z2Y = delta.H.clone() * self.z1.clone() + delta.Y.clone() * self.z2.clone();
         let T_plus_eD = self.T.clone() + delta.D.clone() * e.clone();
         let z2G = delta.G.clone() * self.z2.clone();
@@ -93,11 +108,18 @@ mod tests {
     use crate::curv::cryptographic_primitives::proofs::sigma_correct_homomorphic_elgamal_enc::*;
     use crate::curv::cryptographic_primitives::proofs::PROOF_ERROR_DESCRIPTION;
     use crate::curv::elliptic::curves::secp256_k1::{FE, GE};
+    use crate::curv::elliptic::curves::secp256_r1;
     use std::error::Error;
 
     #[cfg(target_arch = "wasm32")]
     use wasm_bindgen_test::*;
 
+    const CTX: ProofContext = ProofContext {
+        session_id: "session",
+        party_index: 1,
+        label: "test",
+    };
+
     #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
     #[test]
     fn test_correct_general_homo_elgamal() {
@@ -113,8 +135,8 @@ mod tests {
         let D = &H * &witness.x + Y.clone() * &witness.r;
         let E = G.clone() * &witness.r;
         let delta = HomoElGamalStatement { G, H, Y, D, E };
-        let proof = HomoELGamalProof::prove(&witness, &delta);
-        assert!(proof.verify(&delta).is_ok());
+        let proof = HomoELGamalProof::prove(&witness, &delta, &CTX);
+        assert!(proof.verify(&delta, &CTX).is_ok());
     }
 
     #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
@@ -136,8 +158,8 @@ mod tests {
             D,
             E,
         };
-        let proof = HomoELGamalProof::prove(&witness, &delta);
-        assert!(proof.verify(&delta).is_ok());
+        let proof = HomoELGamalProof::prove(&witness, &delta, &CTX);
+        assert!(proof.verify(&delta, &CTX).is_ok());
     }
 
     #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
@@ -156,8 +178,32 @@ mod tests {
         let D = &H * &witness.x + Y.clone() * &witness.r;
         let E = &G * &witness.r + G.clone();
         let delta = HomoElGamalStatement { G, H, Y, D, E };
-        let proof = HomoELGamalProof::prove(&witness, &delta);
-        let result = proof.verify(&delta);
+        let proof = HomoELGamalProof::prove(&witness, &delta, &CTX);
+        let result = proof.verify(&delta, &CTX);
         assert_eq!(result.unwrap_err().description(), PROOF_ERROR_DESCRIPTION);
     }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_correct_general_homo_elgamal_p256() {
+        let witness = HomoElGamalWitness::<secp256_r1::GE> {
+            r: ECScalar::new_random(),
+            x: ECScalar::new_random(),
+        };
+        let G = secp256_r1::GE::generator();
+        let H = secp256_r1::GE::random_point();
+        let Y = secp256_r1::GE::random_point();
+        let D = H.clone() * &witness.x + Y.clone() * &witness.r;
+        let E = G.clone() * &witness.r;
+        let delta = HomoElGamalStatement { G, H, Y, D, E };
+        let proof = HomoELGamalProof::prove(&witness, &delta, &CTX);
+        assert!(proof.verify(&delta, &CTX).is_ok());
+        assert!(proof.verify(&delta, &CTX.for_party(2)).is_err());
+
+        let wrong = HomoElGamalStatement {
+            E: delta.E.clone() + delta.G.clone(),
+            ..delta
+        };
+        assert!(proof.verify(&wrong, &CTX).is_err());
+    }
 }
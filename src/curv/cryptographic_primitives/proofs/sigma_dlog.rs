@@ -6,11 +6,11 @@
 */
 
 use super::ProofError;
-use crate::curv::elliptic::curves::secp256_k1::{FE, GE};
+use crate::curv::elliptic::curves::secp256_k1::GE;
 use crate::curv::elliptic::curves::traits::*;
 
-use crate::curv::cryptographic_primitives::hashing::hash_sha256::HSha256;
-use crate::curv::cryptographic_primitives::hashing::traits::Hash;
+use crate::curv::cryptographic_primitives::hashing::ext::{DigestExt, ProofContext};
+use sha2::{Digest, Sha256};
 use zeroize::Zeroize;
 
 /// This is implementation of Schnorr's identification protocol for elliptic curve groups or a
@@ -23,34 +23,41 @@ use zeroize::Zeroize;
 /// How to prove yourself: Practical solutions to identification and signature problems.
 /// In Advances in Cryptology - CRYPTO ’86, Santa Barbara, California, USA, 1986, Proceedings,
 /// pages 186–194, 1986.
+///
+/// The challenge is bound to a `ProofContext`, the verifier must pass the context of the prover.
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
-pub struct DLogProof {
-    pub pk: GE,
-    pub pk_t_rand_commitment: GE,
-    pub challenge_response: FE,
+#[serde(bound = "")]
+pub struct DLogProof<P: ECPoint = GE> {
+    pub pk: P,
+    pub pk_t_rand_commitment: P,
+    pub challenge_response: P::Scalar,
 }
 
-pub trait ProveDLog {
-    fn prove(sk: &FE) -> DLogProof;
+pub trait ProveDLog<P: ECPoint> {
+    fn prove(sk: &P::Scalar, ctx: &ProofContext) -> DLogProof<P>;
+
+    fn verify(proof: &DLogProof<P>, ctx: &ProofContext) -> Result<(), ProofError>;
+}
 
-    fn verify(proof: &DLogProof) -> Result<(), ProofError>;
+fn challenge<P: ECPoint>(ctx: &ProofContext, commitment: &P, pk: &P) -> P::Scalar {
+    let e = Sha256::new()
+        .chain_context(ctx)
+        .chain_point(commitment)
+        .chain_point(&P::generator())
+        .chain_point(pk)
+        .result_bigint();
+    ECScalar::from(&e)
 }
 
-impl ProveDLog for DLogProof {
-    fn prove(sk: &FE) -> DLogProof {
-        let base_point: GE = ECPoint::generator();
-        let generator_x = base_point.bytes_compressed_to_big_int();
-        let mut sk_t_rand_commitment: FE = ECScalar::new_random();
+impl<P: ECPoint> ProveDLog<P> for DLogProof<P> {
+    fn prove(sk: &P::Scalar, ctx: &ProofContext) -> DLogProof<P> {
+        let base_point = P::generator();
+        let mut sk_t_rand_commitment = P::Scalar::new_random();
         let pk_t_rand_commitment = base_point.scalar_mul(&sk_t_rand_commitment.get_element());
-        let ec_point: GE = ECPoint::generator();
+        let ec_point = P::generator();
         let pk = ec_point.scalar_mul(&sk.get_element());
-        let challenge = HSha256::create_hash(&[
-            &pk_t_rand_commitment.bytes_compressed_to_big_int(),
-            &generator_x,
-            &pk.bytes_compressed_to_big_int(),
-        ]);
-        let challenge_fe: FE = ECScalar::from(&challenge);
-        let challenge_mul_sk = challenge_fe.mul(&sk.get_element());
+        let challenge_fe = challenge(ctx, &pk_t_rand_commitment, &pk);
+        let challenge_mul_sk = ECScalar::mul(&challenge_fe, &sk.get_element());
         let challenge_response = sk_t_rand_commitment.sub(&challenge_mul_sk.get_element());
         sk_t_rand_commitment.zeroize();
         DLogProof {
@@ -60,19 +67,12 @@ impl ProveDLog for DLogProof {
         }
     }
 
-    fn verify(proof: &DLogProof) -> Result<(), ProofError> {
-        let ec_point: GE = ECPoint::generator();
-        let challenge = HSha256::create_hash(&[
-            &proof.pk_t_rand_commitment.bytes_compressed_to_big_int(),
-            &ec_point.bytes_compressed_to_big_int(),
-            &proof.pk.clone().bytes_compressed_to_big_int(),
-        ]);
-
-        let sk_challenge: FE = ECScalar::from(&challenge);
+    fn verify(proof: &DLogProof<P>, ctx: &ProofContext) -> Result<(), ProofError> {
+        let sk_challenge = challenge(ctx, &proof.pk_t_rand_commitment, &proof.pk);
         let pk = proof.pk.clone();
         let pk_challenge = pk.scalar_mul(&sk_challenge.get_element());
 
-        let base_point: GE = ECPoint::generator();
+        let base_point = P::generator();
 
         let mut pk_verifier = base_point.scalar_mul(&proof.challenge_response.get_element());
 
@@ -89,20 +89,52 @@ impl ProveDLog for DLogProof {
 #[cfg(test)]
 mod tests {
     use crate::curv::cryptographic_primitives::proofs::sigma_dlog::*;
-    use crate::curv::elliptic::curves::secp256_k1::FE;
+    use crate::curv::elliptic::curves::secp256_k1::{FE, GE};
+    use crate::curv::elliptic::curves::secp256_r1;
 
     #[cfg(target_arch = "wasm32")]
     use wasm_bindgen_test::*;
 
+    const CTX: ProofContext = ProofContext {
+        session_id: "session",
+        party_index: 1,
+        label: "test",
+    };
+
     #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
     #[test]
     fn test_dlog_proof() {
         let witness: FE = ECScalar::new_random();
-        let dlog_proof = DLogProof::prove(&witness);
-        let verified = DLogProof::verify(&dlog_proof);
+        let dlog_proof = DLogProof::<GE>::prove(&witness, &CTX);
+        let verified = DLogProof::verify(&dlog_proof, &CTX);
         match verified {
             Ok(_t) => assert!(true),
             Err(_e) => assert!(false),
         }
     }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_dlog_proof_p256() {
+        let witness: secp256_r1::FE = ECScalar::new_random();
+        let mut dlog_proof = DLogProof::<secp256_r1::GE>::prove(&witness, &CTX);
+        assert!(DLogProof::verify(&dlog_proof, &CTX).is_ok());
+
+        dlog_proof.challenge_response = ECScalar::new_random();
+        assert!(DLogProof::verify(&dlog_proof, &CTX).is_err());
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_dlog_proof_other_context() {
+        let witness: FE = ECScalar::new_random();
+        let dlog_proof = DLogProof::<GE>::prove(&witness, &CTX);
+        assert!(DLogProof::verify(&dlog_proof, &CTX.for_party(2)).is_err());
+
+        let other_session = ProofContext::new("other session", 1, "test");
+        assert!(DLogProof::verify(&dlog_proof, &other_session).is_err());
+
+        let other_label = ProofContext::new("session", 1, "other");
+        assert!(DLogProof::verify(&dlog_proof, &other_label).is_err());
+    }
 }
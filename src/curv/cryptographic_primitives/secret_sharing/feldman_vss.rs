@@ -7,7 +7,7 @@
 */
 
 use crate::curv::arithmetic::num_bigint::BigInt;
-use crate::curv::elliptic::curves::secp256_k1::{FE, GE};
+use crate::curv::elliptic::curves::secp256_k1::GE;
 use crate::curv::elliptic::curves::traits::*;
 use crate::errors::TssError::{self, VerifyShareError};
 use num_traits::One;
@@ -19,30 +19,31 @@ pub struct ShamirSecretSharing {
 /// Feldman VSS, based on  Paul Feldman. 1987. A practical scheme for non-interactive verifiable secret sharing.
 /// In Foundations of Computer Science, 1987., 28th Annual Symposium on.IEEE, 427–43
 ///
-/// implementation details: The code is using FE and GE. Each party is given an index from 1,..,n and a secret share of type FE.
-/// The index of the party is also the point on the polynomial where we treat this number as u32 but converting it to FE internally.
+/// implementation details: The code is generic over the curve point P and its scalar P::Scalar. Each party is given an index from 1,..,n and a secret share of type P::Scalar.
+/// The index of the party is also the point on the polynomial where we treat this number as u32 but converting it to P::Scalar internally.
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
-pub struct VerifiableSS {
+#[serde(bound = "")]
+pub struct VerifiableSS<P: ECPoint = GE> {
     pub parameters: ShamirSecretSharing,
-    pub commitments: Vec<GE>,
+    pub commitments: Vec<P>,
 }
 
-impl VerifiableSS {
+impl<P: ECPoint> VerifiableSS<P> {
     pub fn reconstruct_limit(&self) -> usize {
         self.parameters.threshold + 1
     }
 
     // generate VerifiableSS from a secret
-    pub fn share(t: usize, n: usize, secret: &FE) -> (VerifiableSS, Vec<FE>) {
+    pub fn share(t: usize, n: usize, secret: &P::Scalar) -> (VerifiableSS<P>, Vec<P::Scalar>) {
         assert!(t < n);
-        let poly = VerifiableSS::sample_polynomial(t, secret);
+        let poly = Self::sample_polynomial(t, secret);
         let index_vec: Vec<usize> = (1..=n).collect();
-        let secret_shares = VerifiableSS::evaluate_polynomial(&poly, &index_vec);
+        let secret_shares = Self::evaluate_polynomial(&poly, &index_vec);
 
-        let G: GE = ECPoint::generator();
+        let G = P::generator();
         let commitments = (0..poly.len())
             .map(|i| G.clone() * poly[i].clone())
-            .collect::<Vec<GE>>();
+            .collect::<Vec<P>>();
         (
             VerifiableSS {
                 parameters: ShamirSecretSharing {
@@ -59,17 +60,17 @@ impl VerifiableSS {
     pub fn share_at_indices(
         t: usize,
         n: usize,
-        secret: &FE,
+        secret: &P::Scalar,
         index_vec: &[usize],
-    ) -> (VerifiableSS, Vec<FE>) {
+    ) -> (VerifiableSS<P>, Vec<P::Scalar>) {
         assert_eq!(n, index_vec.len());
-        let poly = VerifiableSS::sample_polynomial(t, secret);
-        let secret_shares = VerifiableSS::evaluate_polynomial(&poly, index_vec);
+        let poly = Self::sample_polynomial(t, secret);
+        let secret_shares = Self::evaluate_polynomial(&poly, index_vec);
 
-        let G: GE = ECPoint::generator();
+        let G = P::generator();
         let commitments = (0..poly.len())
             .map(|i| G.clone() * &poly[i])
-            .collect::<Vec<GE>>();
+            .collect::<Vec<P>>();
         (
             VerifiableSS {
                 parameters: ShamirSecretSharing {
@@ -83,26 +84,26 @@ impl VerifiableSS {
     }
 
     // returns vector of coefficients
-    pub fn sample_polynomial(t: usize, coef0: &FE) -> Vec<FE> {
+    pub fn sample_polynomial(t: usize, coef0: &P::Scalar) -> Vec<P::Scalar> {
         let mut coefficients = vec![coef0.clone()];
         // sample the remaining coefficients randomly using secure randomness
-        let random_coefficients: Vec<FE> = (0..t).map(|_| ECScalar::new_random()).collect();
+        let random_coefficients: Vec<P::Scalar> = (0..t).map(|_| ECScalar::new_random()).collect();
         coefficients.extend(random_coefficients);
         // return
         coefficients
     }
 
-    pub fn evaluate_polynomial(coefficients: &[FE], index_vec: &[usize]) -> Vec<FE> {
+    pub fn evaluate_polynomial(coefficients: &[P::Scalar], index_vec: &[usize]) -> Vec<P::Scalar> {
         (0..index_vec.len())
             .map(|point| {
                 let point_bn = BigInt::from(index_vec[point] as u32);
 
-                VerifiableSS::mod_evaluate_polynomial(coefficients, ECScalar::from(&point_bn))
+                Self::mod_evaluate_polynomial(coefficients, ECScalar::from(&point_bn))
             })
-            .collect::<Vec<FE>>()
+            .collect::<Vec<P::Scalar>>()
     }
 
-    pub fn mod_evaluate_polynomial(coefficients: &[FE], point: FE) -> FE {
+    pub fn mod_evaluate_polynomial(coefficients: &[P::Scalar], point: P::Scalar) -> P::Scalar {
         // evaluate using Horner's rule
         //  - to combine with fold we consider the coefficients in reverse order
         let mut reversed_coefficients = coefficients.iter().rev();
@@ -110,12 +111,12 @@ impl VerifiableSS {
         let head = reversed_coefficients.next().unwrap();
         let tail = reversed_coefficients;
         tail.fold(head.clone(), |partial, coef| {
-            let partial_times_point = partial.mul(&point.get_element());
-            partial_times_point.add(&coef.get_element())
+            let partial_times_point = partial * &point;
+            partial_times_point + coef
         })
     }
 
-    pub fn reconstruct(&self, indices: &[usize], shares: &[FE]) -> FE {
+    pub fn reconstruct(&self, indices: &[usize], shares: &[P::Scalar]) -> P::Scalar {
         assert_eq!(shares.len(), indices.len());
         assert!(shares.len() >= self.reconstruct_limit());
         // add one to indices to get points
@@ -125,8 +126,8 @@ impl VerifiableSS {
                 let index_bn = BigInt::from(*i as u32 + 1 as u32);
                 ECScalar::from(&index_bn)
             })
-            .collect::<Vec<FE>>();
-        VerifiableSS::lagrange_interpolation_at_zero(&points, &shares)
+            .collect::<Vec<P::Scalar>>();
+        Self::lagrange_interpolation_at_zero(&points, &shares)
     }
 
     // Performs a Lagrange interpolation in field Zp at the origin
@@ -139,7 +140,7 @@ impl VerifiableSS {
     // This is obviously less general than `newton_interpolation_general` as we
     // only get a single value, but it is much faster.
 
-    pub fn lagrange_interpolation_at_zero(points: &[FE], values: &[FE]) -> FE {
+    pub fn lagrange_interpolation_at_zero(points: &[P::Scalar], values: &[P::Scalar]) -> P::Scalar {
         let vec_len = values.len();
 
         assert_eq!(points.len(), vec_len);
@@ -150,8 +151,8 @@ impl VerifiableSS {
                 .map(|i| {
                     let xi = &points[i];
                     let yi = &values[i];
-                    let num: FE = ECScalar::from(&BigInt::one());
-                    let denum: FE = ECScalar::from(&BigInt::one());
+                    let num: P::Scalar = ECScalar::from(&BigInt::one());
+                    let denum: P::Scalar = ECScalar::from(&BigInt::one());
                     let num = points.iter().zip(0..vec_len).fold(num, |acc, x| {
                         if i != x.1 {
                             acc * x.0
@@ -170,20 +171,20 @@ impl VerifiableSS {
                     let denum = denum.invert();
                     num * denum * yi
                 })
-                .collect::<Vec<FE>>();
+                .collect::<Vec<P::Scalar>>();
         let mut lag_coef_iter = lag_coef.iter();
         let head = lag_coef_iter.next().unwrap();
         let tail = lag_coef_iter;
-        tail.fold(head.clone(), |acc, x| acc.add(&x.get_element()))
+        tail.fold(head.clone(), |acc, x| acc + x)
     }
 
-    pub fn validate_share(&self, secret_share: &FE, index: usize) -> Result<(), TssError> {
-        let G: GE = ECPoint::generator();
+    pub fn validate_share(&self, secret_share: &P::Scalar, index: usize) -> Result<(), TssError> {
+        let G = P::generator();
         let ss_point = G * secret_share;
         self.validate_share_public(&ss_point, index)
     }
 
-    pub fn validate_share_public(&self, ss_point: &GE, index: usize) -> Result<(), TssError> {
+    pub fn validate_share_public(&self, ss_point: &P, index: usize) -> Result<(), TssError> {
         let comm_to_point = self.get_point_commitment(index);
         if *ss_point == comm_to_point {
             Ok(())
@@ -192,12 +193,12 @@ impl VerifiableSS {
         }
     }
 
-    pub fn get_point_commitment(&self, index: usize) -> GE {
-        let index_fe: FE = ECScalar::from(&BigInt::from(index as u32));
+    pub fn get_point_commitment(&self, index: usize) -> P {
+        let index_fe: P::Scalar = ECScalar::from(&BigInt::from(index as u32));
         let mut comm_iterator = self.commitments.iter().rev();
         let head = comm_iterator.next().unwrap();
         let tail = comm_iterator;
-        let comm_to_point = tail.fold(head.clone(), |acc, x: &GE| {
+        let comm_to_point = tail.fold(head.clone(), |acc, x: &P| {
             x.clone() + acc * index_fe.clone()
         });
         comm_to_point
@@ -205,20 +206,20 @@ impl VerifiableSS {
 
     //compute \lambda_{index,S}, a lagrangian coefficient that change the (t,n) scheme to (|S|,|S|)
     // used in http://stevengoldfeder.com/papers/GG18.pdf
-    pub fn map_share_to_new_params(&self, index: usize, s: &[usize]) -> FE {
+    pub fn map_share_to_new_params(&self, index: usize, s: &[usize]) -> P::Scalar {
         let s_len = s.len();
         //     assert!(s_len > self.reconstruct_limit());
         // add one to indices to get points
-        let points: Vec<FE> = (0..self.parameters.share_count)
+        let points: Vec<P::Scalar> = (0..self.parameters.share_count)
             .map(|i| {
                 let index_bn = BigInt::from(i as u32 + 1 as u32);
                 ECScalar::from(&index_bn)
             })
-            .collect::<Vec<FE>>();
+            .collect::<Vec<P::Scalar>>();
 
         let xi = &points[index];
-        let num: FE = ECScalar::from(&BigInt::one());
-        let denum: FE = ECScalar::from(&BigInt::one());
+        let num: P::Scalar = ECScalar::from(&BigInt::one());
+        let denum: P::Scalar = ECScalar::from(&BigInt::one());
         let num = (0..s_len).fold(num, |acc, i| {
             if s[i] != index {
                 acc * points[s[i]].clone()
@@ -243,6 +244,7 @@ impl VerifiableSS {
 mod tests {
     use crate::curv::cryptographic_primitives::secret_sharing::feldman_vss::*;
     use crate::curv::elliptic::curves::secp256_k1::{FE, GE};
+    use crate::curv::elliptic::curves::secp256_r1;
 
     #[cfg(target_arch = "wasm32")]
     use wasm_bindgen_test::*;
@@ -252,7 +254,7 @@ mod tests {
     fn test_secret_sharing_3_out_of_5_at_indices() {
         let secret: FE = ECScalar::new_random();
         let parties = [1, 2, 4, 5, 6];
-        let (vss_scheme, secret_shares) = VerifiableSS::share_at_indices(3, 5, &secret, &parties);
+        let (vss_scheme, secret_shares) = VerifiableSS::<GE>::share_at_indices(3, 5, &secret, &parties);
 
         let mut shares_vec = Vec::new();
         shares_vec.push(secret_shares[0].clone());
@@ -270,7 +272,7 @@ mod tests {
     fn test_secret_sharing_3_out_of_5() {
         let secret: FE = ECScalar::new_random();
 
-        let (vss_scheme, secret_shares) = VerifiableSS::share(3, 5, &secret);
+        let (vss_scheme, secret_shares) = VerifiableSS::<GE>::share(3, 5, &secret);
 
         let mut shares_vec = Vec::new();
         shares_vec.push(secret_shares[0].clone());
@@ -308,12 +310,36 @@ mod tests {
         assert_eq!(w, secret_reconstructed);
     }
 
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_secret_sharing_3_out_of_5_p256() {
+        let secret: secp256_r1::FE = ECScalar::new_random();
+
+        let (vss_scheme, secret_shares) = VerifiableSS::<secp256_r1::GE>::share(3, 5, &secret);
+
+        let shares_vec = vec![
+            secret_shares[0].clone(),
+            secret_shares[1].clone(),
+            secret_shares[2].clone(),
+            secret_shares[4].clone(),
+        ];
+        let secret_reconstructed = vss_scheme.reconstruct(&[0, 1, 2, 4], &shares_vec);
+        assert_eq!(secret, secret_reconstructed);
+
+        for (i, share) in secret_shares.iter().enumerate() {
+            assert!(vss_scheme.validate_share(share, i + 1).is_ok());
+        }
+        assert!(vss_scheme
+            .validate_share(&secret_shares[0], 2)
+            .is_err());
+    }
+
     #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
     #[test]
     fn test_secret_sharing_3_out_of_7() {
         let secret: FE = ECScalar::new_random();
 
-        let (vss_scheme, secret_shares) = VerifiableSS::share(3, 7, &secret);
+        let (vss_scheme, secret_shares) = VerifiableSS::<GE>::share(3, 7, &secret);
 
         let mut shares_vec = Vec::new();
         shares_vec.push(secret_shares[0].clone());
@@ -351,7 +377,7 @@ mod tests {
     fn test_secret_sharing_1_out_of_2() {
         let secret: FE = ECScalar::new_random();
 
-        let (vss_scheme, secret_shares) = VerifiableSS::share(1, 2, &secret);
+        let (vss_scheme, secret_shares) = VerifiableSS::<GE>::share(1, 2, &secret);
 
         let mut shares_vec = Vec::new();
         shares_vec.push(secret_shares[0].clone());
@@ -381,14 +407,14 @@ mod tests {
     fn test_secret_sharing_1_out_of_3() {
         let secret: FE = ECScalar::new_random();
 
-        let (vss_scheme, secret_shares) = VerifiableSS::share(1, 3, &secret);
+        let (vss_scheme, secret_shares) = VerifiableSS::<GE>::share(1, 3, &secret);
 
         let mut shares_vec = Vec::new();
         shares_vec.push(secret_shares[0].clone());
         shares_vec.push(secret_shares[1].clone());
 
         // test commitment to point and sum of commitments
-        let (vss_scheme2, secret_shares2) = VerifiableSS::share(1, 3, &secret);
+        let (vss_scheme2, secret_shares2) = VerifiableSS::<GE>::share(1, 3, &secret);
         let sum = secret_shares[0].clone() + secret_shares2[0].clone();
         let point_comm1 = vss_scheme.get_point_commitment(1);
         let point_comm2 = vss_scheme.get_point_commitment(2);
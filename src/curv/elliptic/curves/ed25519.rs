@@ -0,0 +1,671 @@
+/*
+    This file is part of Curv library
+    Copyright 2018 by Kzen Networks
+    (https://github.com/KZen-networks/curv)
+    License MIT: <https://github.com/KZen-networks/curv/blob/master/LICENSE>
+*/
+
+// Ed25519 on top of curve25519-dalek. Scalars and points serialize like the
+// secp256k1 ones: a scalar is a hex string and a point is an {x, y} object of
+// the hex affine Edwards coordinates. dalek does not expose coordinates, so they
+// are recovered from the compressed encoding. Only points of the prime order
+// subgroup are accepted, anything with a torsion component is rejected.
+
+use super::traits::{ECPoint, ECScalar};
+use crate::curv::arithmetic::num_bigint::BigInt;
+use crate::curv::arithmetic::traits::{Converter, Modulo, Samplable};
+use crate::errors::TssError;
+use curve25519_dalek::constants::ED25519_BASEPOINT_POINT;
+use curve25519_dalek::edwards::{CompressedEdwardsY, EdwardsPoint};
+use curve25519_dalek::scalar::Scalar;
+use num_integer::Integer;
+use serde::de;
+use serde::de::{MapAccess, Visitor};
+use serde::ser::SerializeStruct;
+use serde::ser::{Serialize, Serializer};
+use serde::{Deserialize, Deserializer};
+use std::fmt;
+use std::ops::{Add, Mul};
+use zeroize::Zeroize;
+
+// l = 2^252 + 27742317777372353535851937790883648493
+const CURVE_ORDER: &str = "1000000000000000000000000000000014def9dea2f79cd65812631a5cf5d3ed";
+// p = 2^255 - 19
+const FIELD_PRIME: &str = "7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffed";
+
+lazy_static::lazy_static! {
+    static ref CURVE_ORDER_: BigInt = BigInt::from_hex(CURVE_ORDER);
+    static ref FIELD_PRIME_: BigInt = BigInt::from_hex(FIELD_PRIME);
+    // d = -121665 / 121666
+    static ref EDWARDS_D: BigInt = BigInt::mod_mul(
+        &(&*FIELD_PRIME_ - BigInt::from(121665u32)),
+        &BigInt::mod_inv(&BigInt::from(121666u32), &FIELD_PRIME_),
+        &FIELD_PRIME_,
+    );
+    // 2^((p - 1) / 4), a square root of -1
+    static ref SQRT_M1: BigInt = BigInt::mod_pow(
+        &BigInt::from(2u32),
+        &((&*FIELD_PRIME_ - BigInt::from(1u32)) >> 2),
+        &FIELD_PRIME_,
+    );
+}
+
+pub type SK = Scalar;
+pub type PK = EdwardsPoint;
+
+#[derive(Clone, Debug)]
+pub struct Ed25519Scalar {
+    purpose: &'static str,
+    fe: SK,
+}
+#[derive(Clone, Debug)]
+pub struct Ed25519Point {
+    purpose: &'static str,
+    ge: PK,
+}
+
+pub type GE = Ed25519Point;
+pub type FE = Ed25519Scalar;
+
+// left pads a big endian integer to 32 bytes and flips it to little endian,
+// None if it does not fit
+fn to_le_bytes(n: &BigInt) -> Option<[u8; 32]> {
+    let v = BigInt::to_vec(n);
+    if v.len() > 32 {
+        return None;
+    }
+    let mut bytes = [0u8; 32];
+    bytes[32 - v.len()..].copy_from_slice(&v);
+    bytes.reverse();
+    Some(bytes)
+}
+
+fn from_le_bytes(bytes: &[u8]) -> BigInt {
+    let mut be = bytes.to_vec();
+    be.reverse();
+    BigInt::from_bytes_be(&be)
+}
+
+impl Ed25519Scalar {
+    pub fn copy(&self) -> Ed25519Scalar {
+        Ed25519Scalar {
+            purpose: self.purpose,
+            fe: self.fe,
+        }
+    }
+
+    /// Reduces a 64 byte little endian integer, e.g. a SHA-512 digest, mod l
+    /// as RFC 8032 does for nonces and challenges.
+    pub fn from_hash(digest: &[u8; 64]) -> Ed25519Scalar {
+        Ed25519Scalar {
+            purpose: "from_hash",
+            fe: SK::from_bytes_mod_order_wide(digest),
+        }
+    }
+
+    /// Little endian encoding, the `S` half of an Ed25519 signature.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.fe.to_bytes()
+    }
+
+    /// Parses `to_bytes` output; None unless the integer is reduced mod l.
+    pub fn from_canonical_bytes(bytes: [u8; 32]) -> Option<Ed25519Scalar> {
+        Option::from(SK::from_canonical_bytes(bytes)).map(|fe| Ed25519Scalar {
+            purpose: "from_bytes",
+            fe,
+        })
+    }
+}
+
+impl Ed25519Point {
+    pub fn random_point() -> Ed25519Point {
+        let random_scalar: Ed25519Scalar = Ed25519Scalar::new_random();
+        Ed25519Point {
+            purpose: "random_point",
+            ge: ED25519_BASEPOINT_POINT * random_scalar.fe,
+        }
+    }
+
+    fn try_from_compressed(bytes: &[u8]) -> Result<Ed25519Point, TssError> {
+        let compressed =
+            CompressedEdwardsY::from_slice(bytes).map_err(|_| TssError::InvalidPublicKey)?;
+        compressed
+            .decompress()
+            .filter(|point| point.is_torsion_free())
+            .map(|point| Ed25519Point {
+                purpose: "from_bytes",
+                ge: point,
+            })
+            .ok_or(TssError::InvalidPublicKey)
+    }
+
+    fn try_from_coor(x: &BigInt, y: &BigInt) -> Result<Ed25519Point, TssError> {
+        if x >= &*FIELD_PRIME_ || y >= &*FIELD_PRIME_ {
+            return Err(TssError::InvalidPublicKey);
+        }
+        let mut bytes = to_le_bytes(y).ok_or(TssError::InvalidPublicKey)?;
+        if x.is_odd() {
+            bytes[31] |= 0x80;
+        }
+        let point = Ed25519Point::try_from_compressed(&bytes)?;
+        match point.x_coor() {
+            Some(ref x_point) if x_point == x => Ok(point),
+            _ => Err(TssError::InvalidPublicKey),
+        }
+    }
+
+    // affine (x, y) from the compressed encoding: y and the sign of x are
+    // stored, x is the root of x^2 = (y^2 - 1) / (d y^2 + 1)
+    fn coordinates(&self) -> (BigInt, BigInt) {
+        let p = &*FIELD_PRIME_;
+        let mut bytes = self.ge.compress().to_bytes();
+        let x_is_odd = bytes[31] >> 7 == 1;
+        bytes[31] &= 0x7f;
+        let y = from_le_bytes(&bytes);
+
+        let yy = BigInt::mod_mul(&y, &y, p);
+        let u = BigInt::mod_sub(&yy, &BigInt::from(1u32), p);
+        let v = BigInt::mod_add(&BigInt::mod_mul(&EDWARDS_D, &yy, p), &BigInt::from(1u32), p);
+        let xx = BigInt::mod_mul(&u, &BigInt::mod_inv(&v, p), p);
+
+        // p = 5 mod 8, so xx^((p + 3) / 8) is a root of xx or of -xx
+        let mut x = BigInt::mod_pow(&xx, &((p + BigInt::from(3u32)) >> 3), p);
+        if BigInt::mod_mul(&x, &x, p) != xx {
+            x = BigInt::mod_mul(&x, &SQRT_M1, p);
+        }
+        if x.is_odd() != x_is_odd {
+            x = BigInt::mod_sub(&BigInt::from(0u32), &x, p);
+        }
+        (x, y)
+    }
+
+    pub fn copy(&self) -> Ed25519Point {
+        Ed25519Point {
+            purpose: self.purpose,
+            ge: self.ge,
+        }
+    }
+}
+
+impl Zeroize for Ed25519Scalar {
+    fn zeroize(&mut self) {
+        self.fe.zeroize();
+    }
+}
+
+impl ECScalar for Ed25519Scalar {
+    type SecretKey = SK;
+    type ScalarLength = typenum::U32;
+
+    fn new_random() -> Ed25519Scalar {
+        let random: Ed25519Scalar = ECScalar::from(&BigInt::sample_below(&CURVE_ORDER_));
+        Ed25519Scalar {
+            purpose: "random",
+            fe: random.fe,
+        }
+    }
+
+    fn zero() -> Ed25519Scalar {
+        Ed25519Scalar {
+            purpose: "zero",
+            fe: SK::ZERO,
+        }
+    }
+
+    fn get_element(&self) -> SK {
+        self.fe
+    }
+
+    fn set_element(&mut self, element: SK) {
+        self.fe = element
+    }
+
+    fn from(n: &BigInt) -> Ed25519Scalar {
+        let n_reduced = BigInt::mod_add(n, &BigInt::from(0u16), &CURVE_ORDER_);
+        Ed25519Scalar {
+            purpose: "from_big_int",
+            fe: SK::from_bytes_mod_order(to_le_bytes(&n_reduced).unwrap()),
+        }
+    }
+
+    fn to_big_int(&self) -> BigInt {
+        from_le_bytes(self.fe.as_bytes())
+    }
+
+    fn q() -> BigInt {
+        CURVE_ORDER_.clone()
+    }
+
+    fn group_order() -> &'static BigInt {
+        &CURVE_ORDER_
+    }
+
+    fn add(&self, other: &SK) -> Ed25519Scalar {
+        Ed25519Scalar {
+            purpose: "add",
+            fe: self.fe + other,
+        }
+    }
+
+    fn mul(&self, other: &SK) -> Ed25519Scalar {
+        Ed25519Scalar {
+            purpose: "mul",
+            fe: self.fe * other,
+        }
+    }
+
+    fn sub(&self, other: &SK) -> Ed25519Scalar {
+        Ed25519Scalar {
+            purpose: "sub",
+            fe: self.fe - other,
+        }
+    }
+
+    fn invert(&self) -> Ed25519Scalar {
+        Ed25519Scalar {
+            purpose: "invert",
+            fe: self.fe.invert(),
+        }
+    }
+}
+
+impl Mul<Ed25519Scalar> for Ed25519Scalar {
+    type Output = Ed25519Scalar;
+    fn mul(self, other: Ed25519Scalar) -> Ed25519Scalar {
+        ECScalar::mul(&self, &other.get_element())
+    }
+}
+
+impl<'o> Mul<&'o Ed25519Scalar> for Ed25519Scalar {
+    type Output = Ed25519Scalar;
+    fn mul(self, other: &'o Ed25519Scalar) -> Ed25519Scalar {
+        ECScalar::mul(&self, &other.get_element())
+    }
+}
+
+impl Add<Ed25519Scalar> for Ed25519Scalar {
+    type Output = Ed25519Scalar;
+    fn add(self, other: Ed25519Scalar) -> Ed25519Scalar {
+        ECScalar::add(&self, &other.get_element())
+    }
+}
+
+impl<'o> Add<&'o Ed25519Scalar> for Ed25519Scalar {
+    type Output = Ed25519Scalar;
+    fn add(self, other: &'o Ed25519Scalar) -> Ed25519Scalar {
+        ECScalar::add(&self, &other.get_element())
+    }
+}
+
+impl Serialize for Ed25519Scalar {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_big_int().to_hex())
+    }
+}
+
+impl<'de> Deserialize<'de> for Ed25519Scalar {
+    fn deserialize<D>(deserializer: D) -> Result<Ed25519Scalar, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(Ed25519ScalarVisitor)
+    }
+}
+
+struct Ed25519ScalarVisitor;
+
+impl<'de> Visitor<'de> for Ed25519ScalarVisitor {
+    type Value = Ed25519Scalar;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("Ed25519Scalar")
+    }
+
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<Ed25519Scalar, E> {
+        let v =
+            BigInt::parse_bytes(s.as_bytes(), 16).ok_or_else(|| E::custom("invalid scalar hex"))?;
+        Ok(ECScalar::from(&v))
+    }
+}
+
+impl PartialEq for Ed25519Scalar {
+    fn eq(&self, other: &Ed25519Scalar) -> bool {
+        self.fe == other.fe
+    }
+}
+
+impl PartialEq for Ed25519Point {
+    fn eq(&self, other: &Ed25519Point) -> bool {
+        self.ge == other.ge
+    }
+}
+
+impl Zeroize for Ed25519Point {
+    fn zeroize(&mut self) {
+        self.ge.zeroize();
+    }
+}
+
+impl ECPoint for Ed25519Point {
+    type SecretKey = SK;
+    type PublicKey = PK;
+    type Scalar = Ed25519Scalar;
+    type CompressedPointLength = typenum::U32;
+    type UncompressedPointLength = typenum::U64;
+
+    fn generator() -> Ed25519Point {
+        Ed25519Point {
+            purpose: "base_fe",
+            ge: ED25519_BASEPOINT_POINT,
+        }
+    }
+
+    fn get_element(&self) -> PK {
+        self.ge
+    }
+
+    fn bytes_compressed_to_big_int(&self) -> BigInt {
+        BigInt::from_bytes_be(self.to_bytes(true).as_ref())
+    }
+
+    fn x_coor(&self) -> Option<BigInt> {
+        Some(self.coordinates().0)
+    }
+
+    fn y_coor(&self) -> Option<BigInt> {
+        Some(self.coordinates().1)
+    }
+
+    /// Accepts the 32 byte RFC 8032 encoding as well as the raw 64 byte x || y
+    fn from_bytes(bytes: &[u8]) -> Result<Ed25519Point, TssError> {
+        match bytes.len() {
+            32 => Ed25519Point::try_from_compressed(bytes),
+            64 => Ed25519Point::try_from_coor(
+                &BigInt::from_bytes_be(&bytes[..32]),
+                &BigInt::from_bytes_be(&bytes[32..]),
+            ),
+            _ => Err(TssError::InvalidPublicKey),
+        }
+    }
+
+    /// The 32 byte public key encoding of RFC 8032
+    fn pk_to_key_slice(&self) -> Vec<u8> {
+        self.to_bytes(true)
+    }
+
+    fn scalar_mul(&self, fe: &SK) -> Ed25519Point {
+        Ed25519Point {
+            purpose: "scalar_point_mul",
+            ge: self.ge * fe,
+        }
+    }
+
+    fn add_point(&self, other: &PK) -> Ed25519Point {
+        Ed25519Point {
+            purpose: "combine",
+            ge: self.ge + other,
+        }
+    }
+
+    fn sub_point(&self, other: &PK) -> Ed25519Point {
+        Ed25519Point {
+            purpose: "sub_point",
+            ge: self.ge - other,
+        }
+    }
+
+    /// The RFC 8032 encoding when compressed, big endian x || y otherwise
+    fn to_bytes(&self, compressed: bool) -> Vec<u8> {
+        if compressed {
+            return self.ge.compress().to_bytes().to_vec();
+        }
+        let (x, y) = self.coordinates();
+        let mut bytes = to_le_bytes(&x).unwrap().to_vec();
+        bytes.reverse();
+        let mut y_bytes = to_le_bytes(&y).unwrap();
+        y_bytes.reverse();
+        bytes.extend_from_slice(&y_bytes);
+        bytes
+    }
+
+    fn from_coor(x: &BigInt, y: &BigInt) -> Ed25519Point {
+        Ed25519Point::try_from_coor(x, y).unwrap()
+    }
+}
+
+impl Mul<Ed25519Scalar> for Ed25519Point {
+    type Output = Ed25519Point;
+    fn mul(self, other: Ed25519Scalar) -> Self::Output {
+        self.scalar_mul(&other.get_element())
+    }
+}
+
+impl<'o> Mul<&'o Ed25519Scalar> for Ed25519Point {
+    type Output = Ed25519Point;
+    fn mul(self, other: &'o Ed25519Scalar) -> Self::Output {
+        self.scalar_mul(&other.get_element())
+    }
+}
+
+impl<'o> Mul<&'o Ed25519Scalar> for &'o Ed25519Point {
+    type Output = Ed25519Point;
+    fn mul(self, other: &'o Ed25519Scalar) -> Self::Output {
+        self.scalar_mul(&other.get_element())
+    }
+}
+
+impl Add<Ed25519Point> for Ed25519Point {
+    type Output = Ed25519Point;
+    fn add(self, other: Ed25519Point) -> Self::Output {
+        self.add_point(&other.get_element())
+    }
+}
+
+impl<'o> Add<&'o Ed25519Point> for Ed25519Point {
+    type Output = Ed25519Point;
+    fn add(self, other: &'o Ed25519Point) -> Self::Output {
+        self.add_point(&other.get_element())
+    }
+}
+
+impl<'o> Add<&'o Ed25519Point> for &'o Ed25519Point {
+    type Output = Ed25519Point;
+    fn add(self, other: &'o Ed25519Point) -> Self::Output {
+        self.add_point(&other.get_element())
+    }
+}
+
+impl Serialize for Ed25519Point {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let (x, y) = self.coordinates();
+        let mut state = serializer.serialize_struct("Ed25519Point", 2)?;
+        state.serialize_field("x", &x.to_hex())?;
+        state.serialize_field("y", &y.to_hex())?;
+        state.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Ed25519Point {
+    fn deserialize<D>(deserializer: D) -> Result<Ed25519Point, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let fields = &["x", "y"];
+        deserializer.deserialize_struct("Ed25519Point", fields, Ed25519PointVisitor)
+    }
+}
+
+struct Ed25519PointVisitor;
+
+impl<'de> Visitor<'de> for Ed25519PointVisitor {
+    type Value = Ed25519Point;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("Ed25519Point")
+    }
+
+    fn visit_map<E: MapAccess<'de>>(self, mut map: E) -> Result<Ed25519Point, E::Error> {
+        let mut x = None;
+        let mut y = None;
+
+        while let Some(ref key) = map.next_key::<String>()? {
+            let v = map.next_value::<String>()?;
+            let coor = BigInt::parse_bytes(v.as_bytes(), 16)
+                .ok_or_else(|| de::Error::custom("invalid coordinate hex"))?;
+            match key.as_str() {
+                "x" => x = Some(coor),
+                "y" => y = Some(coor),
+                _ => return Err(de::Error::unknown_field(key, &["x", "y"])),
+            }
+        }
+
+        let x = x.ok_or_else(|| de::Error::missing_field("x"))?;
+        let y = y.ok_or_else(|| de::Error::missing_field("y"))?;
+        Ed25519Point::try_from_coor(&x, &y)
+            .map_err(|_| de::Error::custom("point is not in the Ed25519 prime order group"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Ed25519Point, FE, GE};
+    use crate::curv::arithmetic::num_bigint::BigInt;
+    use crate::curv::arithmetic::traits::{Converter, Modulo};
+    use crate::curv::elliptic::curves::traits::{ECPoint, ECScalar};
+    use sha2::{Digest, Sha512};
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_generator() {
+        // RFC 8032 5.1
+        let g = GE::generator();
+        assert_eq!(
+            g.x_coor().unwrap().to_hex(),
+            "216936d3cd6e53fec0a4e231fdd6dc5c692cc7609525a7b2c9562d608f25d51a"
+        );
+        assert_eq!(
+            g.y_coor().unwrap().to_hex(),
+            "6666666666666666666666666666666666666666666666666666666666666658"
+        );
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_public_key_vector() {
+        // RFC 8032 7.1 TEST 1
+        let secret =
+            hex::decode("9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60")
+                .unwrap();
+        let mut h = Sha512::digest(&secret)[..32].to_vec();
+        h[0] &= 248;
+        h[31] &= 127;
+        h[31] |= 64;
+        h.reverse();
+        let a: FE = ECScalar::from(&BigInt::from_bytes_be(&h));
+        assert_eq!(
+            hex::encode(
+                GE::generator()
+                    .scalar_mul(&a.get_element())
+                    .pk_to_key_slice()
+            ),
+            "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a"
+        );
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_serdes_sk() {
+        let scalar: FE = ECScalar::from(&BigInt::from(123456u32));
+        let s = serde_json::to_string(&scalar).unwrap();
+        assert_eq!(s, "\"1e240\"");
+        let des: FE = serde_json::from_str(&s).unwrap();
+        assert_eq!(des, scalar);
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_serdes_pk() {
+        let pk = Ed25519Point::random_point();
+        let s = serde_json::to_string(&pk).unwrap();
+        let expected = format!(
+            "{{\"x\":\"{}\",\"y\":\"{}\"}}",
+            pk.x_coor().unwrap().to_hex(),
+            pk.y_coor().unwrap().to_hex()
+        );
+        assert_eq!(s, expected);
+        let des_pk: GE = serde_json::from_str(&s).unwrap();
+        assert_eq!(des_pk, pk);
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_serdes_bad_pk() {
+        let pk = GE::generator();
+        let s = serde_json::to_string(&pk).unwrap();
+        let s = s.replace("2169", "2168");
+        assert!(serde_json::from_str::<GE>(&s).is_err());
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_from_bytes() {
+        let pk = Ed25519Point::random_point();
+        for bytes in &[pk.to_bytes(true), pk.to_bytes(false)] {
+            assert_eq!(GE::from_bytes(bytes).unwrap(), pk);
+        }
+        let mut bad = pk.to_bytes(false);
+        bad[63] ^= 1;
+        assert!(GE::from_bytes(&bad).is_err());
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_from_bytes_torsion() {
+        // (0, -1) has order 2: on the curve but outside the prime order group
+        let mut bytes = [0xffu8; 32];
+        bytes[0] = 0xec;
+        bytes[31] = 0x7f;
+        assert!(GE::from_bytes(&bytes).is_err());
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_scalar_ops() {
+        let a: FE = ECScalar::new_random();
+        let b: FE = ECScalar::new_random();
+        let q = FE::q();
+        assert_eq!(
+            (a.clone() * &b).to_big_int(),
+            BigInt::mod_mul(&a.to_big_int(), &b.to_big_int(), &q)
+        );
+        assert_eq!(
+            (a.clone() + &b).to_big_int(),
+            BigInt::mod_add(&a.to_big_int(), &b.to_big_int(), &q)
+        );
+        assert_eq!(
+            a.invert().to_big_int(),
+            BigInt::mod_inv(&a.to_big_int(), &q)
+        );
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_minus_point() {
+        let a: FE = ECScalar::new_random();
+        let b: FE = ECScalar::new_random();
+        let g = GE::generator();
+        let a_minus_b: FE = a.sub(&b.get_element());
+        let point_ab1 = g.clone() * a_minus_b;
+        let point_ab2 = (g.clone() * a).sub_point(&(g * b).get_element());
+        assert_eq!(point_ab1, point_ab2);
+    }
+}
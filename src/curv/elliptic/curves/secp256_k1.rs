@@ -111,6 +111,26 @@ impl Secp256k1Point {
             ge: self.ge.clone(),
         }
     }
+
+    // from_coor for untrusted coordinates: too long or off the curve is an
+    // error rather than a panic
+    fn try_from_coor(x: &BigInt, y: &BigInt) -> Result<Secp256k1Point, TssError> {
+        let coor_size = (UNCOMPRESSED_PUBLIC_KEY_SIZE - 1) / 2;
+        let mut v = vec![4u8];
+        for coor in [BigInt::to_vec(x), BigInt::to_vec(y)] {
+            if coor.len() > coor_size {
+                return Err(TssError::InvalidPublicKey);
+            }
+            v.extend(vec![0; coor_size - coor.len()]);
+            v.extend(coor);
+        }
+        PK::parse_slice(&v, None)
+            .map(|ge| Secp256k1Point {
+                purpose: "base_fe",
+                ge,
+            })
+            .map_err(|_| TssError::InvalidPublicKey)
+    }
 }
 
 impl Zeroize for FE {
@@ -121,7 +141,8 @@ impl Zeroize for FE {
     }
 }
 
-impl ECScalar<SK> for Secp256k1Scalar {
+impl ECScalar for Secp256k1Scalar {
+    type SecretKey = SK;
     type ScalarLength = typenum::U32;
 
     fn new_random() -> Secp256k1Scalar {
@@ -310,7 +331,10 @@ impl Zeroize for GE {
     }
 }
 
-impl ECPoint<PK, SK> for Secp256k1Point {
+impl ECPoint for Secp256k1Point {
+    type SecretKey = SK;
+    type PublicKey = PK;
+    type Scalar = Secp256k1Scalar;
     type CompressedPointLength = typenum::U33;
     type UncompressedPointLength = typenum::U65;
     fn generator() -> Secp256k1Point {
@@ -586,24 +610,24 @@ impl<'de> Visitor<'de> for Secp256k1PointVisitor {
     }
 
     fn visit_map<E: MapAccess<'de>>(self, mut map: E) -> Result<Secp256k1Point, E::Error> {
-        let mut x = String::new();
-        let mut y = String::new();
+        let mut x = None;
+        let mut y = None;
 
         while let Some(ref key) = map.next_key::<String>()? {
             let v = map.next_value::<String>()?;
-            if key == "x" {
-                x = v
-            } else if key == "y" {
-                y = v
-            } else {
-                panic!("Serialization failed!")
+            let coor = BigInt::parse_bytes(v.as_bytes(), 16)
+                .ok_or_else(|| de::Error::custom("invalid coordinate hex"))?;
+            match key.as_str() {
+                "x" => x = Some(coor),
+                "y" => y = Some(coor),
+                _ => return Err(de::Error::unknown_field(key, &["x", "y"])),
             }
         }
 
-        let bx = BigInt::from_hex(&x);
-        let by = BigInt::from_hex(&y);
-
-        Ok(Secp256k1Point::from_coor(&bx, &by))
+        let x = x.ok_or_else(|| de::Error::missing_field("x"))?;
+        let y = y.ok_or_else(|| de::Error::missing_field("y"))?;
+        Secp256k1Point::try_from_coor(&x, &y)
+            .map_err(|_| de::Error::custom("point is not on secp256k1"))
     }
 }
 #[cfg(test)]
@@ -0,0 +1,544 @@
+/*
+    This file is part of Curv library
+    Copyright 2018 by Kzen Networks
+    (https://github.com/KZen-networks/curv)
+    License MIT: <https://github.com/KZen-networks/curv/blob/master/LICENSE>
+*/
+
+// NIST P-256 (secp256r1) on top of the RustCrypto p256 crate. Scalars and
+// points serialize exactly like their secp256k1 counterparts: a scalar is a
+// hex string and a point is an {x, y} object of hex coordinates.
+
+use super::traits::{ECPoint, ECScalar};
+use crate::curv::arithmetic::num_bigint::BigInt;
+use crate::curv::arithmetic::traits::{Converter, Modulo, Samplable};
+use crate::errors::TssError;
+use p256::elliptic_curve::ff::PrimeField;
+use p256::elliptic_curve::sec1::{FromEncodedPoint, ToEncodedPoint};
+use p256::{AffinePoint, EncodedPoint, FieldBytes, ProjectivePoint, Scalar};
+use serde::de;
+use serde::de::{MapAccess, Visitor};
+use serde::ser::SerializeStruct;
+use serde::ser::{Serialize, Serializer};
+use serde::{Deserialize, Deserializer};
+use std::fmt;
+use std::ops::{Add, Mul};
+use zeroize::Zeroize;
+
+const CURVE_ORDER: &str = "ffffffff00000000ffffffffffffffffbce6faada7179e84f3b9cac2fc632551";
+
+lazy_static::lazy_static! {
+    static ref CURVE_ORDER_: BigInt = BigInt::from_hex(CURVE_ORDER);
+}
+
+pub type SK = Scalar;
+pub type PK = ProjectivePoint;
+
+#[derive(Clone, Debug)]
+pub struct Secp256r1Scalar {
+    purpose: &'static str,
+    fe: SK,
+}
+#[derive(Clone, Debug)]
+pub struct Secp256r1Point {
+    purpose: &'static str,
+    ge: PK,
+}
+
+pub type GE = Secp256r1Point;
+pub type FE = Secp256r1Scalar;
+
+// left pads a big endian integer to a field element, None if it does not fit
+fn to_field_bytes(n: &BigInt) -> Option<FieldBytes> {
+    let v = BigInt::to_vec(n);
+    if v.len() > 32 {
+        return None;
+    }
+    let mut bytes = FieldBytes::default();
+    bytes[32 - v.len()..].copy_from_slice(&v);
+    Some(bytes)
+}
+
+impl Secp256r1Scalar {
+    pub fn copy(&self) -> Secp256r1Scalar {
+        Secp256r1Scalar {
+            purpose: self.purpose,
+            fe: self.fe,
+        }
+    }
+}
+
+impl Secp256r1Point {
+    pub fn random_point() -> Secp256r1Point {
+        let random_scalar: Secp256r1Scalar = Secp256r1Scalar::new_random();
+        Secp256r1Point {
+            purpose: "random_point",
+            ge: PK::GENERATOR * random_scalar.fe,
+        }
+    }
+
+    fn try_from_coor(x: &BigInt, y: &BigInt) -> Result<Secp256r1Point, TssError> {
+        let x = to_field_bytes(x).ok_or(TssError::InvalidPublicKey)?;
+        let y = to_field_bytes(y).ok_or(TssError::InvalidPublicKey)?;
+        let encoded = EncodedPoint::from_affine_coordinates(&x, &y, false);
+        Option::<AffinePoint>::from(AffinePoint::from_encoded_point(&encoded))
+            .map(|point| Secp256r1Point {
+                purpose: "base_fe",
+                ge: point.into(),
+            })
+            .ok_or(TssError::InvalidPublicKey)
+    }
+
+    pub fn copy(&self) -> Secp256r1Point {
+        Secp256r1Point {
+            purpose: self.purpose,
+            ge: self.ge,
+        }
+    }
+}
+
+impl Zeroize for Secp256r1Scalar {
+    fn zeroize(&mut self) {
+        self.fe.zeroize();
+    }
+}
+
+impl ECScalar for Secp256r1Scalar {
+    type SecretKey = SK;
+    type ScalarLength = typenum::U32;
+
+    fn new_random() -> Secp256r1Scalar {
+        let random: Secp256r1Scalar = ECScalar::from(&BigInt::sample_below(&CURVE_ORDER_));
+        Secp256r1Scalar {
+            purpose: "random",
+            fe: random.fe,
+        }
+    }
+
+    fn zero() -> Secp256r1Scalar {
+        Secp256r1Scalar {
+            purpose: "zero",
+            fe: SK::ZERO,
+        }
+    }
+
+    fn get_element(&self) -> SK {
+        self.fe
+    }
+
+    fn set_element(&mut self, element: SK) {
+        self.fe = element
+    }
+
+    fn from(n: &BigInt) -> Secp256r1Scalar {
+        let n_reduced = BigInt::mod_add(n, &BigInt::from(0u16), &CURVE_ORDER_);
+        let bytes = to_field_bytes(&n_reduced).unwrap();
+        Secp256r1Scalar {
+            purpose: "from_big_int",
+            fe: Option::from(SK::from_repr(bytes)).unwrap(),
+        }
+    }
+
+    fn to_big_int(&self) -> BigInt {
+        BigInt::from_bytes_be(&self.fe.to_repr())
+    }
+
+    fn q() -> BigInt {
+        CURVE_ORDER_.clone()
+    }
+
+    fn group_order() -> &'static BigInt {
+        &CURVE_ORDER_
+    }
+
+    fn add(&self, other: &SK) -> Secp256r1Scalar {
+        Secp256r1Scalar {
+            purpose: "add",
+            fe: self.fe + other,
+        }
+    }
+
+    fn mul(&self, other: &SK) -> Secp256r1Scalar {
+        Secp256r1Scalar {
+            purpose: "mul",
+            fe: self.fe * other,
+        }
+    }
+
+    fn sub(&self, other: &SK) -> Secp256r1Scalar {
+        Secp256r1Scalar {
+            purpose: "sub",
+            fe: self.fe - other,
+        }
+    }
+
+    fn invert(&self) -> Secp256r1Scalar {
+        Secp256r1Scalar {
+            purpose: "invert",
+            fe: self.fe.invert().unwrap_or(SK::ZERO),
+        }
+    }
+}
+
+impl Mul<Secp256r1Scalar> for Secp256r1Scalar {
+    type Output = Secp256r1Scalar;
+    fn mul(self, other: Secp256r1Scalar) -> Secp256r1Scalar {
+        ECScalar::mul(&self, &other.get_element())
+    }
+}
+
+impl<'o> Mul<&'o Secp256r1Scalar> for Secp256r1Scalar {
+    type Output = Secp256r1Scalar;
+    fn mul(self, other: &'o Secp256r1Scalar) -> Secp256r1Scalar {
+        ECScalar::mul(&self, &other.get_element())
+    }
+}
+
+impl Add<Secp256r1Scalar> for Secp256r1Scalar {
+    type Output = Secp256r1Scalar;
+    fn add(self, other: Secp256r1Scalar) -> Secp256r1Scalar {
+        ECScalar::add(&self, &other.get_element())
+    }
+}
+
+impl<'o> Add<&'o Secp256r1Scalar> for Secp256r1Scalar {
+    type Output = Secp256r1Scalar;
+    fn add(self, other: &'o Secp256r1Scalar) -> Secp256r1Scalar {
+        ECScalar::add(&self, &other.get_element())
+    }
+}
+
+impl Serialize for Secp256r1Scalar {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_big_int().to_hex())
+    }
+}
+
+impl<'de> Deserialize<'de> for Secp256r1Scalar {
+    fn deserialize<D>(deserializer: D) -> Result<Secp256r1Scalar, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(Secp256r1ScalarVisitor)
+    }
+}
+
+struct Secp256r1ScalarVisitor;
+
+impl<'de> Visitor<'de> for Secp256r1ScalarVisitor {
+    type Value = Secp256r1Scalar;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("Secp256r1Scalar")
+    }
+
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<Secp256r1Scalar, E> {
+        let v = BigInt::parse_bytes(s.as_bytes(), 16)
+            .ok_or_else(|| E::custom("invalid scalar hex"))?;
+        Ok(ECScalar::from(&v))
+    }
+}
+
+impl PartialEq for Secp256r1Scalar {
+    fn eq(&self, other: &Secp256r1Scalar) -> bool {
+        self.fe == other.fe
+    }
+}
+
+impl PartialEq for Secp256r1Point {
+    fn eq(&self, other: &Secp256r1Point) -> bool {
+        self.ge == other.ge
+    }
+}
+
+impl Zeroize for Secp256r1Point {
+    fn zeroize(&mut self) {
+        self.ge = PK::GENERATOR;
+    }
+}
+
+impl ECPoint for Secp256r1Point {
+    type SecretKey = SK;
+    type PublicKey = PK;
+    type Scalar = Secp256r1Scalar;
+    type CompressedPointLength = typenum::U33;
+    type UncompressedPointLength = typenum::U65;
+
+    fn generator() -> Secp256r1Point {
+        Secp256r1Point {
+            purpose: "base_fe",
+            ge: PK::GENERATOR,
+        }
+    }
+
+    fn get_element(&self) -> PK {
+        self.ge
+    }
+
+    fn bytes_compressed_to_big_int(&self) -> BigInt {
+        BigInt::from_bytes_be(self.to_bytes(true).as_ref())
+    }
+
+    fn x_coor(&self) -> Option<BigInt> {
+        let encoded = self.ge.to_affine().to_encoded_point(false);
+        encoded.x().map(|x| BigInt::from_bytes_be(x))
+    }
+
+    fn y_coor(&self) -> Option<BigInt> {
+        let encoded = self.ge.to_affine().to_encoded_point(false);
+        encoded.y().map(|y| BigInt::from_bytes_be(y))
+    }
+
+    /// Accepts SEC1 encoded points as well as the raw 64 byte x || y
+    fn from_bytes(bytes: &[u8]) -> Result<Secp256r1Point, TssError> {
+        let encoded = match bytes.len() {
+            64 => EncodedPoint::from_untagged_bytes(bytes.into()),
+            _ => EncodedPoint::from_bytes(bytes).map_err(|_| TssError::InvalidPublicKey)?,
+        };
+        Option::<AffinePoint>::from(AffinePoint::from_encoded_point(&encoded))
+            .map(|point| Secp256r1Point {
+                purpose: "from_bytes",
+                ge: point.into(),
+            })
+            .ok_or(TssError::InvalidPublicKey)
+    }
+
+    fn pk_to_key_slice(&self) -> Vec<u8> {
+        self.to_bytes(false)
+    }
+
+    fn scalar_mul(&self, fe: &SK) -> Secp256r1Point {
+        Secp256r1Point {
+            purpose: "scalar_point_mul",
+            ge: self.ge * fe,
+        }
+    }
+
+    fn add_point(&self, other: &PK) -> Secp256r1Point {
+        Secp256r1Point {
+            purpose: "combine",
+            ge: self.ge + other,
+        }
+    }
+
+    fn sub_point(&self, other: &PK) -> Secp256r1Point {
+        Secp256r1Point {
+            purpose: "sub_point",
+            ge: self.ge - other,
+        }
+    }
+
+    /// Serializes a point in (un)compressed form
+    fn to_bytes(&self, compressed: bool) -> Vec<u8> {
+        self.ge
+            .to_affine()
+            .to_encoded_point(compressed)
+            .as_bytes()
+            .to_vec()
+    }
+
+    fn from_coor(x: &BigInt, y: &BigInt) -> Secp256r1Point {
+        Secp256r1Point::try_from_coor(x, y).unwrap()
+    }
+}
+
+impl Mul<Secp256r1Scalar> for Secp256r1Point {
+    type Output = Secp256r1Point;
+    fn mul(self, other: Secp256r1Scalar) -> Self::Output {
+        self.scalar_mul(&other.get_element())
+    }
+}
+
+impl<'o> Mul<&'o Secp256r1Scalar> for Secp256r1Point {
+    type Output = Secp256r1Point;
+    fn mul(self, other: &'o Secp256r1Scalar) -> Self::Output {
+        self.scalar_mul(&other.get_element())
+    }
+}
+
+impl<'o> Mul<&'o Secp256r1Scalar> for &'o Secp256r1Point {
+    type Output = Secp256r1Point;
+    fn mul(self, other: &'o Secp256r1Scalar) -> Self::Output {
+        self.scalar_mul(&other.get_element())
+    }
+}
+
+impl Add<Secp256r1Point> for Secp256r1Point {
+    type Output = Secp256r1Point;
+    fn add(self, other: Secp256r1Point) -> Self::Output {
+        self.add_point(&other.get_element())
+    }
+}
+
+impl<'o> Add<&'o Secp256r1Point> for Secp256r1Point {
+    type Output = Secp256r1Point;
+    fn add(self, other: &'o Secp256r1Point) -> Self::Output {
+        self.add_point(&other.get_element())
+    }
+}
+
+impl<'o> Add<&'o Secp256r1Point> for &'o Secp256r1Point {
+    type Output = Secp256r1Point;
+    fn add(self, other: &'o Secp256r1Point) -> Self::Output {
+        self.add_point(&other.get_element())
+    }
+}
+
+impl Serialize for Secp256r1Point {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("Secp256r1Point", 2)?;
+        state.serialize_field("x", &self.x_coor().unwrap().to_hex())?;
+        state.serialize_field("y", &self.y_coor().unwrap().to_hex())?;
+        state.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Secp256r1Point {
+    fn deserialize<D>(deserializer: D) -> Result<Secp256r1Point, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let fields = &["x", "y"];
+        deserializer.deserialize_struct("Secp256r1Point", fields, Secp256r1PointVisitor)
+    }
+}
+
+struct Secp256r1PointVisitor;
+
+impl<'de> Visitor<'de> for Secp256r1PointVisitor {
+    type Value = Secp256r1Point;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("Secp256r1Point")
+    }
+
+    fn visit_map<E: MapAccess<'de>>(self, mut map: E) -> Result<Secp256r1Point, E::Error> {
+        let mut x = None;
+        let mut y = None;
+
+        while let Some(ref key) = map.next_key::<String>()? {
+            let v = map.next_value::<String>()?;
+            let coor = BigInt::parse_bytes(v.as_bytes(), 16)
+                .ok_or_else(|| de::Error::custom("invalid coordinate hex"))?;
+            match key.as_str() {
+                "x" => x = Some(coor),
+                "y" => y = Some(coor),
+                _ => return Err(de::Error::unknown_field(key, &["x", "y"])),
+            }
+        }
+
+        let x = x.ok_or_else(|| de::Error::missing_field("x"))?;
+        let y = y.ok_or_else(|| de::Error::missing_field("y"))?;
+        Secp256r1Point::try_from_coor(&x, &y).map_err(|_| de::Error::custom("point is not on P-256"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Secp256r1Point, FE, GE};
+    use crate::curv::arithmetic::num_bigint::BigInt;
+    use crate::curv::arithmetic::traits::{Converter, Modulo};
+    use crate::curv::elliptic::curves::traits::{ECPoint, ECScalar};
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_generator() {
+        // FIPS 186-4 D.1.2.3
+        let g = GE::generator();
+        assert_eq!(
+            g.x_coor().unwrap().to_hex(),
+            "6b17d1f2e12c4247f8bce6e563a440f277037d812deb33a0f4a13945d898c296"
+        );
+        assert_eq!(
+            g.y_coor().unwrap().to_hex(),
+            "4fe342e2fe1a7f9b8ee7eb4a7c0f9e162bce33576b315ececbb6406837bf51f5"
+        );
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_serdes_sk() {
+        let scalar: FE = ECScalar::from(&BigInt::from(123456u32));
+        let s = serde_json::to_string(&scalar).unwrap();
+        assert_eq!(s, "\"1e240\"");
+        let des: FE = serde_json::from_str(&s).unwrap();
+        assert_eq!(des, scalar);
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_serdes_pk() {
+        let pk = Secp256r1Point::random_point();
+        let s = serde_json::to_string(&pk).unwrap();
+        let expected = format!(
+            "{{\"x\":\"{}\",\"y\":\"{}\"}}",
+            pk.x_coor().unwrap().to_hex(),
+            pk.y_coor().unwrap().to_hex()
+        );
+        assert_eq!(s, expected);
+        let des_pk: GE = serde_json::from_str(&s).unwrap();
+        assert_eq!(des_pk, pk);
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_serdes_bad_pk() {
+        let pk = GE::generator();
+        let s = serde_json::to_string(&pk).unwrap();
+        let s = s.replace("6b17", "6b18");
+        assert!(serde_json::from_str::<GE>(&s).is_err());
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_from_bytes() {
+        let pk = Secp256r1Point::random_point();
+        for bytes in &[pk.to_bytes(true), pk.to_bytes(false), pk.to_bytes(false)[1..].to_vec()] {
+            assert_eq!(GE::from_bytes(bytes).unwrap(), pk);
+        }
+        let mut bad = pk.to_bytes(false);
+        bad[64] ^= 1;
+        assert!(GE::from_bytes(&bad).is_err());
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_scalar_ops() {
+        let a: FE = ECScalar::new_random();
+        let b: FE = ECScalar::new_random();
+        let q = FE::q();
+        assert_eq!(
+            (a.clone() * &b).to_big_int(),
+            BigInt::mod_mul(&a.to_big_int(), &b.to_big_int(), &q)
+        );
+        assert_eq!(
+            (a.clone() + &b).to_big_int(),
+            BigInt::mod_add(&a.to_big_int(), &b.to_big_int(), &q)
+        );
+        assert_eq!(
+            a.invert().to_big_int(),
+            BigInt::mod_inv(&a.to_big_int(), &q)
+        );
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_minus_point() {
+        let a: FE = ECScalar::new_random();
+        let b: FE = ECScalar::new_random();
+        let g = GE::generator();
+        let a_minus_b: FE = a.sub(&b.get_element());
+        let point_ab1 = g.clone() * a_minus_b;
+        let point_ab2 = (g.clone() * a).sub_point(&(g * b).get_element());
+        assert_eq!(point_ab1, point_ab2);
+    }
+}
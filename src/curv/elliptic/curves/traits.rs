@@ -8,43 +8,78 @@
 use crate::curv::arithmetic::num_bigint::BigInt;
 use crate::errors::TssError;
 use generic_array::ArrayLength;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fmt::Debug;
+use std::ops::{Add, Mul};
 use typenum::Unsigned;
+use zeroize::Zeroize;
 
-pub trait ECScalar<SK> {
+pub trait ECScalar:
+    Mul<Output = Self>
+    + for<'a> Mul<&'a Self, Output = Self>
+    + Add<Output = Self>
+    + for<'a> Add<&'a Self, Output = Self>
+    + Clone
+    + PartialEq
+    + Debug
+    + Serialize
+    + DeserializeOwned
+    + Zeroize
+    + Send
+    + Sync
+    + Sized
+{
+    type SecretKey;
     type ScalarLength: ArrayLength<u8> + Unsigned;
     fn new_random() -> Self;
     fn zero() -> Self;
-    fn get_element(&self) -> SK;
-    fn set_element(&mut self, element: SK);
+    fn get_element(&self) -> Self::SecretKey;
+    fn set_element(&mut self, element: Self::SecretKey);
     fn from(n: &BigInt) -> Self;
     fn to_big_int(&self) -> BigInt;
     fn q() -> BigInt;
-    fn add(&self, other: &SK) -> Self;
-    fn mul(&self, other: &SK) -> Self;
-    fn sub(&self, other: &SK) -> Self;
+    fn add(&self, other: &Self::SecretKey) -> Self;
+    fn mul(&self, other: &Self::SecretKey) -> Self;
+    fn sub(&self, other: &Self::SecretKey) -> Self;
     fn invert(&self) -> Self;
     fn group_order() -> &'static BigInt;
 }
 
 // TODO: add a fn is_point
-pub trait ECPoint<PK, SK>
-where
-    Self: Sized,
+pub trait ECPoint:
+    Mul<<Self as ECPoint>::Scalar, Output = Self>
+    + for<'a> Mul<&'a <Self as ECPoint>::Scalar, Output = Self>
+    + Add<Output = Self>
+    + for<'a> Add<&'a Self, Output = Self>
+    + Clone
+    + PartialEq
+    + Debug
+    + Serialize
+    + DeserializeOwned
+    + Zeroize
+    + Send
+    + Sync
+    + Sized
 {
+    type SecretKey;
+    type PublicKey;
+    type Scalar: ECScalar<SecretKey = Self::SecretKey>;
+
     /// The byte length of point serialized in compressed form
     type CompressedPointLength: ArrayLength<u8> + Unsigned;
     /// The byte length of point serialized in uncompressed form
     type UncompressedPointLength: ArrayLength<u8> + Unsigned;
     fn generator() -> Self;
-    fn get_element(&self) -> PK;
+    fn get_element(&self) -> Self::PublicKey;
     fn x_coor(&self) -> Option<BigInt>;
     fn y_coor(&self) -> Option<BigInt>;
     fn bytes_compressed_to_big_int(&self) -> BigInt;
     fn from_bytes(bytes: &[u8]) -> Result<Self, TssError>;
     fn pk_to_key_slice(&self) -> Vec<u8>;
-    fn scalar_mul(&self, fe: &SK) -> Self;
-    fn add_point(&self, other: &PK) -> Self;
-    fn sub_point(&self, other: &PK) -> Self;
+    fn scalar_mul(&self, fe: &Self::SecretKey) -> Self;
+    fn add_point(&self, other: &Self::PublicKey) -> Self;
+    fn sub_point(&self, other: &Self::PublicKey) -> Self;
     fn from_coor(x: &BigInt, y: &BigInt) -> Self;
     fn to_bytes(&self, compressed: bool) -> Vec<u8>;
 }
@@ -0,0 +1,89 @@
+// Typed protocol messages of threshold EdDSA. They travel the same way as the
+// GG18 ones, see gg_2018::messages for `Outgoing` and the payload encoding.
+
+use crate::common::AEAD;
+use crate::curv::cryptographic_primitives::secret_sharing::feldman_vss::VerifiableSS;
+use crate::curv::elliptic::curves::ed25519::{FE, GE};
+use crate::eddsa::party_i::{KeyGenBroadcastMessage1, SignDecommitPhase1};
+use crate::errors::{Result, TssError};
+use crate::gg_2018::messages::RoundMessage;
+use crate::gg_2018::party_i::{KeyGenDecommitMessage1, SignBroadcastPhase1};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum KeygenMessage {
+    Round1(KeyGenBroadcastMessage1),
+    Round2((KeyGenDecommitMessage1<GE>, VerifiableSS<GE>)),
+    // encrypted secret share, point to point
+    Round3(AEAD),
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum SignMessage {
+    // index of the signer in the key store
+    Round0(u16),
+    Round1(SignBroadcastPhase1),
+    Round2((SignDecommitPhase1, VerifiableSS<GE>)),
+    // encrypted share of the nonce, point to point
+    Round3(AEAD),
+    Round4(FE),
+}
+
+impl RoundMessage for KeygenMessage {
+    fn round(&self) -> u16 {
+        match self {
+            KeygenMessage::Round1(_) => 1,
+            KeygenMessage::Round2(_) => 2,
+            KeygenMessage::Round3(_) => 3,
+        }
+    }
+
+    fn to_json(&self) -> Result<String> {
+        Ok(match self {
+            KeygenMessage::Round1(m) => serde_json::to_string(m)?,
+            KeygenMessage::Round2(m) => serde_json::to_string(m)?,
+            KeygenMessage::Round3(m) => serde_json::to_string(m)?,
+        })
+    }
+
+    fn from_json(round: u16, data: &str) -> Result<Self> {
+        Ok(match round {
+            1 => KeygenMessage::Round1(serde_json::from_str(data)?),
+            2 => KeygenMessage::Round2(serde_json::from_str(data)?),
+            3 => KeygenMessage::Round3(serde_json::from_str(data)?),
+            _ => return Err(TssError::InvalidMessage { round, party: 0 }),
+        })
+    }
+}
+
+impl RoundMessage for SignMessage {
+    fn round(&self) -> u16 {
+        match self {
+            SignMessage::Round0(_) => 0,
+            SignMessage::Round1(_) => 1,
+            SignMessage::Round2(_) => 2,
+            SignMessage::Round3(_) => 3,
+            SignMessage::Round4(_) => 4,
+        }
+    }
+
+    fn to_json(&self) -> Result<String> {
+        Ok(match self {
+            SignMessage::Round0(m) => serde_json::to_string(m)?,
+            SignMessage::Round1(m) => serde_json::to_string(m)?,
+            SignMessage::Round2(m) => serde_json::to_string(m)?,
+            SignMessage::Round3(m) => serde_json::to_string(m)?,
+            SignMessage::Round4(m) => serde_json::to_string(m)?,
+        })
+    }
+
+    fn from_json(round: u16, data: &str) -> Result<Self> {
+        Ok(match round {
+            0 => SignMessage::Round0(serde_json::from_str(data)?),
+            1 => SignMessage::Round1(serde_json::from_str(data)?),
+            2 => SignMessage::Round2(serde_json::from_str(data)?),
+            3 => SignMessage::Round3(serde_json::from_str(data)?),
+            4 => SignMessage::Round4(serde_json::from_str(data)?),
+            _ => return Err(TssError::InvalidMessage { round, party: 0 }),
+        })
+    }
+}
@@ -0,0 +1,10 @@
+// Threshold EdDSA over Ed25519.
+//
+// Keygen deals the key with Feldman VSS like GG18 does, and signing shares a
+// fresh nonce the same way, so a signature takes five rounds and produces a
+// plain RFC 8032 signature for the joint public key. There is no Paillier and
+// no MtA involved: Schnorr signatures are linear in the key and the nonce.
+
+pub mod messages;
+pub mod party_i;
+pub mod session;
@@ -0,0 +1,443 @@
+#![allow(non_snake_case)]
+
+// Threshold EdDSA over Ed25519, following the threshold Schnorr scheme of
+// Stinson and Strobl: the key is dealt with Feldman VSS as in GG18, and every
+// signature gets a fresh nonce r that the signers share the same way. Each
+// signer publishes gamma_i = r_i + k x_i, a point on a degree t polynomial
+// through s = r + k x, so t + 1 of them interpolate a standard RFC 8032
+// signature (R, s) that any Ed25519 verifier accepts.
+
+use crate::curv::arithmetic::num_bigint::BigInt;
+use crate::curv::cryptographic_primitives::commitments::hash_commitment::HashCommitment;
+use crate::curv::cryptographic_primitives::commitments::traits::Commitment;
+use crate::curv::cryptographic_primitives::secret_sharing::feldman_vss::VerifiableSS;
+use crate::curv::elliptic::curves::ed25519::{FE, GE};
+use crate::curv::elliptic::curves::traits::{ECPoint, ECScalar};
+use crate::errors::TssError::{self, InvalidCom, InvalidSS, InvalidSig};
+use crate::gg_2018::party_i::{
+    KeyGenDecommitMessage1, Parameters, SharedKeys, SignBroadcastPhase1,
+};
+use sha2::{Digest, Sha512};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Keys {
+    pub u_i: FE,
+    pub y_i: GE,
+    // hashed into every nonce together with fresh randomness, the role the
+    // prefix half of an RFC 8032 secret key plays
+    pub prefix: FE,
+    pub party_index: usize,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct KeyGenBroadcastMessage1 {
+    pub com: BigInt,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EphemeralKey {
+    pub r_i: FE,
+    pub R_i: GE,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SignDecommitPhase1 {
+    pub blind_factor: BigInt,
+    pub R_i: GE,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LocalSig {
+    pub gamma_i: FE,
+    pub k: FE,
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Signature {
+    pub R: GE,
+    pub s: FE,
+}
+
+impl Keys {
+    pub fn create(index: usize) -> Keys {
+        let u_i: FE = ECScalar::new_random();
+        let y_i = GE::generator() * &u_i;
+        Keys {
+            u_i,
+            y_i,
+            prefix: ECScalar::new_random(),
+            party_index: index,
+        }
+    }
+
+    pub fn phase1_broadcast(&self) -> (KeyGenBroadcastMessage1, KeyGenDecommitMessage1<GE>) {
+        let (com, blind_factor) =
+            HashCommitment::create_commitment(&self.y_i.bytes_compressed_to_big_int());
+        let bcm1 = KeyGenBroadcastMessage1 { com };
+        let decom1 = KeyGenDecommitMessage1 {
+            blind_factor,
+            y_i: self.y_i.clone(),
+        };
+        (bcm1, decom1)
+    }
+
+    pub fn phase2_distribute(&self, params: &Parameters) -> (VerifiableSS<GE>, Vec<FE>) {
+        VerifiableSS::share(params.threshold, params.share_count, &self.u_i)
+    }
+
+    /// Opens the commitments of round1 and checks that every dealing shares the
+    /// committed y_i with the agreed parameters.
+    pub fn phase2_verify_com(
+        params: &Parameters,
+        decom_vec: &[KeyGenDecommitMessage1<GE>],
+        bc1_vec: &[KeyGenBroadcastMessage1],
+        vss_scheme_vec: &[VerifiableSS<GE>],
+    ) -> Result<(), TssError> {
+        check_lengths(
+            params.share_count,
+            &[decom_vec.len(), bc1_vec.len(), vss_scheme_vec.len()],
+        )?;
+        let correct_decom_all = (0..bc1_vec.len()).all(|i| {
+            HashCommitment::create_commitment_with_user_defined_randomness(
+                &decom_vec[i].y_i.bytes_compressed_to_big_int(),
+                &decom_vec[i].blind_factor,
+            ) == bc1_vec[i].com
+        });
+        if !correct_decom_all {
+            return Err(InvalidCom);
+        }
+        verify_dealings(
+            &decom_vec
+                .iter()
+                .map(|decom| decom.y_i.clone())
+                .collect::<Vec<GE>>(),
+            vss_scheme_vec,
+            params.threshold,
+            params.share_count,
+        )
+    }
+
+    pub fn phase3_verify_vss_construct_keypair(
+        &self,
+        params: &Parameters,
+        y_vec: &[GE],
+        secret_shares_vec: &[FE],
+        vss_scheme_vec: &[VerifiableSS<GE>],
+    ) -> Result<SharedKeys<GE>, TssError> {
+        check_lengths(
+            params.share_count,
+            &[y_vec.len(), secret_shares_vec.len(), vss_scheme_vec.len()],
+        )?;
+        for (vss_scheme, share) in vss_scheme_vec.iter().zip(secret_shares_vec) {
+            vss_scheme.validate_share(share, self.party_index)?;
+        }
+
+        let y = sum_points(y_vec).ok_or(InvalidSS)?;
+        let x_i = sum_scalars(secret_shares_vec).ok_or(InvalidSS)?;
+        Ok(SharedKeys { y, x_i })
+    }
+}
+
+impl EphemeralKey {
+    /// Nonce r_i = H(prefix || randomness || message), so a weak random source
+    /// alone does not repeat nonces across messages.
+    pub fn create(keys: &Keys, message: &[u8]) -> EphemeralKey {
+        let randomness: FE = ECScalar::new_random();
+        let digest = Sha512::new()
+            .chain(keys.prefix.to_bytes())
+            .chain(randomness.to_bytes())
+            .chain(message)
+            .finalize();
+        let mut wide = [0u8; 64];
+        wide.copy_from_slice(&digest);
+        let r_i = FE::from_hash(&wide);
+        let R_i = GE::generator() * &r_i;
+        EphemeralKey { r_i, R_i }
+    }
+
+    pub fn phase1_broadcast(&self) -> (SignBroadcastPhase1, SignDecommitPhase1) {
+        let (com, blind_factor) =
+            HashCommitment::create_commitment(&self.R_i.bytes_compressed_to_big_int());
+        let bcm1 = SignBroadcastPhase1 { com };
+        let decom1 = SignDecommitPhase1 {
+            blind_factor,
+            R_i: self.R_i.clone(),
+        };
+        (bcm1, decom1)
+    }
+
+    /// Shares r_i among the signers; `signers` holds their 0 based keygen
+    /// indices, so the shares sit at the same points as the key shares.
+    pub fn phase2_distribute(&self, t: usize, signers: &[usize]) -> (VerifiableSS<GE>, Vec<FE>) {
+        let index_vec = signers.iter().map(|i| i + 1).collect::<Vec<usize>>();
+        VerifiableSS::share_at_indices(t, signers.len(), &self.r_i, &index_vec)
+    }
+
+    pub fn phase2_verify_com(
+        t: usize,
+        decom_vec: &[SignDecommitPhase1],
+        bc1_vec: &[SignBroadcastPhase1],
+        vss_scheme_vec: &[VerifiableSS<GE>],
+    ) -> Result<(), TssError> {
+        check_lengths(decom_vec.len(), &[bc1_vec.len(), vss_scheme_vec.len()])?;
+        let correct_decom_all = (0..bc1_vec.len()).all(|i| {
+            HashCommitment::create_commitment_with_user_defined_randomness(
+                &decom_vec[i].R_i.bytes_compressed_to_big_int(),
+                &decom_vec[i].blind_factor,
+            ) == bc1_vec[i].com
+        });
+        if !correct_decom_all {
+            return Err(InvalidCom);
+        }
+        verify_dealings(
+            &decom_vec
+                .iter()
+                .map(|decom| decom.R_i.clone())
+                .collect::<Vec<GE>>(),
+            vss_scheme_vec,
+            t,
+            decom_vec.len(),
+        )
+    }
+
+    /// Checks our shares of every r_j and returns R together with our share of r.
+    pub fn phase3_verify_vss_construct_nonce(
+        party_index: usize,
+        R_vec: &[GE],
+        secret_shares_vec: &[FE],
+        vss_scheme_vec: &[VerifiableSS<GE>],
+    ) -> Result<(GE, FE), TssError> {
+        check_lengths(
+            R_vec.len(),
+            &[secret_shares_vec.len(), vss_scheme_vec.len()],
+        )?;
+        for (vss_scheme, share) in vss_scheme_vec.iter().zip(secret_shares_vec) {
+            vss_scheme.validate_share(share, party_index)?;
+        }
+        let R = sum_points(R_vec).ok_or(InvalidSS)?;
+        let r_share = sum_scalars(secret_shares_vec).ok_or(InvalidSS)?;
+        Ok((R, r_share))
+    }
+}
+
+impl LocalSig {
+    pub fn compute(
+        message: &[u8],
+        R: &GE,
+        y: &GE,
+        r_share: &FE,
+        shared_keys: &SharedKeys<GE>,
+    ) -> LocalSig {
+        let k = challenge(R, y, message);
+        let gamma_i = r_share.clone() + k.clone() * &shared_keys.x_i;
+        LocalSig { gamma_i, k }
+    }
+
+    /// Checks every gamma_j against the public commitments of the nonce and key
+    /// dealings, so a bad local signature is pinned on the signer who sent it.
+    /// Returns the position of the offending signer, or None if the inputs do
+    /// not line up (not one gamma per signer, or no dealings).
+    pub fn verify_local_sigs(
+        gamma_vec: &[FE],
+        k: &FE,
+        signers: &[usize],
+        vss_nonce_vec: &[VerifiableSS<GE>],
+        vss_key_vec: &[VerifiableSS<GE>],
+    ) -> Result<(), Option<usize>> {
+        if gamma_vec.is_empty() || gamma_vec.len() != signers.len() {
+            return Err(None);
+        }
+        for (j, (gamma_j, signer)) in gamma_vec.iter().zip(signers).enumerate() {
+            let index = signer + 1;
+            let nonce_comm = sum_points(
+                &vss_nonce_vec
+                    .iter()
+                    .map(|vss| vss.get_point_commitment(index))
+                    .collect::<Vec<GE>>(),
+            )
+            .ok_or(None)?;
+            let key_comm = sum_points(
+                &vss_key_vec
+                    .iter()
+                    .map(|vss| vss.get_point_commitment(index))
+                    .collect::<Vec<GE>>(),
+            )
+            .ok_or(None)?;
+            if GE::generator() * gamma_j != nonce_comm + key_comm * k {
+                return Err(Some(j));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Signature {
+    /// Interpolates s from the local signatures of the signers, in signer order.
+    pub fn generate(
+        vss_scheme: &VerifiableSS<GE>,
+        gamma_vec: &[FE],
+        signers: &[usize],
+        R: GE,
+    ) -> Signature {
+        let s = vss_scheme.reconstruct(signers, gamma_vec);
+        Signature { R, s }
+    }
+
+    /// RFC 8032 verification: s G == R + k A with k = H(R || A || M).
+    pub fn verify(&self, message: &[u8], y: &GE) -> Result<(), TssError> {
+        let k = challenge(&self.R, y, message);
+        match GE::generator() * &self.s == self.R.clone() + y.clone() * &k {
+            true => Ok(()),
+            false => Err(InvalidSig),
+        }
+    }
+
+    /// The 64 byte R || S encoding.
+    pub fn to_bytes(&self) -> [u8; 64] {
+        let mut bytes = [0u8; 64];
+        bytes[..32].copy_from_slice(&self.R.pk_to_key_slice());
+        bytes[32..].copy_from_slice(&self.s.to_bytes());
+        bytes
+    }
+
+    /// Parses the 64 byte R || S encoding, rejecting an S that is not reduced.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Signature, TssError> {
+        if bytes.len() != 64 {
+            return Err(InvalidSig);
+        }
+        let R = GE::from_bytes(&bytes[..32])?;
+        let s = FE::from_canonical_bytes(bytes[32..].try_into().unwrap()).ok_or(InvalidSig)?;
+        Ok(Signature { R, s })
+    }
+}
+
+// k = H(R || A || M) mod l
+fn challenge(R: &GE, y: &GE, message: &[u8]) -> FE {
+    let digest = Sha512::new()
+        .chain(R.pk_to_key_slice())
+        .chain(y.pk_to_key_slice())
+        .chain(message)
+        .finalize();
+    let mut wide = [0u8; 64];
+    wide.copy_from_slice(&digest);
+    FE::from_hash(&wide)
+}
+
+// every dealing must be of degree t over n shares and share the committed point
+fn verify_dealings(
+    points: &[GE],
+    vss_scheme_vec: &[VerifiableSS<GE>],
+    t: usize,
+    n: usize,
+) -> Result<(), TssError> {
+    let valid = points.iter().zip(vss_scheme_vec).all(|(point, vss)| {
+        vss.parameters.threshold == t
+            && vss.parameters.share_count == n
+            && vss.commitments.len() == t + 1
+            && vss.commitments[0] == *point
+    });
+    match valid {
+        true => Ok(()),
+        false => Err(InvalidSS),
+    }
+}
+
+// one entry per party, and at least one party
+fn check_lengths(expected: usize, lens: &[usize]) -> Result<(), TssError> {
+    match expected > 0 && lens.iter().all(|len| *len == expected) {
+        true => Ok(()),
+        false => Err(InvalidSS),
+    }
+}
+
+// None for an empty slice, which has no sum to stand for
+fn sum_points(points: &[GE]) -> Option<GE> {
+    let (head, tail) = points.split_first()?;
+    Some(tail.iter().fold(head.clone(), |acc, x| acc + x))
+}
+
+fn sum_scalars(scalars: &[FE]) -> Option<FE> {
+    let (head, tail) = scalars.split_first()?;
+    Some(tail.iter().fold(head.clone(), |acc, x| acc + x))
+}
+
+pub fn verify(sig: &Signature, y: &GE, message: &[u8]) -> Result<(), TssError> {
+    sig.verify(message, y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_local_sigs_interpolate_to_signature() {
+        // a 1 out of 3 key and a nonce shared by the signers 1 and 3
+        let x: FE = ECScalar::new_random();
+        let y = GE::generator() * &x;
+        let (vss_key, key_shares) = VerifiableSS::<GE>::share(1, 3, &x);
+        let signers = vec![0, 2];
+        let keys = Keys::create(1);
+        let nonce = EphemeralKey::create(&keys, b"message");
+        let (vss_nonce, nonce_shares) = nonce.phase2_distribute(1, &signers);
+
+        let mut gamma_vec = Vec::new();
+        let mut k = ECScalar::zero();
+        for (j, signer) in signers.iter().enumerate() {
+            let shared_keys = SharedKeys {
+                y: y.clone(),
+                x_i: key_shares[*signer].clone(),
+            };
+            let local_sig =
+                LocalSig::compute(b"message", &nonce.R_i, &y, &nonce_shares[j], &shared_keys);
+            gamma_vec.push(local_sig.gamma_i);
+            k = local_sig.k;
+        }
+        let vss_nonce_vec = vec![vss_nonce.clone()];
+        let vss_key_vec = vec![vss_key];
+        assert!(LocalSig::verify_local_sigs(
+            &gamma_vec,
+            &k,
+            &signers,
+            &vss_nonce_vec,
+            &vss_key_vec
+        )
+        .is_ok());
+
+        let signature = Signature::generate(&vss_nonce, &gamma_vec, &signers, nonce.R_i.clone());
+        assert!(verify(&signature, &y, b"message").is_ok());
+        assert!(verify(&signature, &(y.clone() + &y), b"message").is_err());
+
+        let mut bytes = signature.to_bytes();
+        assert!(verify(&Signature::from_bytes(&bytes).unwrap(), &y, b"message").is_ok());
+        // an S of l or more is not a valid encoding
+        bytes[63] = 0xff;
+        assert!(Signature::from_bytes(&bytes).is_err());
+
+        // the second signer is blamed for a bad local signature
+        gamma_vec[1] = gamma_vec[1].clone() + &k;
+        assert_eq!(
+            LocalSig::verify_local_sigs(&gamma_vec, &k, &signers, &vss_nonce_vec, &vss_key_vec),
+            Err(Some(1))
+        );
+        // malformed rounds are rejected instead of panicking
+        assert_eq!(
+            LocalSig::verify_local_sigs(
+                &gamma_vec[..1],
+                &k,
+                &signers,
+                &vss_nonce_vec,
+                &vss_key_vec
+            ),
+            Err(None)
+        );
+        assert_eq!(
+            LocalSig::verify_local_sigs(&gamma_vec, &k, &signers, &[], &vss_key_vec),
+            Err(None)
+        );
+        assert!(EphemeralKey::phase3_verify_vss_construct_nonce(1, &[], &[], &[]).is_err());
+    }
+}
@@ -0,0 +1,654 @@
+#![allow(non_snake_case)]
+
+// Round-by-round keygen and signing state machines for threshold EdDSA, driven
+// exactly like the GG18 ones in gg_2018::session.
+
+use crate::common::{aes_decrypt, aes_encrypt};
+use crate::curv::arithmetic::num_bigint::BigInt;
+use crate::curv::arithmetic::traits::Converter;
+use crate::curv::cryptographic_primitives::secret_sharing::feldman_vss::VerifiableSS;
+use crate::curv::elliptic::curves::ed25519::{FE, GE};
+use crate::curv::elliptic::curves::traits::{ECPoint, ECScalar};
+use crate::eddsa::messages::{KeygenMessage, SignMessage};
+use crate::eddsa::party_i::*;
+use crate::errors::{Result, TssError};
+use crate::gg_2018::messages::{party_index, signer_indices, sort_incoming, Outgoing};
+use crate::gg_2018::party_i::{
+    KeyGenDecommitMessage1, Parameters, SharedKeys, SignBroadcastPhase1,
+};
+use crate::gg_2018::session::{p2p_channel, payloads};
+use crate::keystore::EddsaKeyStore;
+
+const KEYGEN_SHARE_LABEL: &[u8] = b"tss-wasm/eddsa/keygen/round3-share/v1";
+const SIGN_SHARE_LABEL: &[u8] = b"tss-wasm/eddsa/sign/round3-share/v1";
+
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum KeygenState {
+    New,
+    Round1 {
+        party_keys: Keys,
+        decom_i: KeyGenDecommitMessage1<GE>,
+        bc_i: KeyGenBroadcastMessage1,
+    },
+    Round2 {
+        party_keys: Keys,
+        decom_i: KeyGenDecommitMessage1<GE>,
+        bc1_vec: Vec<KeyGenBroadcastMessage1>,
+        vss_scheme: VerifiableSS<GE>,
+        secret_shares: Vec<FE>,
+    },
+    Round3 {
+        party_keys: Keys,
+        y_vec: Vec<GE>,
+        vss_scheme_vec: Vec<VerifiableSS<GE>>,
+        secret_shares: Vec<FE>,
+        // raw ECDH secrets with the other parties, see p2p_channel
+        enc_keys: Vec<Vec<u8>>,
+    },
+    Finished {
+        key_store: EddsaKeyStore,
+    },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct KeygenSession {
+    pub params: Parameters,
+    pub party_num_int: u16,
+    // uuid shared by all parties of this keygen, bound into the round3 channels
+    pub session_id: String,
+    pub state: KeygenState,
+}
+
+impl KeygenSession {
+    pub fn new(params: Parameters, party_num_int: u16, session_id: String) -> Self {
+        KeygenSession {
+            params,
+            party_num_int,
+            session_id,
+            state: KeygenState::New,
+        }
+    }
+
+    /// The round whose messages are exchanged next; 4 once keygen is finished.
+    pub fn round(&self) -> u16 {
+        match self.state {
+            KeygenState::New | KeygenState::Round1 { .. } => 1,
+            KeygenState::Round2 { .. } => 2,
+            KeygenState::Round3 { .. } => 3,
+            KeygenState::Finished { .. } => 4,
+        }
+    }
+
+    pub fn check_round(&self, round: u16) -> Result<()> {
+        match self.round() == round {
+            true => Ok(()),
+            false => Err(TssError::RoundOutOfOrder {
+                expected: self.round(),
+                got: round,
+            }),
+        }
+    }
+
+    pub fn key_store(&self) -> Option<&EddsaKeyStore> {
+        match &self.state {
+            KeygenState::Finished { key_store } => Some(key_store),
+            _ => None,
+        }
+    }
+
+    /// Feeds the messages of the other parties for the current round, tagged with
+    /// the sending party. `New` takes no messages and only produces round1.
+    pub fn advance(
+        self,
+        incoming: Vec<(u16, KeygenMessage)>,
+    ) -> Result<(Outgoing<KeygenMessage>, KeygenSession)> {
+        let round = self.round();
+        let KeygenSession {
+            params,
+            party_num_int,
+            session_id,
+            state,
+        } = self;
+        let n = params.share_count as u16;
+        let me = party_index(party_num_int, n)?;
+        let incoming = match state {
+            KeygenState::New => Vec::new(),
+            _ => sort_incoming(incoming, round, n, party_num_int)?,
+        };
+
+        let (outgoing, state) = match state {
+            KeygenState::New => {
+                let party_keys = Keys::create(party_num_int as usize);
+                let (bc_i, decom_i) = party_keys.phase1_broadcast();
+                (
+                    Outgoing::broadcast(1, KeygenMessage::Round1(bc_i.clone())),
+                    KeygenState::Round1 {
+                        party_keys,
+                        decom_i,
+                        bc_i,
+                    },
+                )
+            }
+            KeygenState::Round1 {
+                party_keys,
+                decom_i,
+                bc_i,
+            } => {
+                let mut bc1_vec = payloads!(incoming, KeygenMessage::Round1);
+                bc1_vec.insert(me, bc_i);
+
+                // decommit y_i together with the dealing of u_i
+                let (vss_scheme, secret_shares) = party_keys.phase2_distribute(&params);
+                (
+                    Outgoing::broadcast(
+                        2,
+                        KeygenMessage::Round2((decom_i.clone(), vss_scheme.clone())),
+                    ),
+                    KeygenState::Round2 {
+                        party_keys,
+                        decom_i,
+                        bc1_vec,
+                        vss_scheme,
+                        secret_shares,
+                    },
+                )
+            }
+            KeygenState::Round2 {
+                party_keys,
+                decom_i,
+                bc1_vec,
+                vss_scheme,
+                secret_shares,
+            } => {
+                let mut decom_vec: Vec<KeyGenDecommitMessage1<GE>> = Vec::new();
+                let mut vss_scheme_vec: Vec<VerifiableSS<GE>> = Vec::new();
+                for (decom_j, vss_scheme_j) in payloads!(incoming, KeygenMessage::Round2) {
+                    decom_vec.push(decom_j);
+                    vss_scheme_vec.push(vss_scheme_j);
+                }
+                decom_vec.insert(me, decom_i);
+                vss_scheme_vec.insert(me, vss_scheme);
+                Keys::phase2_verify_com(&params, &decom_vec, &bc1_vec, &vss_scheme_vec)?;
+
+                let y_vec = decom_vec
+                    .into_iter()
+                    .map(|decom| decom.y_i)
+                    .collect::<Vec<GE>>();
+                let mut enc_keys: Vec<Vec<u8>> = Vec::new();
+                let mut p2p = Vec::new();
+                for (k, i) in (1..=n).enumerate() {
+                    if i != party_num_int {
+                        let ecdh_secret = (y_vec[k].clone() * &party_keys.u_i).to_bytes(true);
+                        let plaintext = BigInt::to_vec(&secret_shares[k].to_big_int());
+                        let (key, aad) = p2p_channel(
+                            KEYGEN_SHARE_LABEL,
+                            &ecdh_secret,
+                            &session_id,
+                            party_num_int,
+                            i,
+                            3,
+                        );
+                        p2p.push((
+                            i,
+                            KeygenMessage::Round3(aes_encrypt(&key, &plaintext, &aad)?),
+                        ));
+                        enc_keys.push(ecdh_secret);
+                    }
+                }
+
+                (
+                    Outgoing::p2p(3, p2p),
+                    KeygenState::Round3 {
+                        party_keys,
+                        y_vec,
+                        vss_scheme_vec,
+                        secret_shares,
+                        enc_keys,
+                    },
+                )
+            }
+            KeygenState::Round3 {
+                party_keys,
+                y_vec,
+                vss_scheme_vec,
+                secret_shares,
+                enc_keys,
+            } => {
+                let aead_pack_vec = payloads!(incoming, KeygenMessage::Round3);
+                let mut j = 0;
+                let mut party_shares: Vec<FE> = Vec::new();
+                for i in 1..=n {
+                    if i == party_num_int {
+                        party_shares.push(secret_shares[me].clone());
+                    } else {
+                        let (key, aad) = p2p_channel(
+                            KEYGEN_SHARE_LABEL,
+                            &enc_keys[j],
+                            &session_id,
+                            i,
+                            party_num_int,
+                            3,
+                        );
+                        let out = aes_decrypt(&key, aead_pack_vec[j].clone(), &aad)?;
+                        party_shares.push(ECScalar::from(&BigInt::from_bytes_be(&out[..])));
+                        j += 1;
+                    }
+                }
+
+                let shared_keys = party_keys.phase3_verify_vss_construct_keypair(
+                    &params,
+                    &y_vec,
+                    &party_shares,
+                    &vss_scheme_vec,
+                )?;
+                let y_sum = shared_keys.y.clone();
+
+                (
+                    Outgoing::none(4),
+                    KeygenState::Finished {
+                        key_store: (
+                            party_keys,
+                            shared_keys,
+                            party_num_int,
+                            vss_scheme_vec,
+                            y_sum,
+                        ),
+                    },
+                )
+            }
+            KeygenState::Finished { .. } => {
+                return Err(TssError::RoundOutOfOrder {
+                    expected: 4,
+                    got: 4,
+                })
+            }
+        };
+
+        Ok((
+            outgoing,
+            KeygenSession {
+                params,
+                party_num_int,
+                session_id,
+                state,
+            },
+        ))
+    }
+}
+
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum SignState {
+    New,
+    Round0,
+    Round1 {
+        signers_vec: Vec<usize>,
+        ephemeral_key: EphemeralKey,
+        com: SignBroadcastPhase1,
+        decommit: SignDecommitPhase1,
+    },
+    Round2 {
+        signers_vec: Vec<usize>,
+        ephemeral_key: EphemeralKey,
+        decommit: SignDecommitPhase1,
+        bc1_vec: Vec<SignBroadcastPhase1>,
+        vss_nonce: VerifiableSS<GE>,
+        nonce_shares: Vec<FE>,
+    },
+    Round3 {
+        signers_vec: Vec<usize>,
+        R_vec: Vec<GE>,
+        vss_nonce_vec: Vec<VerifiableSS<GE>>,
+        nonce_shares: Vec<FE>,
+        enc_keys: Vec<Vec<u8>>,
+    },
+    Round4 {
+        signers_vec: Vec<usize>,
+        R: GE,
+        vss_nonce_vec: Vec<VerifiableSS<GE>>,
+        local_sig: LocalSig,
+    },
+    Finished {
+        signature: Signature,
+    },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SignSession {
+    pub party_keys: Keys,
+    pub shared_keys: SharedKeys<GE>,
+    pub party_id: u16,
+    pub vss_scheme_vec: Vec<VerifiableSS<GE>>,
+    pub y_sum: GE,
+    pub threshold: u16,
+    pub party_num_int: u16,
+    // uuid shared by all signers, bound into the round3 channels
+    pub session_id: String,
+    pub message: Vec<u8>,
+    pub state: SignState,
+}
+
+impl SignSession {
+    pub fn new(
+        key_store: EddsaKeyStore,
+        threshold: u16,
+        party_num_int: u16,
+        session_id: String,
+        message: Vec<u8>,
+    ) -> Self {
+        let (party_keys, shared_keys, party_id, vss_scheme_vec, y_sum) = key_store;
+        SignSession {
+            party_keys,
+            shared_keys,
+            party_id,
+            vss_scheme_vec,
+            y_sum,
+            threshold,
+            party_num_int,
+            session_id,
+            message,
+            state: SignState::New,
+        }
+    }
+
+    /// The round whose messages are exchanged next; 5 once signing is finished.
+    pub fn round(&self) -> u16 {
+        match self.state {
+            SignState::New | SignState::Round0 => 0,
+            SignState::Round1 { .. } => 1,
+            SignState::Round2 { .. } => 2,
+            SignState::Round3 { .. } => 3,
+            SignState::Round4 { .. } => 4,
+            SignState::Finished { .. } => 5,
+        }
+    }
+
+    pub fn check_round(&self, round: u16) -> Result<()> {
+        match self.round() == round {
+            true => Ok(()),
+            false => Err(TssError::RoundOutOfOrder {
+                expected: self.round(),
+                got: round,
+            }),
+        }
+    }
+
+    pub fn signature(&self) -> Option<&Signature> {
+        match &self.state {
+            SignState::Finished { signature } => Some(signature),
+            _ => None,
+        }
+    }
+
+    /// Feeds the messages of the other signers for the current round, tagged with
+    /// the sending party. `New` takes no messages and only produces round0.
+    pub fn advance(
+        mut self,
+        incoming: Vec<(u16, SignMessage)>,
+    ) -> Result<(Outgoing<SignMessage>, SignSession)> {
+        let t = self.threshold;
+        let me = party_index(self.party_num_int, t + 1)?;
+        let round = self.round();
+        let state = std::mem::replace(&mut self.state, SignState::New);
+        let incoming = match state {
+            SignState::New => Vec::new(),
+            _ => sort_incoming(incoming, round, t + 1, self.party_num_int)?,
+        };
+
+        let (outgoing, state) = match state {
+            SignState::New => (
+                Outgoing::broadcast(0, SignMessage::Round0(self.party_id)),
+                SignState::Round0,
+            ),
+            SignState::Round0 => {
+                // round 0: collect signers IDs
+                let signers_vec = signer_indices(
+                    payloads!(incoming, SignMessage::Round0),
+                    self.party_id,
+                    self.party_num_int,
+                    t + 1,
+                    self.vss_scheme_vec.len(),
+                )?;
+
+                let ephemeral_key = EphemeralKey::create(&self.party_keys, &self.message);
+                let (com, decommit) = ephemeral_key.phase1_broadcast();
+                (
+                    Outgoing::broadcast(1, SignMessage::Round1(com.clone())),
+                    SignState::Round1 {
+                        signers_vec,
+                        ephemeral_key,
+                        com,
+                        decommit,
+                    },
+                )
+            }
+            SignState::Round1 {
+                signers_vec,
+                ephemeral_key,
+                com,
+                decommit,
+            } => {
+                let mut bc1_vec = payloads!(incoming, SignMessage::Round1);
+                bc1_vec.insert(me, com);
+
+                // decommit R_i together with the dealing of r_i
+                let (vss_nonce, nonce_shares) =
+                    ephemeral_key.phase2_distribute(t.into(), &signers_vec);
+                (
+                    Outgoing::broadcast(
+                        2,
+                        SignMessage::Round2((decommit.clone(), vss_nonce.clone())),
+                    ),
+                    SignState::Round2 {
+                        signers_vec,
+                        ephemeral_key,
+                        decommit,
+                        bc1_vec,
+                        vss_nonce,
+                        nonce_shares,
+                    },
+                )
+            }
+            SignState::Round2 {
+                signers_vec,
+                ephemeral_key,
+                decommit,
+                bc1_vec,
+                vss_nonce,
+                nonce_shares,
+            } => {
+                let mut decom_vec: Vec<SignDecommitPhase1> = Vec::new();
+                let mut vss_nonce_vec: Vec<VerifiableSS<GE>> = Vec::new();
+                for (decom_j, vss_nonce_j) in payloads!(incoming, SignMessage::Round2) {
+                    decom_vec.push(decom_j);
+                    vss_nonce_vec.push(vss_nonce_j);
+                }
+                decom_vec.insert(me, decommit);
+                vss_nonce_vec.insert(me, vss_nonce);
+                EphemeralKey::phase2_verify_com(t.into(), &decom_vec, &bc1_vec, &vss_nonce_vec)?;
+
+                let R_vec = decom_vec
+                    .into_iter()
+                    .map(|decom| decom.R_i)
+                    .collect::<Vec<GE>>();
+                let mut enc_keys: Vec<Vec<u8>> = Vec::new();
+                let mut p2p = Vec::new();
+                for (k, i) in (1..t + 2).enumerate() {
+                    if i != self.party_num_int {
+                        let ecdh_secret = (R_vec[k].clone() * &ephemeral_key.r_i).to_bytes(true);
+                        let plaintext = BigInt::to_vec(&nonce_shares[k].to_big_int());
+                        let (key, aad) = p2p_channel(
+                            SIGN_SHARE_LABEL,
+                            &ecdh_secret,
+                            &self.session_id,
+                            self.party_num_int,
+                            i,
+                            3,
+                        );
+                        p2p.push((i, SignMessage::Round3(aes_encrypt(&key, &plaintext, &aad)?)));
+                        enc_keys.push(ecdh_secret);
+                    }
+                }
+
+                (
+                    Outgoing::p2p(3, p2p),
+                    SignState::Round3 {
+                        signers_vec,
+                        R_vec,
+                        vss_nonce_vec,
+                        nonce_shares,
+                        enc_keys,
+                    },
+                )
+            }
+            SignState::Round3 {
+                signers_vec,
+                R_vec,
+                vss_nonce_vec,
+                nonce_shares,
+                enc_keys,
+            } => {
+                let aead_pack_vec = payloads!(incoming, SignMessage::Round3);
+                let mut j = 0;
+                let mut party_shares: Vec<FE> = Vec::new();
+                for i in 1..t + 2 {
+                    if i == self.party_num_int {
+                        party_shares.push(nonce_shares[me].clone());
+                    } else {
+                        let (key, aad) = p2p_channel(
+                            SIGN_SHARE_LABEL,
+                            &enc_keys[j],
+                            &self.session_id,
+                            i,
+                            self.party_num_int,
+                            3,
+                        );
+                        let out = aes_decrypt(&key, aead_pack_vec[j].clone(), &aad)?;
+                        party_shares.push(ECScalar::from(&BigInt::from_bytes_be(&out[..])));
+                        j += 1;
+                    }
+                }
+
+                let (R, nonce_share) = EphemeralKey::phase3_verify_vss_construct_nonce(
+                    signers_vec[me] + 1,
+                    &R_vec,
+                    &party_shares,
+                    &vss_nonce_vec,
+                )?;
+                let local_sig = LocalSig::compute(
+                    &self.message,
+                    &R,
+                    &self.y_sum,
+                    &nonce_share,
+                    &self.shared_keys,
+                );
+                (
+                    Outgoing::broadcast(4, SignMessage::Round4(local_sig.gamma_i.clone())),
+                    SignState::Round4 {
+                        signers_vec,
+                        R,
+                        vss_nonce_vec,
+                        local_sig,
+                    },
+                )
+            }
+            SignState::Round4 {
+                signers_vec,
+                R,
+                vss_nonce_vec,
+                local_sig,
+            } => {
+                let mut gamma_vec: Vec<FE> = payloads!(incoming, SignMessage::Round4);
+                gamma_vec.insert(me, local_sig.gamma_i);
+                LocalSig::verify_local_sigs(
+                    &gamma_vec,
+                    &local_sig.k,
+                    &signers_vec,
+                    &vss_nonce_vec,
+                    &self.vss_scheme_vec,
+                )
+                .map_err(|j| match j {
+                    Some(j) => TssError::InvalidMessage {
+                        round: 4,
+                        party: j as u16 + 1,
+                    },
+                    None => TssError::InvalidSS,
+                })?;
+
+                let signature =
+                    Signature::generate(&vss_nonce_vec[me], &gamma_vec, &signers_vec, R);
+                verify(&signature, &self.y_sum, &self.message)?;
+
+                (Outgoing::none(5), SignState::Finished { signature })
+            }
+            SignState::Finished { .. } => {
+                return Err(TssError::RoundOutOfOrder {
+                    expected: 5,
+                    got: 5,
+                })
+            }
+        };
+
+        self.state = state;
+        Ok((outgoing, self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{eddsa_keygen, eddsa_sign};
+    use ed25519_dalek::{Signature as DalekSignature, VerifyingKey};
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
+
+    fn keygen(t: usize, n: usize) -> Vec<EddsaKeyStore> {
+        let sessions = eddsa_keygen(t, n);
+        assert!(sessions[0].clone().advance(Vec::new()).is_err());
+        sessions
+            .iter()
+            .map(|session| session.key_store().unwrap().clone())
+            .collect()
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_keygen_sign_sessions() {
+        let key_stores = keygen(1, 3);
+        let y = key_stores[0].4.clone();
+        assert!(key_stores.iter().all(|key_store| key_store.4 == y));
+
+        // any t + 1 parties sign, here the last two
+        let message = b"tss-wasm eddsa";
+        let sessions = eddsa_sign(key_stores[1..].to_vec(), 1, message);
+        let signature = sessions[0].signature().unwrap();
+        assert_eq!(signature, sessions[1].signature().unwrap());
+        assert!(verify(signature, &y, message).is_ok());
+        assert!(verify(signature, &y, b"another message").is_err());
+
+        // a plain RFC 8032 verifier accepts it
+        let verifying_key =
+            VerifyingKey::from_bytes(&y.pk_to_key_slice().try_into().unwrap()).unwrap();
+        let dalek_signature = DalekSignature::from_bytes(&signature.to_bytes());
+        assert!(verifying_key
+            .verify_strict(message, &dalek_signature)
+            .is_ok());
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_round_out_of_order() {
+        let params = Parameters::new(1, 2);
+        let session = KeygenSession::new(params, 1, "uuid".to_string());
+        assert!(session.check_round(1).is_ok());
+        match session.check_round(3) {
+            Err(TssError::RoundOutOfOrder {
+                expected: 1,
+                got: 3,
+            }) => {}
+            _ => panic!("round 3 should be rejected before round 1"),
+        }
+    }
+}
@@ -19,6 +19,8 @@ pub enum TssError {
     Secp256k1Error(#[from] secp256k1::Error),
     #[error("rand error")]
     RandError(#[from] rand::Error),
+    #[error("runtime error")]
+    RuntimeError(#[from] std::io::Error),
 
     #[error("ParseIntError error")]
     ParseError(#[from] std::num::ParseIntError),
@@ -34,6 +36,132 @@ pub enum TssError {
     InvalidPublicKey,
     #[error("VerifyShareError")]
     VerifyShareError,
+    #[error("InvalidPassphrase")]
+    InvalidPassphrase,
+    #[error("InvalidKeyStore")]
+    InvalidKeyStore,
+    #[error("InvalidContext")]
+    InvalidContext,
+    #[error("InvalidKeypairPool")]
+    InvalidKeypairPool,
+    #[error("UnsupportedCurve")]
+    UnsupportedCurve,
+    #[error("RoundOutOfOrder: expected round {expected}, got round {got}")]
+    RoundOutOfOrder { expected: u16, got: u16 },
+    #[error("InvalidMessage: round {round}, party {party}")]
+    InvalidMessage { round: u16, party: u16 },
+    #[error("UnknownIdentity: party {party}")]
+    UnknownIdentity { party: u16 },
+    #[error("UnauthenticatedMessage: round {round}, party {party}")]
+    UnauthenticatedMessage { round: u16, party: u16 },
+    #[error("InconsistentBroadcast: round {round}, party {party}")]
+    InconsistentBroadcast { round: u16, party: u16 },
+    #[error("InvalidArgument: {name}")]
+    InvalidArgument { name: String },
+    #[error("AuthFailed: status {status}, the token was rejected or has expired")]
+    AuthFailed { status: u16 },
+    #[error("ManagerError: status {status}")]
+    ManagerError { status: u16 },
+    #[error("ManagerUnreachable after {attempts} attempts: {msg}")]
+    ManagerUnreachable { attempts: u32, msg: String },
+    #[error("Panic: {msg}")]
+    Panic { msg: String },
+}
+
+// the values of `TssError::code`, exported as constants to the C header
+pub const TSS_UNKNOWN_ERROR: i32 = 100;
+pub const TSS_CONTEXT_ERROR: i32 = 101;
+pub const TSS_SERDE_ERROR: i32 = 102;
+pub const TSS_SECP256K1_ERROR: i32 = 103;
+pub const TSS_RAND_ERROR: i32 = 104;
+pub const TSS_RUNTIME_ERROR: i32 = 105;
+pub const TSS_PARSE_ERROR: i32 = 106;
+pub const TSS_INVALID_ARGUMENT: i32 = 107;
+pub const TSS_INVALID_KEY: i32 = 110;
+pub const TSS_INVALID_PUBLIC_KEY: i32 = 111;
+pub const TSS_INVALID_PASSPHRASE: i32 = 112;
+pub const TSS_INVALID_KEY_STORE: i32 = 113;
+pub const TSS_INVALID_CONTEXT: i32 = 114;
+pub const TSS_INVALID_KEYPAIR_POOL: i32 = 115;
+pub const TSS_UNSUPPORTED_CURVE: i32 = 116;
+pub const TSS_ROUND_OUT_OF_ORDER: i32 = 117;
+pub const TSS_REQUEST_ERROR: i32 = 200;
+pub const TSS_REQUEST_TIMEOUT: i32 = 201;
+pub const TSS_MANAGER_UNREACHABLE: i32 = 202;
+pub const TSS_MANAGER_ERROR: i32 = 203;
+pub const TSS_AUTH_FAILED: i32 = 204;
+pub const TSS_INVALID_SS: i32 = 300;
+pub const TSS_INVALID_COM: i32 = 301;
+pub const TSS_INVALID_SIG: i32 = 302;
+pub const TSS_VERIFY_SHARE_ERROR: i32 = 303;
+pub const TSS_INVALID_MESSAGE: i32 = 304;
+pub const TSS_UNKNOWN_IDENTITY: i32 = 305;
+pub const TSS_UNAUTHENTICATED_MESSAGE: i32 = 306;
+pub const TSS_INCONSISTENT_BROADCAST: i32 = 307;
+pub const TSS_PANIC: i32 = 900;
+
+/// Stable error codes handed to the apps, grouped by what the app can do
+/// about them: 1xx bad input or local state, 2xx the manager could not be
+/// reached or refused the request (204: the token was rejected, get a new
+/// one), 3xx another party misbehaved, 9xx a bug in this library. Codes are
+/// never reused; new variants get new ones.
+impl TssError {
+    pub fn code(&self) -> i32 {
+        match self {
+            TssError::UnknownError { .. } => TSS_UNKNOWN_ERROR,
+            TssError::ContextError => TSS_CONTEXT_ERROR,
+            TssError::SerdeError(_) => TSS_SERDE_ERROR,
+            TssError::Secp256k1Error(_) => TSS_SECP256K1_ERROR,
+            TssError::RandError(_) => TSS_RAND_ERROR,
+            TssError::RuntimeError(_) => TSS_RUNTIME_ERROR,
+            TssError::ParseError(_) => TSS_PARSE_ERROR,
+            TssError::InvalidArgument { .. } => TSS_INVALID_ARGUMENT,
+            TssError::InvalidKey => TSS_INVALID_KEY,
+            TssError::InvalidPublicKey => TSS_INVALID_PUBLIC_KEY,
+            TssError::InvalidPassphrase => TSS_INVALID_PASSPHRASE,
+            TssError::InvalidKeyStore => TSS_INVALID_KEY_STORE,
+            TssError::InvalidContext => TSS_INVALID_CONTEXT,
+            TssError::InvalidKeypairPool => TSS_INVALID_KEYPAIR_POOL,
+            TssError::UnsupportedCurve => TSS_UNSUPPORTED_CURVE,
+            TssError::RoundOutOfOrder { .. } => TSS_ROUND_OUT_OF_ORDER,
+            TssError::RequestError(e) if e.is_timeout() => TSS_REQUEST_TIMEOUT,
+            TssError::RequestError(_) => TSS_REQUEST_ERROR,
+            TssError::ManagerUnreachable { .. } => TSS_MANAGER_UNREACHABLE,
+            TssError::ManagerError { .. } => TSS_MANAGER_ERROR,
+            TssError::AuthFailed { .. } => TSS_AUTH_FAILED,
+            TssError::InvalidSS => TSS_INVALID_SS,
+            TssError::InvalidCom => TSS_INVALID_COM,
+            TssError::InvalidSig => TSS_INVALID_SIG,
+            TssError::VerifyShareError => TSS_VERIFY_SHARE_ERROR,
+            TssError::InvalidMessage { .. } => TSS_INVALID_MESSAGE,
+            TssError::UnknownIdentity { .. } => TSS_UNKNOWN_IDENTITY,
+            TssError::UnauthenticatedMessage { .. } => TSS_UNAUTHENTICATED_MESSAGE,
+            TssError::InconsistentBroadcast { .. } => TSS_INCONSISTENT_BROADCAST,
+            TssError::Panic { .. } => TSS_PANIC,
+        }
+    }
+
+    /// Round the error happened in, where it is known.
+    pub fn round(&self) -> Option<u16> {
+        match self {
+            TssError::RoundOutOfOrder { got, .. } => Some(*got),
+            TssError::InvalidMessage { round, .. }
+            | TssError::UnauthenticatedMessage { round, .. }
+            | TssError::InconsistentBroadcast { round, .. } => Some(*round),
+            _ => None,
+        }
+    }
+
+    /// Party whose message caused the error, where it is known.
+    pub fn party(&self) -> Option<u16> {
+        match self {
+            TssError::InvalidMessage { party, .. }
+            | TssError::UnknownIdentity { party }
+            | TssError::UnauthenticatedMessage { party, .. }
+            | TssError::InconsistentBroadcast { party, .. } => Some(*party),
+            _ => None,
+        }
+    }
 }
 
 #[cfg(target_arch = "wasm32")]
@@ -42,3 +170,26 @@ impl Into<JsValue> for TssError {
         JsValue::from_str(&format!("{:?}", self))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_error_codes() {
+        let err = TssError::InvalidMessage { round: 3, party: 2 };
+        assert_eq!(err.code(), 304);
+        assert_eq!(err.round(), Some(3));
+        assert_eq!(err.party(), Some(2));
+
+        let err = TssError::UnknownIdentity { party: 4 };
+        assert_eq!((err.code(), err.round(), err.party()), (305, None, Some(4)));
+
+        let err = TssError::InvalidPassphrase;
+        assert_eq!((err.code(), err.round(), err.party()), (112, None, None));
+    }
+}
@@ -0,0 +1,612 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+// C ABI for embedding the signer in native services (Go, Python, C). The
+// header tss_wasm.h is generated by the build with the c-header feature.
+//
+// Every function returns TSS_OK or the `TssError::code` of the failure, one of
+// the TSS_* constants of errors.rs; the message of the last failure on the
+// calling thread is available from `tss_last_error_message`. Strings and
+// handles handed out are owned by the caller and released with
+// `tss_string_free` and `tss_key_store_free`.
+//
+// Null pointers are rejected with TSS_INVALID_ARGUMENT, but a non-null one is
+// trusted: the functions taking pointers are unsafe and state in their
+// `# Safety` section what they must point to.
+
+use crate::api::{
+    eddsa_keygen_client_run, eddsa_public_key, eddsa_sign_client_run, eddsa_verify_signature,
+    gg18_keygen_client_run, gg18_public_key, gg18_sign_client_run, gg18_verify_signature, Curve,
+    EddsaSignResult, RoundTiming, SessionProgress, SignResult,
+};
+use crate::common::{set_retry_policy, TokenProvider};
+use crate::errors::{Result, TssError};
+use crate::gg_2018::party_i::DEFAULT_PAILLIER_MODULUS_BITS;
+use crate::keystore::{import_keystore, seal_keystore, SCRYPT_LOG_N, SCRYPT_P, SCRYPT_R};
+use crate::logging::init_logging;
+use crate::runtime::{block_on, init_runtime};
+use std::cell::RefCell;
+use std::ffi::{c_char, c_void, CStr, CString};
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::ptr;
+
+/// Returned by every function on success.
+pub const TSS_OK: i32 = 0;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+// scheme names accepted where a key store is created: the GG18 curves and ed25519
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Scheme {
+    Gg18(Curve),
+    Eddsa,
+}
+
+impl std::str::FromStr for Scheme {
+    type Err = TssError;
+
+    fn from_str(scheme: &str) -> Result<Scheme> {
+        match scheme {
+            "ed25519" => Ok(Scheme::Eddsa),
+            curve => Ok(Scheme::Gg18(curve.parse()?)),
+        }
+    }
+}
+
+impl Scheme {
+    fn name(&self) -> &'static str {
+        match self {
+            Scheme::Gg18(Curve::Secp256k1) => "secp256k1",
+            Scheme::Gg18(Curve::P256) => "p256",
+            Scheme::Eddsa => "ed25519",
+        }
+    }
+}
+
+/// Key store produced by `tss_keygen` or `tss_key_store_import`, opaque to C.
+pub struct TssKeyStore {
+    scheme: Scheme,
+    key_store: String,
+    public_key: String,
+}
+
+impl TssKeyStore {
+    fn new(scheme: Scheme, key_store: String) -> Result<TssKeyStore> {
+        let public_key = match scheme {
+            Scheme::Gg18(curve) => gg18_public_key(&key_store, curve)?,
+            Scheme::Eddsa => eddsa_public_key(&key_store)?,
+        };
+        Ok(TssKeyStore {
+            scheme,
+            key_store,
+            public_key,
+        })
+    }
+}
+
+/// Manager session shared by keygen and sign; all strings are UTF-8 and
+/// `peer_identity_keys` is the JSON array of the other parties' identity keys.
+#[repr(C)]
+pub struct TssSessionParams {
+    pub addr: *const c_char,
+    pub threshold: u32,
+    pub parties: u32,
+    pub delay_ms: u32,
+    pub token: *const c_char,
+    pub task_id: *const c_char,
+    pub party_type: *const c_char,
+    pub identity_key: *const c_char,
+    pub peer_identity_keys: *const c_char,
+    /// Paillier modulus of a GG18 keygen in bits, 2048 to 8192 or 0 for 2048;
+    /// unused by sign and EdDSA.
+    pub paillier_modulus_bits: u32,
+    /// Builds that modulus from safe primes instead of Blum primes.
+    pub safe_primes: bool,
+}
+
+/// Optional progress and token callbacks; `user_data` is passed back unchanged.
+#[repr(C)]
+pub struct TssProgress {
+    pub user_data: *mut c_void,
+    pub on_round: Option<extern "C" fn(user_data: *mut c_void, round: u16, rounds: u16)>,
+    pub on_waiting: Option<extern "C" fn(user_data: *mut c_void, party: u16)>,
+    /// `step` is "setup" or the round, e.g. "round3", and only valid during the call.
+    pub on_timing: Option<
+        extern "C" fn(
+            user_data: *mut c_void,
+            step: *const c_char,
+            compute_ms: u64,
+            network_ms: u64,
+        ),
+    >,
+    /// Called when the manager rejected the token; returns a new one, copied
+    /// before the next call, or null to give up with 204.
+    pub refresh_token: Option<extern "C" fn(user_data: *mut c_void) -> *const c_char>,
+}
+
+// TssProgress is optional, a null pointer reports nothing
+struct CProgress(*const TssProgress);
+
+impl SessionProgress for CProgress {
+    fn on_round(&self, round: u16, rounds: u16) {
+        if let Some(progress) = unsafe { self.0.as_ref() } {
+            if let Some(on_round) = progress.on_round {
+                on_round(progress.user_data, round, rounds);
+            }
+        }
+    }
+
+    fn on_waiting(&self, party: u16) {
+        if let Some(progress) = unsafe { self.0.as_ref() } {
+            if let Some(on_waiting) = progress.on_waiting {
+                on_waiting(progress.user_data, party);
+            }
+        }
+    }
+
+    fn on_timing(&self, timing: &RoundTiming) {
+        if let Some(progress) = unsafe { self.0.as_ref() } {
+            if let (Some(on_timing), Ok(step)) =
+                (progress.on_timing, CString::new(timing.step.as_str()))
+            {
+                on_timing(
+                    progress.user_data,
+                    step.as_ptr(),
+                    timing.compute_ms,
+                    timing.network_ms,
+                );
+            }
+        }
+    }
+}
+
+impl TokenProvider for CProgress {
+    fn refresh_token(&self) -> Pin<Box<dyn Future<Output = Result<String>> + '_>> {
+        let token = match unsafe { self.0.as_ref() } {
+            Some(TssProgress {
+                user_data,
+                refresh_token: Some(refresh_token),
+                ..
+            // the callback hands back null or a NUL-terminated string
+            }) => unsafe { get_str(refresh_token(*user_data), "refresh_token") },
+            _ => Err(TssError::InvalidArgument {
+                name: "refresh_token".to_string(),
+            }),
+        };
+        Box::pin(async move { token })
+    }
+}
+
+// runs the body of an exported function, turning errors and panics into a
+// code and the thread's last error message
+fn ffi_call(f: impl FnOnce() -> Result<()>) -> i32 {
+    let result = panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|payload| {
+        let msg = match payload.downcast::<String>() {
+            Ok(msg) => *msg,
+            Err(payload) => payload
+                .downcast_ref::<&str>()
+                .map_or_else(|| "unknown panic".to_string(), |msg| msg.to_string()),
+        };
+        Err(TssError::Panic { msg })
+    });
+    match result {
+        Ok(()) => {
+            LAST_ERROR.with(|last| *last.borrow_mut() = None);
+            TSS_OK
+        }
+        Err(e) => {
+            let msg = CString::new(format!("{:?}", e).replace('\0', " ")).unwrap_or_default();
+            LAST_ERROR.with(|last| *last.borrow_mut() = Some(msg));
+            e.code()
+        }
+    }
+}
+
+fn invalid_argument(name: &str) -> TssError {
+    TssError::InvalidArgument {
+        name: name.to_string(),
+    }
+}
+
+// `s` is null or a NUL-terminated string valid for the call
+unsafe fn get_str(s: *const c_char, name: &str) -> Result<String> {
+    if s.is_null() {
+        return Err(invalid_argument(name));
+    }
+    let s = CStr::from_ptr(s);
+    Ok(s.to_str().map_err(|_e| invalid_argument(name))?.to_string())
+}
+
+// `p` is null or points to a `T` that outlives the call
+unsafe fn get_ref<'a, T>(p: *const T, name: &str) -> Result<&'a T> {
+    p.as_ref().ok_or_else(|| invalid_argument(name))
+}
+
+// `out` is null or valid for a write of `T`
+unsafe fn put<T>(out: *mut T, value: T, name: &str) -> Result<()> {
+    if out.is_null() {
+        return Err(invalid_argument(name));
+    }
+    out.write(value);
+    Ok(())
+}
+
+// `out` is null or valid for a write of a pointer
+unsafe fn put_string(out: *mut *mut c_char, s: String, name: &str) -> Result<()> {
+    let s = CString::new(s).map_err(|_e| invalid_argument(name))?;
+    if out.is_null() {
+        return Err(invalid_argument(name));
+    }
+    out.write(s.into_raw());
+    Ok(())
+}
+
+// the session fields as the api functions take them
+struct Session {
+    addr: String,
+    t: usize,
+    n: usize,
+    delay: u32,
+    token: String,
+    task_id: String,
+    party_type: String,
+    identity_key: String,
+    peer_identity_keys: String,
+    paillier_modulus_bits: usize,
+    safe_primes: bool,
+}
+
+impl Session {
+    // `params` is null or points to params whose strings are null or
+    // NUL-terminated
+    unsafe fn from_params(params: *const TssSessionParams) -> Result<Session> {
+        let params = get_ref(params, "params")?;
+        Ok(Session {
+            addr: get_str(params.addr, "addr")?,
+            t: params.threshold as usize,
+            n: params.parties as usize,
+            delay: params.delay_ms,
+            token: get_str(params.token, "token")?,
+            task_id: get_str(params.task_id, "task_id")?,
+            party_type: get_str(params.party_type, "party_type")?,
+            identity_key: get_str(params.identity_key, "identity_key")?,
+            peer_identity_keys: get_str(params.peer_identity_keys, "peer_identity_keys")?,
+            paillier_modulus_bits: match params.paillier_modulus_bits {
+                0 => DEFAULT_PAILLIER_MODULUS_BITS,
+                bits => bits as usize,
+            },
+            safe_primes: params.safe_primes,
+        })
+    }
+}
+
+/// Builds the shared async runtime with `worker_threads` workers (0 for one
+/// per core). Optional, the first session builds it with the defaults.
+#[no_mangle]
+pub extern "C" fn tss_init_runtime(worker_threads: u32) -> i32 {
+    ffi_call(|| init_runtime(worker_threads as usize))
+}
+
+/// Writes the logs of the library at `level` ("off", "error", "warn", "info",
+/// "debug" or "trace") and above to stderr, see `init_logging`.
+///
+/// # Safety
+///
+/// `level` must be null or a NUL-terminated UTF-8 string.
+#[no_mangle]
+pub unsafe extern "C" fn tss_init_logging(level: *const c_char) -> i32 {
+    ffi_call(|| init_logging(get_str(level, "level")?))
+}
+
+/// Sets how requests to the manager are retried by the sessions started from
+/// now on, see `set_retry_policy`. A rejected or expired token fails at once
+/// with 204, unless the `refresh_token` callback of the session gives a new
+/// one.
+#[no_mangle]
+pub extern "C" fn tss_set_retry_policy(
+    max_attempts: u32,
+    base_delay_ms: u32,
+    max_delay_ms: u32,
+) -> i32 {
+    ffi_call(|| set_retry_policy(max_attempts, base_delay_ms, max_delay_ms))
+}
+
+/// Runs a whole keygen for `scheme` ("secp256k1", "p256" or "ed25519") and
+/// stores the new key store in `out`. `progress` may be null.
+///
+/// # Safety
+///
+/// `params` must be null or point to a `TssSessionParams` whose strings are
+/// null or NUL-terminated, `scheme` null or a NUL-terminated UTF-8 string,
+/// `progress` null or a `TssProgress` that stays valid until the call returns,
+/// and `out` null or writable.
+#[no_mangle]
+pub unsafe extern "C" fn tss_keygen(
+    params: *const TssSessionParams,
+    scheme: *const c_char,
+    progress: *const TssProgress,
+    out: *mut *mut TssKeyStore,
+) -> i32 {
+    ffi_call(|| {
+        let session = Session::from_params(params)?;
+        let scheme: Scheme = get_str(scheme, "scheme")?.parse()?;
+        let progress = CProgress(progress);
+        let key_store = match scheme {
+            Scheme::Gg18(_) => {
+                block_on(gg18_keygen_client_run(
+                    session.addr,
+                    session.t,
+                    session.n,
+                    session.delay,
+                    session.token,
+                    session.task_id,
+                    session.party_type,
+                    session.identity_key,
+                    session.peer_identity_keys,
+                    scheme.name().to_string(),
+                    session.paillier_modulus_bits,
+                    session.safe_primes,
+                    &progress,
+                    Some(&progress),
+                ))?
+                .key_store
+            }
+            Scheme::Eddsa => {
+                block_on(eddsa_keygen_client_run(
+                    session.addr,
+                    session.t,
+                    session.n,
+                    session.delay,
+                    session.token,
+                    session.task_id,
+                    session.party_type,
+                    session.identity_key,
+                    session.peer_identity_keys,
+                    &progress,
+                    Some(&progress),
+                ))?
+                .key_store
+            }
+        };
+        let key_store = Box::new(TssKeyStore::new(scheme, key_store)?);
+        put(out, Box::into_raw(key_store), "out")
+    })
+}
+
+/// Signs `message` with `key_store` and stores the signature JSON in `out`:
+/// {"r", "s", "v", "timings"} for GG18, whose message must already be hashed,
+/// and {"signature", "timings"} for EdDSA. `progress` may be null.
+///
+/// # Safety
+///
+/// `params` must be null or point to a `TssSessionParams` whose strings are
+/// null or NUL-terminated, `key_store` null or a live handle of this library,
+/// `message` null or a NUL-terminated UTF-8 string, `progress` null or a
+/// `TssProgress` that stays valid until the call returns, and `out` null or
+/// writable.
+#[no_mangle]
+pub unsafe extern "C" fn tss_sign(
+    params: *const TssSessionParams,
+    key_store: *const TssKeyStore,
+    message: *const c_char,
+    progress: *const TssProgress,
+    out: *mut *mut c_char,
+) -> i32 {
+    ffi_call(|| {
+        let session = Session::from_params(params)?;
+        let key_store = get_ref(key_store, "key_store")?;
+        let message = get_str(message, "message")?;
+        let progress = CProgress(progress);
+        let signature = match key_store.scheme {
+            Scheme::Gg18(_) => serde_json::to_string(&block_on(gg18_sign_client_run(
+                session.addr,
+                session.t,
+                session.n,
+                session.delay,
+                key_store.key_store.clone(),
+                message,
+                session.token,
+                session.task_id,
+                session.party_type,
+                session.identity_key,
+                session.peer_identity_keys,
+                key_store.scheme.name().to_string(),
+                &progress,
+                Some(&progress),
+            ))?)?,
+            Scheme::Eddsa => serde_json::to_string(&block_on(eddsa_sign_client_run(
+                session.addr,
+                session.t,
+                session.n,
+                session.delay,
+                key_store.key_store.clone(),
+                message,
+                session.token,
+                session.task_id,
+                session.party_type,
+                session.identity_key,
+                session.peer_identity_keys,
+                &progress,
+                Some(&progress),
+            ))?)?,
+        };
+        put_string(out, signature, "out")
+    })
+}
+
+/// Checks a signature JSON as returned by `tss_sign` against a public key as
+/// returned by `tss_key_store_public_key`. Returns TSS_OK if it verifies.
+///
+/// # Safety
+///
+/// Every argument must be null or a NUL-terminated UTF-8 string.
+#[no_mangle]
+pub unsafe extern "C" fn tss_verify(
+    scheme: *const c_char,
+    public_key: *const c_char,
+    message: *const c_char,
+    signature: *const c_char,
+) -> i32 {
+    ffi_call(|| {
+        let scheme = get_str(scheme, "scheme")?;
+        let public_key = get_str(public_key, "public_key")?;
+        let message = get_str(message, "message")?;
+        let signature = get_str(signature, "signature")?;
+        let valid = match scheme.parse()? {
+            Scheme::Gg18(_) => {
+                let SignResult { r, s, .. } = serde_json::from_str(&signature)?;
+                gg18_verify_signature(public_key, message, r, s, scheme)?
+            }
+            Scheme::Eddsa => {
+                let EddsaSignResult { signature, .. } = serde_json::from_str(&signature)?;
+                eddsa_verify_signature(public_key, message, signature)?
+            }
+        };
+        match valid {
+            true => Ok(()),
+            false => Err(TssError::InvalidSig),
+        }
+    })
+}
+
+/// Wraps a plaintext key store JSON, e.g. from the per-round API.
+///
+/// # Safety
+///
+/// `scheme` and `json` must be null or NUL-terminated UTF-8 strings, and `out`
+/// null or writable.
+#[no_mangle]
+pub unsafe extern "C" fn tss_key_store_from_json(
+    scheme: *const c_char,
+    json: *const c_char,
+    out: *mut *mut TssKeyStore,
+) -> i32 {
+    ffi_call(|| {
+        let scheme = get_str(scheme, "scheme")?.parse()?;
+        let key_store = Box::new(TssKeyStore::new(scheme, get_str(json, "json")?)?);
+        put(out, Box::into_raw(key_store), "out")
+    })
+}
+
+/// Stores the plaintext key store JSON in `out`. It holds the secret share.
+///
+/// # Safety
+///
+/// `key_store` must be null or a live handle of this library, and `out` null
+/// or writable.
+#[no_mangle]
+pub unsafe extern "C" fn tss_key_store_to_json(
+    key_store: *const TssKeyStore,
+    out: *mut *mut c_char,
+) -> i32 {
+    ffi_call(|| {
+        let key_store = get_ref(key_store, "key_store")?;
+        put_string(out, key_store.key_store.clone(), "out")
+    })
+}
+
+/// Stores the hex public key of the group in `out`: compressed SEC1 for the
+/// GG18 curves, RFC 8032 for ed25519.
+///
+/// # Safety
+///
+/// `key_store` must be null or a live handle of this library, and `out` null
+/// or writable.
+#[no_mangle]
+pub unsafe extern "C" fn tss_key_store_public_key(
+    key_store: *const TssKeyStore,
+    out: *mut *mut c_char,
+) -> i32 {
+    ffi_call(|| {
+        let key_store = get_ref(key_store, "key_store")?;
+        put_string(out, key_store.public_key.clone(), "out")
+    })
+}
+
+/// Encrypts the key store under `passphrase` into the blob format of
+/// `export_keystore`.
+///
+/// # Safety
+///
+/// `key_store` must be null or a live handle of this library, `passphrase`
+/// null or a NUL-terminated UTF-8 string, and `out` null or writable.
+#[no_mangle]
+pub unsafe extern "C" fn tss_key_store_export(
+    key_store: *const TssKeyStore,
+    passphrase: *const c_char,
+    out: *mut *mut c_char,
+) -> i32 {
+    ffi_call(|| {
+        let key_store = get_ref(key_store, "key_store")?;
+        let passphrase = get_str(passphrase, "passphrase")?;
+        // the handle was parsed for its scheme on creation, no need to
+        // guess it again as export_keystore does
+        let blob = seal_keystore(
+            &key_store.key_store,
+            &passphrase,
+            SCRYPT_LOG_N,
+            SCRYPT_R,
+            SCRYPT_P,
+        )?;
+        put_string(out, blob, "out")
+    })
+}
+
+/// Decrypts a blob from `tss_key_store_export`. The scheme is not part of the
+/// blob and has to be the one it was exported with.
+///
+/// # Safety
+///
+/// `scheme`, `blob` and `passphrase` must be null or NUL-terminated UTF-8
+/// strings, and `out` null or writable.
+#[no_mangle]
+pub unsafe extern "C" fn tss_key_store_import(
+    scheme: *const c_char,
+    blob: *const c_char,
+    passphrase: *const c_char,
+    out: *mut *mut TssKeyStore,
+) -> i32 {
+    ffi_call(|| {
+        let scheme = get_str(scheme, "scheme")?.parse()?;
+        let json = import_keystore(get_str(blob, "blob")?, get_str(passphrase, "passphrase")?)?;
+        let key_store = Box::new(TssKeyStore::new(scheme, json)?);
+        put(out, Box::into_raw(key_store), "out")
+    })
+}
+
+/// Releases a key store handle; null is ignored.
+///
+/// # Safety
+///
+/// `key_store` must come from this library and not have been freed before.
+#[no_mangle]
+pub unsafe extern "C" fn tss_key_store_free(key_store: *mut TssKeyStore) {
+    if !key_store.is_null() {
+        drop(Box::from_raw(key_store));
+    }
+}
+
+/// Releases a string handed out by this library; null is ignored.
+///
+/// # Safety
+///
+/// `s` must come from this library and not have been freed before.
+#[no_mangle]
+pub unsafe extern "C" fn tss_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// Message of the last failed call on this thread, or null after a success.
+/// The caller frees it with `tss_string_free`.
+#[no_mangle]
+pub extern "C" fn tss_last_error_message() -> *mut c_char {
+    LAST_ERROR.with(|last| match last.borrow().as_ref() {
+        Some(msg) => msg.clone().into_raw(),
+        None => ptr::null_mut(),
+    })
+}
@@ -0,0 +1,382 @@
+// Typed protocol messages exchanged between parties.
+//
+// The session state machines only see these enums; turning them into bytes
+// and moving them between parties is up to the caller. `to_json`/`from_json`
+// give the payload encoding of the manager based driver. It is not compatible
+// with releases before the signed payloads, the encrypted round3 shares and
+// the keygen proofs: all parties of a session must run the same version.
+
+use crate::common::AEAD;
+use crate::curv::cryptographic_primitives::proofs::sigma_correct_homomorphic_elgamal_enc::HomoELGamalProof;
+use crate::curv::cryptographic_primitives::proofs::sigma_dlog::DLogProof;
+use crate::curv::cryptographic_primitives::secret_sharing::feldman_vss::VerifiableSS;
+use crate::curv::elliptic::curves::secp256_k1::GE;
+use crate::curv::elliptic::curves::traits::ECPoint;
+use crate::errors::{Result, TssError};
+use crate::gg_2018::mta::{MessageA, MessageB};
+use crate::gg_2018::party_i::*;
+use sha2::{Digest, Sha256};
+
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub enum KeygenMessage<P: ECPoint = GE> {
+    Round1(KeyGenBroadcastMessage1),
+    Round2(KeyGenDecommitMessage1<P>),
+    // encrypted secret share, point to point
+    Round3(AEAD),
+    Round4(VerifiableSS<P>),
+    Round5(DLogProof<P>),
+}
+
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub enum SignMessage<P: ECPoint = GE> {
+    // index of the signer in the key store
+    Round0(u16),
+    Round1((SignBroadcastPhase1, MessageA)),
+    // MtA answers for gamma_i and w_i, point to point
+    Round2((MessageB<P>, MessageB<P>)),
+    Round3(P::Scalar),
+    Round4(SignDecommitPhase1<P>),
+    Round5(Phase5Com1),
+    Round6((Phase5ADecom1<P>, HomoELGamalProof<P>, DLogProof<P>)),
+    Round7(Phase5Com2),
+    Round8(Phase5DDecom2<P>),
+    Round9(P::Scalar),
+}
+
+/// Messages produced by a transition. A round is either a broadcast round or
+/// a point-to-point round, never both.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Outgoing<M> {
+    pub round: u16,
+    pub broadcast: Option<M>,
+    pub p2p: Vec<(u16, M)>,
+}
+
+impl<M> Outgoing<M> {
+    pub(crate) fn broadcast(round: u16, msg: M) -> Self {
+        Outgoing {
+            round,
+            broadcast: Some(msg),
+            p2p: Vec::new(),
+        }
+    }
+
+    pub(crate) fn p2p(round: u16, p2p: Vec<(u16, M)>) -> Self {
+        Outgoing {
+            round,
+            broadcast: None,
+            p2p,
+        }
+    }
+
+    pub(crate) fn none(round: u16) -> Self {
+        Outgoing {
+            round,
+            broadcast: None,
+            p2p: Vec::new(),
+        }
+    }
+}
+
+pub trait RoundMessage: Sized {
+    fn round(&self) -> u16;
+    /// Encodes the payload only; the round is known from where it is stored.
+    fn to_json(&self) -> Result<String>;
+    fn from_json(round: u16, data: &str) -> Result<Self>;
+}
+
+impl<P: ECPoint> RoundMessage for KeygenMessage<P> {
+    fn round(&self) -> u16 {
+        match self {
+            KeygenMessage::Round1(_) => 1,
+            KeygenMessage::Round2(_) => 2,
+            KeygenMessage::Round3(_) => 3,
+            KeygenMessage::Round4(_) => 4,
+            KeygenMessage::Round5(_) => 5,
+        }
+    }
+
+    fn to_json(&self) -> Result<String> {
+        Ok(match self {
+            KeygenMessage::Round1(m) => serde_json::to_string(m)?,
+            KeygenMessage::Round2(m) => serde_json::to_string(m)?,
+            KeygenMessage::Round3(m) => serde_json::to_string(m)?,
+            KeygenMessage::Round4(m) => serde_json::to_string(m)?,
+            KeygenMessage::Round5(m) => serde_json::to_string(m)?,
+        })
+    }
+
+    fn from_json(round: u16, data: &str) -> Result<Self> {
+        Ok(match round {
+            1 => KeygenMessage::Round1(serde_json::from_str(data)?),
+            2 => KeygenMessage::Round2(serde_json::from_str(data)?),
+            3 => KeygenMessage::Round3(serde_json::from_str(data)?),
+            4 => KeygenMessage::Round4(serde_json::from_str(data)?),
+            5 => KeygenMessage::Round5(serde_json::from_str(data)?),
+            _ => return Err(TssError::InvalidMessage { round, party: 0 }),
+        })
+    }
+}
+
+impl<P: ECPoint> RoundMessage for SignMessage<P> {
+    fn round(&self) -> u16 {
+        match self {
+            SignMessage::Round0(_) => 0,
+            SignMessage::Round1(_) => 1,
+            SignMessage::Round2(_) => 2,
+            SignMessage::Round3(_) => 3,
+            SignMessage::Round4(_) => 4,
+            SignMessage::Round5(_) => 5,
+            SignMessage::Round6(_) => 6,
+            SignMessage::Round7(_) => 7,
+            SignMessage::Round8(_) => 8,
+            SignMessage::Round9(_) => 9,
+        }
+    }
+
+    fn to_json(&self) -> Result<String> {
+        Ok(match self {
+            SignMessage::Round0(m) => serde_json::to_string(m)?,
+            SignMessage::Round1(m) => serde_json::to_string(m)?,
+            SignMessage::Round2(m) => serde_json::to_string(m)?,
+            SignMessage::Round3(m) => serde_json::to_string(m)?,
+            SignMessage::Round4(m) => serde_json::to_string(m)?,
+            SignMessage::Round5(m) => serde_json::to_string(m)?,
+            SignMessage::Round6(m) => serde_json::to_string(m)?,
+            SignMessage::Round7(m) => serde_json::to_string(m)?,
+            SignMessage::Round8(m) => serde_json::to_string(m)?,
+            SignMessage::Round9(m) => serde_json::to_string(m)?,
+        })
+    }
+
+    fn from_json(round: u16, data: &str) -> Result<Self> {
+        Ok(match round {
+            0 => SignMessage::Round0(serde_json::from_str(data)?),
+            1 => SignMessage::Round1(serde_json::from_str(data)?),
+            2 => SignMessage::Round2(serde_json::from_str(data)?),
+            3 => SignMessage::Round3(serde_json::from_str(data)?),
+            4 => SignMessage::Round4(serde_json::from_str(data)?),
+            5 => SignMessage::Round5(serde_json::from_str(data)?),
+            6 => SignMessage::Round6(serde_json::from_str(data)?),
+            7 => SignMessage::Round7(serde_json::from_str(data)?),
+            8 => SignMessage::Round8(serde_json::from_str(data)?),
+            9 => SignMessage::Round9(serde_json::from_str(data)?),
+            _ => return Err(TssError::InvalidMessage { round, party: 0 }),
+        })
+    }
+}
+
+/// Hash of every broadcast a party saw in a round, its own included, sent back
+/// to all parties so a relay showing different values to different parties is
+/// caught (echo broadcast).
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct EchoMessage {
+    pub round: u16,
+    pub digest: String,
+}
+
+impl EchoMessage {
+    /// `payloads` are the encoded broadcast messages of the round, tagged with
+    /// the sending party.
+    pub fn new(session_id: &str, round: u16, payloads: &[(u16, String)]) -> EchoMessage {
+        let mut payloads: Vec<&(u16, String)> = payloads.iter().collect();
+        payloads.sort_by_key(|(from, _)| *from);
+
+        let mut hasher = Sha256::new();
+        hasher.update(b"tss-wasm/gg18/echo-broadcast/v1");
+        hasher.update((session_id.len() as u64).to_be_bytes());
+        hasher.update(session_id.as_bytes());
+        hasher.update(round.to_be_bytes());
+        for (from, payload) in payloads {
+            hasher.update(from.to_be_bytes());
+            hasher.update((payload.len() as u64).to_be_bytes());
+            hasher.update(payload.as_bytes());
+        }
+        EchoMessage {
+            round,
+            digest: hex::encode(hasher.finalize()),
+        }
+    }
+
+    /// Compares the echoes of the other parties with our own.
+    pub fn check(&self, echoes: &[(u16, EchoMessage)]) -> Result<()> {
+        match echoes.iter().find(|(_, echo)| echo != self) {
+            Some((from, _)) => Err(TssError::InconsistentBroadcast {
+                round: self.round,
+                party: *from,
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Checks that `incoming` holds exactly one message of `round` from every
+/// party in `1..=parties` except `party_num_int`, and returns them in party order.
+pub(crate) fn sort_incoming<M: RoundMessage>(
+    mut incoming: Vec<(u16, M)>,
+    round: u16,
+    parties: u16,
+    party_num_int: u16,
+) -> Result<Vec<M>> {
+    incoming.sort_by_key(|(from, _)| *from);
+    let mut expected = (1..=parties).filter(|i| *i != party_num_int);
+    let mut out = Vec::with_capacity(incoming.len());
+    for (from, msg) in incoming {
+        if expected.next() != Some(from) || msg.round() != round {
+            return Err(TssError::InvalidMessage { round, party: from });
+        }
+        out.push(msg);
+    }
+    match expected.next() {
+        Some(missing) => Err(TssError::InvalidMessage {
+            round,
+            party: missing,
+        }),
+        None => Ok(out),
+    }
+}
+
+/// Our slot in the per-party vectors. `party_num_int` comes from the manager's
+/// signup reply, so it is checked against the `parties` of the session instead
+/// of trusted.
+pub(crate) fn party_index(party_num_int: u16, parties: u16) -> Result<usize> {
+    match party_num_int >= 1 && party_num_int <= parties {
+        true => Ok(usize::from(party_num_int - 1)),
+        false => Err(TssError::InvalidArgument {
+            name: "party_num_int".to_string(),
+        }),
+    }
+}
+
+/// Turns the party ids announced in sign round 0 (ordered by party number, with
+/// our own slot at `party_num_int`) into key store indices, rejecting ids outside
+/// `1..=n` and ids announced twice.
+pub(crate) fn signer_indices(
+    party_ids: Vec<u16>,
+    party_id: u16,
+    party_num_int: u16,
+    parties: u16,
+    n: usize,
+) -> Result<Vec<usize>> {
+    let mut peers = party_ids.into_iter();
+    let mut signers_vec = Vec::with_capacity(usize::from(parties));
+    for i in 1..=parties {
+        let signer = if i == party_num_int {
+            Some(party_id)
+        } else {
+            peers.next()
+        };
+        let index = signer
+            .and_then(|signer| usize::from(signer).checked_sub(1))
+            .filter(|index| *index < n && !signers_vec.contains(index))
+            .ok_or(TssError::InvalidMessage { round: 0, party: i })?;
+        signers_vec.push(index);
+    }
+    match peers.next() {
+        Some(_) => Err(TssError::InvalidMessage {
+            round: 0,
+            party: parties + 1,
+        }),
+        None => Ok(signers_vec),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curv::elliptic::curves::secp256_k1::FE;
+    use crate::curv::elliptic::curves::traits::ECScalar;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_signer_indices() {
+        assert_eq!(
+            signer_indices(vec![3, 1], 2, 2, 3, 3).unwrap(),
+            vec![2, 1, 0]
+        );
+
+        // ids out of 1..=n, repeated ids and a wrong signer count are rejected
+        for (ids, party) in [
+            (vec![0, 1], 1),
+            (vec![4, 1], 1),
+            (vec![3, 2], 3),
+            (vec![3, 3], 3),
+            (vec![3], 3),
+            (vec![3, 1, 1], 4),
+        ] {
+            match signer_indices(ids.clone(), 2, 2, 3, 3) {
+                Err(TssError::InvalidMessage { round: 0, party: p }) if p == party => {}
+                _ => panic!("signer ids {:?} should be rejected", ids),
+            }
+        }
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_sort_incoming() {
+        let msg = |round| SignMessage::<GE>::from_json(round, "3").unwrap();
+        let sorted = sort_incoming(vec![(3, msg(0)), (1, msg(0))], 0, 3, 2).unwrap();
+        assert_eq!(sorted.len(), 2);
+
+        // missing, duplicated, own and wrong round messages are rejected
+        match sort_incoming(vec![(1, msg(0))], 0, 3, 2) {
+            Err(TssError::InvalidMessage { round: 0, party: 3 }) => {}
+            _ => panic!("missing message from party 3"),
+        }
+        assert!(sort_incoming(vec![(1, msg(0)), (1, msg(0))], 0, 3, 2).is_err());
+        assert!(sort_incoming(vec![(1, msg(0)), (2, msg(0))], 0, 3, 2).is_err());
+        match sort_incoming(
+            vec![(1, msg(0)), (3, SignMessage::Round9(FE::new_random()))],
+            0,
+            3,
+            2,
+        ) {
+            Err(TssError::InvalidMessage { round: 0, party: 3 }) => {}
+            _ => panic!("round9 message in round0"),
+        }
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_message_json() {
+        let s_i: FE = ECScalar::new_random();
+        let msg = SignMessage::<GE>::Round9(s_i.clone());
+        // the payload alone goes on the wire
+        assert_eq!(msg.to_json().unwrap(), serde_json::to_string(&s_i).unwrap());
+        match SignMessage::<GE>::from_json(9, &msg.to_json().unwrap()).unwrap() {
+            SignMessage::Round9(s) => assert_eq!(s, s_i),
+            _ => panic!("wrong round"),
+        }
+        assert!(SignMessage::<GE>::from_json(10, "1").is_err());
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_echo_message() {
+        let seen = vec![(2, "b".to_string()), (1, "a".to_string())];
+        let echo = EchoMessage::new("uuid", 1, &seen);
+        // independent of the order messages arrived in
+        let same = EchoMessage::new("uuid", 1, &[(1, "a".to_string()), (2, "b".to_string())]);
+        assert!(echo.check(&[(2, same)]).is_ok());
+
+        let equivocated =
+            EchoMessage::new("uuid", 1, &[(1, "a".to_string()), (2, "c".to_string())]);
+        match echo.check(&[(2, equivocated)]) {
+            Err(TssError::InconsistentBroadcast { round: 1, party: 2 }) => {}
+            _ => panic!("different round1 messages not detected"),
+        }
+        assert!(echo
+            .check(&[(2, EchoMessage::new("other", 1, &seen))])
+            .is_err());
+        assert!(echo
+            .check(&[(2, EchoMessage::new("uuid", 2, &seen))])
+            .is_err());
+    }
+}
@@ -15,8 +15,9 @@
 */
 use crate::curv::arithmetic::num_bigint::BigInt;
 use crate::curv::arithmetic::traits::Samplable;
+use crate::curv::cryptographic_primitives::hashing::ext::ProofContext;
 use crate::curv::cryptographic_primitives::proofs::sigma_dlog::{DLogProof, ProveDLog};
-use crate::curv::elliptic::curves::secp256_k1::{FE, GE};
+use crate::curv::elliptic::curves::secp256_k1::GE;
 use crate::curv::elliptic::curves::traits::*;
 use crate::paillier::{Add, Decrypt, Mul};
 use crate::paillier::{DecryptionKey, EncryptionKey, Paillier, RawCiphertext, RawPlaintext};
@@ -28,7 +29,6 @@ use crate::gg_2018::range_proofs::AliceProof;
 use crate::paillier::zkproofs::DLogStatement;
 use crate::paillier::Randomness;
 
-use crate::curv::elliptic::curves::secp256_k1::Secp256k1Scalar;
 use crate::paillier::traits::EncryptWithChosenRandomness;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -38,28 +38,32 @@ pub struct MessageA {
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct MessageB {
+#[serde(bound = "")]
+pub struct MessageB<P: ECPoint = GE> {
     pub c: BigInt, // paillier encryption
-    pub b_proof: DLogProof,
-    pub beta_tag_proof: DLogProof,
+    pub b_proof: DLogProof<P>,
+    pub beta_tag_proof: DLogProof<P>,
 }
 
 impl MessageA {
-    pub fn a(
-        a: &Secp256k1Scalar,
+    pub fn a<S: ECScalar>(
+        a: &S,
         alice_ek: &EncryptionKey,
         dlog_statements: &[DLogStatement],
+        ctx: &ProofContext,
     ) -> (Self, BigInt) {
         let randomness = BigInt::sample_below(&alice_ek.n);
-        let m_a = MessageA::a_with_predefined_randomness(a, alice_ek, &randomness, dlog_statements);
+        let m_a =
+            MessageA::a_with_predefined_randomness(a, alice_ek, &randomness, dlog_statements, ctx);
         (m_a, randomness)
     }
 
-    pub fn a_with_predefined_randomness(
-        a: &Secp256k1Scalar,
+    pub fn a_with_predefined_randomness<S: ECScalar>(
+        a: &S,
         alice_ek: &EncryptionKey,
         randomness: &BigInt,
         dlog_statements: &[DLogStatement],
+        ctx: &ProofContext,
     ) -> Self {
         let c_a = Paillier::encrypt_with_chosen_randomness(
             alice_ek,
@@ -72,7 +76,14 @@ impl MessageA {
         let alice_range_proofs = dlog_statements
             .iter()
             .map(|dlog_statement| {
-                AliceProof::generate(&a.to_big_int(), &c_a, alice_ek, dlog_statement, randomness)
+                AliceProof::generate::<S>(
+                    &a.to_big_int(),
+                    &c_a,
+                    alice_ek,
+                    dlog_statement,
+                    randomness,
+                    ctx,
+                )
             })
             .collect::<Vec<AliceProof>>();
 
@@ -83,13 +94,16 @@ impl MessageA {
     }
 }
 
-impl MessageB {
+impl<P: ECPoint> MessageB<P> {
+    /// `alice_ctx` is the context Alice made her range proofs in, `ctx` the one of Bob.
     pub fn b(
-        b: &Secp256k1Scalar,
+        b: &P::Scalar,
         alice_ek: &EncryptionKey,
         m_a: MessageA,
         dlog_statements: &[DLogStatement],
-    ) -> Result<(Self, Secp256k1Scalar, BigInt, BigInt), TssError> {
+        alice_ctx: &ProofContext,
+        ctx: &ProofContext,
+    ) -> Result<(Self, P::Scalar, BigInt, BigInt), TssError> {
         let beta_tag = BigInt::sample_below(&alice_ek.n);
         let randomness = BigInt::sample_below(&alice_ek.n);
         let (m_b, beta) = MessageB::b_with_predefined_randomness(
@@ -99,19 +113,24 @@ impl MessageB {
             &randomness,
             &beta_tag,
             dlog_statements,
+            alice_ctx,
+            ctx,
         )?;
 
         Ok((m_b, beta, randomness, beta_tag))
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn b_with_predefined_randomness(
-        b: &Secp256k1Scalar,
+        b: &P::Scalar,
         alice_ek: &EncryptionKey,
         m_a: MessageA,
         randomness: &BigInt,
         beta_tag: &BigInt,
         dlog_statements: &[DLogStatement],
-    ) -> Result<(Self, Secp256k1Scalar), TssError> {
+        alice_ctx: &ProofContext,
+        ctx: &ProofContext,
+    ) -> Result<(Self, P::Scalar), TssError> {
         if m_a.range_proofs.len() != dlog_statements.len() {
             return Err(InvalidKey);
         }
@@ -120,12 +139,14 @@ impl MessageB {
             .range_proofs
             .iter()
             .zip(dlog_statements)
-            .map(|(proof, dlog_statement)| proof.verify(&m_a.c, alice_ek, dlog_statement))
+            .map(|(proof, dlog_statement)| {
+                proof.verify::<P::Scalar>(&m_a.c, alice_ek, dlog_statement, alice_ctx)
+            })
             .all(|x| x)
         {
             return Err(InvalidKey);
         };
-        let beta_tag_fe: Secp256k1Scalar = ECScalar::from(beta_tag);
+        let beta_tag_fe: P::Scalar = ECScalar::from(beta_tag);
         let c_beta_tag = Paillier::encrypt_with_chosen_randomness(
             alice_ek,
             RawPlaintext::from(beta_tag),
@@ -139,9 +160,9 @@ impl MessageB {
             RawPlaintext::from(b_bn),
         );
         let c_b = Paillier::add(alice_ek, b_c_a, c_beta_tag);
-        let beta = FE::zero().sub(&beta_tag_fe.get_element());
-        let dlog_proof_b = DLogProof::prove(b);
-        let dlog_proof_beta_tag = DLogProof::prove(&beta_tag_fe);
+        let beta = P::Scalar::zero().sub(&beta_tag_fe.get_element());
+        let dlog_proof_b = DLogProof::<P>::prove(b, ctx);
+        let dlog_proof_beta_tag = DLogProof::<P>::prove(&beta_tag_fe, ctx);
 
         Ok((
             Self {
@@ -156,15 +177,16 @@ impl MessageB {
     pub fn verify_proofs_get_alpha(
         &self,
         dk: &DecryptionKey,
-        a: &Secp256k1Scalar,
-    ) -> Result<(Secp256k1Scalar, BigInt), TssError> {
+        a: &P::Scalar,
+        ctx: &ProofContext,
+    ) -> Result<(P::Scalar, BigInt), TssError> {
         let alice_share = Paillier::decrypt(dk, &RawCiphertext::from(self.c.clone()));
-        let g: GE = ECPoint::generator();
-        let alpha: FE = ECScalar::from(&alice_share.0);
+        let g = P::generator();
+        let alpha: P::Scalar = ECScalar::from(&alice_share.0);
         let g_alpha = g * &alpha;
-        let ba_btag = &self.b_proof.pk * a + &self.beta_tag_proof.pk;
-        match DLogProof::verify(&self.b_proof).is_ok()
-            && DLogProof::verify(&self.beta_tag_proof).is_ok()
+        let ba_btag = self.b_proof.pk.clone() * a + &self.beta_tag_proof.pk;
+        match DLogProof::verify(&self.b_proof, ctx).is_ok()
+            && DLogProof::verify(&self.beta_tag_proof, ctx).is_ok()
             && ba_btag == g_alpha
         {
             true => Ok((alpha, alice_share.0.into_owned())),
@@ -176,25 +198,26 @@ impl MessageB {
     //  with the regular version mta can be used in general
     pub fn verify_proofs_get_alpha_gg18(
         &self,
-        private: &PartyPrivate,
-        a: &FE,
-    ) -> Result<FE, TssError> {
+        private: &PartyPrivate<P>,
+        a: &P::Scalar,
+        ctx: &ProofContext,
+    ) -> Result<P::Scalar, TssError> {
         let alice_share = private.decrypt(self.c.clone());
-        let g: GE = ECPoint::generator();
-        let alpha: FE = ECScalar::from(&alice_share.0);
+        let g = P::generator();
+        let alpha: P::Scalar = ECScalar::from(&alice_share.0);
         let g_alpha = g * &alpha;
-        let ba_btag = &self.b_proof.pk * a + &self.beta_tag_proof.pk;
+        let ba_btag = self.b_proof.pk.clone() * a + &self.beta_tag_proof.pk;
 
-        match DLogProof::verify(&self.b_proof).is_ok()
-            && DLogProof::verify(&self.beta_tag_proof).is_ok()
-            && ba_btag.get_element() == g_alpha.get_element()
+        match DLogProof::verify(&self.b_proof, ctx).is_ok()
+            && DLogProof::verify(&self.beta_tag_proof, ctx).is_ok()
+            && ba_btag == g_alpha
         {
             true => Ok(alpha),
             false => Err(InvalidKey),
         }
     }
 
-    pub fn verify_b_against_public(public_gb: &GE, mta_gb: &GE) -> bool {
-        public_gb.get_element() == mta_gb.get_element()
+    pub fn verify_b_against_public(public_gb: &P, mta_gb: &P) -> bool {
+        public_gb == mta_gb
     }
 }
@@ -17,8 +17,9 @@
 */
 
 use crate::errors::TssError::{self, InvalidCom, InvalidKey, InvalidSS, InvalidSig};
-use crate::paillier::zkproofs::NICorrectKeyProof;
-use crate::paillier::KeyGeneration;
+use crate::keypair_pool::take_keypair;
+use crate::paillier::zkproofs::{NICorrectKeyProof, NoSmallFactorProof, PaillierBlumModulusProof};
+use crate::paillier::{KeyGeneration, PrimeKind};
 use crate::paillier::Paillier;
 use crate::paillier::{DecryptionKey, EncryptionKey};
 
@@ -29,30 +30,33 @@ use crate::curv::elliptic::curves::traits::*;
 use crate::curv::arithmetic::num_bigint::BigInt;
 use crate::curv::cryptographic_primitives::commitments::hash_commitment::HashCommitment;
 use crate::curv::cryptographic_primitives::commitments::traits::Commitment;
+use crate::curv::cryptographic_primitives::hashing::ext::ProofContext;
 use crate::curv::cryptographic_primitives::hashing::hash_sha256::HSha256;
 use crate::curv::cryptographic_primitives::hashing::traits::Hash;
 use crate::curv::cryptographic_primitives::proofs::sigma_correct_homomorphic_elgamal_enc::*;
 use crate::curv::cryptographic_primitives::proofs::sigma_dlog::{DLogProof, ProveDLog};
 use crate::curv::cryptographic_primitives::secret_sharing::feldman_vss::VerifiableSS;
-use crate::curv::elliptic::curves::secp256_k1::{FE, GE};
+use crate::curv::elliptic::curves::secp256_k1::GE;
 use crate::paillier::{Decrypt, RawCiphertext, RawPlaintext};
 use num_integer::Integer;
 
 const SECURITY: usize = 256;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct Keys {
-    pub u_i: FE,
-    pub y_i: GE,
+#[serde(bound = "")]
+pub struct Keys<P: ECPoint = GE> {
+    pub u_i: P::Scalar,
+    pub y_i: P,
     pub dk: DecryptionKey,
     pub ek: EncryptionKey,
     pub party_index: usize,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
-pub struct PartyPrivate {
-    u_i: FE,
-    x_i: FE,
+#[serde(bound = "")]
+pub struct PartyPrivate<P: ECPoint = GE> {
+    u_i: P::Scalar,
+    x_i: P::Scalar,
     dk: DecryptionKey,
 }
 
@@ -61,33 +65,94 @@ pub struct KeyGenBroadcastMessage1 {
     pub e: EncryptionKey,
     pub com: BigInt,
     pub correct_key_proof: NICorrectKeyProof,
+    pub modulus_proof: PaillierBlumModulusProof,
+    pub no_small_factor_proof: NoSmallFactorProof,
 }
 
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
-pub struct KeyGenDecommitMessage1 {
+#[serde(bound = "")]
+pub struct KeyGenDecommitMessage1<P: ECPoint = GE> {
     pub blind_factor: BigInt,
-    pub y_i: GE,
+    pub y_i: P,
 }
 
+pub const DEFAULT_PAILLIER_MODULUS_BITS: usize = 2048;
+// sizes accepted for our own modulus: below 2048 bits is not secure, above
+// 8192 the primes take too long to find on a phone
+pub const MIN_PAILLIER_MODULUS_BITS: usize = 2048;
+pub const MAX_PAILLIER_MODULUS_BITS: usize = 8192;
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Parameters {
     pub threshold: usize,   //t
     pub share_count: usize, //n
+    // size of our own Paillier modulus, and the minimum accepted from others
+    #[serde(default = "default_paillier_modulus_bits")]
+    pub paillier_modulus_bits: usize,
+    #[serde(default)]
+    pub paillier_prime_kind: PrimeKind,
+}
+
+fn default_paillier_modulus_bits() -> usize {
+    DEFAULT_PAILLIER_MODULUS_BITS
+}
+
+impl Parameters {
+    /// Parameters with a 2048 bit Paillier modulus built from Blum primes.
+    pub fn new(threshold: usize, share_count: usize) -> Parameters {
+        Parameters {
+            threshold,
+            share_count,
+            paillier_modulus_bits: DEFAULT_PAILLIER_MODULUS_BITS,
+            paillier_prime_kind: PrimeKind::default(),
+        }
+    }
+
+    /// Parameters with a Paillier modulus of `modulus_bits` built from
+    /// `prime_kind` primes, e.g. 3072 bits where policy requires it.
+    pub fn with_paillier(
+        threshold: usize,
+        share_count: usize,
+        modulus_bits: usize,
+        prime_kind: PrimeKind,
+    ) -> Result<Parameters, TssError> {
+        check_paillier_modulus_bits(modulus_bits)?;
+        Ok(Parameters {
+            threshold,
+            share_count,
+            paillier_modulus_bits: modulus_bits,
+            paillier_prime_kind: prime_kind,
+        })
+    }
+}
+
+/// Fails unless `modulus_bits` is an even size from 2048 to 8192.
+pub fn check_paillier_modulus_bits(modulus_bits: usize) -> Result<(), TssError> {
+    if !(MIN_PAILLIER_MODULUS_BITS..=MAX_PAILLIER_MODULUS_BITS).contains(&modulus_bits)
+        || !modulus_bits.is_multiple_of(2)
+    {
+        return Err(TssError::InvalidArgument {
+            name: "paillier_modulus_bits".to_string(),
+        });
+    }
+    Ok(())
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct SharedKeys {
-    pub y: GE,
-    pub x_i: FE,
+#[serde(bound = "")]
+pub struct SharedKeys<P: ECPoint = GE> {
+    pub y: P,
+    pub x_i: P::Scalar,
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
-pub struct SignKeys {
-    pub w_i: FE,
-    pub g_w_i: GE,
-    pub k_i: FE,
-    pub gamma_i: FE,
-    pub g_gamma_i: GE,
+#[serde(bound = "")]
+pub struct SignKeys<P: ECPoint = GE> {
+    pub w_i: P::Scalar,
+    pub g_w_i: P,
+    pub k_i: P::Scalar,
+    pub gamma_i: P::Scalar,
+    pub g_gamma_i: P,
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
@@ -96,19 +161,21 @@ pub struct SignBroadcastPhase1 {
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
-pub struct SignDecommitPhase1 {
+#[serde(bound = "")]
+pub struct SignDecommitPhase1<P: ECPoint = GE> {
     pub blind_factor: BigInt,
-    pub g_gamma_i: GE,
+    pub g_gamma_i: P,
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
-pub struct LocalSignature {
-    pub l_i: FE,
-    pub rho_i: FE,
-    pub R: GE,
-    pub s_i: FE,
+#[serde(bound = "")]
+pub struct LocalSignature<P: ECPoint = GE> {
+    pub l_i: P::Scalar,
+    pub rho_i: P::Scalar,
+    pub R: P,
+    pub s_i: P::Scalar,
     pub m: BigInt,
-    pub y: GE,
+    pub y: P,
 }
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub struct Phase5Com1 {
@@ -121,32 +188,37 @@ pub struct Phase5Com2 {
 }
 
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
-pub struct Phase5ADecom1 {
-    pub V_i: GE,
-    pub A_i: GE,
-    pub B_i: GE,
+#[serde(bound = "")]
+pub struct Phase5ADecom1<P: ECPoint = GE> {
+    pub V_i: P,
+    pub A_i: P,
+    pub B_i: P,
     pub blind_factor: BigInt,
 }
 
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
-pub struct Phase5DDecom2 {
-    pub u_i: GE,
-    pub t_i: GE,
+#[serde(bound = "")]
+pub struct Phase5DDecom2<P: ECPoint = GE> {
+    pub u_i: P,
+    pub t_i: P,
     pub blind_factor: BigInt,
 }
 
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
-pub struct Signature {
-    pub r: FE,
-    pub s: FE,
+#[serde(bound = "")]
+pub struct Signature<P: ECPoint = GE> {
+    pub r: P::Scalar,
+    pub s: P::Scalar,
     pub recid: u8,
 }
 
-impl Keys {
-    pub fn create(index: usize) -> Keys {
-        let u: FE = ECScalar::new_random();
-        let y = &ECPoint::generator() * &u;
-        let (ek, dk) = Paillier::keypair().keys();
+impl<P: ECPoint> Keys<P> {
+    pub fn create(index: usize) -> Keys<P> {
+        let u: P::Scalar = ECScalar::new_random();
+        let y = P::generator() * &u;
+        let (ek, dk) = take_keypair(DEFAULT_PAILLIER_MODULUS_BITS, PrimeKind::default())
+            .unwrap_or_else(Paillier::keypair)
+            .keys();
 
         Keys {
             u_i: u,
@@ -157,8 +229,31 @@ impl Keys {
         }
     }
 
-    pub fn create_from(u: FE, index: usize) -> Keys {
-        let y = &ECPoint::generator() * &u;
+    /// Like `create`, with the Paillier modulus size and prime kind taken from `params`.
+    /// Both take the keypair from the pool when it holds a matching one.
+    pub fn create_with_params(index: usize, params: &Parameters) -> Keys<P> {
+        let u: P::Scalar = ECScalar::new_random();
+        let y = P::generator() * &u;
+        let (ek, dk) = take_keypair(params.paillier_modulus_bits, params.paillier_prime_kind)
+            .unwrap_or_else(|| {
+                Paillier::keypair_with_prime_kind(
+                    params.paillier_modulus_bits,
+                    params.paillier_prime_kind,
+                )
+            })
+            .keys();
+
+        Keys {
+            u_i: u,
+            y_i: y,
+            dk,
+            ek,
+            party_index: index,
+        }
+    }
+
+    pub fn create_from(u: P::Scalar, index: usize) -> Keys<P> {
+        let y = P::generator() * &u;
         let (ek, dk) = Paillier::keypair().keys();
 
         Keys {
@@ -172,9 +267,12 @@ impl Keys {
 
     pub fn phase1_broadcast_phase3_proof_of_correct_key(
         &self,
-    ) -> (KeyGenBroadcastMessage1, KeyGenDecommitMessage1) {
+        ctx: &ProofContext,
+    ) -> (KeyGenBroadcastMessage1, KeyGenDecommitMessage1<P>) {
         let blind_factor = BigInt::sample(SECURITY);
-        let correct_key_proof = NICorrectKeyProof::proof(&self.dk);
+        let correct_key_proof = NICorrectKeyProof::proof(&self.dk, ctx);
+        let modulus_proof = PaillierBlumModulusProof::prove(&self.dk, ctx);
+        let no_small_factor_proof = NoSmallFactorProof::prove(&self.dk, ctx);
         let com = HashCommitment::create_commitment_with_user_defined_randomness(
             &self.y_i.bytes_compressed_to_big_int(),
             &blind_factor,
@@ -183,6 +281,8 @@ impl Keys {
             e: self.ek.clone(),
             com,
             correct_key_proof,
+            modulus_proof,
+            no_small_factor_proof,
         };
         let decom1 = KeyGenDecommitMessage1 {
             blind_factor,
@@ -191,28 +291,38 @@ impl Keys {
         (bcm1, decom1)
     }
 
+    /// The proofs in `bc1_vec[i]` are checked against `ctx` for party i + 1.
+    #[allow(clippy::type_complexity)]
     pub fn phase1_verify_com_phase3_verify_correct_key_phase2_distribute(
         &self,
         params: &Parameters,
-        decom_vec: &Vec<KeyGenDecommitMessage1>,
+        decom_vec: &Vec<KeyGenDecommitMessage1<P>>,
         bc1_vec: &Vec<KeyGenBroadcastMessage1>,
-    ) -> Result<(VerifiableSS, Vec<FE>, usize), TssError> {
+        ctx: &ProofContext,
+    ) -> Result<(VerifiableSS<P>, Vec<P::Scalar>, usize), TssError> {
         // test length:
         assert_eq!(decom_vec.len(), params.share_count);
         assert_eq!(bc1_vec.len(), params.share_count);
         // test paillier correct key and test decommitments
         let correct_key_correct_decom_all = (0..bc1_vec.len())
             .map(|i| {
+                let ctx_i = ctx.for_party(i as u16 + 1);
                 HashCommitment::create_commitment_with_user_defined_randomness(
                     &decom_vec[i].y_i.bytes_compressed_to_big_int(),
                     &decom_vec[i].blind_factor,
                 ) == bc1_vec[i].com
-                    && bc1_vec[i].correct_key_proof.verify(&bc1_vec[i].e).is_ok()
+                    && bc1_vec[i].e.n.bits() >= params.paillier_modulus_bits
+                    && bc1_vec[i].correct_key_proof.verify(&bc1_vec[i].e, &ctx_i).is_ok()
+                    && bc1_vec[i].modulus_proof.verify(&bc1_vec[i].e, &ctx_i).is_ok()
+                    && bc1_vec[i]
+                        .no_small_factor_proof
+                        .verify(&bc1_vec[i].e, &ctx_i)
+                        .is_ok()
             })
             .all(|x| x == true);
 
         let (vss_scheme, secret_shares) =
-            VerifiableSS::share(params.threshold, params.share_count, &self.u_i);
+            VerifiableSS::<P>::share(params.threshold, params.share_count, &self.u_i);
         match correct_key_correct_decom_all {
             true => Ok((vss_scheme, secret_shares, self.party_index.clone())),
             false => Err(InvalidKey),
@@ -222,11 +332,12 @@ impl Keys {
     pub fn phase2_verify_vss_construct_keypair_phase3_pok_dlog(
         &self,
         params: &Parameters,
-        y_vec: &Vec<GE>,
-        secret_shares_vec: &Vec<FE>,
-        vss_scheme_vec: &Vec<VerifiableSS>,
+        y_vec: &Vec<P>,
+        secret_shares_vec: &Vec<P::Scalar>,
+        vss_scheme_vec: &Vec<VerifiableSS<P>>,
         index: &usize,
-    ) -> Result<(SharedKeys, DLogProof), TssError> {
+        ctx: &ProofContext,
+    ) -> Result<(SharedKeys<P>, DLogProof<P>), TssError> {
         assert_eq!(y_vec.len(), params.share_count);
         assert_eq!(secret_shares_vec.len(), params.share_count);
         assert_eq!(vss_scheme_vec.len(), params.share_count);
@@ -236,7 +347,7 @@ impl Keys {
                 vss_scheme_vec[i]
                     .validate_share(&secret_shares_vec[i], *index)
                     .is_ok()
-                    && vss_scheme_vec[i].commitments[0].get_element() == y_vec[i].get_element()
+                    && vss_scheme_vec[i].commitments[0] == y_vec[i]
             })
             .all(|x| x == true);
 
@@ -245,21 +356,21 @@ impl Keys {
                 let mut y_vec_iter = y_vec.iter();
                 let y0 = y_vec_iter.next().unwrap();
                 let y = y_vec_iter.fold(y0.clone(), |acc, x| acc + x);
-                let x_i = secret_shares_vec.iter().fold(FE::zero(), |acc, x| acc + x);
-                let dlog_proof = DLogProof::prove(&x_i);
+                let x_i = secret_shares_vec.iter().fold(P::Scalar::zero(), |acc, x| acc + x);
+                let dlog_proof = DLogProof::<P>::prove(&x_i, ctx);
                 Ok((SharedKeys { y, x_i }, dlog_proof))
             }
             false => Err(InvalidSS),
         }
     }
 
-    pub fn get_commitments_to_xi(vss_scheme_vec: &Vec<VerifiableSS>) -> Vec<GE> {
+    pub fn get_commitments_to_xi(vss_scheme_vec: &Vec<VerifiableSS<P>>) -> Vec<P> {
         let len = vss_scheme_vec.len();
         let xi_points_vec = (1..len + 1)
             .map(|i| {
                 let xij_points_vec = (0..len)
                     .map(|j| vss_scheme_vec[j].get_point_commitment(i))
-                    .collect::<Vec<GE>>();
+                    .collect::<Vec<P>>();
 
                 let mut xij_points_iter = xij_points_vec.iter();
                 let first = xij_points_iter.next().unwrap();
@@ -267,30 +378,34 @@ impl Keys {
                 let tail = xij_points_iter;
                 tail.fold(first.clone(), |acc, x| acc + x)
             })
-            .collect::<Vec<GE>>();
+            .collect::<Vec<P>>();
 
         xi_points_vec
     }
 
     pub fn update_commitments_to_xi(
-        comm: &GE,
-        vss_scheme: &VerifiableSS,
+        comm: &P,
+        vss_scheme: &VerifiableSS<P>,
         index: usize,
         s: &Vec<usize>,
-    ) -> GE {
+    ) -> P {
         let li = vss_scheme.map_share_to_new_params(index, s);
-        comm * &li
+        comm.clone() * &li
     }
 
+    /// `dlog_proofs_vec[i]` is checked against `ctx` for party i + 1.
     pub fn verify_dlog_proofs(
         params: &Parameters,
-        dlog_proofs_vec: &Vec<DLogProof>,
-        y_vec: &Vec<GE>,
+        dlog_proofs_vec: &Vec<DLogProof<P>>,
+        y_vec: &Vec<P>,
+        ctx: &ProofContext,
     ) -> Result<(), TssError> {
         assert_eq!(y_vec.len(), params.share_count);
         assert_eq!(dlog_proofs_vec.len(), params.share_count);
         let xi_dlog_verify = (0..y_vec.len())
-            .map(|i| DLogProof::verify(&dlog_proofs_vec[i]).is_ok())
+            .map(|i| {
+                DLogProof::<P>::verify(&dlog_proofs_vec[i], &ctx.for_party(i as u16 + 1)).is_ok()
+            })
             .all(|x| x == true);
 
         match xi_dlog_verify {
@@ -300,8 +415,8 @@ impl Keys {
     }
 }
 
-impl PartyPrivate {
-    pub fn set_private(key: Keys, shared_key: SharedKeys) -> PartyPrivate {
+impl<P: ECPoint> PartyPrivate<P> {
+    pub fn set_private(key: Keys<P>, shared_key: SharedKeys<P>) -> PartyPrivate<P> {
         let key_private = PartyPrivate {
             u_i: key.u_i,
             x_i: shared_key.x_i,
@@ -310,8 +425,8 @@ impl PartyPrivate {
         key_private
     }
 
-    pub fn y_i(&self) -> GE {
-        let g: GE = ECPoint::generator();
+    pub fn y_i(&self) -> P {
+        let g = P::generator();
         g * self.u_i.clone()
     }
 
@@ -319,9 +434,9 @@ impl PartyPrivate {
         Paillier::decrypt(&self.dk, &RawCiphertext::from(ciphertext))
     }
 
-    pub fn refresh_private_key(&self, factor: &FE, index: usize) -> Keys {
-        let u: FE = self.u_i.clone() + factor;
-        let y = &ECPoint::generator() * &u;
+    pub fn refresh_private_key(&self, factor: &P::Scalar, index: usize) -> Keys<P> {
+        let u: P::Scalar = self.u_i.clone() + factor;
+        let y = P::generator() * &u;
         let (ek, dk) = Paillier::keypair().keys();
 
         Keys {
@@ -333,7 +448,7 @@ impl PartyPrivate {
         }
     }
 
-    pub fn update_private_key(&self, factor_u_i: &FE, factor_x_i: &FE) -> Self {
+    pub fn update_private_key(&self, factor_u_i: &P::Scalar, factor_x_i: &P::Scalar) -> Self {
         PartyPrivate {
             u_i: self.u_i.clone() + factor_u_i,
             x_i: self.x_i.clone() + factor_x_i,
@@ -342,19 +457,19 @@ impl PartyPrivate {
     }
 }
 
-impl SignKeys {
+impl<P: ECPoint> SignKeys<P> {
     pub fn create(
-        private: &PartyPrivate,
-        vss_scheme: &VerifiableSS,
+        private: &PartyPrivate<P>,
+        vss_scheme: &VerifiableSS<P>,
         index: usize,
         s: &Vec<usize>,
-    ) -> SignKeys {
+    ) -> SignKeys<P> {
         let li = vss_scheme.map_share_to_new_params(index, s);
         let w_i = li * &private.x_i;
-        let g: GE = ECPoint::generator();
-        let g_w_i = &g * &w_i;
-        let gamma_i: FE = ECScalar::new_random();
-        let g_gamma_i = &g * &gamma_i;
+        let g = P::generator();
+        let g_w_i = g.clone() * &w_i;
+        let gamma_i: P::Scalar = ECScalar::new_random();
+        let g_gamma_i = g.clone() * &gamma_i;
         SignKeys {
             w_i,
             g_w_i,
@@ -364,9 +479,9 @@ impl SignKeys {
         }
     }
 
-    pub fn phase1_broadcast(&self) -> (SignBroadcastPhase1, SignDecommitPhase1) {
+    pub fn phase1_broadcast(&self) -> (SignBroadcastPhase1, SignDecommitPhase1<P>) {
         let blind_factor = BigInt::sample(SECURITY);
-        let g: GE = ECPoint::generator();
+        let g = P::generator();
         let g_gamma_i = g * &self.gamma_i;
         let com = HashCommitment::create_commitment_with_user_defined_randomness(
             &g_gamma_i.bytes_compressed_to_big_int(),
@@ -382,44 +497,44 @@ impl SignKeys {
         )
     }
 
-    pub fn phase2_delta_i(&self, alpha_vec: &Vec<FE>, beta_vec: &Vec<FE>) -> FE {
+    pub fn phase2_delta_i(&self, alpha_vec: &Vec<P::Scalar>, beta_vec: &Vec<P::Scalar>) -> P::Scalar {
         let vec_len = alpha_vec.len();
         assert_eq!(alpha_vec.len(), beta_vec.len());
         // assert_eq!(alpha_vec.len(), self.s.len() - 1);
-        let ki_gamma_i = self.k_i.mul(&self.gamma_i.get_element());
+        let ki_gamma_i = self.k_i.clone() * &self.gamma_i;
         let sum = (0..vec_len)
-            .map(|i| alpha_vec[i].add(&beta_vec[i].get_element()))
+            .map(|i| alpha_vec[i].clone() + &beta_vec[i])
             .fold(ki_gamma_i, |acc, x| acc + x);
         return sum;
     }
 
-    pub fn phase2_sigma_i(&self, miu_vec: &Vec<FE>, ni_vec: &Vec<FE>) -> FE {
+    pub fn phase2_sigma_i(&self, miu_vec: &Vec<P::Scalar>, ni_vec: &Vec<P::Scalar>) -> P::Scalar {
         let vec_len = miu_vec.len();
         assert_eq!(miu_vec.len(), ni_vec.len());
         //assert_eq!(miu_vec.len(), self.s.len() - 1);
-        let ki_w_i = self.k_i.mul(&self.w_i.get_element());
+        let ki_w_i = self.k_i.clone() * &self.w_i;
         let sum = (0..vec_len)
-            .map(|i| miu_vec[i].add(&ni_vec[i].get_element()))
+            .map(|i| miu_vec[i].clone() + &ni_vec[i])
             .fold(ki_w_i, |acc, x| acc + x);
         return sum;
     }
 
-    pub fn phase3_reconstruct_delta(delta_vec: &Vec<FE>) -> FE {
-        let sum = delta_vec.iter().fold(FE::zero(), |acc, x| acc + x);
+    pub fn phase3_reconstruct_delta(delta_vec: &Vec<P::Scalar>) -> P::Scalar {
+        let sum = delta_vec.iter().fold(P::Scalar::zero(), |acc, x| acc + x);
         sum.invert()
     }
 
     pub fn phase4(
-        delta_inv: &FE,
-        b_proof_vec: &Vec<&DLogProof>,
-        phase1_decommit_vec: Vec<SignDecommitPhase1>,
+        delta_inv: &P::Scalar,
+        b_proof_vec: &Vec<&DLogProof<P>>,
+        phase1_decommit_vec: Vec<SignDecommitPhase1<P>>,
         // blind_vec: &Vec<BigInt>,
         //  g_gamma_i_vec: &Vec<GE>,
         bc1_vec: &Vec<SignBroadcastPhase1>,
-    ) -> Result<GE, TssError> {
+    ) -> Result<P, TssError> {
         let test_b_vec_and_com = (0..b_proof_vec.len())
             .map(|i| {
-                b_proof_vec[i].pk.get_element() == phase1_decommit_vec[i].g_gamma_i.get_element()
+                b_proof_vec[i].pk == phase1_decommit_vec[i].g_gamma_i
                     && HashCommitment::create_commitment_with_user_defined_randomness(
                         &phase1_decommit_vec[i]
                             .g_gamma_i
@@ -446,19 +561,19 @@ impl SignKeys {
     }
 }
 
-impl LocalSignature {
+impl<P: ECPoint> LocalSignature<P> {
     pub fn phase5_local_sig(
-        k_i: &FE,
+        k_i: &P::Scalar,
         message: &BigInt,
-        R: &GE,
-        sigma_i: &FE,
-        pubkey: &GE,
-    ) -> LocalSignature {
-        let m_fe: FE = ECScalar::from(message);
-        let r: FE = ECScalar::from(&R.x_coor().unwrap().mod_floor(&FE::q()));
+        R: &P,
+        sigma_i: &P::Scalar,
+        pubkey: &P,
+    ) -> LocalSignature<P> {
+        let m_fe: P::Scalar = ECScalar::from(message);
+        let r: P::Scalar = ECScalar::from(&R.x_coor().unwrap().mod_floor(&P::Scalar::q()));
         let s_i = m_fe * k_i + r * sigma_i;
-        let l_i: FE = ECScalar::new_random();
-        let rho_i: FE = ECScalar::new_random();
+        let l_i: P::Scalar = ECScalar::new_random();
+        let rho_i: P::Scalar = ECScalar::new_random();
         LocalSignature {
             l_i,
             rho_i,
@@ -471,13 +586,14 @@ impl LocalSignature {
 
     pub fn phase5a_broadcast_5b_zkproof(
         &self,
-    ) -> (Phase5Com1, Phase5ADecom1, HomoELGamalProof, DLogProof) {
+        ctx: &ProofContext,
+    ) -> (Phase5Com1, Phase5ADecom1<P>, HomoELGamalProof<P>, DLogProof<P>) {
         let blind_factor = BigInt::sample(SECURITY);
-        let g: GE = ECPoint::generator();
-        let A_i = &g * &self.rho_i;
-        let l_i_rho_i = self.l_i.mul(&self.rho_i.get_element());
-        let B_i = &g * &l_i_rho_i;
-        let V_i = &self.R * &self.s_i + &g * &self.l_i;
+        let g = P::generator();
+        let A_i = g.clone() * &self.rho_i;
+        let l_i_rho_i = self.l_i.clone() * &self.rho_i;
+        let B_i = g.clone() * &l_i_rho_i;
+        let V_i = self.R.clone() * &self.s_i + g.clone() * &self.l_i;
         let input_hash = HSha256::create_hash_from_ge(&[&V_i, &A_i, &B_i]).to_big_int();
         let com = HashCommitment::create_commitment_with_user_defined_randomness(
             &input_hash,
@@ -494,8 +610,8 @@ impl LocalSignature {
             D: V_i.clone(),
             E: B_i.clone(),
         };
-        let dlog_proof_rho = DLogProof::prove(&self.rho_i);
-        let proof = HomoELGamalProof::prove(&witness, &delta);
+        let dlog_proof_rho = DLogProof::<P>::prove(&self.rho_i, ctx);
+        let proof = HomoELGamalProof::<P>::prove(&witness, &delta, ctx);
 
         (
             Phase5Com1 { com },
@@ -510,18 +626,22 @@ impl LocalSignature {
         )
     }
 
+    /// `ctx_vec[i]` is the context the proofs at position i were made in.
+    #[allow(clippy::too_many_arguments)]
     pub fn phase5c(
         &self,
-        decom_vec: &Vec<Phase5ADecom1>,
+        decom_vec: &Vec<Phase5ADecom1<P>>,
         com_vec: &Vec<Phase5Com1>,
-        elgamal_proofs: &Vec<HomoELGamalProof>,
-        dlog_proofs_rho: &[DLogProof],
-        v_i: &GE,
-        R: &GE,
-    ) -> Result<(Phase5Com2, Phase5DDecom2), TssError> {
+        elgamal_proofs: &Vec<HomoELGamalProof<P>>,
+        dlog_proofs_rho: &[DLogProof<P>],
+        v_i: &P,
+        R: &P,
+        ctx_vec: &[ProofContext],
+    ) -> Result<(Phase5Com2, Phase5DDecom2<P>), TssError> {
         assert_eq!(decom_vec.len(), com_vec.len());
+        assert_eq!(ctx_vec.len(), com_vec.len());
 
-        let g: GE = ECPoint::generator();
+        let g = P::generator();
         let test_com_elgamal = (0..com_vec.len())
             .map(|i| {
                 let delta = HomoElGamalStatement {
@@ -542,17 +662,17 @@ impl LocalSignature {
                     &input_hash,
                     &decom_vec[i].blind_factor,
                 ) == com_vec[i].com
-                    && elgamal_proofs[i].verify(&delta).is_ok()
-                    && DLogProof::verify(&dlog_proofs_rho[i]).is_ok()
+                    && elgamal_proofs[i].verify(&delta, &ctx_vec[i]).is_ok()
+                    && DLogProof::<P>::verify(&dlog_proofs_rho[i], &ctx_vec[i]).is_ok()
             })
             .all(|x| x == true);
 
         let v_vec = (0..com_vec.len())
             .map(|i| &decom_vec[i].V_i)
-            .collect::<Vec<&GE>>();
+            .collect::<Vec<&P>>();
         let a_vec = (0..com_vec.len())
             .map(|i| &decom_vec[i].A_i)
-            .collect::<Vec<&GE>>();
+            .collect::<Vec<&P>>();
 
         let v = v_vec.iter().fold(v_i.clone(), |acc, x| acc + *x);
         // V = -mG -ry - sum (vi)
@@ -561,14 +681,14 @@ impl LocalSignature {
         let tail = a_i_iter;
         let a = tail.fold((*head).clone(), |acc, x| acc.add_point(&(*x).get_element()));
 
-        let r: FE = ECScalar::from(&self.R.x_coor().unwrap().mod_floor(&FE::q()));
-        let yr = &self.y * &r;
-        let g: GE = ECPoint::generator();
-        let m_fe: FE = ECScalar::from(&self.m);
-        let gm = &g * &m_fe;
+        let r: P::Scalar = ECScalar::from(&self.R.x_coor().unwrap().mod_floor(&P::Scalar::q()));
+        let yr = self.y.clone() * &r;
+        let g = P::generator();
+        let m_fe: P::Scalar = ECScalar::from(&self.m);
+        let gm = g.clone() * &m_fe;
         let v = v.sub_point(&gm.get_element()).sub_point(&yr.get_element());
-        let u_i = &v * &self.rho_i;
-        let t_i = &a * &self.l_i;
+        let u_i = v.clone() * &self.rho_i;
+        let t_i = a.clone() * &self.l_i;
         let input_hash = HSha256::create_hash_from_ge(&[&u_i, &t_i]).to_big_int();
         let blind_factor = BigInt::sample(SECURITY);
         let com = HashCommitment::create_commitment_with_user_defined_randomness(
@@ -593,10 +713,10 @@ impl LocalSignature {
 
     pub fn phase5d(
         &self,
-        decom_vec2: &Vec<Phase5DDecom2>,
+        decom_vec2: &Vec<Phase5DDecom2<P>>,
         com_vec2: &Vec<Phase5Com2>,
-        decom_vec1: &Vec<Phase5ADecom1>,
-    ) -> Result<FE, TssError> {
+        decom_vec1: &Vec<Phase5ADecom1<P>>,
+    ) -> Result<P::Scalar, TssError> {
         assert_eq!(decom_vec2.len(), decom_vec1.len());
         assert_eq!(decom_vec2.len(), com_vec2.len());
 
@@ -614,15 +734,15 @@ impl LocalSignature {
 
         let t_vec = (0..com_vec2.len())
             .map(|i| &decom_vec2[i].t_i)
-            .collect::<Vec<&GE>>();
+            .collect::<Vec<&P>>();
         let u_vec = (0..com_vec2.len())
             .map(|i| &decom_vec2[i].u_i)
-            .collect::<Vec<&GE>>();
+            .collect::<Vec<&P>>();
         let b_vec = (0..decom_vec1.len())
             .map(|i| &decom_vec1[i].B_i)
-            .collect::<Vec<&GE>>();
+            .collect::<Vec<&P>>();
 
-        let g: GE = ECPoint::generator();
+        let g = P::generator();
         let biased_sum_tb = t_vec
             .iter()
             .zip(b_vec)
@@ -641,9 +761,9 @@ impl LocalSignature {
             false => Err(InvalidCom),
         }
     }
-    pub fn output_signature(&self, s_vec: &Vec<FE>) -> Result<Signature, TssError> {
+    pub fn output_signature(&self, s_vec: &Vec<P::Scalar>) -> Result<Signature<P>, TssError> {
         let mut s = s_vec.iter().fold(self.s_i.clone(), |acc, x| acc + x);
-        let r: FE = ECScalar::from(&self.R.x_coor().unwrap().mod_floor(&FE::q()));
+        let r: P::Scalar = ECScalar::from(&self.R.x_coor().unwrap().mod_floor(&P::Scalar::q()));
 
         /*
          Calculate recovery id - it is not possible to compute the public key out of the signature
@@ -655,11 +775,11 @@ impl LocalSignature {
             .R
             .y_coor()
             .ok_or(TssError::InvalidSig)?
-            .mod_floor(&FE::q());
+            .mod_floor(&P::Scalar::q());
         let is_ry_odd = ry.test_bit(0);
         let mut recid = if is_ry_odd { 1 } else { 0 };
         let s_bn = s.clone().to_big_int();
-        let s_tag_bn = &FE::q() - &s_bn;
+        let s_tag_bn = &P::Scalar::q() - &s_bn;
         if s_bn > s_tag_bn {
             s = ECScalar::from(&s_tag_bn);
             recid ^= 1;
@@ -674,17 +794,17 @@ impl LocalSignature {
     }
 }
 
-pub fn verify(sig: &Signature, y: &GE, message: &BigInt) -> Result<(), TssError> {
+pub fn verify<P: ECPoint>(sig: &Signature<P>, y: &P, message: &BigInt) -> Result<(), TssError> {
     let b = sig.s.invert();
-    let a: FE = ECScalar::from(message);
+    let a: P::Scalar = ECScalar::from(message);
     let u1 = a * &b;
     let u2 = sig.r.clone() * &b;
 
-    let g: GE = ECPoint::generator();
-    let gu1 = &g * &u1;
-    let yu2 = y * &u2;
+    let g = P::generator();
+    let gu1 = g.clone() * &u1;
+    let yu2 = y.clone() * &u2;
     // can be faster using shamir trick
-    if sig.r.clone() == ECScalar::from(&(gu1 + yu2).x_coor().unwrap().mod_floor(&FE::q())) {
+    if sig.r.clone() == ECScalar::from(&(gu1 + yu2).x_coor().unwrap().mod_floor(&P::Scalar::q())) {
         Ok(())
     } else {
         Err(InvalidSig)
@@ -11,11 +11,10 @@
 
 use crate::curv::arithmetic::num_bigint::BigInt;
 use crate::curv::arithmetic::traits::*;
-use crate::curv::elliptic::curves::secp256_k1::Secp256k1Scalar as Scalar;
 use crate::curv::elliptic::curves::traits::ECScalar;
 use sha2::Sha256;
 
-use crate::curv::cryptographic_primitives::hashing::ext::DigestExt;
+use crate::curv::cryptographic_primitives::hashing::ext::{DigestExt, ProofContext};
 use crate::sha2::Digest;
 
 use crate::num_integer::Integer;
@@ -109,12 +108,14 @@ pub struct AliceProof {
 }
 
 impl AliceProof {
-    /// verify Alice's proof using the proof and public keys
-    pub fn verify(
+    /// verify Alice's proof using the proof and public keys, for a plaintext in the
+    /// scalar field `S`. `ctx` is the transcript context of Alice.
+    pub fn verify<S: ECScalar>(
         &self,
         cipher: &BigInt,
         alice_ek: &EncryptionKey,
         dlog_statement: &DLogStatement,
+        ctx: &ProofContext,
     ) -> bool {
         let N = &alice_ek.n;
         let NN = &alice_ek.nn;
@@ -123,7 +124,7 @@ impl AliceProof {
         let h2 = &dlog_statement.ni;
         let Gen = alice_ek.n.borrow() + 1u32;
 
-        if self.s1 > Scalar::group_order().pow(3u32) {
+        if self.s1 > S::group_order().pow(3u32) {
             return false;
         }
 
@@ -140,6 +141,7 @@ impl AliceProof {
         let u = (gs1 * BigInt::mod_pow(&self.s, N, NN) * cipher_e_inv) % NN;
 
         let e = Sha256::new()
+            .chain_context(ctx)
             .chain_bigint(N)
             .chain_bigint(&Gen)
             .chain_bigint(cipher)
@@ -155,18 +157,20 @@ impl AliceProof {
     }
     /// Create the proof using Alice's Paillier private keys and public ZKP setup.
     /// Requires randomness used for encrypting Alice's secret a.
-    /// It is assumed that secp256k1 curve is used.
-    pub fn generate(
+    /// `S` is the scalar field of the curve the MtA shares live on.
+    pub fn generate<S: ECScalar>(
         a: &BigInt,
         cipher: &BigInt,
         alice_ek: &EncryptionKey,
         dlog_statement: &DLogStatement,
         r: &BigInt,
+        ctx: &ProofContext,
     ) -> Self {
-        let round1 = AliceZkpRound1::from(alice_ek, dlog_statement, a, Scalar::group_order());
+        let round1 = AliceZkpRound1::from(alice_ek, dlog_statement, a, S::group_order());
 
         let Gen = alice_ek.n.borrow() + 1u32;
         let e = Sha256::new()
+            .chain_context(ctx)
             .chain_bigint(&alice_ek.n)
             .chain_bigint(&Gen)
             .chain_bigint(cipher)
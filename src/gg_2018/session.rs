@@ -0,0 +1,1101 @@
+#![allow(non_snake_case)]
+
+// Round-by-round keygen and signing state machines for GG18.
+//
+// A session only ever moves forward: `advance` consumes the current state
+// together with the messages received from the other parties for that round,
+// and returns the messages to deliver for the next round plus the next state.
+// Nothing here touches the network, the caller moves the messages around.
+// Which fields exist is decided by the state variant, so a round can no longer
+// run against data an earlier round never produced.
+
+use crate::common::{aes_decrypt, aes_encrypt, hkdf_sha256, AES_KEY_BYTES_LEN};
+use crate::curv::arithmetic::num_bigint::BigInt;
+use crate::curv::arithmetic::traits::Converter;
+use crate::curv::cryptographic_primitives::hashing::ext::ProofContext;
+use crate::curv::cryptographic_primitives::proofs::sigma_correct_homomorphic_elgamal_enc::HomoELGamalProof;
+use crate::curv::cryptographic_primitives::proofs::sigma_dlog::DLogProof;
+use crate::curv::cryptographic_primitives::secret_sharing::feldman_vss::VerifiableSS;
+use crate::curv::elliptic::curves::secp256_k1::GE;
+use crate::curv::elliptic::curves::traits::{ECPoint, ECScalar};
+use crate::errors::{Result, TssError};
+use crate::gg_2018::messages::{
+    party_index, signer_indices, sort_incoming, KeygenMessage, Outgoing, SignMessage,
+};
+use crate::gg_2018::mta::{MessageA, MessageB};
+use crate::gg_2018::party_i::*;
+use crate::keystore::KeyStore;
+use crate::paillier::EncryptionKey;
+
+// unwraps the payloads of messages whose round sort_incoming already checked
+macro_rules! payloads {
+    ($incoming:expr, $variant:path) => {
+        $incoming
+            .into_iter()
+            .map(|m| match m {
+                $variant(x) => x,
+                _ => unreachable!(),
+            })
+            .collect::<Vec<_>>()
+    };
+}
+pub(crate) use payloads;
+
+// labels of the proof contexts, by the round the proofs are sent in
+const KEYGEN_ROUND1: &str = "gg18/keygen/round1";
+const KEYGEN_ROUND5: &str = "gg18/keygen/round5";
+const SIGN_ROUND1: &str = "gg18/sign/round1";
+const SIGN_ROUND2: &str = "gg18/sign/round2";
+const SIGN_ROUND6: &str = "gg18/sign/round6";
+
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub enum KeygenState<P: ECPoint = GE> {
+    New,
+    Round1 {
+        party_keys: Keys<P>,
+        decom_i: KeyGenDecommitMessage1<P>,
+        bc_i: KeyGenBroadcastMessage1,
+    },
+    Round2 {
+        party_keys: Keys<P>,
+        decom_i: KeyGenDecommitMessage1<P>,
+        bc1_vec: Vec<KeyGenBroadcastMessage1>,
+    },
+    Round3 {
+        party_keys: Keys<P>,
+        bc1_vec: Vec<KeyGenBroadcastMessage1>,
+        y_sum: P,
+        vss_scheme: VerifiableSS<P>,
+        secret_shares: Vec<P::Scalar>,
+        // raw ECDH secrets with the other parties, see share_channel
+        enc_keys: Vec<Vec<u8>>,
+        point_vec: Vec<P>,
+    },
+    Round4 {
+        party_keys: Keys<P>,
+        bc1_vec: Vec<KeyGenBroadcastMessage1>,
+        y_sum: P,
+        vss_scheme: VerifiableSS<P>,
+        point_vec: Vec<P>,
+        party_shares: Vec<P::Scalar>,
+    },
+    Round5 {
+        party_keys: Keys<P>,
+        bc1_vec: Vec<KeyGenBroadcastMessage1>,
+        y_sum: P,
+        point_vec: Vec<P>,
+        shared_keys: SharedKeys<P>,
+        dlog_proof: DLogProof<P>,
+        vss_scheme_vec: Vec<VerifiableSS<P>>,
+    },
+    Finished {
+        key_store: KeyStore<P>,
+    },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct KeygenSession<P: ECPoint = GE> {
+    pub params: Parameters,
+    pub party_num_int: u16,
+    // uuid shared by all parties of this keygen, bound into the round3 channels
+    // and the proofs
+    pub session_id: String,
+    pub state: KeygenState<P>,
+}
+
+impl<P: ECPoint> KeygenSession<P> {
+    pub fn new(params: Parameters, party_num_int: u16, session_id: String) -> Self {
+        KeygenSession {
+            params,
+            party_num_int,
+            session_id,
+            state: KeygenState::New,
+        }
+    }
+
+    /// The round whose messages are exchanged next; 6 once keygen is finished.
+    pub fn round(&self) -> u16 {
+        match self.state {
+            KeygenState::New | KeygenState::Round1 { .. } => 1,
+            KeygenState::Round2 { .. } => 2,
+            KeygenState::Round3 { .. } => 3,
+            KeygenState::Round4 { .. } => 4,
+            KeygenState::Round5 { .. } => 5,
+            KeygenState::Finished { .. } => 6,
+        }
+    }
+
+    pub fn check_round(&self, round: u16) -> Result<()> {
+        match self.round() == round {
+            true => Ok(()),
+            false => Err(TssError::RoundOutOfOrder {
+                expected: self.round(),
+                got: round,
+            }),
+        }
+    }
+
+    pub fn key_store(&self) -> Option<&KeyStore<P>> {
+        match &self.state {
+            KeygenState::Finished { key_store } => Some(key_store),
+            _ => None,
+        }
+    }
+
+    /// Feeds the messages of the other parties for the current round, tagged with
+    /// the sending party. `New` takes no messages and only produces round1.
+    pub fn advance(
+        self,
+        incoming: Vec<(u16, KeygenMessage<P>)>,
+    ) -> Result<(Outgoing<KeygenMessage<P>>, KeygenSession<P>)> {
+        let round = self.round();
+        let KeygenSession {
+            params,
+            party_num_int,
+            session_id,
+            state,
+        } = self;
+        let n = params.share_count as u16;
+        let me = party_index(party_num_int, n)?;
+        let incoming = match state {
+            KeygenState::New => Vec::new(),
+            _ => sort_incoming(incoming, round, n, party_num_int)?,
+        };
+
+        let (outgoing, state) = match state {
+            KeygenState::New => {
+                let party_keys = Keys::create_with_params(party_num_int as usize, &params);
+                let ctx = ProofContext::new(&session_id, party_num_int, KEYGEN_ROUND1);
+                let (bc_i, decom_i) = party_keys.phase1_broadcast_phase3_proof_of_correct_key(&ctx);
+                (
+                    Outgoing::broadcast(1, KeygenMessage::Round1(bc_i.clone())),
+                    KeygenState::Round1 {
+                        party_keys,
+                        decom_i,
+                        bc_i,
+                    },
+                )
+            }
+            KeygenState::Round1 {
+                party_keys,
+                decom_i,
+                bc_i,
+            } => {
+                let mut bc1_vec = payloads!(incoming, KeygenMessage::Round1);
+                bc1_vec.insert(me, bc_i);
+
+                // send ephemeral public keys and check commitments correctness
+                (
+                    Outgoing::broadcast(2, KeygenMessage::Round2(decom_i.clone())),
+                    KeygenState::Round2 {
+                        party_keys,
+                        decom_i,
+                        bc1_vec,
+                    },
+                )
+            }
+            KeygenState::Round2 {
+                party_keys,
+                decom_i,
+                bc1_vec,
+            } => {
+                let decom_vec_j = payloads!(incoming, KeygenMessage::Round2);
+                let mut j = 0;
+                let mut point_vec: Vec<P> = Vec::new();
+                let mut decom_vec: Vec<KeyGenDecommitMessage1<P>> = Vec::new();
+                let mut enc_keys: Vec<Vec<u8>> = Vec::new();
+                for i in 1..=n {
+                    if i == party_num_int {
+                        point_vec.push(decom_i.y_i.clone());
+                        decom_vec.push(decom_i.clone());
+                    } else {
+                        let decom_j = &decom_vec_j[j];
+                        point_vec.push(decom_j.y_i.clone());
+                        decom_vec.push(decom_j.clone());
+                        let key_bn: BigInt = (decom_j.y_i.clone() * party_keys.u_i.clone())
+                            .x_coor()
+                            .unwrap();
+                        let key_bytes = BigInt::to_vec(&key_bn);
+                        let mut template: Vec<u8> = vec![0u8; AES_KEY_BYTES_LEN - key_bytes.len()];
+                        template.extend_from_slice(&key_bytes[..]);
+                        enc_keys.push(template);
+                        j += 1;
+                    }
+                }
+
+                let (head, tail) = point_vec.split_at(1);
+                let y_sum = tail.iter().fold(head[0].clone(), |acc, x| acc + x);
+                P::from_bytes(&y_sum.to_bytes(false)[1..])?;
+
+                let ctx = ProofContext::new(&session_id, party_num_int, KEYGEN_ROUND1);
+                let (vss_scheme, secret_shares, _index) = party_keys
+                    .phase1_verify_com_phase3_verify_correct_key_phase2_distribute(
+                        &params, &decom_vec, &bc1_vec, &ctx,
+                    )?;
+
+                let mut j = 0;
+                let mut p2p = Vec::new();
+                for (k, i) in (1..=n).enumerate() {
+                    if i != party_num_int {
+                        // prepare encrypted ss for party i:
+                        let plaintext = BigInt::to_vec(&secret_shares[k].to_big_int());
+                        let (key, aad) = share_channel(&enc_keys[j], &session_id, party_num_int, i);
+                        let aead_pack_i = aes_encrypt(&key, &plaintext, &aad)?;
+                        p2p.push((i, KeygenMessage::Round3(aead_pack_i)));
+                        j += 1;
+                    }
+                }
+
+                (
+                    Outgoing::p2p(3, p2p),
+                    KeygenState::Round3 {
+                        party_keys,
+                        bc1_vec,
+                        y_sum,
+                        vss_scheme,
+                        secret_shares,
+                        enc_keys,
+                        point_vec,
+                    },
+                )
+            }
+            KeygenState::Round3 {
+                party_keys,
+                bc1_vec,
+                y_sum,
+                vss_scheme,
+                secret_shares,
+                enc_keys,
+                point_vec,
+            } => {
+                let aead_pack_vec = payloads!(incoming, KeygenMessage::Round3);
+                let mut j = 0;
+                let mut party_shares: Vec<P::Scalar> = Vec::new();
+                for i in 1..=n {
+                    if i == party_num_int {
+                        party_shares.push(secret_shares[(i - 1) as usize].clone());
+                    } else {
+                        let (key, aad) = share_channel(&enc_keys[j], &session_id, i, party_num_int);
+                        let out = aes_decrypt(&key, aead_pack_vec[j].clone(), &aad)?;
+                        let out_bn = BigInt::from_bytes_be(&out[..]);
+                        party_shares.push(ECScalar::from(&out_bn));
+                        j += 1;
+                    }
+                }
+
+                (
+                    Outgoing::broadcast(4, KeygenMessage::Round4(vss_scheme.clone())),
+                    KeygenState::Round4 {
+                        party_keys,
+                        bc1_vec,
+                        y_sum,
+                        vss_scheme,
+                        point_vec,
+                        party_shares,
+                    },
+                )
+            }
+            KeygenState::Round4 {
+                party_keys,
+                bc1_vec,
+                y_sum,
+                vss_scheme,
+                point_vec,
+                party_shares,
+            } => {
+                let mut vss_scheme_vec: Vec<VerifiableSS<P>> =
+                    payloads!(incoming, KeygenMessage::Round4);
+                vss_scheme_vec.insert(me, vss_scheme);
+
+                let (shared_keys, dlog_proof) = party_keys
+                    .phase2_verify_vss_construct_keypair_phase3_pok_dlog(
+                        &params,
+                        &point_vec,
+                        &party_shares,
+                        &vss_scheme_vec,
+                        &(party_num_int as usize), // FIXME
+                        &ProofContext::new(&session_id, party_num_int, KEYGEN_ROUND5),
+                    )?;
+
+                (
+                    Outgoing::broadcast(5, KeygenMessage::Round5(dlog_proof.clone())),
+                    KeygenState::Round5 {
+                        party_keys,
+                        bc1_vec,
+                        y_sum,
+                        point_vec,
+                        shared_keys,
+                        dlog_proof,
+                        vss_scheme_vec,
+                    },
+                )
+            }
+            KeygenState::Round5 {
+                party_keys,
+                bc1_vec,
+                y_sum,
+                point_vec,
+                shared_keys,
+                dlog_proof,
+                vss_scheme_vec,
+            } => {
+                let mut dlog_proof_vec: Vec<DLogProof<P>> =
+                    payloads!(incoming, KeygenMessage::Round5);
+                dlog_proof_vec.insert(me, dlog_proof);
+                let ctx = ProofContext::new(&session_id, party_num_int, KEYGEN_ROUND5);
+                Keys::verify_dlog_proofs(&params, &dlog_proof_vec, &point_vec, &ctx)?;
+
+                let paillier_key_vec = bc1_vec
+                    .into_iter()
+                    .map(|bc1| bc1.e)
+                    .collect::<Vec<EncryptionKey>>();
+
+                (
+                    Outgoing::none(6),
+                    KeygenState::Finished {
+                        key_store: (
+                            party_keys,
+                            shared_keys,
+                            party_num_int,
+                            vss_scheme_vec,
+                            paillier_key_vec,
+                            y_sum,
+                        ),
+                    },
+                )
+            }
+            KeygenState::Finished { .. } => {
+                return Err(TssError::RoundOutOfOrder {
+                    expected: 6,
+                    got: 6,
+                })
+            }
+        };
+
+        Ok((
+            outgoing,
+            KeygenSession {
+                params,
+                party_num_int,
+                session_id,
+                state,
+            },
+        ))
+    }
+}
+
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub enum SignState<P: ECPoint = GE> {
+    New,
+    Round0,
+    Round1 {
+        signers_vec: Vec<usize>,
+        xi_com_vec: Vec<P>,
+        sign_keys: SignKeys<P>,
+        com: SignBroadcastPhase1,
+        decommit: SignDecommitPhase1<P>,
+    },
+    Round2 {
+        signers_vec: Vec<usize>,
+        xi_com_vec: Vec<P>,
+        sign_keys: SignKeys<P>,
+        decommit: SignDecommitPhase1<P>,
+        bc1_vec: Vec<SignBroadcastPhase1>,
+        beta_vec: Vec<P::Scalar>,
+        ni_vec: Vec<P::Scalar>,
+    },
+    Round3 {
+        sign_keys: SignKeys<P>,
+        decommit: SignDecommitPhase1<P>,
+        bc1_vec: Vec<SignBroadcastPhase1>,
+        m_b_gamma_rec_vec: Vec<MessageB<P>>,
+        delta_i: P::Scalar,
+        sigma: P::Scalar,
+    },
+    Round4 {
+        sign_keys: SignKeys<P>,
+        decommit: SignDecommitPhase1<P>,
+        bc1_vec: Vec<SignBroadcastPhase1>,
+        m_b_gamma_rec_vec: Vec<MessageB<P>>,
+        delta_inv: P::Scalar,
+        sigma: P::Scalar,
+    },
+    Round5 {
+        local_sig: LocalSignature<P>,
+        r: P,
+        phase_5a_decom: Phase5ADecom1<P>,
+        helgamal_proof: HomoELGamalProof<P>,
+        dlog_proof_rho: DLogProof<P>,
+    },
+    Round6 {
+        local_sig: LocalSignature<P>,
+        r: P,
+        phase_5a_decom: Phase5ADecom1<P>,
+        commit5a_vec: Vec<Phase5Com1>,
+    },
+    Round7 {
+        local_sig: LocalSignature<P>,
+        phase5_com2: Phase5Com2,
+        phase_5d_decom2: Phase5DDecom2<P>,
+        phase_5a_decomm_vec_includes_i: Vec<Phase5ADecom1<P>>,
+    },
+    Round8 {
+        local_sig: LocalSignature<P>,
+        phase_5d_decom2: Phase5DDecom2<P>,
+        commit5c_vec: Vec<Phase5Com2>,
+        phase_5a_decomm_vec_includes_i: Vec<Phase5ADecom1<P>>,
+    },
+    Round9 {
+        local_sig: LocalSignature<P>,
+    },
+    Finished {
+        signature: Signature<P>,
+    },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct SignSession<P: ECPoint = GE> {
+    pub party_keys: Keys<P>,
+    pub shared_keys: SharedKeys<P>,
+    pub party_id: u16,
+    pub vss_scheme_vec: Vec<VerifiableSS<P>>,
+    pub paillier_key_vector: Vec<EncryptionKey>,
+    pub y_sum: P,
+    pub threshould: u16,
+    pub party_num_int: u16,
+    // uuid shared by all signers, bound into the proofs
+    pub session_id: String,
+    pub message: Vec<u8>,
+    pub state: SignState<P>,
+}
+
+impl<P: ECPoint> SignSession<P> {
+    pub fn new(
+        key_store: KeyStore<P>,
+        threshould: u16,
+        party_num_int: u16,
+        session_id: String,
+        message: Vec<u8>,
+    ) -> Self {
+        let (party_keys, shared_keys, party_id, vss_scheme_vec, paillier_key_vector, y_sum) =
+            key_store;
+        SignSession {
+            party_keys,
+            shared_keys,
+            party_id,
+            vss_scheme_vec,
+            paillier_key_vector,
+            y_sum,
+            threshould,
+            party_num_int,
+            session_id,
+            message,
+            state: SignState::New,
+        }
+    }
+
+    /// The round whose messages are exchanged next; 10 once signing is finished.
+    pub fn round(&self) -> u16 {
+        match self.state {
+            SignState::New | SignState::Round0 => 0,
+            SignState::Round1 { .. } => 1,
+            SignState::Round2 { .. } => 2,
+            SignState::Round3 { .. } => 3,
+            SignState::Round4 { .. } => 4,
+            SignState::Round5 { .. } => 5,
+            SignState::Round6 { .. } => 6,
+            SignState::Round7 { .. } => 7,
+            SignState::Round8 { .. } => 8,
+            SignState::Round9 { .. } => 9,
+            SignState::Finished { .. } => 10,
+        }
+    }
+
+    pub fn check_round(&self, round: u16) -> Result<()> {
+        match self.round() == round {
+            true => Ok(()),
+            false => Err(TssError::RoundOutOfOrder {
+                expected: self.round(),
+                got: round,
+            }),
+        }
+    }
+
+    pub fn signature(&self) -> Option<&Signature<P>> {
+        match &self.state {
+            SignState::Finished { signature } => Some(signature),
+            _ => None,
+        }
+    }
+
+    /// Feeds the messages of the other signers for the current round, tagged with
+    /// the sending party. `New` takes no messages and only produces round0.
+    pub fn advance(
+        mut self,
+        incoming: Vec<(u16, SignMessage<P>)>,
+    ) -> Result<(Outgoing<SignMessage<P>>, SignSession<P>)> {
+        let t = self.threshould;
+        let me = party_index(self.party_num_int, t + 1)?;
+        let round = self.round();
+        let state = std::mem::replace(&mut self.state, SignState::New);
+        let incoming = match state {
+            SignState::New => Vec::new(),
+            _ => sort_incoming(incoming, round, t + 1, self.party_num_int)?,
+        };
+
+        let (outgoing, state) = match state {
+            SignState::New => (
+                Outgoing::broadcast(0, SignMessage::Round0(self.party_id)),
+                SignState::Round0,
+            ),
+            SignState::Round0 => {
+                // round 0: collect signers IDs
+                let signers_vec = signer_indices(
+                    payloads!(incoming, SignMessage::Round0),
+                    self.party_id,
+                    self.party_num_int,
+                    t + 1,
+                    self.paillier_key_vector
+                        .len()
+                        .min(self.vss_scheme_vec.len()),
+                )?;
+
+                let private =
+                    PartyPrivate::set_private(self.party_keys.clone(), self.shared_keys.clone());
+                let sign_keys = SignKeys::create(
+                    &private,
+                    &self.vss_scheme_vec[signers_vec[me]],
+                    signers_vec[me],
+                    &signers_vec,
+                );
+                let xi_com_vec = Keys::get_commitments_to_xi(&self.vss_scheme_vec);
+
+                let (com, decommit) = sign_keys.phase1_broadcast();
+                let ctx = ProofContext::new(&self.session_id, self.party_num_int, SIGN_ROUND1);
+                let (m_a_k, _) = MessageA::a(&sign_keys.k_i, &self.party_keys.ek, &[], &ctx);
+                (
+                    Outgoing::broadcast(1, SignMessage::Round1((com.clone(), m_a_k))),
+                    SignState::Round1 {
+                        signers_vec,
+                        xi_com_vec,
+                        sign_keys,
+                        com,
+                        decommit,
+                    },
+                )
+            }
+            SignState::Round1 {
+                signers_vec,
+                xi_com_vec,
+                sign_keys,
+                com,
+                decommit,
+            } => {
+                let round1_vec = payloads!(incoming, SignMessage::Round1);
+                let mut j = 0;
+                let mut bc1_vec: Vec<SignBroadcastPhase1> = Vec::new();
+                let mut m_a_vec: Vec<MessageA> = Vec::new();
+                for i in 1..t + 2 {
+                    if i == self.party_num_int {
+                        bc1_vec.push(com.clone());
+                    } else {
+                        let (bc1_j, m_a_party_j) = round1_vec[j].clone();
+                        bc1_vec.push(bc1_j);
+                        m_a_vec.push(m_a_party_j);
+                        j += 1;
+                    }
+                }
+
+                let mut p2p = Vec::new();
+                let mut beta_vec: Vec<P::Scalar> = Vec::new();
+                let mut ni_vec: Vec<P::Scalar> = Vec::new();
+                let alice_ctx =
+                    ProofContext::new(&self.session_id, self.party_num_int, SIGN_ROUND1);
+                let ctx = ProofContext::new(&self.session_id, self.party_num_int, SIGN_ROUND2);
+                let mut j = 0;
+                for i in 1..t + 2 {
+                    if i != self.party_num_int {
+                        let ek_j = &self.paillier_key_vector[signers_vec[usize::from(i - 1)]];
+                        let alice_ctx = alice_ctx.for_party(i);
+                        let (m_b_gamma, beta_gamma, _, _) = MessageB::b(
+                            &sign_keys.gamma_i,
+                            ek_j,
+                            m_a_vec[j].clone(),
+                            &[],
+                            &alice_ctx,
+                            &ctx,
+                        )?;
+                        let (m_b_w, beta_wi, _, _) = MessageB::b(
+                            &sign_keys.w_i,
+                            ek_j,
+                            m_a_vec[j].clone(),
+                            &[],
+                            &alice_ctx,
+                            &ctx,
+                        )?;
+                        p2p.push((i, SignMessage::Round2((m_b_gamma, m_b_w))));
+                        beta_vec.push(beta_gamma);
+                        ni_vec.push(beta_wi);
+                        j += 1;
+                    }
+                }
+
+                (
+                    Outgoing::p2p(2, p2p),
+                    SignState::Round2 {
+                        signers_vec,
+                        xi_com_vec,
+                        sign_keys,
+                        decommit,
+                        bc1_vec,
+                        beta_vec,
+                        ni_vec,
+                    },
+                )
+            }
+            SignState::Round2 {
+                signers_vec,
+                xi_com_vec,
+                sign_keys,
+                decommit,
+                bc1_vec,
+                beta_vec,
+                ni_vec,
+            } => {
+                let mut m_b_gamma_rec_vec: Vec<MessageB<P>> = Vec::new();
+                let mut m_b_w_rec_vec: Vec<MessageB<P>> = Vec::new();
+                for (m_b_gamma_i, m_b_w_i) in payloads!(incoming, SignMessage::Round2) {
+                    m_b_gamma_rec_vec.push(m_b_gamma_i);
+                    m_b_w_rec_vec.push(m_b_w_i);
+                }
+
+                let mut alpha_vec: Vec<P::Scalar> = Vec::new();
+                let mut miu_vec: Vec<P::Scalar> = Vec::new();
+                let ctx = ProofContext::new(&self.session_id, self.party_num_int, SIGN_ROUND2);
+                let mut j = 0;
+                for i in 1..t + 2 {
+                    if i != self.party_num_int {
+                        let ctx_j = ctx.for_party(i);
+                        let alpha_ij_gamma = m_b_gamma_rec_vec[j].verify_proofs_get_alpha(
+                            &self.party_keys.dk,
+                            &sign_keys.k_i,
+                            &ctx_j,
+                        )?;
+                        let m_b = &m_b_w_rec_vec[j];
+                        let alpha_ij_wi = m_b.verify_proofs_get_alpha(
+                            &self.party_keys.dk,
+                            &sign_keys.k_i,
+                            &ctx_j,
+                        )?;
+                        alpha_vec.push(alpha_ij_gamma.0);
+                        miu_vec.push(alpha_ij_wi.0);
+                        let signer_j = signers_vec[usize::from(i - 1)];
+                        let g_w_i = Keys::update_commitments_to_xi(
+                            &xi_com_vec[signer_j],
+                            &self.vss_scheme_vec[signer_j],
+                            signer_j,
+                            &signers_vec,
+                        );
+                        if m_b.b_proof.pk != g_w_i {
+                            return Err(TssError::InvalidKey);
+                        }
+                        j += 1;
+                    }
+                }
+
+                let delta_i = sign_keys.phase2_delta_i(&alpha_vec, &beta_vec);
+                let sigma = sign_keys.phase2_sigma_i(&miu_vec, &ni_vec);
+                (
+                    Outgoing::broadcast(3, SignMessage::Round3(delta_i.clone())),
+                    SignState::Round3 {
+                        sign_keys,
+                        decommit,
+                        bc1_vec,
+                        m_b_gamma_rec_vec,
+                        delta_i,
+                        sigma,
+                    },
+                )
+            }
+            SignState::Round3 {
+                sign_keys,
+                decommit,
+                bc1_vec,
+                m_b_gamma_rec_vec,
+                delta_i,
+                sigma,
+            } => {
+                let mut delta_vec: Vec<P::Scalar> = payloads!(incoming, SignMessage::Round3);
+                delta_vec.insert(me, delta_i);
+                let delta_inv = SignKeys::<P>::phase3_reconstruct_delta(&delta_vec);
+
+                // decommit to gamma_i
+                (
+                    Outgoing::broadcast(4, SignMessage::Round4(decommit.clone())),
+                    SignState::Round4 {
+                        sign_keys,
+                        decommit,
+                        bc1_vec,
+                        m_b_gamma_rec_vec,
+                        delta_inv,
+                        sigma,
+                    },
+                )
+            }
+            SignState::Round4 {
+                sign_keys,
+                decommit,
+                mut bc1_vec,
+                m_b_gamma_rec_vec,
+                delta_inv,
+                sigma,
+            } => {
+                let decommit_vec: Vec<SignDecommitPhase1<P>> =
+                    payloads!(incoming, SignMessage::Round4);
+                let decomm_i = decommit;
+                bc1_vec.remove(me);
+                let b_proof_vec = m_b_gamma_rec_vec
+                    .iter()
+                    .map(|m_b| &m_b.b_proof)
+                    .collect::<Vec<&DLogProof<P>>>();
+
+                let R = SignKeys::phase4(&delta_inv, &b_proof_vec, decommit_vec, &bc1_vec)?;
+                // adding local g_gamma_i
+                let R = R + decomm_i.g_gamma_i * &delta_inv;
+
+                // we assume the message is already hashed (by the signer).
+                let message_bn = BigInt::from_bytes_be(&self.message[..]);
+                let local_sig = LocalSignature::phase5_local_sig(
+                    &sign_keys.k_i,
+                    &message_bn,
+                    &R,
+                    &sigma,
+                    &self.y_sum,
+                );
+                let ctx = ProofContext::new(&self.session_id, self.party_num_int, SIGN_ROUND6);
+                let (phase5_com, phase_5a_decom, helgamal_proof, dlog_proof_rho) =
+                    local_sig.phase5a_broadcast_5b_zkproof(&ctx);
+
+                //phase (5A)  broadcast commit
+                (
+                    Outgoing::broadcast(5, SignMessage::Round5(phase5_com.clone())),
+                    SignState::Round5 {
+                        local_sig,
+                        r: R,
+                        phase_5a_decom,
+                        helgamal_proof,
+                        dlog_proof_rho,
+                    },
+                )
+            }
+            SignState::Round5 {
+                local_sig,
+                r,
+                phase_5a_decom,
+                helgamal_proof,
+                dlog_proof_rho,
+            } => {
+                // the other signers' commitments; phase5c checks them against their decommitments
+                let commit5a_vec: Vec<Phase5Com1> = payloads!(incoming, SignMessage::Round5);
+
+                //phase (5B)  broadcast decommit and (5B) ZK proof
+                let data =
+                    SignMessage::Round6((phase_5a_decom.clone(), helgamal_proof, dlog_proof_rho));
+                (
+                    Outgoing::broadcast(6, data),
+                    SignState::Round6 {
+                        local_sig,
+                        r,
+                        phase_5a_decom,
+                        commit5a_vec,
+                    },
+                )
+            }
+            SignState::Round6 {
+                local_sig,
+                r,
+                phase_5a_decom,
+                commit5a_vec,
+            } => {
+                let decommit5a_and_elgamal_and_dlog_vec: Vec<(
+                    Phase5ADecom1<P>,
+                    HomoELGamalProof<P>,
+                    DLogProof<P>,
+                )> = payloads!(incoming, SignMessage::Round6);
+                let phase_5a_decomm_vec = decommit5a_and_elgamal_and_dlog_vec
+                    .iter()
+                    .map(|d| d.0.clone())
+                    .collect::<Vec<Phase5ADecom1<P>>>();
+                let phase_5a_elgamal_vec = decommit5a_and_elgamal_and_dlog_vec
+                    .iter()
+                    .map(|d| d.1.clone())
+                    .collect::<Vec<HomoELGamalProof<P>>>();
+                let phase_5a_dlog_vec = decommit5a_and_elgamal_and_dlog_vec
+                    .iter()
+                    .map(|d| d.2.clone())
+                    .collect::<Vec<DLogProof<P>>>();
+                let ctx = ProofContext::new(&self.session_id, self.party_num_int, SIGN_ROUND6);
+                let ctx_vec = (1..t + 2)
+                    .filter(|i| *i != self.party_num_int)
+                    .map(|i| ctx.for_party(i))
+                    .collect::<Vec<ProofContext>>();
+                let (phase5_com2, phase_5d_decom2) = local_sig.phase5c(
+                    &phase_5a_decomm_vec,
+                    &commit5a_vec,
+                    &phase_5a_elgamal_vec,
+                    &phase_5a_dlog_vec,
+                    &phase_5a_decom.V_i,
+                    &r,
+                    &ctx_vec,
+                )?;
+                let mut phase_5a_decomm_vec_includes_i = phase_5a_decomm_vec;
+                phase_5a_decomm_vec_includes_i.insert(me, phase_5a_decom);
+
+                (
+                    Outgoing::broadcast(7, SignMessage::Round7(phase5_com2.clone())),
+                    SignState::Round7 {
+                        local_sig,
+                        phase5_com2,
+                        phase_5d_decom2,
+                        phase_5a_decomm_vec_includes_i,
+                    },
+                )
+            }
+            SignState::Round7 {
+                local_sig,
+                phase5_com2,
+                phase_5d_decom2,
+                phase_5a_decomm_vec_includes_i,
+            } => {
+                let mut commit5c_vec: Vec<Phase5Com2> = payloads!(incoming, SignMessage::Round7);
+                commit5c_vec.insert(me, phase5_com2);
+
+                (
+                    Outgoing::broadcast(8, SignMessage::Round8(phase_5d_decom2.clone())),
+                    SignState::Round8 {
+                        local_sig,
+                        phase_5d_decom2,
+                        commit5c_vec,
+                        phase_5a_decomm_vec_includes_i,
+                    },
+                )
+            }
+            SignState::Round8 {
+                local_sig,
+                phase_5d_decom2,
+                commit5c_vec,
+                phase_5a_decomm_vec_includes_i,
+            } => {
+                let mut decommit5d_vec: Vec<Phase5DDecom2<P>> =
+                    payloads!(incoming, SignMessage::Round8);
+                decommit5d_vec.insert(me, phase_5d_decom2);
+                let s_i = local_sig.phase5d(
+                    &decommit5d_vec,
+                    &commit5c_vec,
+                    &phase_5a_decomm_vec_includes_i,
+                )?;
+
+                (
+                    Outgoing::broadcast(9, SignMessage::Round9(s_i.clone())),
+                    SignState::Round9 { local_sig },
+                )
+            }
+            SignState::Round9 { local_sig } => {
+                let s_i_vec: Vec<P::Scalar> = payloads!(incoming, SignMessage::Round9);
+                let signature = local_sig.output_signature(&s_i_vec)?;
+                verify(&signature, &self.y_sum, &local_sig.m)?;
+
+                (Outgoing::none(10), SignState::Finished { signature })
+            }
+            SignState::Finished { .. } => {
+                return Err(TssError::RoundOutOfOrder {
+                    expected: 10,
+                    got: 10,
+                })
+            }
+        };
+
+        self.state = state;
+        Ok((outgoing, self))
+    }
+}
+
+// Key and associated data for the round3 share sent from `from` to `to`. The
+// key is derived from the ECDH secret over the same transcript that is bound as
+// associated data, so each direction of each session gets its own key.
+fn share_channel(
+    ecdh_secret: &[u8],
+    session_id: &str,
+    from: u16,
+    to: u16,
+) -> ([u8; AES_KEY_BYTES_LEN], Vec<u8>) {
+    p2p_channel(
+        b"tss-wasm/gg18/keygen/round3-share/v1",
+        ecdh_secret,
+        session_id,
+        from,
+        to,
+        3,
+    )
+}
+
+// Same as share_channel for any protocol, `label` keeps the channels of
+// different protocols and rounds apart.
+pub(crate) fn p2p_channel(
+    label: &[u8],
+    ecdh_secret: &[u8],
+    session_id: &str,
+    from: u16,
+    to: u16,
+    round: u16,
+) -> ([u8; AES_KEY_BYTES_LEN], Vec<u8>) {
+    let mut transcript = label.to_vec();
+    transcript.extend_from_slice(&(session_id.len() as u64).to_be_bytes());
+    transcript.extend_from_slice(session_id.as_bytes());
+    transcript.extend_from_slice(&from.to_be_bytes());
+    transcript.extend_from_slice(&to.to_be_bytes());
+    transcript.extend_from_slice(&round.to_be_bytes());
+
+    let mut key = [0u8; AES_KEY_BYTES_LEN];
+    hkdf_sha256(session_id.as_bytes(), ecdh_secret, &transcript, &mut key);
+    (key, transcript)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curv::elliptic::curves::secp256_r1;
+    use crate::test_util::route;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
+
+    fn keygen_sign_sessions<P: ECPoint>() {
+        let (t, n) = (1, 2);
+        let mut sessions = Vec::new();
+        let mut outgoing = Vec::new();
+        for i in 1..=n {
+            let params = Parameters::new(t, n);
+            let (out, session) = KeygenSession::<P>::new(params, i as u16, "uuid".to_string())
+                .advance(Vec::new())
+                .unwrap();
+            outgoing.push(out);
+            sessions.push(session);
+        }
+        while sessions[0].key_store().is_none() {
+            let mut next_outgoing = Vec::new();
+            let mut next_sessions = Vec::new();
+            for (k, session) in sessions.into_iter().enumerate() {
+                let incoming = route(&outgoing, k as u16 + 1);
+                let (out, session) = session.advance(incoming).unwrap();
+                next_outgoing.push(out);
+                next_sessions.push(session);
+            }
+            outgoing = next_outgoing;
+            sessions = next_sessions;
+        }
+        assert!(sessions[0].clone().advance(Vec::new()).is_err());
+
+        let message = vec![42u8; 32];
+        let mut sign_sessions = Vec::new();
+        let mut outgoing = Vec::new();
+        for (k, session) in sessions.iter().enumerate() {
+            let key_store = session.key_store().unwrap().clone();
+            let (out, session) = SignSession::new(
+                key_store,
+                t as u16,
+                k as u16 + 1,
+                "uuid".to_string(),
+                message.clone(),
+            )
+            .advance(Vec::new())
+            .unwrap();
+            outgoing.push(out);
+            sign_sessions.push(session);
+        }
+        while sign_sessions[0].signature().is_none() {
+            let mut next_outgoing = Vec::new();
+            let mut next_sessions = Vec::new();
+            for (k, session) in sign_sessions.into_iter().enumerate() {
+                let incoming = route(&outgoing, k as u16 + 1);
+                let (out, session) = session.advance(incoming).unwrap();
+                next_outgoing.push(out);
+                next_sessions.push(session);
+            }
+            outgoing = next_outgoing;
+            sign_sessions = next_sessions;
+        }
+
+        let y = &sessions[0].key_store().unwrap().5;
+        let signature = sign_sessions[0].signature().unwrap();
+        assert_eq!(signature, sign_sessions[1].signature().unwrap());
+        assert!(verify(signature, y, &BigInt::from_bytes_be(&message)).is_ok());
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_keygen_sign_sessions() {
+        keygen_sign_sessions::<GE>();
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_keygen_sign_sessions_p256() {
+        keygen_sign_sessions::<secp256_r1::GE>();
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_keygen_rejects_proofs_of_other_session() {
+        let params = Parameters::new(1, 2);
+        let mut outgoing = Vec::new();
+        let mut sessions = Vec::new();
+        for (i, session_id) in [(1, "uuid"), (2, "other uuid")] {
+            let (out, session) =
+                KeygenSession::<GE>::new(params.clone(), i, session_id.to_string())
+                    .advance(Vec::new())
+                    .unwrap();
+            outgoing.push(out);
+            sessions.push(session);
+        }
+        let (out, session) = sessions.remove(0).advance(route(&outgoing, 1)).unwrap();
+        let other = sessions.remove(0).advance(route(&outgoing, 2)).unwrap().0;
+        // the round1 proofs of party 2 were made for another session
+        assert!(session.advance(route(&[out, other], 1)).is_err());
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_round_out_of_order() {
+        let params = Parameters::new(1, 2);
+        let session = KeygenSession::<GE>::new(params, 1, "uuid".to_string());
+        assert!(session.check_round(1).is_ok());
+        match session.check_round(3) {
+            Err(TssError::RoundOutOfOrder {
+                expected: 1,
+                got: 3,
+            }) => {}
+            _ => panic!("round 3 should be rejected before round 1"),
+        }
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_party_number_out_of_range() {
+        // a party number from the manager outside 1..=n is an error, not a panic
+        for party_num_int in [0, 3] {
+            let session =
+                KeygenSession::<GE>::new(Parameters::new(1, 2), party_num_int, "uuid".to_string());
+            match session.advance(Vec::new()) {
+                Err(TssError::InvalidArgument { name }) if name == "party_num_int" => {}
+                _ => panic!("party number {} should be rejected", party_num_int),
+            }
+        }
+    }
+}
@@ -0,0 +1,318 @@
+// Long-term party identity keys.
+//
+// Every round message goes on the wire wrapped in a `SignedPayload`, signed
+// with the sender's identity key over the kind of message, the session uuid,
+// sender, recipient and round. The public keys of the other parties are pinned by the caller, so a
+// compromised manager or a leaked token cannot write messages for a party it
+// does not hold the identity key of.
+//
+// Round contexts go to the host app, in plaintext unless sealing is enabled,
+// so they only name the own identity key by its public key. The key itself
+// stays in a slot of this process, filled when a context is created or by
+// `gg18_set_identity_key` before resuming one after a restart.
+
+use crate::common::keccak256;
+use crate::errors::{Result, TssError};
+
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::RwLock;
+use zeroize::Zeroize;
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+const SIGNED_PAYLOAD_DOMAIN: &[u8] = b"tss-wasm/gg18/signed-payload/v1";
+
+lazy_static::lazy_static! {
+    // identity keys of this process by their public key, so round contexts
+    // can do without the secret one
+    static ref IDENTITY_KEYS: RwLock<HashMap<String, String>> = RwLock::new(HashMap::new());
+}
+
+/// What a signed payload carries. Part of the signed digest, so an echo of a
+/// round can never be taken for the round broadcast itself, or the other way.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PayloadKind {
+    Round = 1,
+    Echo = 2,
+}
+
+/// Round message payload together with the sender's signature.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct SignedPayload {
+    pub payload: String,
+    pub signature: String,
+}
+
+/// Public key of the own identity key and the identity keys the other parties
+/// signed up with.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PartyIdentities {
+    public_key: String,
+    peers: Vec<(u16, String)>,
+}
+
+/// Generates a new identity key and returns it hex encoded. It is meant to be
+/// long-term: keep it in the platform key store and share its public key.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn gg18_identity_key_new() -> Result<String> {
+    let mut rng = OsRng::new()?;
+    let mut bytes = [0u8; 32];
+    loop {
+        rng.fill_bytes(&mut bytes);
+        if let Ok(secret_key) = secp256k1::SecretKey::parse(&bytes) {
+            bytes.zeroize();
+            return Ok(hex::encode(secret_key.serialize()));
+        }
+    }
+}
+
+/// Makes the identity key available to the rounds of the contexts created
+/// with it. Creating a context does this already; call it after a restart,
+/// before resuming a saved context. The key stays in memory until the process
+/// ends.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn gg18_set_identity_key(identity_key: String) -> Result<()> {
+    let public_key = gg18_identity_public_key(identity_key.clone())?;
+    let mut keys = IDENTITY_KEYS.write().unwrap_or_else(|e| e.into_inner());
+    if let Some(mut old) = keys.insert(public_key, identity_key) {
+        old.zeroize();
+    }
+    Ok(())
+}
+
+/// Hex encoded compressed public key of the given identity key.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn gg18_identity_public_key(identity_key: String) -> Result<String> {
+    let secret_key = parse_secret_key(&identity_key)?;
+    Ok(hex::encode(
+        secp256k1::PublicKey::from_secret_key(&secret_key).serialize_compressed(),
+    ))
+}
+
+impl PartyIdentities {
+    /// Checks the identity keys the manager registered at signup against the
+    /// pinned ones: every other party must have signed up with a distinct
+    /// pinned key that is not our own.
+    pub fn new(
+        identity_key: &str,
+        pinned: &[String],
+        roster: Vec<(u16, String)>,
+    ) -> Result<PartyIdentities> {
+        gg18_set_identity_key(identity_key.to_string())?;
+        let own = gg18_identity_public_key(identity_key.to_string())?;
+        let pinned = pinned
+            .iter()
+            .map(|key| normalize_public_key(key))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut peers: Vec<(u16, String)> = Vec::with_capacity(roster.len());
+        for (party, key) in roster {
+            let key =
+                normalize_public_key(&key).map_err(|_| TssError::UnknownIdentity { party })?;
+            if key == own || !pinned.contains(&key) || peers.iter().any(|(_, k)| *k == key) {
+                return Err(TssError::UnknownIdentity { party });
+            }
+            peers.push((party, key));
+        }
+
+        Ok(PartyIdentities {
+            public_key: own,
+            peers,
+        })
+    }
+
+    /// Signs a payload from `from` to `to` (0 for a broadcast) and returns
+    /// the `SignedPayload` as JSON.
+    pub fn sign(
+        &self,
+        kind: PayloadKind,
+        uuid: &str,
+        from: u16,
+        to: u16,
+        round: u16,
+        payload: String,
+    ) -> Result<String> {
+        let secret_key = {
+            let keys = IDENTITY_KEYS.read().unwrap_or_else(|e| e.into_inner());
+            // not set in this process since it was restarted
+            let identity_key = keys.get(&self.public_key).ok_or(TssError::InvalidKey)?;
+            parse_secret_key(identity_key)?
+        };
+        let message = digest(kind, uuid, from, to, round, &payload);
+        let (signature, _) = secp256k1::sign(&message, &secret_key);
+        Ok(serde_json::to_string(&SignedPayload {
+            payload,
+            signature: hex::encode(&signature.serialize()[..]),
+        })?)
+    }
+
+    /// Verifies a `SignedPayload` against the identity key of `from` and
+    /// returns the payload.
+    pub fn open(
+        &self,
+        kind: PayloadKind,
+        uuid: &str,
+        from: u16,
+        to: u16,
+        round: u16,
+        data: &str,
+    ) -> Result<String> {
+        let invalid = || TssError::UnauthenticatedMessage { round, party: from };
+        let public_key = self
+            .peers
+            .iter()
+            .find(|(party, _)| *party == from)
+            .map(|(_, key)| key)
+            .ok_or(TssError::UnknownIdentity { party: from })?;
+        let public_key = hex::decode(public_key).map_err(|_| invalid())?;
+        let public_key = secp256k1::PublicKey::parse_slice(&public_key, None)?;
+
+        let signed: SignedPayload = serde_json::from_str(data).map_err(|_| invalid())?;
+        let signature = hex::decode(&signed.signature).map_err(|_| invalid())?;
+        let signature = secp256k1::Signature::parse_slice(&signature).map_err(|_| invalid())?;
+        let message = digest(kind, uuid, from, to, round, &signed.payload);
+        if !secp256k1::verify(&message, &signature, &public_key) {
+            return Err(invalid());
+        }
+        Ok(signed.payload)
+    }
+}
+
+fn parse_secret_key(identity_key: &str) -> Result<secp256k1::SecretKey> {
+    let mut bytes = hex::decode(identity_key).map_err(|_| TssError::InvalidKey)?;
+    let secret_key = secp256k1::SecretKey::parse_slice(&bytes).map_err(|_| TssError::InvalidKey);
+    bytes.zeroize();
+    secret_key
+}
+
+// accepts compressed and uncompressed keys, compares them compressed
+fn normalize_public_key(public_key: &str) -> Result<String> {
+    let bytes = hex::decode(public_key).map_err(|_| TssError::InvalidPublicKey)?;
+    let public_key =
+        secp256k1::PublicKey::parse_slice(&bytes, None).map_err(|_| TssError::InvalidPublicKey)?;
+    Ok(hex::encode(public_key.serialize_compressed()))
+}
+
+fn digest(
+    kind: PayloadKind,
+    uuid: &str,
+    from: u16,
+    to: u16,
+    round: u16,
+    payload: &str,
+) -> secp256k1::Message {
+    let mut bytes =
+        Vec::with_capacity(SIGNED_PAYLOAD_DOMAIN.len() + uuid.len() + payload.len() + 23);
+    bytes.extend_from_slice(SIGNED_PAYLOAD_DOMAIN);
+    bytes.push(kind as u8);
+    bytes.extend_from_slice(&(uuid.len() as u64).to_be_bytes());
+    bytes.extend_from_slice(uuid.as_bytes());
+    bytes.extend_from_slice(&from.to_be_bytes());
+    bytes.extend_from_slice(&to.to_be_bytes());
+    bytes.extend_from_slice(&round.to_be_bytes());
+    bytes.extend_from_slice(&(payload.len() as u64).to_be_bytes());
+    bytes.extend_from_slice(payload.as_bytes());
+    secp256k1::Message::parse(&keccak256(&bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_signed_payload() {
+        let keys: Vec<String> = (0..3).map(|_| gg18_identity_key_new().unwrap()).collect();
+        let public: Vec<String> = keys
+            .iter()
+            .map(|k| gg18_identity_public_key(k.clone()).unwrap())
+            .collect();
+        let party = |i: usize| {
+            let roster = (0..3)
+                .filter(|j| *j != i)
+                .map(|j| (j as u16 + 1, public[j].clone()))
+                .collect();
+            PartyIdentities::new(&keys[i], &public, roster).unwrap()
+        };
+        let (p1, p2) = (party(0), party(1));
+
+        let data = p1
+            .sign(PayloadKind::Round, "uuid", 1, 0, 3, "payload".to_string())
+            .unwrap();
+        assert_eq!(
+            p2.open(PayloadKind::Round, "uuid", 1, 0, 3, &data).unwrap(),
+            "payload"
+        );
+
+        // bound to kind, session, sender, recipient and round
+        assert!(p2.open(PayloadKind::Echo, "uuid", 1, 0, 3, &data).is_err());
+        assert!(p2
+            .open(PayloadKind::Round, "other", 1, 0, 3, &data)
+            .is_err());
+        assert!(p2.open(PayloadKind::Round, "uuid", 3, 0, 3, &data).is_err());
+        assert!(p2.open(PayloadKind::Round, "uuid", 1, 2, 3, &data).is_err());
+        assert!(p2.open(PayloadKind::Round, "uuid", 1, 0, 4, &data).is_err());
+
+        // party 3 cannot speak for party 1
+        let forged = party(2)
+            .sign(PayloadKind::Round, "uuid", 1, 0, 3, "payload".to_string())
+            .unwrap();
+        match p2.open(PayloadKind::Round, "uuid", 1, 0, 3, &forged) {
+            Err(TssError::UnauthenticatedMessage { round: 3, party: 1 }) => {}
+            _ => panic!("forged message accepted"),
+        }
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_identity_key_not_in_context() {
+        let key = gg18_identity_key_new().unwrap();
+        let peer = gg18_identity_public_key(gg18_identity_key_new().unwrap()).unwrap();
+        let identities = PartyIdentities::new(&key, &[peer.clone()], vec![(2, peer)]).unwrap();
+        let json = serde_json::to_string(&identities).unwrap();
+        assert!(!json.contains(&key));
+        assert!(!format!("{:?}", identities).contains(&key));
+
+        // a context of a key this process has not been given cannot sign
+        let unknown = gg18_identity_key_new().unwrap();
+        let json = json.replace(
+            &gg18_identity_public_key(key.clone()).unwrap(),
+            &gg18_identity_public_key(unknown.clone()).unwrap(),
+        );
+        let restored: PartyIdentities = serde_json::from_str(&json).unwrap();
+        assert!(matches!(
+            restored.sign(PayloadKind::Round, "uuid", 1, 0, 1, "payload".to_string()),
+            Err(TssError::InvalidKey)
+        ));
+        gg18_set_identity_key(unknown).unwrap();
+        assert!(restored
+            .sign(PayloadKind::Round, "uuid", 1, 0, 1, "payload".to_string())
+            .is_ok());
+        assert!(gg18_set_identity_key("00".to_string()).is_err());
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_roster_pinning() {
+        let key = gg18_identity_key_new().unwrap();
+        let own = gg18_identity_public_key(key.clone()).unwrap();
+        let peer = gg18_identity_public_key(gg18_identity_key_new().unwrap()).unwrap();
+        let stranger = gg18_identity_public_key(gg18_identity_key_new().unwrap()).unwrap();
+        let pinned = vec![peer.clone()];
+
+        assert!(PartyIdentities::new(&key, &pinned, vec![(2, peer.clone())]).is_ok());
+        for roster in [
+            vec![(2, stranger)],
+            vec![(2, own)],
+            vec![(2, peer.clone()), (3, peer)],
+        ] {
+            assert!(PartyIdentities::new(&key, &pinned, roster).is_err());
+        }
+    }
+}
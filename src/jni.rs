@@ -1,20 +1,138 @@
 #![cfg(not(target_arch = "wasm32"))]
 
 use crate::api::{
-    gg18_keygen_client_new_context, gg18_keygen_client_round1, gg18_keygen_client_round2,
+    eddsa_keygen_client_new_context, eddsa_keygen_client_next_round, eddsa_keygen_client_round1,
+    eddsa_keygen_client_round2, eddsa_keygen_client_round3, eddsa_keygen_client_run,
+    eddsa_sign_client_new_context, eddsa_sign_client_next_round, eddsa_sign_client_round0,
+    eddsa_sign_client_round1, eddsa_sign_client_round2, eddsa_sign_client_round3,
+    eddsa_sign_client_round4, eddsa_sign_client_run, gg18_disable_echo_broadcast,
+    gg18_enable_echo_broadcast, gg18_keygen_client_new_context,
+    gg18_keygen_client_new_context_with_curve, gg18_keygen_client_new_context_with_paillier,
+    gg18_keygen_client_next_round, gg18_keygen_client_round1, gg18_keygen_client_round2,
     gg18_keygen_client_round3, gg18_keygen_client_round4, gg18_keygen_client_round5,
-    gg18_sign_client_new_context, gg18_sign_client_round0, gg18_sign_client_round1,
+    gg18_keygen_client_run, gg18_sign_client_new_context, gg18_sign_client_new_context_with_curve,
+    gg18_sign_client_next_round, gg18_sign_client_round0, gg18_sign_client_round1,
     gg18_sign_client_round2, gg18_sign_client_round3, gg18_sign_client_round4,
     gg18_sign_client_round5, gg18_sign_client_round6, gg18_sign_client_round7,
-    gg18_sign_client_round8, gg18_sign_client_round9,
+    gg18_sign_client_round8, gg18_sign_client_round9, gg18_sign_client_run, RoundTiming,
+    SessionProgress,
+};
+use crate::common::{set_retry_policy, TokenProvider};
+use crate::errors::{Result, TssError};
+use crate::identity::{gg18_identity_key_new, gg18_identity_public_key, gg18_set_identity_key};
+use crate::keypair_pool::{
+    gg18_keypair_pool_export, gg18_keypair_pool_fill, gg18_keypair_pool_import,
+    gg18_keypair_pool_size, gg18_set_keypair_pool_key,
+};
+use crate::keystore::{export_keystore, import_keystore};
+use crate::logging::init_logging;
+use crate::runtime::{block_on, init_runtime};
+use crate::sealed_context::{
+    gg18_disable_context_sealing, gg18_enable_context_sealing, gg18_set_context_sealing_key,
 };
 
 // ここから JNI 用のラッパー関数を定義する
-use jni::objects::{JClass, JString};
-use jni::sys::{jint, jstring};
+use jni::objects::{JClass, JObject, JString, JThrowable, JValue};
+use jni::sys::{jboolean, jint, jlong, jstring, JNI_TRUE};
 use jni::JNIEnv;
+use std::cell::RefCell;
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
 use std::ptr;
-use tokio::runtime::Runtime;
+
+// TssError を投げるときの例外クラス。アプリ側で次のコンストラクタを持つ
+// RuntimeException のサブクラスとして定義する（README の「JNIの例外」を参照）
+//   TssException(int code, String message, int round, int party)
+// code は TssError::code の値、round, party は不明なとき -1
+const TSS_EXCEPTION_CLASS: &str =
+    "jp/datasign/bunsin_wallet/cryptography/multiparty_ecdsa/TssException";
+
+// TssError を TssException として投げる。クラスが見つからない場合は RuntimeException にする
+fn throw_tss_error(env: &mut JNIEnv, e: &TssError) {
+    // 保留中の例外 (JNI 呼び出しの失敗など) は TssException で置き換える
+    let _ = env.exception_clear();
+    let thrown = env.new_string(format!("{:?}", e)).and_then(|message| {
+        let exception = env.new_object(
+            TSS_EXCEPTION_CLASS,
+            "(ILjava/lang/String;II)V",
+            &[
+                JValue::Int(e.code()),
+                JValue::Object(&message),
+                JValue::Int(e.round().map_or(-1, jint::from)),
+                JValue::Int(e.party().map_or(-1, jint::from)),
+            ],
+        )?;
+        env.throw(JThrowable::from(exception))
+    });
+    if thrown.is_err() {
+        let _ = env.exception_clear();
+        let message = format!(
+            "TssException(code={}, round={}, party={}): {:?}",
+            e.code(),
+            e.round().map_or(-1, jint::from),
+            e.party().map_or(-1, jint::from),
+            e
+        );
+        let _ = env.throw_new("java/lang/RuntimeException", message);
+    }
+}
+
+// JNI 関数の本体を実行する。エラーは Java 例外として投げて default を返す。
+// パニックは FFI 境界を越えて巻き戻らないようにここで捕捉する
+fn jni_call<T>(env: &mut JNIEnv, default: T, f: impl FnOnce(&mut JNIEnv) -> Result<T>) -> T {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| f(env))).unwrap_or_else(|payload| {
+        let msg = match payload.downcast::<String>() {
+            Ok(msg) => *msg,
+            Err(payload) => payload
+                .downcast_ref::<&str>()
+                .map_or_else(|| "unknown panic".to_string(), |msg| msg.to_string()),
+        };
+        Err(TssError::Panic { msg })
+    });
+    match result {
+        Ok(value) => value,
+        Err(e) => {
+            throw_tss_error(env, &e);
+            default
+        }
+    }
+}
+
+// JString を Rust の String に変換する（null などは InvalidArgument）
+fn get_string(env: &mut JNIEnv, s: &JString, name: &str) -> Result<String> {
+    match env.get_string(s) {
+        Ok(s) => Ok(s.into()),
+        Err(_e) => Err(TssError::InvalidArgument {
+            name: name.to_string(),
+        }),
+    }
+}
+
+// jint を usize に変換する（負の値は InvalidArgument）
+fn get_usize(value: jint, name: &str) -> Result<usize> {
+    usize::try_from(value).map_err(|_e| TssError::InvalidArgument {
+        name: name.to_string(),
+    })
+}
+
+// jint を u32 に変換する（負の値は InvalidArgument）
+fn get_u32(value: jint, name: &str) -> Result<u32> {
+    u32::try_from(value).map_err(|_e| TssError::InvalidArgument {
+        name: name.to_string(),
+    })
+}
+
+// 結果文字列を JString に変換して返す
+fn new_string(env: &mut JNIEnv, s: String) -> Result<jstring> {
+    match env.new_string(s) {
+        Ok(s) => Ok(s.into_raw()),
+        Err(e) => Err(TssError::UnknownError {
+            msg: format!("Couldn't create java string: {:?}", e),
+            line: line!(),
+        }),
+    }
+}
 
 macro_rules! jni_round_wrapper {
     ($jni_name:ident, $rust_fn:path) => {
@@ -26,33 +144,93 @@ macro_rules! jni_round_wrapper {
             jdelay: jint,
             jtoken: JString,
         ) -> jstring {
-            // JString を Rust の String に変換
-            let context: String = env
-                .get_string(&jcontext)
-                .expect("Invalid context string")
-                .into();
-            let delay: u32 = jdelay as u32;
-            let token: String = env
-                .get_string(&jtoken)
-                .expect("Invalid token string")
-                .into();
-
-            let rt = Runtime::new().unwrap();
-            match rt.block_on($rust_fn(context, delay, token)) {
-                Ok(result_str) => env
-                    .new_string(result_str)
-                    .expect("Couldn't create java string")
-                    .into_raw(),
-                Err(e) => {
-                    let _ = env.throw_new("java/lang/RuntimeException", format!("Error: {:?}", e));
-                    ptr::null_mut()
-                }
-            }
+            jni_call(&mut env, ptr::null_mut(), |env| {
+                // JString を Rust の String に変換
+                let context = get_string(env, &jcontext, "context")?;
+                let delay = get_u32(jdelay, "delay")?;
+                let token = get_string(env, &jtoken, "token")?;
+
+                let result_str = block_on($rust_fn(context, delay, token))?;
+                new_string(env, result_str)
+            })
         }
     };
 }
 
-/// JNIラッパー: com.example.myapplication2.MultiPartyECDSA.gg18KeygenClientNewContext(String, int, int, int)
+// Java の進捗コールバック (onRound(int, int), onWaiting(int) を持つオブジェクト) に
+// SessionProgress を転送する。呼び出しは block_on を呼んだ JNI スレッド上で行われる
+// onTiming(String step, long computeMs, long networkMs) は任意で、無ければ呼ばれない
+// String refreshToken() も任意で、マネージャーがトークンを拒否したときに新しいトークンを取得する
+struct JavaProgress<'a, 'local, 'obj> {
+    env: RefCell<&'a mut JNIEnv<'local>>,
+    callback: &'a JObject<'obj>,
+}
+
+impl JavaProgress<'_, '_, '_> {
+    fn call(&self, name: &str, sig: &str, args: &[JValue]) {
+        let mut env = self.env.borrow_mut();
+        // 進捗通知はベストエフォート: コールバックが例外を投げてもセッションは続ける
+        if env.call_method(self.callback, name, sig, args).is_err() {
+            let _ = env.exception_clear();
+        }
+    }
+}
+
+impl TokenProvider for JavaProgress<'_, '_, '_> {
+    fn refresh_token(&self) -> Pin<Box<dyn Future<Output = Result<String>> + '_>> {
+        // Java の呼び出しは同期的に行い、結果だけを future として返す
+        let token = {
+            let mut env = self.env.borrow_mut();
+            match env
+                .call_method(self.callback, "refreshToken", "()Ljava/lang/String;", &[])
+                .and_then(|token| token.l())
+            {
+                Ok(token) => get_string(&mut env, &JString::from(token), "refreshToken"),
+                Err(_e) => {
+                    // メソッドが無い・例外を投げた場合は更新できなかったものとして扱う
+                    let _ = env.exception_clear();
+                    Err(TssError::InvalidArgument {
+                        name: "refreshToken".to_string(),
+                    })
+                }
+            }
+        };
+        Box::pin(async move { token })
+    }
+}
+
+impl SessionProgress for JavaProgress<'_, '_, '_> {
+    fn on_round(&self, round: u16, rounds: u16) {
+        self.call(
+            "onRound",
+            "(II)V",
+            &[JValue::Int(round as jint), JValue::Int(rounds as jint)],
+        );
+    }
+
+    fn on_waiting(&self, party: u16) {
+        self.call("onWaiting", "(I)V", &[JValue::Int(party as jint)]);
+    }
+
+    fn on_timing(&self, timing: &RoundTiming) {
+        let step = match self.env.borrow_mut().new_string(&timing.step) {
+            Ok(step) => step,
+            Err(_e) => return,
+        };
+        self.call(
+            "onTiming",
+            "(Ljava/lang/String;JJ)V",
+            &[
+                JValue::Object(&step),
+                JValue::Long(timing.compute_ms as jlong),
+                JValue::Long(timing.network_ms as jlong),
+            ],
+        );
+    }
+}
+
+/// JNIラッパー: GG18RawInterface.gg18KeygenClientNewContext(String, int, int, int, String, String, String, String, String)
+/// secp256k1 の鍵を扱う
 #[no_mangle]
 pub extern "system" fn Java_jp_datasign_bunsin_1wallet_cryptography_multiparty_1ecdsa_GG18RawInterface_gg18KeygenClientNewContext(
     mut env: JNIEnv,
@@ -63,48 +241,140 @@ pub extern "system" fn Java_jp_datasign_bunsin_1wallet_cryptography_multiparty_1
     jdelay: jint,
     jtoken: JString,
     jtaskid: JString,
-    jpartytype: JString
-) -> jstring {
-    // JStringをRustのStringに変換
-    let addr: String = env
-        .get_string(&jaddr)
-        .expect("Invalid address string")
-        .into();
-    let t: usize = jt as usize;
-    let n: usize = jn as usize;
-    let delay: u32 = jdelay as u32;
-    let token: String = env
-        .get_string(&jtoken)
-        .expect("Invalid token string")
-        .into();
-     let task_id: String = env
-         .get_string(&jtaskid)
-         .expect("Invalid taskId string")
-         .into();
-    let party_type: String = env
-        .get_string(&jpartytype)
-        .expect("Invalid party type string")
-        .into();
-
-    let rt = Runtime::new().unwrap();
-
-    // Rustの関数を呼び出す
-    match rt.block_on(gg18_keygen_client_new_context(addr, t, n, delay, token, task_id, party_type)) {
-        Ok(result_str) => {
-            // 結果文字列をJStringに変換して返す
-            env.new_string(result_str)
-                .expect("Couldn't create java string")
-                .into_raw()
-        }
-        Err(e) => {
-            // エラーが発生した場合、Java例外を投げる
-            let _ = env.throw_new("java/lang/RuntimeException", format!("Error: {:?}", e));
-            ptr::null_mut()
-        }
-    }
+    jpartytype: JString,
+    jidentity_key: JString,
+    jpeer_identity_keys: JString
+) -> jstring {
+    jni_call(&mut env, ptr::null_mut(), |env| {
+        // JStringをRustのStringに変換
+        let addr = get_string(env, &jaddr, "address")?;
+        let t = get_usize(jt, "t")?;
+        let n = get_usize(jn, "n")?;
+        let delay = get_u32(jdelay, "delay")?;
+        let token = get_string(env, &jtoken, "token")?;
+        let task_id = get_string(env, &jtaskid, "taskId")?;
+        let party_type = get_string(env, &jpartytype, "party type")?;
+        let identity_key = get_string(env, &jidentity_key, "identity key")?;
+        // 他パーティの識別公開鍵 (16進数) の JSON 配列
+        let peer_identity_keys = get_string(env, &jpeer_identity_keys, "peer identity keys")?;
+
+        // Rustの関数を呼び出す
+        let result_str = block_on(gg18_keygen_client_new_context(
+            addr,
+            t,
+            n,
+            delay,
+            token,
+            task_id,
+            party_type,
+            identity_key,
+            peer_identity_keys,
+        ))?;
+        new_string(env, result_str)
+    })
 }
 
-/// JNIラッパー: com.example.myapplication2.MultiPartyECDSA.gg18SignClientNewContext(String, int, int, String, String)
+/// JNIラッパー: GG18RawInterface.gg18KeygenClientNewContextWithCurve(String, int, int, int, String, String, String, String, String, String)
+/// 曲線を指定する。"secp256k1" (空文字列も同じ) または "p256"
+#[no_mangle]
+pub extern "system" fn Java_jp_datasign_bunsin_1wallet_cryptography_multiparty_1ecdsa_GG18RawInterface_gg18KeygenClientNewContextWithCurve(
+    mut env: JNIEnv,
+    _class: JClass,
+    jaddr: JString,
+    jt: jint,
+    jn: jint,
+    jdelay: jint,
+    jtoken: JString,
+    jtaskid: JString,
+    jpartytype: JString,
+    jidentity_key: JString,
+    jpeer_identity_keys: JString,
+    jcurve: JString
+) -> jstring {
+    jni_call(&mut env, ptr::null_mut(), |env| {
+        // JStringをRustのStringに変換
+        let addr = get_string(env, &jaddr, "address")?;
+        let t = get_usize(jt, "t")?;
+        let n = get_usize(jn, "n")?;
+        let delay = get_u32(jdelay, "delay")?;
+        let token = get_string(env, &jtoken, "token")?;
+        let task_id = get_string(env, &jtaskid, "taskId")?;
+        let party_type = get_string(env, &jpartytype, "party type")?;
+        let identity_key = get_string(env, &jidentity_key, "identity key")?;
+        // 他パーティの識別公開鍵 (16進数) の JSON 配列
+        let peer_identity_keys = get_string(env, &jpeer_identity_keys, "peer identity keys")?;
+        // "secp256k1" (空文字列も同じ) または "p256"
+        let curve = get_string(env, &jcurve, "curve")?;
+
+        // Rustの関数を呼び出す
+        let result_str = block_on(gg18_keygen_client_new_context_with_curve(
+            addr,
+            t,
+            n,
+            delay,
+            token,
+            task_id,
+            party_type,
+            identity_key,
+            peer_identity_keys,
+            curve,
+        ))?;
+        new_string(env, result_str)
+    })
+}
+
+/// JNIラッパー: GG18RawInterface.gg18KeygenClientNewContextWithPaillier(String, int, int, int, String, String, String, String, String, String, int, boolean)
+/// Paillier 鍵の法のビット長 (2048〜8192) と safe prime を使うかを指定する
+#[no_mangle]
+pub extern "system" fn Java_jp_datasign_bunsin_1wallet_cryptography_multiparty_1ecdsa_GG18RawInterface_gg18KeygenClientNewContextWithPaillier(
+    mut env: JNIEnv,
+    _class: JClass,
+    jaddr: JString,
+    jt: jint,
+    jn: jint,
+    jdelay: jint,
+    jtoken: JString,
+    jtaskid: JString,
+    jpartytype: JString,
+    jidentity_key: JString,
+    jpeer_identity_keys: JString,
+    jcurve: JString,
+    jpaillier_modulus_bits: jint,
+    jsafe_primes: jboolean
+) -> jstring {
+    jni_call(&mut env, ptr::null_mut(), |env| {
+        // JStringをRustのStringに変換
+        let addr = get_string(env, &jaddr, "address")?;
+        let t = get_usize(jt, "t")?;
+        let n = get_usize(jn, "n")?;
+        let delay = get_u32(jdelay, "delay")?;
+        let token = get_string(env, &jtoken, "token")?;
+        let task_id = get_string(env, &jtaskid, "taskId")?;
+        let party_type = get_string(env, &jpartytype, "party type")?;
+        let identity_key = get_string(env, &jidentity_key, "identity key")?;
+        let peer_identity_keys = get_string(env, &jpeer_identity_keys, "peer identity keys")?;
+        let curve = get_string(env, &jcurve, "curve")?;
+
+        let result_str = block_on(gg18_keygen_client_new_context_with_paillier(
+            addr,
+            t,
+            n,
+            delay,
+            token,
+            task_id,
+            party_type,
+            identity_key,
+            peer_identity_keys,
+            curve,
+            get_usize(jpaillier_modulus_bits, "paillier_modulus_bits")?,
+            jsafe_primes == JNI_TRUE,
+        ))?;
+        new_string(env, result_str)
+    })
+}
+
+/// JNIラッパー: GG18RawInterface.gg18SignClientNewContext(String, int, int, String, String, String, String, String, String, String)
+/// secp256k1 の鍵を扱う
 #[no_mangle]
 pub extern "system" fn Java_jp_datasign_bunsin_1wallet_cryptography_multiparty_1ecdsa_GG18RawInterface_gg18SignClientNewContext(
     mut env: JNIEnv,
@@ -116,51 +386,304 @@ pub extern "system" fn Java_jp_datasign_bunsin_1wallet_cryptography_multiparty_1
     jmessage: JString,
     jtoken: JString,
     jtaskid: JString,
-    jpartytype: JString
-) -> jstring {
-    // 各JStringをRustのStringに変換
-    let addr: String = env
-        .get_string(&jaddr)
-        .expect("Invalid address string")
-        .into();
-    let t: usize = jt as usize;
-    let n: usize = jn as usize;
-    let key_store: String = env
-        .get_string(&jkey_store)
-        .expect("Invalid key_store string")
-        .into();
-    let message: String = env
-        .get_string(&jmessage)
-        .expect("Invalid message string")
-        .into();
-    let token: String = env
-        .get_string(&jtoken)
-        .expect("Invalid token string")
-        .into();
-    let task_id: String = env
-         .get_string(&jtaskid)
-         .expect("Invalid taskId string")
-         .into();
-    let party_type: String = env
-        .get_string(&jpartytype)
-        .expect("Invalid party type string")
-        .into();
-    let rt = Runtime::new().unwrap();
-
-    // Rustの関数を呼び出す
-    match rt.block_on(gg18_sign_client_new_context(addr, t, n, key_store, message, token, task_id, party_type)) {
-        Ok(result_str) => {
-            // 結果文字列をJStringに変換して返す
-            env.new_string(result_str)
-                .expect("Couldn't create java string")
-                .into_raw()
-        }
-        Err(e) => {
-            // エラーが発生した場合、Java例外を投げる
-            let _ = env.throw_new("java/lang/RuntimeException", format!("Error: {:?}", e));
-            ptr::null_mut()
-        }
-    }
+    jpartytype: JString,
+    jidentity_key: JString,
+    jpeer_identity_keys: JString
+) -> jstring {
+    jni_call(&mut env, ptr::null_mut(), |env| {
+        // 各JStringをRustのStringに変換
+        let addr = get_string(env, &jaddr, "address")?;
+        let t = get_usize(jt, "t")?;
+        let n = get_usize(jn, "n")?;
+        let key_store = get_string(env, &jkey_store, "key_store")?;
+        let message = get_string(env, &jmessage, "message")?;
+        let token = get_string(env, &jtoken, "token")?;
+        let task_id = get_string(env, &jtaskid, "taskId")?;
+        let party_type = get_string(env, &jpartytype, "party type")?;
+        let identity_key = get_string(env, &jidentity_key, "identity key")?;
+        // 他パーティの識別公開鍵 (16進数) の JSON 配列
+        let peer_identity_keys = get_string(env, &jpeer_identity_keys, "peer identity keys")?;
+
+        // Rustの関数を呼び出す
+        let result_str = block_on(gg18_sign_client_new_context(
+            addr,
+            t,
+            n,
+            key_store,
+            message,
+            token,
+            task_id,
+            party_type,
+            identity_key,
+            peer_identity_keys,
+        ))?;
+        new_string(env, result_str)
+    })
+}
+
+/// JNIラッパー: GG18RawInterface.gg18SignClientNewContextWithCurve(String, int, int, String, String, String, String, String, String, String, String)
+/// 曲線を指定する。"secp256k1" (空文字列も同じ) または "p256"
+#[no_mangle]
+pub extern "system" fn Java_jp_datasign_bunsin_1wallet_cryptography_multiparty_1ecdsa_GG18RawInterface_gg18SignClientNewContextWithCurve(
+    mut env: JNIEnv,
+    _class: JClass,
+    jaddr: JString,
+    jt: jint,
+    jn: jint,
+    jkey_store: JString,
+    jmessage: JString,
+    jtoken: JString,
+    jtaskid: JString,
+    jpartytype: JString,
+    jidentity_key: JString,
+    jpeer_identity_keys: JString,
+    jcurve: JString
+) -> jstring {
+    jni_call(&mut env, ptr::null_mut(), |env| {
+        // 各JStringをRustのStringに変換
+        let addr = get_string(env, &jaddr, "address")?;
+        let t = get_usize(jt, "t")?;
+        let n = get_usize(jn, "n")?;
+        let key_store = get_string(env, &jkey_store, "key_store")?;
+        let message = get_string(env, &jmessage, "message")?;
+        let token = get_string(env, &jtoken, "token")?;
+        let task_id = get_string(env, &jtaskid, "taskId")?;
+        let party_type = get_string(env, &jpartytype, "party type")?;
+        let identity_key = get_string(env, &jidentity_key, "identity key")?;
+        // 他パーティの識別公開鍵 (16進数) の JSON 配列
+        let peer_identity_keys = get_string(env, &jpeer_identity_keys, "peer identity keys")?;
+        // "secp256k1" (空文字列も同じ) または "p256"
+        let curve = get_string(env, &jcurve, "curve")?;
+
+        // Rustの関数を呼び出す
+        let result_str = block_on(gg18_sign_client_new_context_with_curve(
+            addr,
+            t,
+            n,
+            key_store,
+            message,
+            token,
+            task_id,
+            party_type,
+            identity_key,
+            peer_identity_keys,
+            curve,
+        ))?;
+        new_string(env, result_str)
+    })
+}
+
+/// JNIラッパー: GG18RawInterface.gg18KeygenClientNextRound(String)
+#[no_mangle]
+pub extern "system" fn Java_jp_datasign_bunsin_1wallet_cryptography_multiparty_1ecdsa_GG18RawInterface_gg18KeygenClientNextRound(
+    mut env: JNIEnv,
+    _class: JClass,
+    jcontext: JString
+) -> jint {
+    jni_call(&mut env, -1, |env| {
+        let context = get_string(env, &jcontext, "context")?;
+
+        // 保存済みのコンテキストから次に呼ぶラウンド番号を返す（アプリ再起動後の再開用）
+        Ok(gg18_keygen_client_next_round(context)? as jint)
+    })
+}
+
+/// JNIラッパー: GG18RawInterface.gg18SignClientNextRound(String)
+#[no_mangle]
+pub extern "system" fn Java_jp_datasign_bunsin_1wallet_cryptography_multiparty_1ecdsa_GG18RawInterface_gg18SignClientNextRound(
+    mut env: JNIEnv,
+    _class: JClass,
+    jcontext: JString
+) -> jint {
+    jni_call(&mut env, -1, |env| {
+        let context = get_string(env, &jcontext, "context")?;
+
+        // 保存済みのコンテキストから次に呼ぶラウンド番号を返す（アプリ再起動後の再開用）
+        Ok(gg18_sign_client_next_round(context)? as jint)
+    })
+}
+
+/// JNIラッパー: GG18RawInterface.exportKeystore(String, String)
+#[no_mangle]
+pub extern "system" fn Java_jp_datasign_bunsin_1wallet_cryptography_multiparty_1ecdsa_GG18RawInterface_exportKeystore(
+    mut env: JNIEnv,
+    _class: JClass,
+    jkey_store: JString,
+    jpassphrase: JString
+) -> jstring {
+    jni_call(&mut env, ptr::null_mut(), |env| {
+        let key_store = get_string(env, &jkey_store, "key_store")?;
+        let passphrase = get_string(env, &jpassphrase, "passphrase")?;
+
+        // Rustの関数を呼び出す（非同期処理がないのでランタイムは不要）
+        let result_str = export_keystore(key_store, passphrase)?;
+        new_string(env, result_str)
+    })
+}
+
+/// JNIラッパー: GG18RawInterface.importKeystore(String, String)
+#[no_mangle]
+pub extern "system" fn Java_jp_datasign_bunsin_1wallet_cryptography_multiparty_1ecdsa_GG18RawInterface_importKeystore(
+    mut env: JNIEnv,
+    _class: JClass,
+    jblob: JString,
+    jpassphrase: JString
+) -> jstring {
+    jni_call(&mut env, ptr::null_mut(), |env| {
+        let blob = get_string(env, &jblob, "keystore blob")?;
+        let passphrase = get_string(env, &jpassphrase, "passphrase")?;
+
+        // Rustの関数を呼び出す（非同期処理がないのでランタイムは不要）
+        let result_str = import_keystore(blob, passphrase)?;
+        new_string(env, result_str)
+    })
+}
+
+/// JNIラッパー: GG18RawInterface.gg18IdentityKeyNew()
+#[no_mangle]
+pub extern "system" fn Java_jp_datasign_bunsin_1wallet_cryptography_multiparty_1ecdsa_GG18RawInterface_gg18IdentityKeyNew(
+    mut env: JNIEnv,
+    _class: JClass
+) -> jstring {
+    jni_call(&mut env, ptr::null_mut(), |env| {
+        // 長期利用する識別鍵を生成する（プラットフォームのキーストアに保存すること）
+        let result_str = gg18_identity_key_new()?;
+        new_string(env, result_str)
+    })
+}
+
+/// JNIラッパー: GG18RawInterface.gg18IdentityPublicKey(String)
+#[no_mangle]
+pub extern "system" fn Java_jp_datasign_bunsin_1wallet_cryptography_multiparty_1ecdsa_GG18RawInterface_gg18IdentityPublicKey(
+    mut env: JNIEnv,
+    _class: JClass,
+    jidentity_key: JString
+) -> jstring {
+    jni_call(&mut env, ptr::null_mut(), |env| {
+        let identity_key = get_string(env, &jidentity_key, "identity key")?;
+
+        let result_str = gg18_identity_public_key(identity_key)?;
+        new_string(env, result_str)
+    })
+}
+
+/// JNIラッパー: GG18RawInterface.gg18SetIdentityKey(String)
+#[no_mangle]
+pub extern "system" fn Java_jp_datasign_bunsin_1wallet_cryptography_multiparty_1ecdsa_GG18RawInterface_gg18SetIdentityKey(
+    mut env: JNIEnv,
+    _class: JClass,
+    jidentity_key: JString
+) {
+    // コンテキストには識別鍵の公開鍵だけが入るので、アプリ再起動後に保存済みの
+    // コンテキストを再開する前に識別鍵を渡し直す（コンテキスト作成時は自動で設定される）
+    jni_call(&mut env, (), |env| {
+        let identity_key = get_string(env, &jidentity_key, "identity key")?;
+        gg18_set_identity_key(identity_key)
+    })
+}
+
+/// JNIラッパー: GG18RawInterface.gg18EnableContextSealing()
+#[no_mangle]
+pub extern "system" fn Java_jp_datasign_bunsin_1wallet_cryptography_multiparty_1ecdsa_GG18RawInterface_gg18EnableContextSealing(
+    mut env: JNIEnv,
+    _class: JClass
+) {
+    // ライブラリ内で生成した鍵でコンテキストを暗号化する
+    jni_call(&mut env, (), |_env| gg18_enable_context_sealing())
+}
+
+/// JNIラッパー: GG18RawInterface.gg18SetContextSealingKey(String)
+#[no_mangle]
+pub extern "system" fn Java_jp_datasign_bunsin_1wallet_cryptography_multiparty_1ecdsa_GG18RawInterface_gg18SetContextSealingKey(
+    mut env: JNIEnv,
+    _class: JClass,
+    jkey: JString
+) {
+    jni_call(&mut env, (), |env| {
+        // 16進数の32バイト鍵
+        let key = get_string(env, &jkey, "key")?;
+        gg18_set_context_sealing_key(key)
+    })
+}
+
+/// JNIラッパー: GG18RawInterface.gg18InitRuntime(int)
+#[no_mangle]
+pub extern "system" fn Java_jp_datasign_bunsin_1wallet_cryptography_multiparty_1ecdsa_GG18RawInterface_gg18InitRuntime(
+    mut env: JNIEnv,
+    _class: JClass,
+    jworker_threads: jint
+) {
+    // 全ラウンドで共有する非同期ランタイムを一度だけ生成する（0 はコア数）
+    // 呼ばなかった場合は最初のラウンド呼び出し時に既定の設定で生成される
+    jni_call(&mut env, (), |_env| {
+        init_runtime(get_usize(jworker_threads, "worker_threads")?)
+    })
+}
+
+/// JNIラッパー: GG18RawInterface.gg18InitLogging(String)
+#[no_mangle]
+pub extern "system" fn Java_jp_datasign_bunsin_1wallet_cryptography_multiparty_1ecdsa_GG18RawInterface_gg18InitLogging(
+    mut env: JNIEnv,
+    _class: JClass,
+    jlevel: JString
+) {
+    // ライブラリのログを logcat に転送する（"off", "error", "warn", "info", "debug", "trace"）
+    // セッション UUID・パーティー番号・ラウンド・経過時間のみを出力し、秘密情報は出力しない
+    jni_call(&mut env, (), |env| {
+        let level = get_string(env, &jlevel, "level")?;
+        init_logging(level)
+    })
+}
+
+/// JNIラッパー: GG18RawInterface.gg18SetRetryPolicy(int, int, int)
+#[no_mangle]
+pub extern "system" fn Java_jp_datasign_bunsin_1wallet_cryptography_multiparty_1ecdsa_GG18RawInterface_gg18SetRetryPolicy(
+    mut env: JNIEnv,
+    _class: JClass,
+    jmax_attempts: jint,
+    jbase_delay_ms: jint,
+    jmax_delay_ms: jint
+) {
+    // マネージャーへのリクエストの再試行回数（初回を含む）と待ち時間（指数的に増加、ジッターあり）を設定する
+    // トークンが拒否された場合（401/403）は再試行せず、エラーコード 204 の TssException を投げる
+    jni_call(&mut env, (), |_env| {
+        set_retry_policy(
+            get_u32(jmax_attempts, "max_attempts")?,
+            get_u32(jbase_delay_ms, "base_delay_ms")?,
+            get_u32(jmax_delay_ms, "max_delay_ms")?,
+        )
+    })
+}
+
+/// JNIラッパー: GG18RawInterface.gg18DisableContextSealing()
+#[no_mangle]
+pub extern "system" fn Java_jp_datasign_bunsin_1wallet_cryptography_multiparty_1ecdsa_GG18RawInterface_gg18DisableContextSealing(
+    mut env: JNIEnv,
+    _class: JClass
+) {
+    jni_call(&mut env, (), |_env| {
+        gg18_disable_context_sealing();
+        Ok(())
+    })
+}
+
+/// JNIラッパー: GG18RawInterface.gg18EnableEchoBroadcast()
+#[no_mangle]
+pub extern "system" fn Java_jp_datasign_bunsin_1wallet_cryptography_multiparty_1ecdsa_GG18RawInterface_gg18EnableEchoBroadcast(
+    _env: JNIEnv,
+    _class: JClass
+) {
+    // ブロードキャストラウンドごとに受信内容のハッシュを全員で照合する（全パーティで有効にすること）
+    gg18_enable_echo_broadcast();
+}
+
+/// JNIラッパー: GG18RawInterface.gg18DisableEchoBroadcast()
+#[no_mangle]
+pub extern "system" fn Java_jp_datasign_bunsin_1wallet_cryptography_multiparty_1ecdsa_GG18RawInterface_gg18DisableEchoBroadcast(
+    _env: JNIEnv,
+    _class: JClass
+) {
+    gg18_disable_echo_broadcast();
 }
 
 // キー生成系ラッパー関数
@@ -226,3 +749,458 @@ jni_round_wrapper!(
     Java_jp_datasign_bunsin_1wallet_cryptography_multiparty_1ecdsa_GG18RawInterface_gg18SignClientRound9,
     gg18_sign_client_round9
 );
+
+/// JNIラッパー: GG18RawInterface.gg18SetKeypairPoolKey(String)
+#[no_mangle]
+pub extern "system" fn Java_jp_datasign_bunsin_1wallet_cryptography_multiparty_1ecdsa_GG18RawInterface_gg18SetKeypairPoolKey(
+    mut env: JNIEnv,
+    _class: JClass,
+    jkey: JString
+) {
+    jni_call(&mut env, (), |env| {
+        // 16進数の32バイト鍵
+        let key = get_string(env, &jkey, "key")?;
+        gg18_set_keypair_pool_key(key)
+    })
+}
+
+/// JNIラッパー: GG18RawInterface.gg18KeypairPoolFill(int, int, boolean)
+#[no_mangle]
+pub extern "system" fn Java_jp_datasign_bunsin_1wallet_cryptography_multiparty_1ecdsa_GG18RawInterface_gg18KeypairPoolFill(
+    mut env: JNIEnv,
+    _class: JClass,
+    jcount: jint,
+    jmodulus_bits: jint,
+    jsafe_primes: jboolean
+) -> jint {
+    // 素数探索が終わるまで戻らないので、UIスレッド以外から呼び出すこと
+    jni_call(&mut env, 0, |_env| {
+        let size = gg18_keypair_pool_fill(
+            get_usize(jcount, "count")?,
+            get_usize(jmodulus_bits, "modulus_bits")?,
+            jsafe_primes == JNI_TRUE,
+        )?;
+        Ok(size as jint)
+    })
+}
+
+/// JNIラッパー: GG18RawInterface.gg18KeypairPoolSize()
+#[no_mangle]
+pub extern "system" fn Java_jp_datasign_bunsin_1wallet_cryptography_multiparty_1ecdsa_GG18RawInterface_gg18KeypairPoolSize(
+    mut env: JNIEnv,
+    _class: JClass
+) -> jint {
+    jni_call(&mut env, 0, |_env| Ok(gg18_keypair_pool_size() as jint))
+}
+
+/// JNIラッパー: GG18RawInterface.gg18KeypairPoolExport()
+#[no_mangle]
+pub extern "system" fn Java_jp_datasign_bunsin_1wallet_cryptography_multiparty_1ecdsa_GG18RawInterface_gg18KeypairPoolExport(
+    mut env: JNIEnv,
+    _class: JClass
+) -> jstring {
+    jni_call(&mut env, ptr::null_mut(), |env| {
+        // プールは空になる。以前のエクスポートを上書きして保存すること（鍵ペアの再利用を防ぐため）
+        let result_str = gg18_keypair_pool_export()?;
+        new_string(env, result_str)
+    })
+}
+
+/// JNIラッパー: GG18RawInterface.gg18KeypairPoolImport(String)
+#[no_mangle]
+pub extern "system" fn Java_jp_datasign_bunsin_1wallet_cryptography_multiparty_1ecdsa_GG18RawInterface_gg18KeypairPoolImport(
+    mut env: JNIEnv,
+    _class: JClass,
+    jpool: JString
+) -> jint {
+    jni_call(&mut env, 0, |env| {
+        let pool = get_string(env, &jpool, "keypair pool")?;
+        Ok(gg18_keypair_pool_import(pool)? as jint)
+    })
+}
+
+/// JNIラッパー: GG18RawInterface.eddsaKeygenClientNewContext(String, int, int, int, String, String, String, String, String)
+#[no_mangle]
+pub extern "system" fn Java_jp_datasign_bunsin_1wallet_cryptography_multiparty_1ecdsa_GG18RawInterface_eddsaKeygenClientNewContext(
+    mut env: JNIEnv,
+    _class: JClass,
+    jaddr: JString,
+    jt: jint,
+    jn: jint,
+    jdelay: jint,
+    jtoken: JString,
+    jtaskid: JString,
+    jpartytype: JString,
+    jidentity_key: JString,
+    jpeer_identity_keys: JString
+) -> jstring {
+    jni_call(&mut env, ptr::null_mut(), |env| {
+        // JStringをRustのStringに変換
+        let addr = get_string(env, &jaddr, "address")?;
+        let t = get_usize(jt, "t")?;
+        let n = get_usize(jn, "n")?;
+        let delay = get_u32(jdelay, "delay")?;
+        let token = get_string(env, &jtoken, "token")?;
+        let task_id = get_string(env, &jtaskid, "taskId")?;
+        let party_type = get_string(env, &jpartytype, "party type")?;
+        let identity_key = get_string(env, &jidentity_key, "identity key")?;
+        // 他パーティの識別公開鍵 (16進数) の JSON 配列
+        let peer_identity_keys = get_string(env, &jpeer_identity_keys, "peer identity keys")?;
+
+        // Rustの関数を呼び出す
+        let result_str = block_on(eddsa_keygen_client_new_context(
+            addr,
+            t,
+            n,
+            delay,
+            token,
+            task_id,
+            party_type,
+            identity_key,
+            peer_identity_keys,
+        ))?;
+        new_string(env, result_str)
+    })
+}
+
+/// JNIラッパー: GG18RawInterface.eddsaSignClientNewContext(String, int, int, String, String, String, String, String, String, String)
+#[no_mangle]
+pub extern "system" fn Java_jp_datasign_bunsin_1wallet_cryptography_multiparty_1ecdsa_GG18RawInterface_eddsaSignClientNewContext(
+    mut env: JNIEnv,
+    _class: JClass,
+    jaddr: JString,
+    jt: jint,
+    jn: jint,
+    jkey_store: JString,
+    jmessage: JString,
+    jtoken: JString,
+    jtaskid: JString,
+    jpartytype: JString,
+    jidentity_key: JString,
+    jpeer_identity_keys: JString
+) -> jstring {
+    jni_call(&mut env, ptr::null_mut(), |env| {
+        // 各JStringをRustのStringに変換
+        let addr = get_string(env, &jaddr, "address")?;
+        let t = get_usize(jt, "t")?;
+        let n = get_usize(jn, "n")?;
+        let key_store = get_string(env, &jkey_store, "key_store")?;
+        // EdDSA ではハッシュ前のメッセージをそのまま渡す
+        let message = get_string(env, &jmessage, "message")?;
+        let token = get_string(env, &jtoken, "token")?;
+        let task_id = get_string(env, &jtaskid, "taskId")?;
+        let party_type = get_string(env, &jpartytype, "party type")?;
+        let identity_key = get_string(env, &jidentity_key, "identity key")?;
+        // 他パーティの識別公開鍵 (16進数) の JSON 配列
+        let peer_identity_keys = get_string(env, &jpeer_identity_keys, "peer identity keys")?;
+
+        // Rustの関数を呼び出す
+        let result_str = block_on(eddsa_sign_client_new_context(
+            addr,
+            t,
+            n,
+            key_store,
+            message,
+            token,
+            task_id,
+            party_type,
+            identity_key,
+            peer_identity_keys,
+        ))?;
+        new_string(env, result_str)
+    })
+}
+
+/// JNIラッパー: GG18RawInterface.eddsaKeygenClientNextRound(String)
+#[no_mangle]
+pub extern "system" fn Java_jp_datasign_bunsin_1wallet_cryptography_multiparty_1ecdsa_GG18RawInterface_eddsaKeygenClientNextRound(
+    mut env: JNIEnv,
+    _class: JClass,
+    jcontext: JString
+) -> jint {
+    jni_call(&mut env, -1, |env| {
+        let context = get_string(env, &jcontext, "context")?;
+
+        // 保存済みのコンテキストから次に呼ぶラウンド番号を返す（アプリ再起動後の再開用）
+        Ok(eddsa_keygen_client_next_round(context)? as jint)
+    })
+}
+
+/// JNIラッパー: GG18RawInterface.eddsaSignClientNextRound(String)
+#[no_mangle]
+pub extern "system" fn Java_jp_datasign_bunsin_1wallet_cryptography_multiparty_1ecdsa_GG18RawInterface_eddsaSignClientNextRound(
+    mut env: JNIEnv,
+    _class: JClass,
+    jcontext: JString
+) -> jint {
+    jni_call(&mut env, -1, |env| {
+        let context = get_string(env, &jcontext, "context")?;
+
+        // 保存済みのコンテキストから次に呼ぶラウンド番号を返す（アプリ再起動後の再開用）
+        Ok(eddsa_sign_client_next_round(context)? as jint)
+    })
+}
+
+// EdDSA キー生成系ラッパー関数
+jni_round_wrapper!(
+    Java_jp_datasign_bunsin_1wallet_cryptography_multiparty_1ecdsa_GG18RawInterface_eddsaKeygenClientRound1,
+    eddsa_keygen_client_round1
+);
+jni_round_wrapper!(
+    Java_jp_datasign_bunsin_1wallet_cryptography_multiparty_1ecdsa_GG18RawInterface_eddsaKeygenClientRound2,
+    eddsa_keygen_client_round2
+);
+jni_round_wrapper!(
+    Java_jp_datasign_bunsin_1wallet_cryptography_multiparty_1ecdsa_GG18RawInterface_eddsaKeygenClientRound3,
+    eddsa_keygen_client_round3
+);
+
+// EdDSA 署名系ラッパー関数
+jni_round_wrapper!(
+    Java_jp_datasign_bunsin_1wallet_cryptography_multiparty_1ecdsa_GG18RawInterface_eddsaSignClientRound0,
+    eddsa_sign_client_round0
+);
+jni_round_wrapper!(
+    Java_jp_datasign_bunsin_1wallet_cryptography_multiparty_1ecdsa_GG18RawInterface_eddsaSignClientRound1,
+    eddsa_sign_client_round1
+);
+jni_round_wrapper!(
+    Java_jp_datasign_bunsin_1wallet_cryptography_multiparty_1ecdsa_GG18RawInterface_eddsaSignClientRound2,
+    eddsa_sign_client_round2
+);
+jni_round_wrapper!(
+    Java_jp_datasign_bunsin_1wallet_cryptography_multiparty_1ecdsa_GG18RawInterface_eddsaSignClientRound3,
+    eddsa_sign_client_round3
+);
+jni_round_wrapper!(
+    Java_jp_datasign_bunsin_1wallet_cryptography_multiparty_1ecdsa_GG18RawInterface_eddsaSignClientRound4,
+    eddsa_sign_client_round4
+);
+
+/// JNIラッパー: GG18RawInterface.gg18KeygenClientRun(String, int, int, int, String, String, String, String, String, String, int, boolean, Object)
+/// キー生成を最後のラウンドまで実行し、{"keyStore", "publicKey"} の JSON を返す
+/// Paillier 鍵の指定は gg18KeygenClientNewContextWithPaillier と同じ (既定は 2048 と false)
+#[no_mangle]
+pub extern "system" fn Java_jp_datasign_bunsin_1wallet_cryptography_multiparty_1ecdsa_GG18RawInterface_gg18KeygenClientRun(
+    mut env: JNIEnv,
+    _class: JClass,
+    jaddr: JString,
+    jt: jint,
+    jn: jint,
+    jdelay: jint,
+    jtoken: JString,
+    jtaskid: JString,
+    jpartytype: JString,
+    jidentity_key: JString,
+    jpeer_identity_keys: JString,
+    jcurve: JString,
+    jpaillier_modulus_bits: jint,
+    jsafe_primes: jboolean,
+    jprogress: JObject
+) -> jstring {
+    jni_call(&mut env, ptr::null_mut(), |env| {
+        // JStringをRustのStringに変換
+        let addr = get_string(env, &jaddr, "address")?;
+        let t = get_usize(jt, "t")?;
+        let n = get_usize(jn, "n")?;
+        let delay = get_u32(jdelay, "delay")?;
+        let token = get_string(env, &jtoken, "token")?;
+        let task_id = get_string(env, &jtaskid, "taskId")?;
+        let party_type = get_string(env, &jpartytype, "party type")?;
+        let identity_key = get_string(env, &jidentity_key, "identity key")?;
+        let peer_identity_keys = get_string(env, &jpeer_identity_keys, "peer identity keys")?;
+        let curve = get_string(env, &jcurve, "curve")?;
+
+        // 進捗を Java のコールバックに通知しながら全ラウンドを実行する
+        let progress = JavaProgress {
+            env: RefCell::new(&mut *env),
+            callback: &jprogress,
+        };
+        let result = block_on(gg18_keygen_client_run(
+            addr,
+            t,
+            n,
+            delay,
+            token,
+            task_id,
+            party_type,
+            identity_key,
+            peer_identity_keys,
+            curve,
+            get_usize(jpaillier_modulus_bits, "paillier_modulus_bits")?,
+            jsafe_primes == JNI_TRUE,
+            &progress,
+            Some(&progress),
+        ))?;
+        new_string(env, serde_json::to_string(&result)?)
+    })
+}
+
+/// JNIラッパー: GG18RawInterface.gg18SignClientRun(String, int, int, int, String, String, String, String, String, String, String, String, Object)
+/// 署名を最後のラウンドまで実行し、{"r", "s", "v"} の JSON を返す
+#[no_mangle]
+pub extern "system" fn Java_jp_datasign_bunsin_1wallet_cryptography_multiparty_1ecdsa_GG18RawInterface_gg18SignClientRun(
+    mut env: JNIEnv,
+    _class: JClass,
+    jaddr: JString,
+    jt: jint,
+    jn: jint,
+    jdelay: jint,
+    jkey_store: JString,
+    jmessage: JString,
+    jtoken: JString,
+    jtaskid: JString,
+    jpartytype: JString,
+    jidentity_key: JString,
+    jpeer_identity_keys: JString,
+    jcurve: JString,
+    jprogress: JObject
+) -> jstring {
+    jni_call(&mut env, ptr::null_mut(), |env| {
+        // 各JStringをRustのStringに変換
+        let addr = get_string(env, &jaddr, "address")?;
+        let t = get_usize(jt, "t")?;
+        let n = get_usize(jn, "n")?;
+        let delay = get_u32(jdelay, "delay")?;
+        let key_store = get_string(env, &jkey_store, "key_store")?;
+        let message = get_string(env, &jmessage, "message")?;
+        let token = get_string(env, &jtoken, "token")?;
+        let task_id = get_string(env, &jtaskid, "taskId")?;
+        let party_type = get_string(env, &jpartytype, "party type")?;
+        let identity_key = get_string(env, &jidentity_key, "identity key")?;
+        let peer_identity_keys = get_string(env, &jpeer_identity_keys, "peer identity keys")?;
+        let curve = get_string(env, &jcurve, "curve")?;
+
+        // 進捗を Java のコールバックに通知しながら全ラウンドを実行する
+        let progress = JavaProgress {
+            env: RefCell::new(&mut *env),
+            callback: &jprogress,
+        };
+        let result = block_on(gg18_sign_client_run(
+            addr,
+            t,
+            n,
+            delay,
+            key_store,
+            message,
+            token,
+            task_id,
+            party_type,
+            identity_key,
+            peer_identity_keys,
+            curve,
+            &progress,
+            Some(&progress),
+        ))?;
+        new_string(env, serde_json::to_string(&result)?)
+    })
+}
+
+/// JNIラッパー: GG18RawInterface.eddsaKeygenClientRun(String, int, int, int, String, String, String, String, String, Object)
+/// EdDSA キー生成を最後のラウンドまで実行し、{"keyStore", "publicKey"} の JSON を返す
+#[no_mangle]
+pub extern "system" fn Java_jp_datasign_bunsin_1wallet_cryptography_multiparty_1ecdsa_GG18RawInterface_eddsaKeygenClientRun(
+    mut env: JNIEnv,
+    _class: JClass,
+    jaddr: JString,
+    jt: jint,
+    jn: jint,
+    jdelay: jint,
+    jtoken: JString,
+    jtaskid: JString,
+    jpartytype: JString,
+    jidentity_key: JString,
+    jpeer_identity_keys: JString,
+    jprogress: JObject
+) -> jstring {
+    jni_call(&mut env, ptr::null_mut(), |env| {
+        // JStringをRustのStringに変換
+        let addr = get_string(env, &jaddr, "address")?;
+        let t = get_usize(jt, "t")?;
+        let n = get_usize(jn, "n")?;
+        let delay = get_u32(jdelay, "delay")?;
+        let token = get_string(env, &jtoken, "token")?;
+        let task_id = get_string(env, &jtaskid, "taskId")?;
+        let party_type = get_string(env, &jpartytype, "party type")?;
+        let identity_key = get_string(env, &jidentity_key, "identity key")?;
+        let peer_identity_keys = get_string(env, &jpeer_identity_keys, "peer identity keys")?;
+
+        // 進捗を Java のコールバックに通知しながら全ラウンドを実行する
+        let progress = JavaProgress {
+            env: RefCell::new(&mut *env),
+            callback: &jprogress,
+        };
+        let result = block_on(eddsa_keygen_client_run(
+            addr,
+            t,
+            n,
+            delay,
+            token,
+            task_id,
+            party_type,
+            identity_key,
+            peer_identity_keys,
+            &progress,
+            Some(&progress),
+        ))?;
+        new_string(env, serde_json::to_string(&result)?)
+    })
+}
+
+/// JNIラッパー: GG18RawInterface.eddsaSignClientRun(String, int, int, int, String, String, String, String, String, String, String, Object)
+/// EdDSA 署名を最後のラウンドまで実行し、{"signature"} の JSON を返す
+#[no_mangle]
+pub extern "system" fn Java_jp_datasign_bunsin_1wallet_cryptography_multiparty_1ecdsa_GG18RawInterface_eddsaSignClientRun(
+    mut env: JNIEnv,
+    _class: JClass,
+    jaddr: JString,
+    jt: jint,
+    jn: jint,
+    jdelay: jint,
+    jkey_store: JString,
+    jmessage: JString,
+    jtoken: JString,
+    jtaskid: JString,
+    jpartytype: JString,
+    jidentity_key: JString,
+    jpeer_identity_keys: JString,
+    jprogress: JObject
+) -> jstring {
+    jni_call(&mut env, ptr::null_mut(), |env| {
+        // 各JStringをRustのStringに変換
+        let addr = get_string(env, &jaddr, "address")?;
+        let t = get_usize(jt, "t")?;
+        let n = get_usize(jn, "n")?;
+        let delay = get_u32(jdelay, "delay")?;
+        let key_store = get_string(env, &jkey_store, "key_store")?;
+        // EdDSA ではハッシュ前のメッセージをそのまま渡す
+        let message = get_string(env, &jmessage, "message")?;
+        let token = get_string(env, &jtoken, "token")?;
+        let task_id = get_string(env, &jtaskid, "taskId")?;
+        let party_type = get_string(env, &jpartytype, "party type")?;
+        let identity_key = get_string(env, &jidentity_key, "identity key")?;
+        let peer_identity_keys = get_string(env, &jpeer_identity_keys, "peer identity keys")?;
+
+        // 進捗を Java のコールバックに通知しながら全ラウンドを実行する
+        let progress = JavaProgress {
+            env: RefCell::new(&mut *env),
+            callback: &jprogress,
+        };
+        let result = block_on(eddsa_sign_client_run(
+            addr,
+            t,
+            n,
+            delay,
+            key_store,
+            message,
+            token,
+            task_id,
+            party_type,
+            identity_key,
+            peer_identity_keys,
+            &progress,
+            Some(&progress),
+        ))?;
+        new_string(env, serde_json::to_string(&result)?)
+    })
+}
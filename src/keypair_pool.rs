@@ -0,0 +1,316 @@
+// Pre-generated Paillier keypairs.
+//
+// Prime search dominates keygen round1, so the host can fill a pool while the
+// device is idle (from a worker thread, an idle callback, a Web Worker) and
+// `Keys::create` takes a keypair from it instead of searching inline. Pooled
+// keypairs are kept sealed under the pool key and bound to their modulus size
+// and prime kind, so Blum and safe-prime keypairs never stand in for each
+// other. A keypair taken by a keygen is remembered for the rest of the
+// process and cannot be imported again.
+
+use crate::common::{aes_decrypt, aes_encrypt, AEAD, AES_KEY_BYTES_LEN};
+use crate::curv::arithmetic::traits::Converter;
+use crate::errors::{Result, TssError};
+use crate::gg_2018::party_i::check_paillier_modulus_bits;
+use crate::paillier::{KeyGeneration, Keypair, Paillier, PrimeKind};
+
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::sync::Mutex;
+use zeroize::Zeroize;
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+pub const KEYPAIR_POOL_VERSION: u32 = 1;
+/// Most keypairs the pool holds; a keygen needs one per party.
+pub const KEYPAIR_POOL_MAX_SIZE: usize = 16;
+
+lazy_static::lazy_static! {
+    static ref POOL: Mutex<Pool> = Mutex::new(Pool {
+        key: None,
+        keypairs: Vec::new(),
+        taken: Vec::new(),
+    });
+}
+
+struct Pool {
+    // created on first use unless the host sets one to persist the pool
+    key: Option<[u8; AES_KEY_BYTES_LEN]>,
+    keypairs: Vec<PooledKeypair>,
+    // fingerprints of the keypairs handed to a keygen
+    taken: Vec<[u8; 32]>,
+}
+
+/// A sealed keypair together with what it was generated for.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct PooledKeypair {
+    pub modulus_bits: usize,
+    pub prime_kind: PrimeKind,
+    pub sealed: AEAD,
+}
+
+/// Exported pool as handed to the host for storage.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct KeypairPool {
+    pub version: u32,
+    pub keypairs: Vec<PooledKeypair>,
+}
+
+/// Seals the pool under the given hex encoded 32 byte key, e.g. one kept in the
+/// platform key store so an exported pool can be imported after a restart.
+/// Keypairs already in the pool are resealed under the new key.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn gg18_set_keypair_pool_key(key: String) -> Result<()> {
+    let mut bytes = hex::decode(&key).map_err(|_| TssError::InvalidKey)?;
+    if bytes.len() != AES_KEY_BYTES_LEN {
+        bytes.zeroize();
+        return Err(TssError::InvalidKey);
+    }
+    let mut new_key = [0u8; AES_KEY_BYTES_LEN];
+    new_key.copy_from_slice(&bytes);
+    bytes.zeroize();
+
+    let mut pool = lock_pool();
+    let mut old_key = pool.key()?;
+    let resealed = pool
+        .keypairs
+        .iter()
+        .map(|pooled| {
+            let keypair = open(&old_key, pooled)?;
+            seal(&new_key, pooled.modulus_bits, pooled.prime_kind, &keypair)
+        })
+        .collect::<Result<Vec<_>>>();
+    old_key.zeroize();
+    if let Some(old) = pool.key.as_mut() {
+        old.zeroize();
+    }
+    pool.keypairs = resealed?;
+    pool.key = Some(new_key);
+    new_key.zeroize();
+    Ok(())
+}
+
+/// Generates `count` keypairs with a `modulus_bits` modulus (2048 to 8192) and
+/// adds them to the pool, which holds at most `KEYPAIR_POOL_MAX_SIZE`. Blocks
+/// for the whole prime search, so call it off the UI thread or one keypair at
+/// a time while idle. Returns the number of pooled keypairs.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn gg18_keypair_pool_fill(
+    count: usize,
+    modulus_bits: usize,
+    safe_primes: bool,
+) -> Result<usize> {
+    check_paillier_modulus_bits(modulus_bits)?;
+    let prime_kind = match safe_primes {
+        true => PrimeKind::Safe,
+        false => PrimeKind::Blum,
+    };
+    fill(count, modulus_bits, prime_kind)
+}
+
+// gg18_keypair_pool_fill for any modulus size, so the tests can use small ones
+fn fill(count: usize, modulus_bits: usize, prime_kind: PrimeKind) -> Result<usize> {
+    if count > KEYPAIR_POOL_MAX_SIZE.saturating_sub(gg18_keypair_pool_size()) {
+        return Err(TssError::InvalidArgument {
+            name: "count".to_string(),
+        });
+    }
+    for _ in 0..count {
+        let keypair = Paillier::keypair_with_prime_kind(modulus_bits, prime_kind);
+        // the prime search runs unlocked, so a keygen can take from the pool meanwhile
+        let mut pool = lock_pool();
+        if pool.keypairs.len() >= KEYPAIR_POOL_MAX_SIZE {
+            // filled up by another caller in the meantime
+            break;
+        }
+        let mut key = pool.key()?;
+        let sealed = seal(&key, modulus_bits, prime_kind, &keypair);
+        key.zeroize();
+        pool.keypairs.push(sealed?);
+    }
+    Ok(gg18_keypair_pool_size())
+}
+
+/// Number of keypairs in the pool.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn gg18_keypair_pool_size() -> usize {
+    lock_pool().keypairs.len()
+}
+
+/// Moves every pooled keypair out of the library as sealed JSON. A keypair must
+/// only ever be used once: store the export in place of any earlier one and
+/// import it a single time.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn gg18_keypair_pool_export() -> Result<String> {
+    let keypairs = std::mem::take(&mut lock_pool().keypairs);
+    Ok(serde_json::to_string(&KeypairPool {
+        version: KEYPAIR_POOL_VERSION,
+        keypairs,
+    })?)
+}
+
+/// Adds the keypairs of an exported pool. They must have been sealed under the
+/// current pool key, and none may be in the pool already or have been taken by
+/// a keygen of this process. Returns the number of pooled keypairs.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn gg18_keypair_pool_import(keypair_pool: String) -> Result<usize> {
+    let keypair_pool: KeypairPool =
+        serde_json::from_str(&keypair_pool).map_err(|_| TssError::InvalidKeypairPool)?;
+    if keypair_pool.version != KEYPAIR_POOL_VERSION {
+        return Err(TssError::InvalidKeypairPool);
+    }
+
+    let mut pool = lock_pool();
+    if pool.keypairs.len() + keypair_pool.keypairs.len() > KEYPAIR_POOL_MAX_SIZE {
+        return Err(TssError::InvalidKeypairPool);
+    }
+    let mut key = pool.key()?;
+    let fingerprints = pool
+        .keypairs
+        .iter()
+        .chain(keypair_pool.keypairs.iter())
+        .map(|pooled| open(&key, pooled).map(|keypair| fingerprint(&keypair)))
+        .collect::<Result<Vec<_>>>();
+    key.zeroize();
+    let fingerprints = fingerprints.map_err(|_| TssError::InvalidKeypairPool)?;
+
+    // a Paillier keypair must never serve two keygens
+    let mut seen = pool.taken.clone();
+    for fingerprint in fingerprints {
+        if seen.contains(&fingerprint) {
+            return Err(TssError::InvalidKeypairPool);
+        }
+        seen.push(fingerprint);
+    }
+    pool.keypairs.extend(keypair_pool.keypairs);
+    Ok(pool.keypairs.len())
+}
+
+/// Removes a pooled keypair of the given size and kind from the pool, if any.
+pub fn take_keypair(modulus_bits: usize, prime_kind: PrimeKind) -> Option<Keypair> {
+    let mut pool = lock_pool();
+    let position = pool.keypairs.iter().position(|pooled| {
+        pooled.modulus_bits == modulus_bits && pooled.prime_kind == prime_kind
+    })?;
+    let pooled = pool.keypairs.remove(position);
+    let mut key = pool.key().ok()?;
+    let keypair = open(&key, &pooled).ok();
+    key.zeroize();
+    let keypair = keypair?;
+    pool.taken.push(fingerprint(&keypair));
+    Some(keypair)
+}
+
+impl Pool {
+    fn key(&mut self) -> Result<[u8; AES_KEY_BYTES_LEN]> {
+        if let Some(key) = self.key {
+            return Ok(key);
+        }
+        let mut key = [0u8; AES_KEY_BYTES_LEN];
+        OsRng::new()?.fill_bytes(&mut key);
+        self.key = Some(key);
+        Ok(key)
+    }
+}
+
+fn lock_pool() -> std::sync::MutexGuard<'static, Pool> {
+    POOL.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+// hash of the modulus, to recognise a keypair without keeping its primes around
+fn fingerprint(keypair: &Keypair) -> [u8; 32] {
+    let n = &keypair.p * &keypair.q;
+    Sha256::digest(n.to_hex().as_bytes()).into()
+}
+
+// modulus size and prime kind go into the AAD, so they cannot be swapped
+fn aad(modulus_bits: usize, prime_kind: PrimeKind) -> Vec<u8> {
+    format!("{}-{:?}", modulus_bits, prime_kind).into_bytes()
+}
+
+fn seal(
+    key: &[u8],
+    modulus_bits: usize,
+    prime_kind: PrimeKind,
+    keypair: &Keypair,
+) -> Result<PooledKeypair> {
+    let mut plaintext = serde_json::to_vec(keypair)?;
+    let sealed = aes_encrypt(key, &plaintext, &aad(modulus_bits, prime_kind));
+    plaintext.zeroize();
+    Ok(PooledKeypair {
+        modulus_bits,
+        prime_kind,
+        sealed: sealed?,
+    })
+}
+
+fn open(key: &[u8], pooled: &PooledKeypair) -> Result<Keypair> {
+    let aad = aad(pooled.modulus_bits, pooled.prime_kind);
+    let mut plaintext =
+        aes_decrypt(key, pooled.sealed.clone(), &aad).map_err(|_| TssError::InvalidKeypairPool)?;
+    let keypair = serde_json::from_slice(&plaintext);
+    plaintext.zeroize();
+    Ok(keypair?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
+
+    // the pool is process wide, so every case runs in a single test with a
+    // modulus size no other test uses
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_keypair_pool() {
+        let bits = 384;
+        assert!(take_keypair(bits, PrimeKind::Blum).is_none());
+
+        // sizes below the protocol minimum and overfilling are refused
+        assert!(gg18_keypair_pool_fill(1, bits, false).is_err());
+        assert!(fill(KEYPAIR_POOL_MAX_SIZE + 1, bits, PrimeKind::Blum).is_err());
+
+        fill(2, bits, PrimeKind::Blum).unwrap();
+        let exported = gg18_keypair_pool_export().unwrap();
+
+        // a modified size is caught by the AAD
+        let mut tampered: KeypairPool = serde_json::from_str(&exported).unwrap();
+        tampered.keypairs[0].modulus_bits = 2048;
+        assert!(gg18_keypair_pool_import(serde_json::to_string(&tampered).unwrap()).is_err());
+
+        // keypairs already in the pool are refused
+        gg18_keypair_pool_import(exported.clone()).unwrap();
+        assert!(gg18_keypair_pool_import(exported.clone()).is_err());
+
+        let keypair = take_keypair(bits, PrimeKind::Blum).unwrap();
+        let (ek, _) = keypair.keys();
+        assert_eq!(ek.n.bits(), bits);
+        assert!(take_keypair(bits, PrimeKind::Safe).is_none());
+
+        // the export carries no plaintext primes and empties the pool
+        let remaining = gg18_keypair_pool_export().unwrap();
+        assert!(!remaining.contains(&keypair.p.to_string()));
+        assert!(take_keypair(bits, PrimeKind::Blum).is_none());
+
+        // the earlier export still holds the keypair that was taken
+        assert!(gg18_keypair_pool_import(exported).is_err());
+        let mut twice: KeypairPool = serde_json::from_str(&remaining).unwrap();
+        twice.keypairs.push(twice.keypairs[0].clone());
+        assert!(gg18_keypair_pool_import(serde_json::to_string(&twice).unwrap()).is_err());
+
+        // keypairs are resealed when the key changes and need it to be imported
+        gg18_keypair_pool_import(remaining.clone()).unwrap();
+        gg18_set_keypair_pool_key(hex::encode([7u8; AES_KEY_BYTES_LEN])).unwrap();
+        let resealed = gg18_keypair_pool_export().unwrap();
+        assert!(gg18_keypair_pool_import(remaining).is_err());
+        gg18_keypair_pool_import(resealed).unwrap();
+
+        let other = take_keypair(bits, PrimeKind::Blum).unwrap();
+        assert_ne!(other, keypair);
+        assert!(take_keypair(bits, PrimeKind::Blum).is_none());
+    }
+}
@@ -0,0 +1,313 @@
+use crate::common::{aes_decrypt, aes_encrypt, AEAD, AES_KEY_BYTES_LEN};
+use crate::curv::cryptographic_primitives::secret_sharing::feldman_vss::VerifiableSS;
+use crate::curv::elliptic::curves::ed25519;
+use crate::curv::elliptic::curves::secp256_k1::GE;
+use crate::curv::elliptic::curves::secp256_r1;
+use crate::eddsa;
+use crate::errors::{Result, TssError};
+use crate::gg_2018::party_i::{Keys, SharedKeys};
+use crate::paillier::EncryptionKey;
+
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+pub const KEYSTORE_VERSION: u32 = 1;
+pub const KDF_ALGORITHM: &str = "scrypt";
+pub const KDF_SALT_BYTES_LEN: usize = 16;
+
+// scrypt cost parameters: N = 2^15, r = 8, p = 1 (32 MiB of memory per derivation)
+pub const SCRYPT_LOG_N: u8 = 15;
+pub const SCRYPT_R: u32 = 8;
+pub const SCRYPT_P: u32 = 1;
+
+// upper bounds accepted on import, so a crafted blob cannot make us allocate
+// gigabytes or run scrypt for minutes: memory is 128 * r * 2^log_n bytes (at
+// most 1 GiB) and the work grows with r * p on top of that
+const SCRYPT_MAX_LOG_N: u8 = 20;
+const SCRYPT_MAX_R: u32 = 32;
+const SCRYPT_MAX_P: u32 = 16;
+const SCRYPT_MAX_R_P: u32 = 64;
+const SCRYPT_MAX_MEMORY: u64 = 1 << 30;
+
+fn kdf_params_bounded(kdf: &KdfParams) -> bool {
+    kdf.log_n <= SCRYPT_MAX_LOG_N
+        && kdf.r <= SCRYPT_MAX_R
+        && kdf.p <= SCRYPT_MAX_P
+        && kdf.r * kdf.p <= SCRYPT_MAX_R_P
+        && 128 * kdf.r as u64 * (1u64 << kdf.log_n) <= SCRYPT_MAX_MEMORY
+}
+
+/// The key store produced by `gg18_keygen_client_round5` and consumed by
+/// `gg18_sign_client_new_context`.
+pub type KeyStore<P = GE> = (
+    Keys<P>,
+    SharedKeys<P>,
+    u16,
+    Vec<VerifiableSS<P>>,
+    Vec<EncryptionKey>,
+    P,
+);
+
+/// The key store produced by `eddsa_keygen_client_round3` and consumed by
+/// `eddsa_sign_client_new_context`.
+pub type EddsaKeyStore = (
+    eddsa::party_i::Keys,
+    SharedKeys<ed25519::GE>,
+    u16,
+    Vec<VerifiableSS<ed25519::GE>>,
+    ed25519::GE,
+);
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct KdfParams {
+    pub algorithm: String,
+    pub log_n: u8,
+    pub r: u32,
+    pub p: u32,
+    pub salt: String,
+}
+
+/// Self-describing, passphrase-protected key store blob.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct EncryptedKeyStore {
+    pub version: u32,
+    pub kdf: KdfParams,
+    pub aead: AEAD,
+}
+
+/// Encrypts a plaintext key store, GG18 on any curve or EdDSA, under a key
+/// derived from `passphrase`.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn export_keystore(keystore: String, passphrase: String) -> Result<String> {
+    export_keystore_with_params(&keystore, &passphrase, SCRYPT_LOG_N, SCRYPT_R, SCRYPT_P)
+}
+
+/// Decrypts a blob produced by `export_keystore` back into the plaintext key store.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn import_keystore(blob: String, passphrase: String) -> Result<String> {
+    let encrypted: EncryptedKeyStore = serde_json::from_str(&blob)?;
+    if encrypted.version != KEYSTORE_VERSION
+        || encrypted.kdf.algorithm != KDF_ALGORITHM
+        || !kdf_params_bounded(&encrypted.kdf)
+    {
+        return Err(TssError::InvalidKeyStore);
+    }
+    let salt = hex::decode(&encrypted.kdf.salt).map_err(|_| TssError::InvalidKeyStore)?;
+
+    let mut key = derive_key(
+        passphrase.as_bytes(),
+        &salt,
+        encrypted.kdf.log_n,
+        encrypted.kdf.r,
+        encrypted.kdf.p,
+    )?;
+    let out = aes_decrypt(&key, encrypted.aead, &[]);
+    key.zeroize();
+    let plaintext = out.map_err(|_| TssError::InvalidPassphrase)?;
+
+    String::from_utf8(plaintext).map_err(|_| TssError::InvalidKeyStore)
+}
+
+pub fn export_keystore_with_params(
+    keystore: &str,
+    passphrase: &str,
+    log_n: u8,
+    r: u32,
+    p: u32,
+) -> Result<String> {
+    // refuse to wrap anything that is not a key store
+    if !is_keystore(keystore) {
+        return Err(TssError::InvalidKeyStore);
+    }
+    seal_keystore(keystore, passphrase, log_n, r, p)
+}
+
+// tries every scheme a key store can come from, as the blob does not name it
+fn is_keystore(keystore: &str) -> bool {
+    serde_json::from_str::<KeyStore>(keystore).is_ok()
+        || serde_json::from_str::<KeyStore<secp256_r1::GE>>(keystore).is_ok()
+        || serde_json::from_str::<EddsaKeyStore>(keystore).is_ok()
+}
+
+// encrypts a key store its caller has already parsed, of any curve or scheme
+pub(crate) fn seal_keystore(
+    keystore: &str,
+    passphrase: &str,
+    log_n: u8,
+    r: u32,
+    p: u32,
+) -> Result<String> {
+    let mut salt = [0u8; KDF_SALT_BYTES_LEN];
+    let mut rng = OsRng::new()?;
+    rng.fill_bytes(&mut salt);
+
+    let mut key = derive_key(passphrase.as_bytes(), &salt, log_n, r, p)?;
+    let aead = aes_encrypt(&key, keystore.as_bytes(), &[]);
+    key.zeroize();
+
+    Ok(serde_json::to_string(&EncryptedKeyStore {
+        version: KEYSTORE_VERSION,
+        kdf: KdfParams {
+            algorithm: KDF_ALGORITHM.to_string(),
+            log_n,
+            r,
+            p,
+            salt: hex::encode(salt),
+        },
+        aead: aead?,
+    })?)
+}
+
+fn derive_key(
+    passphrase: &[u8],
+    salt: &[u8],
+    log_n: u8,
+    r: u32,
+    p: u32,
+) -> Result<[u8; AES_KEY_BYTES_LEN]> {
+    let params = scrypt::Params::new(log_n, r, p).map_err(|_| TssError::InvalidKeyStore)?;
+    let mut key = [0u8; AES_KEY_BYTES_LEN];
+    scrypt::scrypt(passphrase, salt, &params, &mut key).map_err(|_e| TssError::UnknownError {
+        msg: ("scrypt").to_string(),
+        line: (line!()),
+    })?;
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curv::elliptic::curves::traits::{ECPoint, ECScalar};
+    use crate::paillier::Keypair;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
+
+    // cheap parameters so the tests stay fast; the format is the same
+    const TEST_LOG_N: u8 = 4;
+
+    fn test_keystore() -> String {
+        curve_keystore::<GE>()
+    }
+
+    fn curve_keystore<P: ECPoint>() -> String {
+        let p = str::parse("148677972634832330983979593310074301486537017973460461278300587514468301043894574906886127642530475786889672304776052879927627556769456140664043088700743909632312483413393134504352834240399191134336344285483935856491230340093391784574980688823380828143810804684752914935441384845195613674104960646037368551517").unwrap();
+        let q = str::parse("158741574437007245654463598139927898730476924736461654463975966787719309357536545869203069369466212089132653564188443272208127277664424448947476335413293018778018615899291704693105620242763173357203898195318179150836424196645745308205164116144020613415407736216097185962171301808761138424668335445923774195463").unwrap();
+        let (ek, dk) = Keypair { p, q }.keys();
+        let u_i: P::Scalar = ECScalar::new_random();
+        let y_i = P::generator() * &u_i;
+        let keys = Keys {
+            u_i: u_i.clone(),
+            y_i: y_i.clone(),
+            dk,
+            ek: ek.clone(),
+            party_index: 1,
+        };
+        let shared_keys = SharedKeys {
+            y: y_i.clone(),
+            x_i: u_i,
+        };
+        let keystore: KeyStore<P> = (keys, shared_keys, 1, vec![], vec![ek], y_i);
+        serde_json::to_string(&keystore).unwrap()
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_export_import_keystore() {
+        let keystore = test_keystore();
+        let blob =
+            export_keystore_with_params(&keystore, "passphrase", TEST_LOG_N, SCRYPT_R, SCRYPT_P)
+                .unwrap();
+        assert!(!blob.contains(&keystore));
+
+        let recovered = import_keystore(blob, "passphrase".to_string()).unwrap();
+        assert_eq!(recovered, keystore);
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_export_import_p256_keystore() {
+        let keystore = curve_keystore::<secp256_r1::GE>();
+        let blob =
+            export_keystore_with_params(&keystore, "passphrase", TEST_LOG_N, SCRYPT_R, SCRYPT_P)
+                .unwrap();
+        let recovered = import_keystore(blob, "passphrase".to_string()).unwrap();
+        assert_eq!(recovered, keystore);
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_export_import_eddsa_keystore() {
+        let keys = eddsa::party_i::Keys::create(1);
+        let shared_keys = SharedKeys {
+            y: keys.y_i.clone(),
+            x_i: keys.u_i.clone(),
+        };
+        let y = keys.y_i.clone();
+        let keystore: EddsaKeyStore = (keys, shared_keys, 1, vec![], y);
+        let keystore = serde_json::to_string(&keystore).unwrap();
+        let blob =
+            export_keystore_with_params(&keystore, "passphrase", TEST_LOG_N, SCRYPT_R, SCRYPT_P)
+                .unwrap();
+        let recovered = import_keystore(blob, "passphrase".to_string()).unwrap();
+        assert_eq!(recovered, keystore);
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_import_keystore_wrong_passphrase() {
+        let blob = export_keystore_with_params(
+            &test_keystore(),
+            "passphrase",
+            TEST_LOG_N,
+            SCRYPT_R,
+            SCRYPT_P,
+        )
+        .unwrap();
+        match import_keystore(blob, "wrong".to_string()) {
+            Err(TssError::InvalidPassphrase) => {}
+            _ => panic!("import should fail with InvalidPassphrase"),
+        }
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_import_keystore_rejects_costly_kdf() {
+        let blob = export_keystore_with_params(
+            &test_keystore(),
+            "passphrase",
+            TEST_LOG_N,
+            SCRYPT_R,
+            SCRYPT_P,
+        )
+        .unwrap();
+        let mut encrypted: EncryptedKeyStore = serde_json::from_str(&blob).unwrap();
+        for (log_n, r, p) in [(21, 8, 1), (4, 33, 1), (4, 1, 17), (4, 16, 8), (20, 16, 1)] {
+            encrypted.kdf.log_n = log_n;
+            encrypted.kdf.r = r;
+            encrypted.kdf.p = p;
+            let blob = serde_json::to_string(&encrypted).unwrap();
+            match import_keystore(blob, "passphrase".to_string()) {
+                Err(TssError::InvalidKeyStore) => {}
+                _ => panic!("import should refuse log_n {} r {} p {}", log_n, r, p),
+            }
+        }
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_export_rejects_non_keystore() {
+        let result = export_keystore_with_params(
+            "{\"not\":\"a keystore\"}",
+            "passphrase",
+            TEST_LOG_N,
+            SCRYPT_R,
+            SCRYPT_P,
+        );
+        assert!(result.is_err());
+    }
+}
@@ -16,6 +16,7 @@ extern crate sha2;
 
 pub mod curv;
 
+pub mod eddsa;
 pub mod gg_2018;
 pub mod paillier;
 
@@ -24,6 +25,14 @@ pub mod common;
 
 pub mod api;
 pub mod errors;
+pub mod ffi;
+pub mod identity;
+pub mod keypair_pool;
+pub mod keystore;
+pub mod logging;
+pub mod runtime;
+pub mod sealed_context;
+pub mod worker;
 pub mod jni;
 
 #[cfg(target_arch = "wasm32")]
@@ -32,32 +41,20 @@ extern crate wasm_bindgen;
 #[cfg(all(test, target_arch = "wasm32"))]
 extern crate wasm_bindgen_test;
 
-#[cfg(target_arch = "wasm32")]
-use wasm_bindgen::prelude::*;
+// the session drivers of the integration tests, which name this crate
+// `tss_wasm`, also serve the unit tests
+#[cfg(test)]
+extern crate self as tss_wasm;
+#[cfg(test)]
+#[path = "../tests/common/mod.rs"]
+mod test_util;
 
-#[cfg(target_arch = "wasm32")]
-#[wasm_bindgen]
-extern "C" {
-    // Use `js_namespace` here to bind `console.log(..)` instead of just
-    // `log(..)`
-    #[wasm_bindgen(js_namespace = console)]
-    fn log(s: &str);
-
-    // The `console.log` is quite polymorphic, so we can bind it with multiple
-    // signatures. Note that we need to use `js_name` to ensure we always call
-    // `log` in JS.
-    #[wasm_bindgen(js_namespace = console, js_name = log)]
-    fn log_u32(a: u32);
-
-    // Multiple arguments too!
-    #[wasm_bindgen(js_namespace = console, js_name = log)]
-    fn log_many(a: &str, b: &str);
-}
+#[doc(hidden)]
+pub use log as __log;
 
-#[cfg(target_arch = "wasm32")]
+/// Formats like `println!` and logs the line at info level through the `log`
+/// crate, see `logging::init_logging`.
 #[macro_export]
 macro_rules! console_log {
-    // Note that this is using the `log` function imported above during
-    // `bare_bones`
-    ($($t:tt)*) => (log(&format_args!($($t)*).to_string()))
+    ($($t:tt)*) => ($crate::__log::info!($($t)*))
 }
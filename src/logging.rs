@@ -0,0 +1,333 @@
+// Logging of the protocol through the `log` crate, with the session, party,
+// round and timing as structured fields. Nothing is printed unless the host
+// installs a logger: any `log` implementation in Rust, or `init_logging`,
+// which forwards to logcat on Android, to the console in wasm and to stderr on
+// other native targets.
+//
+// The protocol code only logs through the functions below, which take ids,
+// round names, counts and timings. Message payloads, key stores, shares,
+// passphrases and tokens have no way in, so they cannot reach the logs.
+
+use crate::errors::{Result, TssError};
+use log::kv::{Error, Key, Value, VisitSource};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+const TAG: &str = "tss_wasm";
+
+/// Milliseconds since the stopwatch was started; `Instant` is not available
+/// in wasm, where the JS clock is used instead.
+pub(crate) struct Stopwatch {
+    #[cfg(target_arch = "wasm32")]
+    started: f64,
+    #[cfg(not(target_arch = "wasm32"))]
+    started: std::time::Instant,
+}
+
+impl Stopwatch {
+    pub(crate) fn start() -> Stopwatch {
+        Stopwatch {
+            #[cfg(target_arch = "wasm32")]
+            started: js_sys::Date::now(),
+            #[cfg(not(target_arch = "wasm32"))]
+            started: std::time::Instant::now(),
+        }
+    }
+
+    pub(crate) fn elapsed_ms(&self) -> u64 {
+        #[cfg(target_arch = "wasm32")]
+        return (js_sys::Date::now() - self.started).max(0.0) as u64;
+        #[cfg(not(target_arch = "wasm32"))]
+        return self.started.elapsed().as_millis() as u64;
+    }
+}
+
+/// One exchange of messages of a session, from sending ours to having those
+/// of every other party. Logs when it finishes, or, if it is dropped before,
+/// that it was aborted, with the time spent either way.
+pub struct RoundTrace {
+    session: String,
+    party: u16,
+    round: String,
+    started: Stopwatch,
+    finished: bool,
+}
+
+impl RoundTrace {
+    pub fn start(session: &str, party: u16, round: &str) -> RoundTrace {
+        log::debug!(
+            session = session,
+            party = party,
+            round = round;
+            "round started"
+        );
+        RoundTrace {
+            session: session.to_string(),
+            party,
+            round: round.to_string(),
+            started: Stopwatch::start(),
+            finished: false,
+        }
+    }
+
+    /// The message of party `from` has arrived.
+    pub fn received(&self, from: u16) {
+        log::debug!(
+            session = self.session.as_str(),
+            party = self.party,
+            round = self.round.as_str(),
+            from = from,
+            elapsed_ms = self.started.elapsed_ms();
+            "message received"
+        );
+    }
+
+    pub fn finish(mut self) {
+        self.finished = true;
+        log::info!(
+            session = self.session.as_str(),
+            party = self.party,
+            round = self.round.as_str(),
+            elapsed_ms = self.started.elapsed_ms();
+            "round finished"
+        );
+    }
+}
+
+impl Drop for RoundTrace {
+    fn drop(&mut self) {
+        if !self.finished {
+            log::warn!(
+                session = self.session.as_str(),
+                party = self.party,
+                round = self.round.as_str(),
+                elapsed_ms = self.started.elapsed_ms();
+                "round aborted"
+            );
+        }
+    }
+}
+
+/// A request to the manager failed and will be sent again after `delay_ms`.
+pub(crate) fn request_retry(path: &str, attempt: u32, code: i32, delay_ms: u32) {
+    log::debug!(
+        path = path,
+        attempt = attempt,
+        code = code,
+        delay_ms = delay_ms;
+        "retrying request to the manager"
+    );
+}
+
+/// A request to the manager failed for good after `attempt` attempts.
+pub(crate) fn request_failed(path: &str, attempt: u32, code: i32) {
+    log::warn!(
+        path = path,
+        attempt = attempt,
+        code = code;
+        "request to the manager failed"
+    );
+}
+
+/// The token provider handed out a new token after the manager rejected one;
+/// the token itself is not logged.
+pub(crate) fn token_refreshed() {
+    log::info!("token refreshed");
+}
+
+pub(crate) fn token_refresh_failed(code: i32) {
+    log::warn!(code = code; "token refresh failed");
+}
+
+// `message key=value ...`, the line written by the hooks below
+fn format_record(record: &Record) -> String {
+    struct Fields(String);
+
+    impl<'kvs> VisitSource<'kvs> for Fields {
+        fn visit_pair(
+            &mut self,
+            key: Key<'kvs>,
+            value: Value<'kvs>,
+        ) -> std::result::Result<(), Error> {
+            self.0.push_str(&format!(" {}={}", key, value));
+            Ok(())
+        }
+    }
+
+    let mut fields = Fields(record.args().to_string());
+    let _ = record.key_values().visit(&mut fields);
+    fields.0
+}
+
+struct HostLogger;
+
+impl Log for HostLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            write_line(record.level(), &format_record(record));
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static HOST_LOGGER: HostLogger = HostLogger;
+
+#[cfg(target_os = "android")]
+fn write_line(level: Level, line: &str) {
+    use std::ffi::{c_char, c_int, CString};
+
+    #[link(name = "log")]
+    extern "C" {
+        fn __android_log_write(prio: c_int, tag: *const c_char, text: *const c_char) -> c_int;
+    }
+
+    // android/log.h priorities
+    let prio = match level {
+        Level::Error => 6,
+        Level::Warn => 5,
+        Level::Info => 4,
+        Level::Debug => 3,
+        Level::Trace => 2,
+    };
+    let tag = CString::new(TAG).unwrap_or_default();
+    let text = CString::new(line.replace('\0', " ")).unwrap_or_default();
+    unsafe {
+        __android_log_write(prio, tag.as_ptr(), text.as_ptr());
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = console, js_name = error)]
+    fn console_error(s: &str);
+    #[wasm_bindgen(js_namespace = console, js_name = warn)]
+    fn console_warn(s: &str);
+    #[wasm_bindgen(js_namespace = console, js_name = info)]
+    fn console_info(s: &str);
+    #[wasm_bindgen(js_namespace = console, js_name = debug)]
+    fn console_debug(s: &str);
+}
+
+#[cfg(target_arch = "wasm32")]
+fn write_line(level: Level, line: &str) {
+    let line = format!("[{}] {}", TAG, line);
+    match level {
+        Level::Error => console_error(&line),
+        Level::Warn => console_warn(&line),
+        Level::Info => console_info(&line),
+        Level::Debug | Level::Trace => console_debug(&line),
+    }
+}
+
+#[cfg(not(any(target_os = "android", target_arch = "wasm32")))]
+fn write_line(level: Level, line: &str) {
+    eprintln!("[{}] {} {}", TAG, level, line);
+}
+
+/// Forwards the logs of this library at `level` ("off", "error", "warn",
+/// "info", "debug" or "trace") and above to logcat on Android, to the console
+/// in wasm and to stderr elsewhere. If the host already installed a logger it
+/// is kept and only the level changes.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn init_logging(level: String) -> Result<()> {
+    let level: LevelFilter = level.parse().map_err(|_e| TssError::InvalidArgument {
+        name: "level".to_string(),
+    })?;
+    let _ = log::set_logger(&HOST_LOGGER);
+    log::set_max_level(level);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Mutex, Once};
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
+
+    lazy_static::lazy_static! {
+        static ref LINES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+    }
+
+    struct CaptureLogger;
+
+    impl Log for CaptureLogger {
+        fn enabled(&self, _metadata: &Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &Record) {
+            let line = format!("{} {}", record.level(), format_record(record));
+            LINES.lock().unwrap().push(line);
+        }
+
+        fn flush(&self) {}
+    }
+
+    static CAPTURE_LOGGER: CaptureLogger = CaptureLogger;
+    static INSTALL_CAPTURE_LOGGER: Once = Once::new();
+
+    // a logger can only be installed once per process, whichever test comes first
+    fn capture_logs() {
+        INSTALL_CAPTURE_LOGGER.call_once(|| {
+            let _ = log::set_logger(&CAPTURE_LOGGER);
+        });
+        log::set_max_level(LevelFilter::Trace);
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_round_trace() {
+        capture_logs();
+
+        let trace = RoundTrace::start("session-a", 2, "round1");
+        trace.received(1);
+        trace.received(3);
+        trace.finish();
+        drop(RoundTrace::start("session-a", 2, "round2"));
+        request_retry("/trace-test", 1, 202, 200);
+        crate::console_log!("trace-test {}", 1);
+
+        // other tests may log concurrently
+        let lines: Vec<String> = LINES
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|line| line.contains("session=session-a ") || line.contains("trace-test"))
+            .map(|line| {
+                // the elapsed time varies
+                match line.find(" elapsed_ms=") {
+                    Some(at) => line[..at].to_string(),
+                    None => line.clone(),
+                }
+            })
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                "DEBUG round started session=session-a party=2 round=round1",
+                "DEBUG message received session=session-a party=2 round=round1 from=1",
+                "DEBUG message received session=session-a party=2 round=round1 from=3",
+                "INFO round finished session=session-a party=2 round=round1",
+                "DEBUG round started session=session-a party=2 round=round2",
+                "WARN round aborted session=session-a party=2 round=round2",
+                "DEBUG retrying request to the manager path=/trace-test attempt=1 code=202 delay_ms=200",
+                "INFO trace-test 1",
+            ]
+        );
+
+        // the logger installed by the host is kept
+        assert!(init_logging("warn".to_string()).is_ok());
+        assert_eq!(log::max_level(), LevelFilter::Warn);
+        assert!(init_logging("loud".to_string()).is_err());
+    }
+}
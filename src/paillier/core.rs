@@ -102,8 +102,37 @@ impl<'e> From<MinimalDecryptionKey> for DecryptionKey {
     fn from(dk: MinimalDecryptionKey) -> Self {
         let p = dk.p;
         let q = dk.q;
-
-        DecryptionKey { p, q }
+        let n = &p * &q;
+        let pp = &p * &p;
+        let qq = &q * &q;
+        let pminusone = &p - BigInt::one();
+        let qminusone = &q - BigInt::one();
+        let pinv = BigInt::mod_inv(&p, &q);
+        let ppinv = BigInt::mod_inv(&pp, &qq);
+        let hp = h(&p, &pp, &n);
+        let hq = h(&q, &qq, &n);
+        let np = &n % (&p * &pminusone);
+        let nq = &n % (&q * &qminusone);
+        let phi = &pminusone * &qminusone;
+        let (dp, dq) = crt_decompose(BigInt::mod_inv(&n, &phi), &pminusone, &qminusone);
+
+        DecryptionKey {
+            p,
+            q,
+            n,
+            pp,
+            qq,
+            pminusone,
+            qminusone,
+            pinv,
+            ppinv,
+            hp,
+            hq,
+            np,
+            nq,
+            dp,
+            dq,
+        }
     }
 }
 
@@ -233,26 +262,24 @@ impl<'m, 'r, 'd>
     }
 }
 
+/// Encryption by the key owner, computed modulo p^2 and q^2 with r^N reduced
+/// to exponents of half the size.
 impl<'m, 'd> Encrypt<DecryptionKey, RawPlaintext<'m>, RawCiphertext<'d>> for Paillier {
     fn encrypt(dk: &DecryptionKey, m: RawPlaintext<'m>) -> RawCiphertext<'d> {
-        let dk_pp = &dk.p * &dk.p;
-        let dk_qq = &dk.q * &dk.q;
-        let dk_n = &dk.q * &dk.p;
-        let dk_ppinv = BigInt::mod_inv(&dk_pp, &dk_qq);
-        let (mp, mq) = crt_decompose(m.0.borrow(), &dk_pp, &dk_qq);
-
-        let rp = BigInt::sample_below(&dk.p);
-        let rnp = BigInt::mod_pow(&rp, &dk_n, &dk_pp);
-        let gmp = (BigInt::from(1 as u16) + mp * &dk_n) % &dk_pp; // TODO[Morten] maybe there's more to get here
-        let cp = (gmp * rnp) % &dk_pp;
-
-        let rq = BigInt::sample_below(&dk.q);
-        let rnq = BigInt::mod_pow(&rq, &dk_n, &dk_qq);
-        let gmq = (BigInt::from(1 as u16) + mq * &dk_n) % &dk_qq; // TODO[Morten] maybe there's more to get here
-        let cq = (gmq * rnq) % &dk_qq;
-
-        let c = crt_recombine(cp, cq, &dk_pp, &dk_qq, &dk_ppinv);
-        RawCiphertext(Cow::Owned(c))
+        // r^N mod p^2 only depends on r mod p
+        let rp = loop {
+            let rp = BigInt::sample_below(&dk.p);
+            if !NumberTests::is_zero(&rp) {
+                break rp;
+            }
+        };
+        let rq = loop {
+            let rq = BigInt::sample_below(&dk.q);
+            if !NumberTests::is_zero(&rq) {
+                break rq;
+            }
+        };
+        encrypt_crt(dk, m.0.borrow(), &rp, &rq)
     }
 }
 
@@ -265,23 +292,8 @@ impl<'m, 'r, 'd>
         m: RawPlaintext<'m>,
         r: &'r Randomness,
     ) -> RawCiphertext<'d> {
-        let dk_pp = &dk.p * &dk.p;
-        let dk_qq = &dk.q * &dk.q;
-        let dk_n = &dk.q * &dk.p;
-        let dk_ppinv = BigInt::mod_inv(&dk_pp, &dk_qq);
-        let (mp, mq) = crt_decompose(m.0.borrow(), &dk_pp, &dk_qq);
-        let (rp, rq) = crt_decompose(&r.0, &dk_pp, &dk_qq);
-
-        let rnp = BigInt::mod_pow(&rp, &dk_n, &dk_pp);
-        let gmp = (BigInt::from(1 as u16) + mp * &dk_n) % &dk_pp; // TODO[Morten] maybe there's more to get here
-        let cp = (gmp * rnp) % &dk_pp;
-
-        let rnq = BigInt::mod_pow(&rq, &dk_n, &dk_qq);
-        let gmq = (BigInt::from(1 as u16) + mq * &dk_n) % &dk_qq; // TODO[Morten] maybe there's more to get here
-        let cq = (gmq * rnq) % &dk_qq;
-
-        let c = crt_recombine(cp, cq, &dk_pp, &dk_qq, &dk_ppinv);
-        RawCiphertext(Cow::Owned(c))
+        let (rp, rq) = crt_decompose(&r.0, &dk.pp, &dk.qq);
+        encrypt_crt(dk, m.0.borrow(), &rp, &rq)
     }
 }
 
@@ -298,9 +310,8 @@ impl<'m, 'r, 'd>
         m: RawPlaintext<'m>,
         rn: &'r PrecomputedRandomness,
     ) -> RawCiphertext<'d> {
-        let dk_n = &dk.q * &dk.p;
-        let dk_nn = &dk_n * &dk_n;
-        let gm = (BigInt::from(1 as u16) + m.0.borrow() as &BigInt * &dk_n) % &dk_nn;
+        let dk_nn = &dk.n * &dk.n;
+        let gm = (BigInt::from(1 as u16) + m.0.borrow() as &BigInt * &dk.n) % &dk_nn;
         let c = (gm * &rn.0) % &dk_nn;
         RawCiphertext(Cow::Owned(c))
     }
@@ -324,8 +335,6 @@ impl<'c, 'd> Rerandomize<EncryptionKey, RawCiphertext<'c>, RawCiphertext<'d>> fo
     }
 }
 
-/// TODO
-///
 /// Efficient decryption using CRT based on [Paillier99, section 7](http://citeseerx.ist.psu.edu/viewdoc/download?doi=10.1.1.112.4035&rep=rep1&type=pdf)
 impl<'c, 'm> Decrypt<DecryptionKey, RawCiphertext<'c>, RawPlaintext<'m>> for Paillier {
     fn decrypt(dk: &DecryptionKey, c: RawCiphertext<'c>) -> RawPlaintext<'m> {
@@ -333,34 +342,25 @@ impl<'c, 'm> Decrypt<DecryptionKey, RawCiphertext<'c>, RawPlaintext<'m>> for Pai
     }
 }
 
-/// TODO
-///
-/// Efficient decryption using CRT based on [Paillier99, section 7](http://citeseerx.ist.psu.edu/viewdoc/download?doi=10.1.1.112.4035&rep=rep1&type=pdf)
+/// Efficient decryption using CRT based on [Paillier99, section 7](http://citeseerx.ist.psu.edu/viewdoc/download?doi=10.1.1.112.4035&rep=rep1&type=pdf),
+/// with the per-key values precomputed in `DecryptionKey`.
 impl<'c, 'm> Decrypt<DecryptionKey, &'c RawCiphertext<'c>, RawPlaintext<'m>> for Paillier {
     fn decrypt(dk: &DecryptionKey, c: &'c RawCiphertext<'c>) -> RawPlaintext<'m> {
-        let dk_qq = &dk.q * &dk.q;
-        let dk_pp = &dk.p * &dk.p;
-        let dk_n = &dk.p * &dk.q;
-        let dk_pinv = BigInt::mod_inv(&dk.p, &dk.q);
-        let dk_qminusone = &dk.q - BigInt::one();
-        let dk_pminusone = &dk.p - BigInt::one();
-        let dk_hp = h(&dk.p, &dk_pp, &dk_n);
-        let dk_hq = h(&dk.q, &dk_qq, &dk_n);
-        let (cp, cq) = crt_decompose(c.0.borrow(), &dk_pp, &dk_qq);
+        let (cp, cq) = crt_decompose(c.0.borrow(), &dk.pp, &dk.qq);
         // decrypt in parallel with respectively p and q
 
         // process using p
-        let dp = BigInt::mod_pow(&cp, &dk_pminusone, &dk_pp);
+        let dp = BigInt::mod_pow(&cp, &dk.pminusone, &dk.pp);
         let lp = l(&dp, &dk.p);
-        let mp = (&lp * &dk_hp) % &dk.p;
+        let mp = (&lp * &dk.hp) % &dk.p;
 
         // process using q
-        let dq = BigInt::mod_pow(&cq, &dk_qminusone, &dk_qq);
+        let dq = BigInt::mod_pow(&cq, &dk.qminusone, &dk.qq);
         let lq = l(&dq, &dk.q);
-        let mq = (&lq * &dk_hq) % &dk.q;
+        let mq = (&lq * &dk.hq) % &dk.q;
 
         // perform CRT
-        let m = crt_recombine(mp, mq, &dk.p, &dk.q, &dk_pinv);
+        let m = crt_recombine(mp, mq, &dk.p, &dk.q, &dk.pinv);
         RawPlaintext(Cow::Owned(m))
     }
 }
@@ -373,11 +373,10 @@ impl<'c, 'm> Open<DecryptionKey, RawCiphertext<'c>, RawPlaintext<'m>, Randomness
 
 impl<'c, 'm> Open<DecryptionKey, &'c RawCiphertext<'c>, RawPlaintext<'m>, Randomness> for Paillier {
     fn open(dk: &DecryptionKey, c: &'c RawCiphertext<'c>) -> (RawPlaintext<'m>, Randomness) {
-        let dk_n = &dk.p * &dk.q;
-        let dk_nn = &dk_n * &dk_n;
+        let dk_nn = &dk.n * &dk.n;
 
         let m = Self::decrypt(dk, c);
-        let gminv = (dk_nn.clone() + BigInt::one() - (m.0.borrow() as &BigInt) * &dk_n) % &dk_nn;
+        let gminv = (dk_nn.clone() + BigInt::one() - (m.0.borrow() as &BigInt) * &dk.n) % &dk_nn;
         let rn = (c.0.borrow() as &BigInt * gminv) % &dk_nn;
         let r = extract_nroot(dk, &rn);
         (m, Randomness(r))
@@ -443,6 +442,22 @@ impl<'c, 'm, 'd> Mul<EncryptionKey, RawPlaintext<'m>, RawCiphertext<'c>, RawCiph
     }
 }
 
+// (1 + N)^m * r^N modulo p^2 and q^2, recombined modulo N^2
+fn encrypt_crt(dk: &DecryptionKey, m: &BigInt, rp: &BigInt, rq: &BigInt) -> RawCiphertext<'static> {
+    let (mp, mq) = crt_decompose(m, &dk.pp, &dk.qq);
+
+    let rnp = BigInt::mod_pow(rp, &dk.np, &dk.pp);
+    let gmp = (BigInt::one() + mp * &dk.n) % &dk.pp;
+    let cp = (gmp * rnp) % &dk.pp;
+
+    let rnq = BigInt::mod_pow(rq, &dk.nq, &dk.qq);
+    let gmq = (BigInt::one() + mq * &dk.n) % &dk.qq;
+    let cq = (gmq * rnq) % &dk.qq;
+
+    let c = crt_recombine(cp, cq, &dk.pp, &dk.qq, &dk.ppinv);
+    RawCiphertext(Cow::Owned(c))
+}
+
 fn h(p: &BigInt, pp: &BigInt, n: &BigInt) -> BigInt {
     // here we assume:
     //  - p \in {P, Q}
@@ -491,21 +506,12 @@ where
 
 /// Extract randomness component of a zero ciphertext.
 pub fn extract_nroot(dk: &DecryptionKey, z: &BigInt) -> BigInt {
-    let dk_n = &dk.p * &dk.q;
-
-    let dk_pinv = BigInt::mod_inv(&dk.p, &dk.q);
-    let dk_qminusone = &dk.q - BigInt::one();
-    let dk_pminusone = &dk.p - BigInt::one();
-
-    let dk_phi = &dk_pminusone * &dk_qminusone;
-    let dk_dn = BigInt::mod_inv(&dk_n, &dk_phi);
-    let (dk_dp, dk_dq) = crt_decompose(dk_dn, &dk_pminusone, &dk_qminusone);
     let (zp, zq) = crt_decompose(z, &dk.p, &dk.q);
 
-    let rp = BigInt::mod_pow(&zp, &dk_dp, &dk.p);
-    let rq = BigInt::mod_pow(&zq, &dk_dq, &dk.q);
+    let rp = BigInt::mod_pow(&zp, &dk.dp, &dk.p);
+    let rq = BigInt::mod_pow(&zq, &dk.dq, &dk.q);
 
-    let r = crt_recombine(rp, rq, &dk.p, &dk.q, &dk_pinv);
+    let r = crt_recombine(rp, rq, &dk.p, &dk.q, &dk.pinv);
     r
 }
 
@@ -548,6 +554,21 @@ mod tests {
         assert_eq!(c, d);
     }
 
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_correct_encryption_with_decryption_key() {
+        let (ek, dk) = test_keypair().keys();
+
+        let m = RawPlaintext::from(BigInt::from(10 as u16));
+        let r = Randomness::sample(&ek);
+        let c = Paillier::encrypt_with_chosen_randomness(&ek, m.clone(), &r);
+        let d = Paillier::encrypt_with_chosen_randomness(&dk, m.clone(), &r);
+        assert_eq!(c, d);
+
+        let c = Paillier::encrypt(&dk, m.clone());
+        assert_eq!(Paillier::decrypt(&dk, c), m);
+    }
+
     #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
     #[test]
     fn test_correct_addition() {
@@ -5,16 +5,51 @@ use crate::curv::arithmetic::traits::*;
 use crate::paillier::traits::*;
 use crate::paillier::{Keypair, Paillier};
 use num_traits::{One, Zero};
+
+/// Kind of primes a modulus is built from. Both are 3 mod 4, as required by
+/// the Paillier-Blum modulus proof. Safe primes p = 2p' + 1 also make the
+/// quadratic residues a cyclic group of order p'q', as N_tilde setups need,
+/// but take far longer to generate.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum PrimeKind {
+    #[default]
+    Blum,
+    Safe,
+}
+
 impl KeyGeneration<Keypair> for Paillier {
-    fn keypair_with_modulus_size(bit_length: usize) -> Keypair {
-        let p = BigInt::sample_prime(bit_length / 2);
-        let q = BigInt::sample_prime(bit_length / 2);
+    fn keypair_with_prime_kind(bit_length: usize, kind: PrimeKind) -> Keypair {
+        let sample = || match kind {
+            PrimeKind::Blum => BigInt::sample_blum_prime(bit_length / 2),
+            PrimeKind::Safe => BigInt::sample_safe_prime(bit_length / 2),
+        };
+        let p = sample();
+        let q = loop {
+            let q = sample();
+            if q != p {
+                break q;
+            }
+        };
         Keypair { p, q }
     }
 }
 
+// Candidates tried from one random start before sampling a new one. Near
+// 2^1024 one integer in ln(2^1024) ~ 710 is prime, so one in ~355 of the odd
+// candidates of `sample_prime`, and as well of the 3 mod 4 candidates of
+// `sample_blum_prime` since half of the odd primes are 3 mod 4. A walk of 500
+// finds a prime with probability 1 - e^(-500/355) ~ 3/4; restarting instead
+// of walking on keeps the walk short, as a long one favours the primes that
+// follow the largest gaps.
+const PRIME_SEARCH_STEPS: usize = 500;
+
 pub trait PrimeSampable {
     fn sample_prime(bitsize: usize) -> Self;
+    /// A prime congruent to 3 mod 4 with its two top bits set, so the product
+    /// of two of them has exactly twice the bits.
+    fn sample_blum_prime(bitsize: usize) -> Self;
+    /// A prime p = 2p' + 1 with p' prime, top two bits set as above.
+    fn sample_safe_prime(bitsize: usize) -> Self;
 }
 
 impl PrimeSampable for BigInt {
@@ -24,19 +59,17 @@ impl PrimeSampable for BigInt {
         let one = BigInt::one();
         let two = &one + &one;
         loop {
-            let mut candidate = Self::sample(bitsize);
             // We flip the LSB to make sure tue candidate is odd.
-            //  BitManipulation::set_bit(&mut candidate, 0, true);
-            BigInt::set_bit(&mut candidate, 0, true);
-
             // To ensure the appropiate size
             // we set the MSB of the candidate.
-            BigInt::set_bit(&mut candidate, bitsize - 1, true);
+            // (set_bit returns the updated value, it does not modify in place)
+            let mut candidate = Self::sample(bitsize)
+                .set_bit(0, true)
+                .set_bit(bitsize - 1, true);
 
-            // If no prime number is found in 500 iterations,
+            // If no prime number is found in PRIME_SEARCH_STEPS iterations,
             // restart the loop (re-seed).
-            // FIXME: Why 500?
-            for _ in 0..500 {
+            for _ in 0..PRIME_SEARCH_STEPS {
                 if is_prime(&candidate) {
                     return candidate;
                 }
@@ -44,6 +77,58 @@ impl PrimeSampable for BigInt {
             }
         }
     }
+
+    fn sample_blum_prime(bitsize: usize) -> Self {
+        let four = BigInt::from(4u32);
+        loop {
+            // The two lowest bits set make the candidate 3 mod 4,
+            // stepping by 4 keeps it that way.
+            let mut candidate = Self::sample(bitsize)
+                .set_bit(0, true)
+                .set_bit(1, true)
+                .set_bit(bitsize - 2, true)
+                .set_bit(bitsize - 1, true);
+
+            for _ in 0..PRIME_SEARCH_STEPS {
+                if is_prime(&candidate) {
+                    return candidate;
+                }
+                candidate += &four;
+            }
+        }
+    }
+
+    fn sample_safe_prime(bitsize: usize) -> Self {
+        let two = BigInt::from(2u32);
+        loop {
+            // candidate for p', one bit shorter than p
+            let mut candidate = Self::sample(bitsize - 1)
+                .set_bit(0, true)
+                .set_bit(bitsize - 3, true)
+                .set_bit(bitsize - 2, true);
+
+            // Safe primes are roughly ln(2^bitsize) times rarer than primes,
+            // so the search runs much longer before re-seeding.
+            for _ in 0..(1 << 16) {
+                if safe_prime_sieve(&candidate) && is_prime(&candidate) {
+                    let p = &candidate * &two + BigInt::one();
+                    if is_prime(&p) {
+                        return p;
+                    }
+                }
+                candidate += &two;
+            }
+        }
+    }
+}
+
+// Cheap pre-check for p' and p = 2p' + 1: neither may have a small factor,
+// so p' mod r must be neither 0 nor (r - 1) / 2.
+fn safe_prime_sieve(candidate: &BigInt) -> bool {
+    SMALL_PRIMES[1..].iter().all(|r| {
+        let m = u32::_from(&(candidate % BigInt::from(*r)));
+        m != 0 && m != (r - 1) / 2
+    })
 }
 
 // Runs the following three tests on a given `candidate` to determine
@@ -52,7 +137,7 @@ impl PrimeSampable for BigInt {
 // 1. Divide the candidate by the first 999 small prime numbers.
 // 2. Run Fermat's Little Theorem against the candidate.
 // 3. Run five rounds of the Miller-Rabin test on the candidate.
-fn is_prime(candidate: &BigInt) -> bool {
+pub(crate) fn is_prime(candidate: &BigInt) -> bool {
     // First, simple trial divide
     for p in SMALL_PRIMES.iter() {
         let prime = BigInt::from(*p);
@@ -325,3 +410,34 @@ static SMALL_PRIMES: [u32; 2048] = [
     17609, 17623, 17627, 17657, 17659, 17669, 17681, 17683, 17707, 17713, 17729,
     17737, 17747, 17749, 17761, 17783, 17789, 17791, 17807, 17827, 17837, 17839,
     17851, 17863 ];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_safe_prime_keypair() {
+        let four = BigInt::from(4u32);
+        let three = BigInt::from(3u32);
+        let keypair = Paillier::keypair_with_prime_kind(256, PrimeKind::Safe);
+        for p in [&keypair.p, &keypair.q].iter() {
+            assert_eq!(p.bits(), 128);
+            assert_eq!(*p % &four, three);
+            assert!(is_prime(p));
+            assert!(is_prime(&((*p - BigInt::one()) >> 1)));
+        }
+        let (ek, _) = keypair.keys();
+        assert_eq!(ek.n.bits(), 256);
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_modulus_size() {
+        let (ek, _) = Paillier::keypair_with_modulus_size(1024).keys();
+        assert_eq!(ek.n.bits(), 1024);
+    }
+}
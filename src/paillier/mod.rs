@@ -49,10 +49,26 @@ pub struct EncryptionKey {
 }
 
 /// Private decryption key.
+///
+/// Carries the values decryption and encryption by the key owner need for
+/// working modulo p^2 and q^2 instead of N^2.
 #[derive(Clone, Debug, PartialEq)]
 pub struct DecryptionKey {
-    pub p: BigInt, // first prime
-    pub q: BigInt, // second prime
+    pub p: BigInt,         // first prime
+    pub q: BigInt,         // second prime
+    pub n: BigInt,         // the modulus
+    pub pp: BigInt,        // p^2
+    pub qq: BigInt,        // q^2
+    pub pminusone: BigInt, // p - 1
+    pub qminusone: BigInt, // q - 1
+    pub pinv: BigInt,      // p^-1 mod q
+    pub ppinv: BigInt,     // p^-2 mod q^2
+    pub hp: BigInt,        // L_p(g^(p-1) mod p^2)^-1 mod p
+    pub hq: BigInt,        // L_q(g^(q-1) mod q^2)^-1 mod q
+    pub np: BigInt,        // N mod phi(p^2), exponent for r^N mod p^2
+    pub nq: BigInt,        // N mod phi(q^2), exponent for r^N mod q^2
+    pub dp: BigInt,        // N^-1 mod p - 1, exponent for N-th roots mod p
+    pub dq: BigInt,        // N^-1 mod q - 1, exponent for N-th roots mod q
 }
 
 /// Unencrypted message without type information.
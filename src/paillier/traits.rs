@@ -1,5 +1,7 @@
 //! Abstract operations exposed by the library.
 
+use crate::paillier::keygen::PrimeKind;
+
 /// Secure generation of fresh key pairs.
 pub trait KeyGeneration<KP> {
     /// Generate fresh key pair with currently recommended security level (2048 bit modulus).
@@ -10,7 +12,12 @@ pub trait KeyGeneration<KP> {
     /// Generate fresh key pair with security level specified as the `bit_length` of the modulus.
     ///
     /// Currently recommended security level is a minimum of 2048 bits.
-    fn keypair_with_modulus_size(big_length: usize) -> KP;
+    fn keypair_with_modulus_size(bit_length: usize) -> KP {
+        Self::keypair_with_prime_kind(bit_length, PrimeKind::default())
+    }
+
+    /// Generate fresh key pair from two primes of the given kind.
+    fn keypair_with_prime_kind(bit_length: usize, kind: PrimeKind) -> KP;
 }
 
 pub trait PrecomputeRandomness<EK, R, PR> {
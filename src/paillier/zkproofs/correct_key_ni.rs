@@ -17,11 +17,13 @@ use std::ops::Shl;
 
 use crate::curv::arithmetic::num_bigint::{from, BigInt};
 use crate::curv::arithmetic::traits::*;
+use crate::curv::cryptographic_primitives::hashing::ext::{DigestExt, ProofContext};
 use crate::curv::cryptographic_primitives::hashing::hash_sha256::HSha256;
 use crate::curv::cryptographic_primitives::hashing::traits::Hash;
 use crate::paillier::{extract_nroot, DecryptionKey, EncryptionKey};
 use num_integer::Integer;
 use num_traits::{One, Zero};
+use sha2::{Digest, Sha256};
 // This protocol is based on the NIZK protocol in https://eprint.iacr.org/2018/057.pdf
 // for parameters = e = N, m2 = 11, alpha = 6379 see https://eprint.iacr.org/2018/987.pdf 6.2.3
 // for full details.
@@ -40,20 +42,9 @@ pub struct NICorrectKeyProof {
 }
 
 impl NICorrectKeyProof {
-    pub fn proof(dk: &DecryptionKey) -> NICorrectKeyProof {
+    pub fn proof(dk: &DecryptionKey, ctx: &ProofContext) -> NICorrectKeyProof {
         let dk_n = &dk.q * &dk.p;
-        let key_length = &dk_n.bits();
-
-        let salt_bn = from(SALT_STRING);
-
-        // TODO: use flatten (Morten?)
-        let rho_vec = (0..M2)
-            .map(|i| {
-                let seed_bn =
-                    HSha256::create_hash(&[&dk_n, &salt_bn, &BigInt::from(i.clone() as u32)]);
-                mask_generation(&key_length, &seed_bn) % &dk_n
-            })
-            .collect::<Vec<BigInt>>();
+        let rho_vec = rho_vec(&dk_n, ctx);
 
         let sigma_vec = rho_vec
             .iter()
@@ -65,18 +56,12 @@ impl NICorrectKeyProof {
         NICorrectKeyProof { sigma_vec }
     }
 
-    pub fn verify(&self, ek: &EncryptionKey) -> Result<(), CorrectKeyProofError> {
-        let key_length = ek.n.bits() as usize;
-        let salt_bn = from(SALT_STRING);
-
-        let rho_vec = (0..M2)
-            .map(|i| {
-                let seed_bn =
-                    HSha256::create_hash(&[&ek.n, &salt_bn, &BigInt::from(i.clone() as u32)]);
-
-                mask_generation(&key_length, &seed_bn) % &ek.n
-            })
-            .collect::<Vec<BigInt>>();
+    pub fn verify(
+        &self,
+        ek: &EncryptionKey,
+        ctx: &ProofContext,
+    ) -> Result<(), CorrectKeyProofError> {
+        let rho_vec = rho_vec(&ek.n, ctx);
         let alpha_primorial: BigInt = str::parse(&P).unwrap();
         let gcd_test = alpha_primorial.gcd(&ek.n);
 
@@ -92,6 +77,24 @@ impl NICorrectKeyProof {
     }
 }
 
+// the values to take the n-th roots of, derived from the modulus and the proof context
+fn rho_vec(n: &BigInt, ctx: &ProofContext) -> Vec<BigInt> {
+    let key_length = n.bits();
+    let salt_bn = from(SALT_STRING);
+
+    (0..M2)
+        .map(|i| {
+            let seed_bn = Sha256::new()
+                .chain_context(ctx)
+                .chain_bigint(n)
+                .chain_bigint(&salt_bn)
+                .chain_bigint(&BigInt::from(i as u32))
+                .result_bigint();
+            mask_generation(&key_length, &seed_bn) % n
+        })
+        .collect::<Vec<BigInt>>()
+}
+
 // generate random element of size :
 // based on https://tools.ietf.org/html/rfc8017#appendix-B.2.1
 pub fn mask_generation(out_length: &usize, seed: &BigInt) -> BigInt {
@@ -118,7 +121,9 @@ mod tests {
     #[test]
     fn test_correct_zk_proof() {
         let (ek, dk) = Paillier::keypair().keys();
-        let proof = NICorrectKeyProof::proof(&dk);
-        assert!(proof.verify(&ek).is_ok());
+        let ctx = ProofContext::new("session", 1, "test");
+        let proof = NICorrectKeyProof::proof(&dk, &ctx);
+        assert!(proof.verify(&ek, &ctx).is_ok());
+        assert!(proof.verify(&ek, &ctx.for_party(2)).is_err());
     }
 }
@@ -17,5 +17,9 @@
 mod correct_key_ni;
 pub use self::correct_key_ni::CorrectKeyProofError;
 pub use self::correct_key_ni::NICorrectKeyProof;
+mod no_small_factor;
+pub use self::no_small_factor::{NoSmallFactorProof, NoSmallFactorProofError};
+mod paillier_blum_modulus;
+pub use self::paillier_blum_modulus::{PaillierBlumModulusProof, PaillierBlumModulusProofError};
 mod wi_dlog_proof;
 pub use self::wi_dlog_proof::DLogStatement;
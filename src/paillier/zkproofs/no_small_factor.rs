@@ -0,0 +1,215 @@
+#![allow(non_snake_case)]
+
+use crate::curv::arithmetic::num_bigint::BigInt;
+use crate::curv::arithmetic::traits::*;
+use crate::curv::cryptographic_primitives::hashing::ext::{DigestExt, ProofContext};
+use crate::curv::cryptographic_primitives::hashing::hash_sha256::HSha256;
+use crate::curv::cryptographic_primitives::hashing::traits::Hash;
+use crate::paillier::zkproofs::correct_key_ni::mask_generation;
+use crate::paillier::{DecryptionKey, EncryptionKey};
+use num_traits::Zero;
+use sha2::{Digest, Sha256};
+
+// No small factor proof (figure 28 in https://eprint.iacr.org/2021/060.pdf),
+// rewritten over non-negative integers: the prover knows N0 = pq with
+// p, q < 2^(L + EPSILON + 1) * sqrt(N0), so neither factor of a 2048 bit
+// modulus is shorter than about 2^255.
+const L: usize = 256;
+const EPSILON: usize = 512;
+
+// The proof is broadcast, so every party checks it against the same
+// ring-Pedersen parameters. N_HAT is the RSA-2048 challenge modulus, whose
+// factorization nobody is known to hold; s and t are hashed squares mod N_HAT.
+const N_HAT: &str = "25195908475657893494027183240048398571429282126204032027777137836043662020707595556264018525880784406918290641249515082189298559149176184502808489120072844992687392807287776735971418347270261896375014971824691165077613379859095700097330459748808428401797429100642458691817195118746121515172654632282216869987549182422433637259085141865462043576798423387184774447920739934236584823824281198163815010674810451660377306056201619676256133844143603833904414952634432190114657544454178424020924616515723350778707749817125772467962926386356373289912154831438167899885040445364023527381951378636564391212010397122822120720357";
+// salt as system parameter
+const SALT_STRING: &[u8] = b"no-small-factor";
+
+#[derive(Debug)]
+pub struct NoSmallFactorProofError;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NoSmallFactorProof {
+    pub P: BigInt,
+    pub Q: BigInt,
+    pub A: BigInt,
+    pub B: BigInt,
+    pub T: BigInt,
+    pub sigma: BigInt,
+    pub z1: BigInt,
+    pub z2: BigInt,
+    pub w1: BigInt,
+    pub w2: BigInt,
+    pub v: BigInt,
+}
+
+struct RingPedersen {
+    n_hat: BigInt,
+    s: BigInt,
+    t: BigInt,
+}
+
+impl RingPedersen {
+    fn new() -> RingPedersen {
+        let n_hat: BigInt = str::parse(N_HAT).unwrap();
+        let square = |i: u32| {
+            let salt_bn = BigInt::from_bytes_be(SALT_STRING);
+            let seed_bn = HSha256::create_hash(&[&n_hat, &salt_bn, &BigInt::from(i)]);
+            let r = mask_generation(&n_hat.bits(), &seed_bn) % &n_hat;
+            BigInt::mod_mul(&r, &r, &n_hat)
+        };
+        let (s, t) = (square(0), square(1));
+        RingPedersen { n_hat, s, t }
+    }
+
+    // s^a * t^b mod N_HAT
+    fn commit(&self, a: &BigInt, b: &BigInt) -> BigInt {
+        BigInt::mod_mul(
+            &BigInt::mod_pow(&self.s, a, &self.n_hat),
+            &BigInt::mod_pow(&self.t, b, &self.n_hat),
+            &self.n_hat,
+        )
+    }
+}
+
+impl NoSmallFactorProof {
+    pub fn prove(dk: &DecryptionKey, ctx: &ProofContext) -> NoSmallFactorProof {
+        let (p, q) = (&dk.p, &dk.q);
+        let n0 = p * q;
+        let rp = RingPedersen::new();
+        let n0_n_hat = &n0 * &rp.n_hat;
+
+        let alpha = BigInt::sample_below(&(n0.sqrt() << (L + EPSILON)));
+        let beta = BigInt::sample_below(&(n0.sqrt() << (L + EPSILON)));
+        let mu = BigInt::sample_below(&(&rp.n_hat << L));
+        let nu = BigInt::sample_below(&(&rp.n_hat << L));
+        let sigma_hat = BigInt::sample_below(&(&n0_n_hat << L));
+        let r = BigInt::sample_below(&(&n0_n_hat << (L + EPSILON)));
+        let x = BigInt::sample_below(&(&rp.n_hat << (L + EPSILON)));
+        let y = BigInt::sample_below(&(&rp.n_hat << (L + EPSILON)));
+
+        let P = rp.commit(p, &mu);
+        let Q = rp.commit(q, &nu);
+        let A = rp.commit(&alpha, &x);
+        let B = rp.commit(&beta, &y);
+        let T = BigInt::mod_mul(
+            &BigInt::mod_pow(&Q, &alpha, &rp.n_hat),
+            &BigInt::mod_pow(&rp.t, &r, &rp.n_hat),
+            &rp.n_hat,
+        );
+        // s^N0 * t^sigma = Q^p * t^sigma_hat
+        let sigma = &sigma_hat + &nu * p;
+
+        let e = challenge(ctx, &n0, &[&P, &Q, &A, &B, &T, &sigma]);
+        NoSmallFactorProof {
+            z1: alpha + &e * p,
+            z2: beta + &e * q,
+            w1: x + &e * &mu,
+            w2: y + &e * &nu,
+            v: r + &e * &sigma_hat,
+            P,
+            Q,
+            A,
+            B,
+            T,
+            sigma,
+        }
+    }
+
+    pub fn verify(
+        &self,
+        ek: &EncryptionKey,
+        ctx: &ProofContext,
+    ) -> Result<(), NoSmallFactorProofError> {
+        let n0 = &ek.n;
+        let rp = RingPedersen::new();
+        let n_hat = &rp.n_hat;
+
+        let in_group = [&self.P, &self.Q, &self.A, &self.B, &self.T]
+            .iter()
+            .all(|x| !x.is_zero() && *x < n_hat);
+        let bound = (n0.sqrt() + 1u32) << (L + EPSILON + 1);
+        if !in_group || self.z1 >= bound || self.z2 >= bound {
+            return Err(NoSmallFactorProofError);
+        }
+
+        let e = challenge(
+            ctx,
+            n0,
+            &[&self.P, &self.Q, &self.A, &self.B, &self.T, &self.sigma],
+        );
+        let R = rp.commit(n0, &self.sigma);
+        let pow_mul = |a: &BigInt, b: &BigInt, e: &BigInt| {
+            BigInt::mod_mul(a, &BigInt::mod_pow(b, e, n_hat), n_hat)
+        };
+        let valid = rp.commit(&self.z1, &self.w1) == pow_mul(&self.A, &self.P, &e)
+            && rp.commit(&self.z2, &self.w2) == pow_mul(&self.B, &self.Q, &e)
+            && pow_mul(&BigInt::mod_pow(&self.Q, &self.z1, n_hat), &rp.t, &self.v)
+                == pow_mul(&self.T, &R, &e);
+        match valid {
+            true => Ok(()),
+            false => Err(NoSmallFactorProofError),
+        }
+    }
+}
+
+// L bit Fiat-Shamir challenge
+fn challenge(ctx: &ProofContext, n0: &BigInt, commitments: &[&BigInt]) -> BigInt {
+    let salt_bn = BigInt::from_bytes_be(SALT_STRING);
+    commitments
+        .iter()
+        .fold(
+            Sha256::new()
+                .chain_context(ctx)
+                .chain_bigint(n0)
+                .chain_bigint(&salt_bn),
+            |acc, x| acc.chain_bigint(x),
+        )
+        .result_bigint()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::paillier::keygen::PrimeSampable;
+    use crate::paillier::KeyGeneration;
+    use crate::paillier::{Keypair, Paillier};
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_no_small_factor_proof() {
+        let (ek, dk) = Paillier::keypair().keys();
+        let ctx = ProofContext::new("session", 1, "test");
+        let proof = NoSmallFactorProof::prove(&dk, &ctx);
+        assert!(proof.verify(&ek, &ctx).is_ok());
+        assert!(proof.verify(&ek, &ctx.for_party(2)).is_err());
+
+        let (other_ek, _) = Paillier::keypair().keys();
+        assert!(proof.verify(&other_ek, &ctx).is_err());
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_no_small_factor_proof_rejects_small_factor() {
+        // 2048 bit modulus with a 128 bit factor
+        let p = BigInt::sample_blum_prime(128);
+        let q = BigInt::sample_blum_prime(1920);
+        let (ek, dk) = Keypair { p, q }.keys();
+        let ctx = ProofContext::new("session", 1, "test");
+        let proof = NoSmallFactorProof::prove(&dk, &ctx);
+        assert!(proof.verify(&ek, &ctx).is_err());
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_ring_pedersen_modulus() {
+        // guards against a typo in N_HAT: RSA-2048 has no small factors
+        let n_hat: BigInt = str::parse(N_HAT).unwrap();
+        assert_eq!(n_hat.bits(), 2048);
+        for p in (3u32..10_000).step_by(2) {
+            assert!(!(&n_hat % BigInt::from(p)).is_zero());
+        }
+    }
+}
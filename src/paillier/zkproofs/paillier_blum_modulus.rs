@@ -0,0 +1,222 @@
+use crate::curv::arithmetic::num_bigint::BigInt;
+use crate::curv::arithmetic::traits::*;
+use crate::curv::cryptographic_primitives::hashing::ext::{DigestExt, ProofContext};
+use crate::paillier::keygen::is_prime;
+use crate::paillier::zkproofs::correct_key_ni::mask_generation;
+use crate::paillier::{DecryptionKey, EncryptionKey};
+use num_integer::Integer;
+use num_traits::{One, Zero};
+use sha2::{Digest, Sha256};
+
+// Paillier-Blum modulus proof (figure 16 in https://eprint.iacr.org/2021/060.pdf):
+// N is the product of two primes congruent to 3 mod 4 and gcd(N, phi(N)) = 1.
+// Each iteration halves the cheating probability, M = 80 gives 2^-80.
+const M: usize = 80;
+// salt as system parameter
+const SALT_STRING: &[u8] = b"paillier-blum-modulus";
+
+#[derive(Debug)]
+pub struct PaillierBlumModulusProofError;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PaillierBlumModulusProof {
+    pub w: BigInt,
+    pub x_vec: Vec<BigInt>,
+    pub a_vec: Vec<bool>,
+    pub b_vec: Vec<bool>,
+    pub z_vec: Vec<BigInt>,
+}
+
+impl PaillierBlumModulusProof {
+    pub fn prove(dk: &DecryptionKey, ctx: &ProofContext) -> PaillierBlumModulusProof {
+        let (p, q) = (&dk.p, &dk.q);
+        let n = p * q;
+        let phi = (p - BigInt::one()) * (q - BigInt::one());
+        let minus_one = &n - BigInt::one();
+
+        // Jacobi symbol -1: a quadratic residue modulo exactly one of p and q
+        let w = loop {
+            let w = BigInt::sample_below(&n);
+            if w.gcd(&n) == BigInt::one() && is_residue(&w, p) != is_residue(&w, q) {
+                break w;
+            }
+        };
+        let n_inv = BigInt::mod_inv(&n, &phi);
+
+        let mut proof = PaillierBlumModulusProof {
+            w: w.clone(),
+            x_vec: Vec::with_capacity(M),
+            a_vec: Vec::with_capacity(M),
+            b_vec: Vec::with_capacity(M),
+            z_vec: Vec::with_capacity(M),
+        };
+        for y in challenges(&n, &w, ctx) {
+            // -1 and w are non-residues modulo p and modulo one of the primes
+            // respectively, so exactly one of +-y, +-wy is a residue modulo both
+            let (a, b, y_prime) = [(false, false), (true, false), (false, true), (true, true)]
+                .iter()
+                .map(|(a, b)| (*a, *b, twist(&y, *a, *b, &w, &minus_one, &n)))
+                .find(|(_, _, y_prime)| is_residue(y_prime, p) && is_residue(y_prime, q))
+                .expect("challenge not coprime to N");
+
+            proof.x_vec.push(fourth_root(&y_prime, p, q));
+            proof.a_vec.push(a);
+            proof.b_vec.push(b);
+            proof.z_vec.push(BigInt::mod_pow(&y, &n_inv, &n));
+        }
+        proof
+    }
+
+    pub fn verify(
+        &self,
+        ek: &EncryptionKey,
+        ctx: &ProofContext,
+    ) -> Result<(), PaillierBlumModulusProofError> {
+        let n = &ek.n;
+        let lengths_ok = [
+            self.x_vec.len(),
+            self.a_vec.len(),
+            self.b_vec.len(),
+            self.z_vec.len(),
+        ]
+        .iter()
+        .all(|len| *len == M);
+        if n.is_even() || *n <= BigInt::one() || is_prime(n) || !lengths_ok {
+            return Err(PaillierBlumModulusProofError);
+        }
+        if self.w >= *n || jacobi(&self.w, n) != -1 {
+            return Err(PaillierBlumModulusProofError);
+        }
+
+        let four = BigInt::from(4u32);
+        let minus_one = n - BigInt::one();
+        let valid = challenges(n, &self.w, ctx).iter().enumerate().all(|(i, y)| {
+            let (x, z) = (&self.x_vec[i], &self.z_vec[i]);
+            x < n
+                && z < n
+                && BigInt::mod_pow(z, n, n) == *y
+                && BigInt::mod_pow(x, &four, n)
+                    == twist(y, self.a_vec[i], self.b_vec[i], &self.w, &minus_one, n)
+        });
+        match valid {
+            true => Ok(()),
+            false => Err(PaillierBlumModulusProofError),
+        }
+    }
+}
+
+fn challenges(n: &BigInt, w: &BigInt, ctx: &ProofContext) -> Vec<BigInt> {
+    let key_length = n.bits();
+    let salt_bn = BigInt::from_bytes_be(SALT_STRING);
+    (0..M)
+        .map(|i| {
+            let seed_bn = Sha256::new()
+                .chain_context(ctx)
+                .chain_bigint(n)
+                .chain_bigint(w)
+                .chain_bigint(&salt_bn)
+                .chain_bigint(&BigInt::from(i as u32))
+                .result_bigint();
+            mask_generation(&key_length, &seed_bn) % n
+        })
+        .collect()
+}
+
+// (-1)^a * w^b * y mod n
+fn twist(y: &BigInt, a: bool, b: bool, w: &BigInt, minus_one: &BigInt, n: &BigInt) -> BigInt {
+    let mut out = y.clone();
+    if a {
+        out = BigInt::mod_mul(&out, minus_one, n);
+    }
+    if b {
+        out = BigInt::mod_mul(&out, w, n);
+    }
+    out
+}
+
+fn is_residue(a: &BigInt, p: &BigInt) -> bool {
+    let exp = (p - BigInt::one()) >> 1;
+    BigInt::mod_pow(a, &exp, p) == BigInt::one()
+}
+
+// For p = 3 mod 4 and a residue y, y^(((p + 1) / 4)^2) is the fourth root of y
+// that is itself a residue. Both roots are combined with the CRT.
+fn fourth_root(y: &BigInt, p: &BigInt, q: &BigInt) -> BigInt {
+    let root = |p: &BigInt| {
+        let e = (p + BigInt::one()) >> 2;
+        let e = (&e * &e) % (p - BigInt::one());
+        BigInt::mod_pow(y, &e, p)
+    };
+    let (x_p, x_q) = (root(p), root(q));
+    let p_inv = BigInt::mod_inv(p, q);
+    let h = BigInt::mod_mul(&BigInt::mod_sub(&x_q, &x_p, q), &p_inv, q);
+    x_p + p * h
+}
+
+// Jacobi symbol (a / n) for odd n
+fn jacobi(a: &BigInt, n: &BigInt) -> i8 {
+    let (three, four, five, eight) = (
+        BigInt::from(3u32),
+        BigInt::from(4u32),
+        BigInt::from(5u32),
+        BigInt::from(8u32),
+    );
+    let mut a = a % n;
+    let mut n = n.clone();
+    let mut t = 1;
+    while !a.is_zero() {
+        while a.is_even() {
+            a >>= 1;
+            let r = &n % &eight;
+            if r == three || r == five {
+                t = -t;
+            }
+        }
+        std::mem::swap(&mut a, &mut n);
+        if &a % &four == three && &n % &four == three {
+            t = -t;
+        }
+        a = &a % &n;
+    }
+    match n.is_one() {
+        true => t,
+        false => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::paillier::KeyGeneration;
+    use crate::paillier::Paillier;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_paillier_blum_modulus_proof() {
+        let (ek, dk) = Paillier::keypair().keys();
+        let ctx = ProofContext::new("session", 1, "test");
+        let proof = PaillierBlumModulusProof::prove(&dk, &ctx);
+        assert!(proof.verify(&ek, &ctx).is_ok());
+        assert!(proof.verify(&ek, &ctx.for_party(2)).is_err());
+
+        let (other_ek, _) = Paillier::keypair().keys();
+        assert!(proof.verify(&other_ek, &ctx).is_err());
+
+        let mut bad_proof = proof.clone();
+        bad_proof.a_vec[0] = !bad_proof.a_vec[0];
+        assert!(bad_proof.verify(&ek, &ctx).is_err());
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_jacobi() {
+        // (a / 15) for a = 0..15
+        let expected = [0, 1, 1, 0, 1, 0, 0, -1, 1, 0, 0, -1, 0, -1, -1];
+        for (a, j) in expected.iter().enumerate() {
+            assert_eq!(jacobi(&BigInt::from(a as u32), &BigInt::from(15u32)), *j);
+        }
+    }
+}
@@ -16,6 +16,7 @@ use std::iter;
 
 use crate::curv::arithmetic::num_bigint::BigInt;
 use crate::curv::arithmetic::traits::*;
+use crate::curv::cryptographic_primitives::hashing::ext::{DigestExt, ProofContext};
 use crate::num_integer::Integer;
 use serde::{Deserialize, Serialize};
 
@@ -40,12 +41,12 @@ impl Error for IncorrectProof {}
 
 use std::borrow::Borrow;
 #[allow(dead_code)]
-pub fn compute_digest<IT>(it: IT) -> BigInt
+pub fn compute_digest<IT>(ctx: &ProofContext, it: IT) -> BigInt
 where
     IT: Iterator,
     IT::Item: Borrow<BigInt>,
 {
-    let mut hasher = Sha256::new();
+    let mut hasher = Sha256::new().chain_context(ctx);
     for value in it {
         let bytes: Vec<u8> = value.borrow().to_bytes_be();
         hasher.update(&bytes);
@@ -81,7 +82,11 @@ pub struct DLogStatement {
 
 impl CompositeDLogProof {
     #[allow(dead_code)]
-    pub fn prove(statement: &DLogStatement, secret: &BigInt) -> CompositeDLogProof {
+    pub fn prove(
+        statement: &DLogStatement,
+        secret: &BigInt,
+        ctx: &ProofContext,
+    ) -> CompositeDLogProof {
         //   pub fn prove(statement: &DLogStatement, secret: &BigInt, dk: &DecryptionKey) -> DLogProof{
 
         //   let one = BigInt::one();
@@ -92,6 +97,7 @@ impl CompositeDLogProof {
         let r = BigInt::sample_below(&R);
         let x = BigInt::mod_pow(&statement.g, &r, &statement.N);
         let e = compute_digest(
+            ctx,
             iter::once(&x)
                 .chain(iter::once(&statement.g))
                 .chain(iter::once(&statement.N))
@@ -102,7 +108,11 @@ impl CompositeDLogProof {
         CompositeDLogProof { x, y }
     }
     #[allow(dead_code)]
-    pub fn verify(&self, statement: &DLogStatement) -> Result<(), IncorrectProof> {
+    pub fn verify(
+        &self,
+        statement: &DLogStatement,
+        ctx: &ProofContext,
+    ) -> Result<(), IncorrectProof> {
         //assert N > 2^k
         assert!(statement.N > BigInt::from(2u32).pow(K as u32));
 
@@ -111,6 +121,7 @@ impl CompositeDLogProof {
         assert_eq!(statement.ni.gcd(&statement.N), BigInt::one());
 
         let e = compute_digest(
+            ctx,
             iter::once(&self.x)
                 .chain(iter::once(&statement.g))
                 .chain(iter::once(&statement.N))
@@ -169,9 +180,11 @@ mod tests {
             g: h1,
             ni: h2,
         };
-        let proof = CompositeDLogProof::prove(&statement, &secret);
-        let v = proof.verify(&statement);
+        let ctx = ProofContext::new("session", 1, "test");
+        let proof = CompositeDLogProof::prove(&statement, &secret, &ctx);
+        let v = proof.verify(&statement, &ctx);
         assert!(v.is_ok());
+        assert!(proof.verify(&statement, &ctx.for_party(2)).is_err());
     }
 
     #[test]
@@ -196,8 +209,9 @@ mod tests {
             g: h1,
             ni: h2,
         };
-        let proof = CompositeDLogProof::prove(&statement, &secret);
-        let v = proof.verify(&statement);
+        let ctx = ProofContext::new("session", 1, "test");
+        let proof = CompositeDLogProof::prove(&statement, &secret, &ctx);
+        let v = proof.verify(&statement, &ctx);
         assert!(v.is_ok());
     }
 
@@ -224,8 +238,9 @@ mod tests {
             g: h1,
             ni: h2,
         };
-        let proof = CompositeDLogProof::prove(&statement, &secret);
-        let v = proof.verify(&statement);
+        let ctx = ProofContext::new("session", 1, "test");
+        let proof = CompositeDLogProof::prove(&statement, &secret, &ctx);
+        let v = proof.verify(&statement, &ctx);
         assert!(v.is_ok());
     }
 }
@@ -0,0 +1,84 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+// Process-wide tokio runtime the blocking entry points (JNI and other native
+// callers) drive the async API on. It is built once, either explicitly by
+// `init_runtime` or with the default configuration on first use, and shared by
+// every later call instead of paying the runtime startup on each round.
+
+use crate::errors::Result;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use tokio::runtime::{Builder, Runtime};
+
+lazy_static::lazy_static! {
+    static ref RUNTIME: Mutex<Option<Arc<Runtime>>> = Mutex::new(None);
+}
+
+fn build_runtime(worker_threads: usize) -> Result<Runtime> {
+    let mut builder = Builder::new_multi_thread();
+    builder.enable_all().thread_name("tss-wasm-runtime");
+    // 0 keeps the tokio default of one worker per core
+    if worker_threads > 0 {
+        builder.worker_threads(worker_threads);
+    }
+    Ok(builder.build()?)
+}
+
+/// Builds the shared runtime with `worker_threads` workers (0 for the tokio
+/// default). Does nothing if the runtime already exists, so it is safe to call
+/// on every app start.
+pub fn init_runtime(worker_threads: usize) -> Result<()> {
+    let mut runtime = RUNTIME.lock().unwrap_or_else(|e| e.into_inner());
+    if runtime.is_none() {
+        *runtime = Some(Arc::new(build_runtime(worker_threads)?));
+    }
+    Ok(())
+}
+
+fn runtime() -> Result<Arc<Runtime>> {
+    init_runtime(0)?;
+    let runtime = RUNTIME.lock().unwrap_or_else(|e| e.into_inner());
+    Ok(runtime.as_ref().expect("runtime initialized").clone())
+}
+
+/// Runs `future` to completion on the shared runtime. The lock is only held to
+/// look the runtime up, so calls from several threads run concurrently.
+pub fn block_on<T, F>(future: F) -> Result<T>
+where
+    F: Future<Output = Result<T>>,
+{
+    runtime()?.block_on(future)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::sleep;
+
+    #[test]
+    fn test_block_on_shared_runtime() {
+        init_runtime(2).unwrap();
+        let first = Arc::as_ptr(&runtime().unwrap());
+        // a second init keeps the runtime built by the first one
+        init_runtime(4).unwrap();
+        assert_eq!(first, Arc::as_ptr(&runtime().unwrap()));
+
+        let result = block_on(async {
+            sleep(1).await;
+            Ok(42)
+        });
+        assert_eq!(result.unwrap(), 42);
+    }
+
+    #[test]
+    fn test_sleep_does_not_block_the_executor() {
+        let start = std::time::Instant::now();
+        block_on(async {
+            // on a blocking sleep these would run one after the other
+            tokio::join!(sleep(100), sleep(100), sleep(100), sleep(100));
+            Ok(())
+        })
+        .unwrap();
+        assert!(start.elapsed() < std::time::Duration::from_millis(300));
+    }
+}
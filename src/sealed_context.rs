@@ -0,0 +1,349 @@
+use crate::common::{aes_decrypt, aes_encrypt, AEAD, AES_KEY_BYTES_LEN};
+use crate::errors::{Result, TssError};
+
+use rand::{rngs::OsRng, RngCore};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+use zeroize::Zeroize;
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+// version 2 binds the kind, session and round of the context as associated
+// data; version 1 contexts carried none and are no longer opened
+pub const SEALED_CONTEXT_VERSION: u32 = 2;
+
+lazy_static::lazy_static! {
+    // session key used to seal round contexts; None means contexts travel as plaintext JSON
+    static ref CONTEXT_KEY: RwLock<Option<[u8; AES_KEY_BYTES_LEN]>> = RwLock::new(None);
+}
+
+/// A round context the library hands to the host app between rounds.
+pub trait RoundContext: Serialize + DeserializeOwned {
+    /// Tells the kinds of context apart, e.g. a keygen one from a sign one.
+    const KIND: &'static str;
+    fn uuid(&self) -> &str;
+    /// The round the context is waiting for.
+    fn round(&self) -> u16;
+}
+
+/// What a sealed context belongs to. Kept in the clear, but authenticated
+/// along with the ciphertext, so a context of another kind, session or round
+/// cannot be passed off as this one.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ContextBinding {
+    pub kind: String,
+    pub uuid: String,
+    pub round: u16,
+}
+
+impl ContextBinding {
+    fn aad(&self) -> Vec<u8> {
+        format!("{}\0{}\0{}", self.kind, self.uuid, self.round).into_bytes()
+    }
+}
+
+/// Round context as handed to the host app while sealing is enabled.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct SealedContext {
+    pub version: u32,
+    pub binding: ContextBinding,
+    pub sealed: AEAD,
+}
+
+/// Seals every round context from now on under a fresh random key that never
+/// leaves the library. Contexts do not survive a restart of the process.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn gg18_enable_context_sealing() -> Result<()> {
+    let mut key = [0u8; AES_KEY_BYTES_LEN];
+    let mut rng = OsRng::new()?;
+    rng.fill_bytes(&mut key);
+    set_context_key(Some(key));
+    key.zeroize();
+    Ok(())
+}
+
+/// Seals every round context from now on under the given hex encoded 32 byte key,
+/// e.g. one kept in the platform key store so contexts can be resumed later.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn gg18_set_context_sealing_key(key: String) -> Result<()> {
+    let mut bytes = hex::decode(&key).map_err(|_| TssError::InvalidKey)?;
+    if bytes.len() != AES_KEY_BYTES_LEN {
+        bytes.zeroize();
+        return Err(TssError::InvalidKey);
+    }
+    let mut key = [0u8; AES_KEY_BYTES_LEN];
+    key.copy_from_slice(&bytes);
+    bytes.zeroize();
+    set_context_key(Some(key));
+    key.zeroize();
+    Ok(())
+}
+
+/// Drops the session key; round contexts are plaintext JSON again.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn gg18_disable_context_sealing() {
+    set_context_key(None);
+}
+
+fn set_context_key(key: Option<[u8; AES_KEY_BYTES_LEN]>) {
+    let mut guard = CONTEXT_KEY.write().unwrap_or_else(|e| e.into_inner());
+    if let Some(old) = guard.as_mut() {
+        old.zeroize();
+    }
+    *guard = key;
+}
+
+fn context_key() -> Option<[u8; AES_KEY_BYTES_LEN]> {
+    *CONTEXT_KEY.read().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Serializes a round context, sealing it if a session key is set.
+pub fn seal_context<T: RoundContext>(context: &T) -> Result<String> {
+    let mut key = context_key();
+    let result = seal_context_with(key.as_ref(), context);
+    key.zeroize();
+    result
+}
+
+/// Parses a round context produced by `seal_context`. While sealing is enabled
+/// plaintext contexts are rejected, so a host cannot slip in a modified one.
+pub fn open_context<T: RoundContext>(context: &str) -> Result<T> {
+    let mut key = context_key();
+    let result = open_context_with(key.as_ref(), context, None);
+    key.zeroize();
+    result
+}
+
+/// Like `open_context`, for a context that must be waiting for `round`.
+pub fn open_context_at<T: RoundContext>(context: &str, round: u16) -> Result<T> {
+    let mut key = context_key();
+    let result = open_context_with(key.as_ref(), context, Some(round));
+    key.zeroize();
+    result
+}
+
+/// Reads part of a context of kind `kind`, e.g. only the fields that tell
+/// which type to open it as.
+pub fn peek_context<T: DeserializeOwned>(context: &str, kind: &str) -> Result<T> {
+    let mut key = context_key();
+    let result = match key.as_ref() {
+        Some(key) => unseal(key, context, kind).and_then(|(mut plaintext, _binding)| {
+            let result = serde_json::from_slice(&plaintext);
+            plaintext.zeroize();
+            Ok(result?)
+        }),
+        None => Ok(serde_json::from_str(context)?),
+    };
+    key.zeroize();
+    result
+}
+
+fn seal_context_with<T: RoundContext>(
+    key: Option<&[u8; AES_KEY_BYTES_LEN]>,
+    context: &T,
+) -> Result<String> {
+    let key = match key {
+        Some(key) => key,
+        None => return Ok(serde_json::to_string(context)?),
+    };
+    let binding = ContextBinding {
+        kind: T::KIND.to_string(),
+        uuid: context.uuid().to_string(),
+        round: context.round(),
+    };
+    let mut plaintext = serde_json::to_vec(context)?;
+    let aead = aes_encrypt(key, &plaintext, &binding.aad());
+    plaintext.zeroize();
+
+    Ok(serde_json::to_string(&SealedContext {
+        version: SEALED_CONTEXT_VERSION,
+        binding,
+        sealed: aead?,
+    })?)
+}
+
+fn open_context_with<T: RoundContext>(
+    key: Option<&[u8; AES_KEY_BYTES_LEN]>,
+    context: &str,
+    round: Option<u16>,
+) -> Result<T> {
+    let key = match key {
+        Some(key) => key,
+        None => return Ok(serde_json::from_str(context)?),
+    };
+    let (mut plaintext, binding) = unseal(key, context, T::KIND)?;
+    let result = serde_json::from_slice::<T>(&plaintext);
+    plaintext.zeroize();
+    let context = result?;
+    if context.uuid() != binding.uuid || context.round() != binding.round {
+        return Err(TssError::InvalidContext);
+    }
+    match round {
+        Some(round) if round != binding.round => Err(TssError::RoundOutOfOrder {
+            expected: binding.round,
+            got: round,
+        }),
+        _ => Ok(context),
+    }
+}
+
+// decrypts a sealed context of kind `kind`, checking its binding
+fn unseal(
+    key: &[u8; AES_KEY_BYTES_LEN],
+    context: &str,
+    kind: &str,
+) -> Result<(Vec<u8>, ContextBinding)> {
+    let sealed: SealedContext =
+        serde_json::from_str(context).map_err(|_| TssError::InvalidContext)?;
+    if sealed.version != SEALED_CONTEXT_VERSION || sealed.binding.kind != kind {
+        return Err(TssError::InvalidContext);
+    }
+    let plaintext = aes_decrypt(key, sealed.sealed, &sealed.binding.aad())
+        .map_err(|_| TssError::InvalidContext)?;
+    Ok((plaintext, sealed.binding))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct TestContext {
+        uuid: String,
+        round: u16,
+        secret: String,
+    }
+
+    impl RoundContext for TestContext {
+        const KIND: &'static str = "test";
+
+        fn uuid(&self) -> &str {
+            &self.uuid
+        }
+
+        fn round(&self) -> u16 {
+            self.round
+        }
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct OtherContext {
+        uuid: String,
+        round: u16,
+        secret: String,
+    }
+
+    impl RoundContext for OtherContext {
+        const KIND: &'static str = "other";
+
+        fn uuid(&self) -> &str {
+            &self.uuid
+        }
+
+        fn round(&self) -> u16 {
+            self.round
+        }
+    }
+
+    fn test_context(uuid: &str, round: u16) -> TestContext {
+        TestContext {
+            uuid: uuid.to_string(),
+            round,
+            secret: "k_i".to_string(),
+        }
+    }
+
+    // the keys are passed explicitly, the process wide one is left to the
+    // tests of the setters below
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_seal_open_context() {
+        let key = [3u8; AES_KEY_BYTES_LEN];
+        let context = test_context("session-a", 2);
+
+        let plain = seal_context_with(None, &context).unwrap();
+        assert_eq!(open_context_with::<TestContext>(None, &plain, None).unwrap(), context);
+
+        let sealed = seal_context_with(Some(&key), &context).unwrap();
+        assert!(!sealed.contains("k_i"));
+        assert_eq!(
+            open_context_with::<TestContext>(Some(&key), &sealed, Some(2)).unwrap(),
+            context
+        );
+        // plaintext contexts are refused while sealing is on
+        assert!(open_context_with::<TestContext>(Some(&key), &plain, None).is_err());
+
+        // any modification of the ciphertext is detected
+        let mut tampered: SealedContext = serde_json::from_str(&sealed).unwrap();
+        tampered.sealed.ciphertext[0] ^= 1;
+        let tampered = serde_json::to_string(&tampered).unwrap();
+        match open_context_with::<TestContext>(Some(&key), &tampered, None) {
+            Err(TssError::InvalidContext) => {}
+            _ => panic!("tampered context should be rejected"),
+        }
+
+        // a context sealed under another key does not open
+        let other_key = [7u8; AES_KEY_BYTES_LEN];
+        assert!(open_context_with::<TestContext>(Some(&other_key), &sealed, None).is_err());
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_sealed_context_binding() {
+        let key = [3u8; AES_KEY_BYTES_LEN];
+        let sealed = seal_context_with(Some(&key), &test_context("session-a", 2)).unwrap();
+
+        // an older checkpoint of the session is not taken for a later round
+        match open_context_with::<TestContext>(Some(&key), &sealed, Some(3)) {
+            Err(TssError::RoundOutOfOrder {
+                expected: 2,
+                got: 3,
+            }) => {}
+            _ => panic!("a context of round 2 should not open for round 3"),
+        }
+
+        // nor as a context of another kind
+        assert!(open_context_with::<OtherContext>(Some(&key), &sealed, None).is_err());
+        assert!(peek_context_with_kind(&key, &sealed, "other").is_err());
+        assert!(peek_context_with_kind(&key, &sealed, "test").is_ok());
+
+        // the binding in the clear cannot be changed to another session or round
+        for (uuid, round) in [("session-b", 2), ("session-a", 3)] {
+            let mut relabeled: SealedContext = serde_json::from_str(&sealed).unwrap();
+            relabeled.binding.uuid = uuid.to_string();
+            relabeled.binding.round = round;
+            let relabeled = serde_json::to_string(&relabeled).unwrap();
+            match open_context_with::<TestContext>(Some(&key), &relabeled, None) {
+                Err(TssError::InvalidContext) => {}
+                _ => panic!("a relabeled context should be rejected"),
+            }
+        }
+
+        // contexts sealed before the binding was added are refused
+        let mut legacy: SealedContext = serde_json::from_str(&sealed).unwrap();
+        legacy.version = 1;
+        let legacy = serde_json::to_string(&legacy).unwrap();
+        assert!(open_context_with::<TestContext>(Some(&key), &legacy, None).is_err());
+    }
+
+    fn peek_context_with_kind(
+        key: &[u8; AES_KEY_BYTES_LEN],
+        context: &str,
+        kind: &str,
+    ) -> Result<serde_json::Value> {
+        let (plaintext, _binding) = unseal(key, context, kind)?;
+        Ok(serde_json::from_slice(&plaintext)?)
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_context_sealing_key() {
+        assert!(gg18_set_context_sealing_key("00".to_string()).is_err());
+        assert!(gg18_set_context_sealing_key("zz".to_string()).is_err());
+    }
+}
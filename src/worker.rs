@@ -0,0 +1,225 @@
+#![cfg(target_arch = "wasm32")]
+
+// Whole-session entry points for the wasm build. Unlike the per-round
+// functions they keep the context inside the module, so a Web Worker (see
+// worker.js) or a Node worker thread can run keygen or sign off the main
+// thread and only post progress and the result back.
+
+use crate::api::{self, RoundTiming, SessionProgress};
+use crate::common::TokenProvider;
+use crate::errors::{Result, TssError};
+use std::future::Future;
+use std::pin::Pin;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+// the optional `{ onRound(round, rounds), onWaiting(party),
+// onTiming(step, computeMs, networkMs), refreshToken() }` object of the caller
+struct JsProgress(JsValue);
+
+impl JsProgress {
+    // the result of the callback, None if there is none or it threw
+    fn call(&self, method: &str, args: &[JsValue]) -> Option<JsValue> {
+        if !self.0.is_object() {
+            return None;
+        }
+        let callback = js_sys::Reflect::get(&self.0, &JsValue::from_str(method)).ok()?;
+        let callback = callback.dyn_ref::<js_sys::Function>()?;
+        let args: js_sys::Array = args.iter().collect();
+        // a throwing callback must not abort the session
+        callback.apply(&self.0, &args).ok()
+    }
+}
+
+impl TokenProvider for JsProgress {
+    // `refreshToken()` may return the token or a promise of it
+    fn refresh_token(&self) -> Pin<Box<dyn Future<Output = Result<String>> + '_>> {
+        Box::pin(async move {
+            let mut token = self.call("refreshToken", &[]);
+            let promise = token.as_ref().and_then(|t| t.dyn_ref::<js_sys::Promise>()).cloned();
+            if let Some(promise) = promise {
+                token = wasm_bindgen_futures::JsFuture::from(promise).await.ok();
+            }
+            token
+                .and_then(|token| token.as_string())
+                .ok_or_else(|| TssError::InvalidArgument {
+                    name: "refreshToken".to_string(),
+                })
+        })
+    }
+}
+
+impl SessionProgress for JsProgress {
+    fn on_round(&self, round: u16, rounds: u16) {
+        self.call("onRound", &[round.into(), rounds.into()]);
+    }
+
+    fn on_waiting(&self, party: u16) {
+        self.call("onWaiting", &[party.into()]);
+    }
+
+    fn on_timing(&self, timing: &RoundTiming) {
+        self.call(
+            "onTiming",
+            &[
+                timing.step.as_str().into(),
+                (timing.compute_ms as f64).into(),
+                (timing.network_ms as f64).into(),
+            ],
+        );
+    }
+}
+
+/// Runs a whole GG18 keygen and resolves to `{ keyStore, publicKey, timings }`
+/// as JSON. Pass 2048 and false for the default Paillier key, see
+/// `gg18_keygen_client_new_context_with_paillier`. `progress` is an optional `{ onRound(round, rounds),
+/// onWaiting(party), onTiming(step, computeMs, networkMs), refreshToken() }`;
+/// `refreshToken` is asked for a new token, or a promise of one, when the
+/// manager rejects the current one.
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub async fn gg18_keygen_client_run(
+    addr: String,
+    t: usize,
+    n: usize,
+    delay: u32,
+    token: String,
+    task_id: String,
+    party_type: String,
+    identity_key: String,
+    peer_identity_keys: String,
+    curve: String,
+    paillier_modulus_bits: usize,
+    safe_primes: bool,
+    progress: JsValue,
+) -> Result<String> {
+    let progress = JsProgress(progress);
+    let result = api::gg18_keygen_client_run(
+        addr,
+        t,
+        n,
+        delay,
+        token,
+        task_id,
+        party_type,
+        identity_key,
+        peer_identity_keys,
+        curve,
+        paillier_modulus_bits,
+        safe_primes,
+        &progress,
+        Some(&progress),
+    )
+    .await?;
+    Ok(serde_json::to_string(&result)?)
+}
+
+/// Runs a whole GG18 sign and resolves to `{ r, s, v, timings }` as JSON.
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub async fn gg18_sign_client_run(
+    addr: String,
+    t: usize,
+    n: usize,
+    delay: u32,
+    key_store: String,
+    message_str: String,
+    token: String,
+    task_id: String,
+    party_type: String,
+    identity_key: String,
+    peer_identity_keys: String,
+    curve: String,
+    progress: JsValue,
+) -> Result<String> {
+    let progress = JsProgress(progress);
+    let result = api::gg18_sign_client_run(
+        addr,
+        t,
+        n,
+        delay,
+        key_store,
+        message_str,
+        token,
+        task_id,
+        party_type,
+        identity_key,
+        peer_identity_keys,
+        curve,
+        &progress,
+        Some(&progress),
+    )
+    .await?;
+    Ok(serde_json::to_string(&result)?)
+}
+
+/// Runs a whole EdDSA keygen and resolves to `{ keyStore, publicKey, timings }`
+/// as JSON.
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub async fn eddsa_keygen_client_run(
+    addr: String,
+    t: usize,
+    n: usize,
+    delay: u32,
+    token: String,
+    task_id: String,
+    party_type: String,
+    identity_key: String,
+    peer_identity_keys: String,
+    progress: JsValue,
+) -> Result<String> {
+    let progress = JsProgress(progress);
+    let result = api::eddsa_keygen_client_run(
+        addr,
+        t,
+        n,
+        delay,
+        token,
+        task_id,
+        party_type,
+        identity_key,
+        peer_identity_keys,
+        &progress,
+        Some(&progress),
+    )
+    .await?;
+    Ok(serde_json::to_string(&result)?)
+}
+
+/// Runs a whole EdDSA sign and resolves to `{ signature, timings }` as JSON.
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub async fn eddsa_sign_client_run(
+    addr: String,
+    t: usize,
+    n: usize,
+    delay: u32,
+    key_store: String,
+    message_str: String,
+    token: String,
+    task_id: String,
+    party_type: String,
+    identity_key: String,
+    peer_identity_keys: String,
+    progress: JsValue,
+) -> Result<String> {
+    let progress = JsProgress(progress);
+    let result = api::eddsa_sign_client_run(
+        addr,
+        t,
+        n,
+        delay,
+        key_store,
+        message_str,
+        token,
+        task_id,
+        party_type,
+        identity_key,
+        peer_identity_keys,
+        &progress,
+        Some(&progress),
+    )
+    .await?;
+    Ok(serde_json::to_string(&result)?)
+}
@@ -0,0 +1,126 @@
+/*
+ * Exercises the C ABI the way a native caller links it. Run by tests/ffi.rs,
+ * which builds the fixtures:
+ *
+ *   ffi_test <eddsa key store> <eddsa message> <eddsa signature json>
+ *            <secp256k1 public key> <secp256k1 message> <secp256k1 signature json>
+ */
+#include <stdio.h>
+#include <string.h>
+
+#include "tss_wasm.h"
+
+static int failures = 0;
+
+#define CHECK(cond)                                                   \
+    do {                                                              \
+        if (!(cond)) {                                                \
+            fprintf(stderr, "%s:%d: %s\n", __FILE__, __LINE__, #cond); \
+            failures++;                                               \
+        }                                                             \
+    } while (0)
+
+static void test_key_store(const char *json) {
+    TssKeyStore *key_store = NULL;
+    CHECK(tss_key_store_from_json("ed25519", json, &key_store) == TSS_OK);
+    CHECK(key_store != NULL);
+    CHECK(tss_last_error_message() == NULL);
+
+    char *public_key = NULL;
+    CHECK(tss_key_store_public_key(key_store, &public_key) == TSS_OK);
+    CHECK(public_key != NULL && strlen(public_key) == 64);
+
+    char *blob = NULL;
+    CHECK(tss_key_store_export(key_store, "passphrase", &blob) == TSS_OK);
+    if (blob == NULL) {
+        return;
+    }
+
+    TssKeyStore *imported = NULL;
+    CHECK(tss_key_store_import("ed25519", blob, "wrong", &imported) == TSS_INVALID_PASSPHRASE);
+    CHECK(imported == NULL);
+    CHECK(tss_key_store_import("ed25519", blob, "passphrase", &imported) == TSS_OK);
+    if (imported == NULL) {
+        return;
+    }
+
+    char *imported_json = NULL;
+    char *imported_public_key = NULL;
+    CHECK(tss_key_store_to_json(imported, &imported_json) == TSS_OK);
+    CHECK(strcmp(imported_json, json) == 0);
+    CHECK(tss_key_store_public_key(imported, &imported_public_key) == TSS_OK);
+    CHECK(strcmp(imported_public_key, public_key) == 0);
+
+    tss_string_free(imported_public_key);
+    tss_string_free(imported_json);
+    tss_key_store_free(imported);
+    tss_string_free(blob);
+    tss_string_free(public_key);
+    tss_key_store_free(key_store);
+}
+
+static void test_verify(const char *scheme, const char *public_key, const char *message,
+                        const char *signature) {
+    CHECK(tss_verify(scheme, public_key, message, signature) == TSS_OK);
+    CHECK(tss_verify(scheme, public_key, "01", signature) == TSS_INVALID_SIG);
+    CHECK(tss_verify(scheme, public_key, "00", signature) == TSS_INVALID_SIG);
+}
+
+static void test_errors(const char *json) {
+    TssKeyStore *key_store = NULL;
+    CHECK(tss_key_store_from_json("ed25519", "{", &key_store) == TSS_SERDE_ERROR);
+    CHECK(key_store == NULL);
+    char *msg = tss_last_error_message();
+    CHECK(msg != NULL && strlen(msg) > 0);
+    tss_string_free(msg);
+
+    CHECK(tss_key_store_from_json("ed25519", NULL, &key_store) == TSS_INVALID_ARGUMENT);
+    msg = tss_last_error_message();
+    CHECK(msg != NULL && strstr(msg, "json") != NULL);
+    tss_string_free(msg);
+
+    CHECK(tss_key_store_from_json("ed25519", json, NULL) == TSS_INVALID_ARGUMENT);
+    CHECK(tss_key_store_to_json(NULL, &msg) == TSS_INVALID_ARGUMENT);
+    CHECK(tss_sign(NULL, NULL, "00", NULL, &msg) == TSS_INVALID_ARGUMENT);
+
+    /* a success clears the last error */
+    CHECK(tss_key_store_from_json("ed25519", json, &key_store) == TSS_OK);
+    CHECK(tss_last_error_message() == NULL);
+    tss_key_store_free(key_store);
+
+    tss_key_store_free(NULL);
+    tss_string_free(NULL);
+}
+
+int main(int argc, char **argv) {
+    if (argc != 7) {
+        fprintf(stderr, "usage: %s <eddsa key store> <eddsa message> <eddsa signature> "
+                        "<secp256k1 public key> <secp256k1 message> <secp256k1 signature>\n",
+                argv[0]);
+        return 2;
+    }
+
+    CHECK(tss_init_runtime(1) == TSS_OK);
+    CHECK(tss_init_logging("warn") == TSS_OK);
+    CHECK(tss_init_logging("loud") == TSS_INVALID_ARGUMENT);
+    CHECK(tss_set_retry_policy(0, 100, 1000) == TSS_INVALID_ARGUMENT);
+    CHECK(tss_set_retry_policy(3, 100, 1000) == TSS_OK);
+    test_key_store(argv[1]);
+
+    TssKeyStore *key_store = NULL;
+    char *public_key = NULL;
+    CHECK(tss_key_store_from_json("ed25519", argv[1], &key_store) == TSS_OK);
+    CHECK(tss_key_store_public_key(key_store, &public_key) == TSS_OK);
+    test_verify("ed25519", public_key, argv[2], argv[3]);
+    tss_string_free(public_key);
+    tss_key_store_free(key_store);
+
+    test_verify("secp256k1", argv[4], argv[5], argv[6]);
+    test_errors(argv[1]);
+
+    if (failures > 0) {
+        fprintf(stderr, "%d checks failed\n", failures);
+        return 1;
+    }
+    return 0;
+}
@@ -1,4 +1,6 @@
 #![allow(non_snake_case)]
+// each test crate uses only some of the drivers
+#![allow(dead_code)]
 
 #[cfg(not(target_arch = "wasm32"))]
 pub const BENCH_SAMPLE_SIZE: usize = 10;
@@ -20,6 +22,7 @@ pub const BENCH_SAMPLE_SIZE: usize = 10;
 */
 
 use tss_wasm::curv::arithmetic::num_bigint::from;
+use tss_wasm::curv::cryptographic_primitives::hashing::ext::ProofContext;
 use tss_wasm::curv::cryptographic_primitives::hashing::hash_sha256::HSha256;
 use tss_wasm::curv::cryptographic_primitives::hashing::traits::Hash;
 use tss_wasm::curv::cryptographic_primitives::proofs::sigma_dlog::DLogProof;
@@ -27,17 +30,24 @@ use tss_wasm::curv::cryptographic_primitives::secret_sharing::feldman_vss::Verif
 use tss_wasm::curv::elliptic::curves::secp256_k1::{FE, GE};
 use tss_wasm::curv::elliptic::curves::traits::*;
 
+use tss_wasm::eddsa::session::{KeygenSession, SignSession};
+use tss_wasm::gg_2018::messages::Outgoing;
 use tss_wasm::gg_2018::mta::*;
 use tss_wasm::gg_2018::party_i::*;
+use tss_wasm::keystore::EddsaKeyStore;
+
+const SESSION_ID: &str = "uuid";
+
+// context of the proofs party i (from 0) sends at step `label`
+fn ctx(i: usize, label: &str) -> ProofContext {
+    ProofContext::new(SESSION_ID, i as u16 + 1, label)
+}
 
 pub fn keygen_t_n_parties(
     t: usize,
     n: usize,
 ) -> (Vec<Keys>, Vec<SharedKeys>, Vec<GE>, GE, VerifiableSS) {
-    let parames = Parameters {
-        threshold: t,
-        share_count: n.clone(),
-    };
+    let parames = Parameters::new(t, n.clone());
     let party_keys_vec = (0..n.clone())
         .map(|i| Keys::create(i))
         .collect::<Vec<Keys>>();
@@ -45,7 +55,8 @@ pub fn keygen_t_n_parties(
     let mut bc1_vec = Vec::new();
     let mut decom_vec = Vec::new();
     for i in 0..n.clone() {
-        let (bc1, decom1) = party_keys_vec[i].phase1_broadcast_phase3_proof_of_correct_key();
+        let (bc1, decom1) =
+            party_keys_vec[i].phase1_broadcast_phase3_proof_of_correct_key(&ctx(i, "keygen/1"));
         bc1_vec.push(bc1);
         decom_vec.push(decom1);
     }
@@ -63,7 +74,10 @@ pub fn keygen_t_n_parties(
     for i in 0..n.clone() {
         let (vss_scheme, secret_shares, index) = party_keys_vec[i]
             .phase1_verify_com_phase3_verify_correct_key_phase2_distribute(
-                &parames, &decom_vec, &bc1_vec,
+                &parames,
+                &decom_vec,
+                &bc1_vec,
+                &ctx(i, "keygen/1"),
             )
             .expect("invalid key");
         vss_scheme_vec.push(vss_scheme);
@@ -93,6 +107,7 @@ pub fn keygen_t_n_parties(
                 &party_shares[i],
                 &vss_scheme_vec,
                 &(&index_vec[i] + 1),
+                &ctx(i, "keygen/5"),
             )
             .expect("invalid vss");
         shared_keys_vec.push(shared_keys);
@@ -104,7 +119,8 @@ pub fn keygen_t_n_parties(
         .collect::<Vec<GE>>();
 
     //both parties run:
-    Keys::verify_dlog_proofs(&parames, &dlog_proof_vec, &y_vec).expect("bad dlog proof");
+    Keys::verify_dlog_proofs(&parames, &dlog_proof_vec, &y_vec, &ctx(0, "keygen/5"))
+        .expect("bad dlog proof");
 
     //test
     let xi_vec = (0..t.clone() + 1)
@@ -162,7 +178,12 @@ pub fn sign(t: usize, n: usize, ttag: usize, s: Vec<usize>) {
     // m_a_vec = [ma_0;ma_1;,...]
     let mut m_a_vec = Vec::new();
     for i in 0..ttag.clone() {
-        let (m_a_k, _) = MessageA::a(&sign_keys_vec[i].k_i, &party_keys_vec[s[i]].ek, &[]);
+        let (m_a_k, _) = MessageA::a(
+            &sign_keys_vec[i].k_i,
+            &party_keys_vec[s[i]].ek,
+            &[],
+            &ctx(i, "sign/1"),
+        );
 
         m_a_vec.push(m_a_k);
     }
@@ -185,18 +206,22 @@ pub fn sign(t: usize, n: usize, ttag: usize, s: Vec<usize>) {
         for j in 0..ttag - 1 {
             let ind = if j < i { j } else { j + 1 };
 
-            let (m_b_gamma, beta_gamma, _, _) = MessageB::b(
+            let (m_b_gamma, beta_gamma, _, _) = MessageB::<GE>::b(
                 &sign_keys_vec[i].gamma_i,
                 &party_keys_vec[s[ind]].ek,
                 m_a_vec[ind].clone(),
                 &[],
+                &ctx(ind, "sign/1"),
+                &ctx(i, "sign/2"),
             )
             .unwrap();
-            let (m_b_w, beta_wi, _, _) = MessageB::b(
+            let (m_b_w, beta_wi, _, _) = MessageB::<GE>::b(
                 &sign_keys_vec[i].w_i,
                 &party_keys_vec[s[ind]].ek,
                 m_a_vec[ind].clone(),
                 &[],
+                &ctx(ind, "sign/1"),
+                &ctx(i, "sign/2"),
             )
             .unwrap();
 
@@ -231,11 +256,19 @@ pub fn sign(t: usize, n: usize, ttag: usize, s: Vec<usize>) {
             let m_b = m_b_gamma_vec_i[j].clone();
 
             let alpha_ij_gamma = m_b
-                .verify_proofs_get_alpha(&party_keys_vec[s[ind]].dk, &sign_keys_vec[ind].k_i)
+                .verify_proofs_get_alpha(
+                    &party_keys_vec[s[ind]].dk,
+                    &sign_keys_vec[ind].k_i,
+                    &ctx(i, "sign/2"),
+                )
                 .expect("wrong dlog or m_b");
             let m_b = m_b_w_vec_i[j].clone();
             let alpha_ij_wi = m_b
-                .verify_proofs_get_alpha(&party_keys_vec[s[ind]].dk, &sign_keys_vec[ind].k_i)
+                .verify_proofs_get_alpha(
+                    &party_keys_vec[s[ind]].dk,
+                    &sign_keys_vec[ind].k_i,
+                    &ctx(i, "sign/2"),
+                )
                 .expect("wrong dlog or m_b");
 
             // since we actually run two MtAwc each party needs to make sure that the values B are the same as the public values
@@ -262,7 +295,7 @@ pub fn sign(t: usize, n: usize, ttag: usize, s: Vec<usize>) {
     }
 
     // all parties broadcast delta_i and compute delta_i ^(-1)
-    let delta_inv = SignKeys::phase3_reconstruct_delta(&delta_vec);
+    let delta_inv = SignKeys::<GE>::phase3_reconstruct_delta(&delta_vec);
 
     // de-commit to g^gamma_i from phase1, test comm correctness, and that it is the same value used in MtA.
     // Return R
@@ -310,7 +343,7 @@ pub fn sign(t: usize, n: usize, ttag: usize, s: Vec<usize>) {
     let mut dlog_proof_rho_vec = Vec::new();
     for i in 0..ttag.clone() {
         let (phase5_com, phase_5a_decom, helgamal_proof, dlog_proof_rho) =
-            local_sig_vec[i].phase5a_broadcast_5b_zkproof();
+            local_sig_vec[i].phase5a_broadcast_5b_zkproof(&ctx(i, "sign/6"));
         phase5_com_vec.push(phase5_com);
         phase_5a_decom_vec.push(phase_5a_decom);
         helgamal_proof_vec.push(helgamal_proof);
@@ -338,6 +371,10 @@ pub fn sign(t: usize, n: usize, ttag: usize, s: Vec<usize>) {
                 &phase_5a_dlog_vec_clone,
                 &phase_5a_decom_vec[i].V_i,
                 &R_vec[0],
+                &(0..ttag)
+                    .filter(|j| *j != i)
+                    .map(|j| ctx(j, "sign/6"))
+                    .collect::<Vec<ProofContext>>(),
             )
             .expect("error phase5");
         phase5_com2_vec.push(phase5_com2);
@@ -360,3 +397,91 @@ pub fn sign(t: usize, n: usize, ttag: usize, s: Vec<usize>) {
         .output_signature(&s_vec)
         .expect("verification failed");
 }
+
+// Drivers of the message based sessions, also mounted by lib.rs as
+// `test_util` for the session unit tests. They run the sessions in memory and
+// hand the messages around themselves, no manager involved. Items are named
+// through `tss_wasm::` so the file builds in both crates.
+
+/// Delivers every party's outgoing messages to `party_num_int`, newest sender
+/// first so the sessions have to sort them.
+pub fn route<M: Clone>(outgoing: &[Outgoing<M>], party_num_int: u16) -> Vec<(u16, M)> {
+    let mut incoming = Vec::new();
+    for (k, out) in outgoing.iter().enumerate().rev() {
+        let from = k as u16 + 1;
+        if from == party_num_int {
+            continue;
+        }
+        match &out.broadcast {
+            Some(msg) => incoming.push((from, msg.clone())),
+            None => incoming.extend(
+                out.p2p
+                    .iter()
+                    .filter(|(to, _)| *to == party_num_int)
+                    .map(|(_, msg)| (from, msg.clone())),
+            ),
+        }
+    }
+    incoming
+}
+
+/// Runs a `t` out of `n` EdDSA keygen to the end and returns the finished
+/// sessions, in party order.
+pub fn eddsa_keygen(t: usize, n: usize) -> Vec<KeygenSession> {
+    let mut sessions = Vec::new();
+    let mut outgoing = Vec::new();
+    for i in 1..=n {
+        let params = Parameters::new(t, n);
+        let (out, session) = KeygenSession::new(params, i as u16, "uuid".to_string())
+            .advance(Vec::new())
+            .unwrap();
+        outgoing.push(out);
+        sessions.push(session);
+    }
+    while sessions[0].key_store().is_none() {
+        let mut next_outgoing = Vec::new();
+        let mut next_sessions = Vec::new();
+        for (k, session) in sessions.into_iter().enumerate() {
+            let incoming = route(&outgoing, k as u16 + 1);
+            let (out, session) = session.advance(incoming).unwrap();
+            next_outgoing.push(out);
+            next_sessions.push(session);
+        }
+        outgoing = next_outgoing;
+        sessions = next_sessions;
+    }
+    sessions
+}
+
+/// Signs `message` with the parties of `key_stores`, which must be `t + 1`, and
+/// returns the finished sessions.
+pub fn eddsa_sign(key_stores: Vec<EddsaKeyStore>, t: u16, message: &[u8]) -> Vec<SignSession> {
+    let mut sessions = Vec::new();
+    let mut outgoing = Vec::new();
+    for (k, key_store) in key_stores.into_iter().enumerate() {
+        let (out, session) = SignSession::new(
+            key_store,
+            t,
+            k as u16 + 1,
+            "uuid".to_string(),
+            message.to_vec(),
+        )
+        .advance(Vec::new())
+        .unwrap();
+        outgoing.push(out);
+        sessions.push(session);
+    }
+    while sessions[0].signature().is_none() {
+        let mut next_outgoing = Vec::new();
+        let mut next_sessions = Vec::new();
+        for (k, session) in sessions.into_iter().enumerate() {
+            let incoming = route(&outgoing, k as u16 + 1);
+            let (out, session) = session.advance(incoming).unwrap();
+            next_outgoing.push(out);
+            next_sessions.push(session);
+        }
+        outgoing = next_outgoing;
+        sessions = next_sessions;
+    }
+    sessions
+}
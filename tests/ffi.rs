@@ -0,0 +1,80 @@
+#![cfg(target_os = "linux")]
+
+// Builds tests/c/ffi_test.c against the header generated into OUT_DIR and
+// the cdylib and runs it on fixtures made here, so it needs the c-header
+// feature. Keygen and sign need a manager and are covered by the Rust tests
+// of the run functions.
+
+mod common;
+
+use common::{eddsa_keygen, eddsa_sign};
+use std::path::PathBuf;
+use std::process::Command;
+use tss_wasm::keystore::EddsaKeyStore;
+
+// a 1 out of 2 key and its signature of `message`
+fn eddsa_fixture(message: &[u8]) -> (EddsaKeyStore, [u8; 64]) {
+    let key_stores: Vec<EddsaKeyStore> = eddsa_keygen(1, 2)
+        .iter()
+        .map(|session| session.key_store().unwrap().clone())
+        .collect();
+    let sessions = eddsa_sign(key_stores.clone(), 1, message);
+    let signature = sessions[0].signature().unwrap().to_bytes();
+    (key_stores[0].clone(), signature)
+}
+
+#[test]
+fn test_c_abi() {
+    let message = b"tss-wasm ffi";
+    let (key_store, signature) = eddsa_fixture(message);
+    let eddsa_signature = serde_json::json!({ "signature": hex::encode(signature) });
+
+    // a plain secp256k1 signature checks the GG18 verify path
+    let secret_key = secp256k1::SecretKey::parse(&[7u8; 32]).unwrap();
+    let public_key = secp256k1::PublicKey::from_secret_key(&secret_key);
+    let hash = [42u8; 32];
+    let (sig, _recid) = secp256k1::sign(&secp256k1::Message::parse(&hash), &secret_key);
+    let sig = sig.serialize();
+    let ecdsa_signature = serde_json::json!({
+        "r": hex::encode(&sig[..32]),
+        "s": hex::encode(&sig[32..]),
+        "v": "0",
+    });
+
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    // cargo builds the cdylib into target/<profile>/deps, next to this test
+    let lib_dir = std::env::current_exe()
+        .unwrap()
+        .parent()
+        .unwrap()
+        .to_path_buf();
+    let exe = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("ffi_test");
+    let status = Command::new("cc")
+        .arg(manifest_dir.join("tests/c/ffi_test.c"))
+        .arg("-I")
+        .arg(env!("OUT_DIR"))
+        .arg("-L")
+        .arg(&lib_dir)
+        .arg(format!("-Wl,-rpath,{}", lib_dir.display()))
+        .arg("-ltss_wasm")
+        .arg("-o")
+        .arg(&exe)
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let output = Command::new(&exe)
+        .arg(serde_json::to_string(&key_store).unwrap())
+        .arg(hex::encode(message))
+        .arg(eddsa_signature.to_string())
+        .arg(hex::encode(public_key.serialize_compressed()))
+        .arg(hex::encode(hash))
+        .arg(ecdsa_signature.to_string())
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
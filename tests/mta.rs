@@ -21,7 +21,8 @@ extern crate wasm_bindgen;
 #[cfg(all(test, target_arch = "wasm32"))]
 extern crate wasm_bindgen_test;
 
-use tss_wasm::curv::elliptic::curves::secp256_k1::FE;
+use tss_wasm::curv::cryptographic_primitives::hashing::ext::ProofContext;
+use tss_wasm::curv::elliptic::curves::secp256_k1::{FE, GE};
 use tss_wasm::curv::elliptic::curves::traits::*;
 
 use tss_wasm::gg_2018::mta::*;
@@ -43,10 +44,16 @@ fn test_mta() {
             let (ek_alice, dk_alice) = keypair.keys();
     */
     let bob_input: FE = ECScalar::new_random();
-    let (m_a, _) = MessageA::a(&alice_input, &ek_alice, &[]);
-    let (m_b, beta, _, _) = MessageB::b(&bob_input, &ek_alice, m_a, &[]).unwrap();
+    let alice_ctx = ProofContext::new("uuid", 1, "mta/a");
+    let bob_ctx = ProofContext::new("uuid", 2, "mta/b");
+    let (m_a, _) = MessageA::a(&alice_input, &ek_alice, &[], &alice_ctx);
+    let (m_b, beta, _, _) =
+        MessageB::<GE>::b(&bob_input, &ek_alice, m_a, &[], &alice_ctx, &bob_ctx).unwrap();
+    assert!(m_b
+        .verify_proofs_get_alpha(&dk_alice, &alice_input, &alice_ctx)
+        .is_err());
     let alpha = m_b
-        .verify_proofs_get_alpha(&dk_alice, &alice_input)
+        .verify_proofs_get_alpha(&dk_alice, &alice_input, &bob_ctx)
         .expect("wrong dlog or m_b");
 
     let left = alpha.0 + beta;
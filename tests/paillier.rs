@@ -0,0 +1,85 @@
+#[macro_use]
+#[cfg(not(target_arch = "wasm32"))]
+extern crate criterion;
+
+#[cfg(target_arch = "wasm32")]
+extern crate wasm_bindgen;
+
+#[cfg(all(test, target_arch = "wasm32"))]
+extern crate wasm_bindgen_test;
+
+mod common;
+
+use tss_wasm::curv::arithmetic::num_bigint::BigInt;
+use tss_wasm::curv::arithmetic::traits::*;
+use tss_wasm::paillier::*;
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen_test::*;
+
+// Decryption the plain way, c^phi(N) mod N^2, as a baseline for the CRT one.
+pub fn decrypt_textbook(ek: &EncryptionKey, dk: &DecryptionKey, c: &BigInt) -> BigInt {
+    let phi = (&dk.p - BigInt::from(1u32)) * (&dk.q - BigInt::from(1u32));
+    let u = BigInt::mod_pow(c, &phi, &ek.nn);
+    let l = (u - BigInt::from(1u32)) / &ek.n;
+    BigInt::mod_mul(&l, &BigInt::mod_inv(&phi, &ek.n), &ek.n)
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+#[test]
+fn test_crt_decryption() {
+    let (ek, dk) = Paillier::keypair().keys();
+    let m = BigInt::sample_below(&ek.n);
+
+    let c: BigInt = Paillier::encrypt(&ek, RawPlaintext::from(&m)).into();
+    assert_eq!(decrypt_textbook(&ek, &dk, &c), m);
+    let d: BigInt = Paillier::encrypt(&dk, RawPlaintext::from(&m)).into();
+    assert_eq!(decrypt_textbook(&ek, &dk, &d), m);
+
+    let recovered: BigInt = Paillier::decrypt(&dk, RawCiphertext::from(&c)).into();
+    assert_eq!(recovered, m);
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub mod bench {
+    use super::*;
+    use criterion::Criterion;
+
+    pub fn bench_decrypt(c: &mut Criterion) {
+        let (ek, dk) = Paillier::keypair().keys();
+        let m = BigInt::sample_below(&ek.n);
+        let ciphertext: BigInt = Paillier::encrypt(&ek, RawPlaintext::from(&m)).into();
+
+        let (textbook_ek, textbook_dk, textbook_c) = (ek.clone(), dk.clone(), ciphertext.clone());
+        c.bench_function("paillier decrypt textbook", move |b| {
+            b.iter(|| decrypt_textbook(&textbook_ek, &textbook_dk, &textbook_c))
+        });
+        c.bench_function("paillier decrypt crt", move |b| {
+            b.iter(|| Paillier::decrypt(&dk, RawCiphertext::from(&ciphertext)))
+        });
+    }
+
+    pub fn bench_encrypt(c: &mut Criterion) {
+        let (ek, dk) = Paillier::keypair().keys();
+        let m = BigInt::sample_below(&ek.n);
+
+        let ek_m = m.clone();
+        c.bench_function("paillier encrypt with encryption key", move |b| {
+            b.iter(|| Paillier::encrypt(&ek, RawPlaintext::from(&ek_m)))
+        });
+        c.bench_function("paillier encrypt with decryption key crt", move |b| {
+            b.iter(|| Paillier::encrypt(&dk, RawPlaintext::from(&m)))
+        });
+    }
+
+    criterion_group! {
+    name = paillier;
+    config = Criterion::default().sample_size(super::common::BENCH_SAMPLE_SIZE);
+    targets =
+    self::bench_decrypt,
+    self::bench_encrypt
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+criterion_main!(bench::paillier);
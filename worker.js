@@ -0,0 +1,59 @@
+// Runs a whole keygen or sign session in a Web Worker, so the Paillier work
+// does not freeze the page. Needs the web build (`npm run build`):
+//
+//   const worker = new Worker(new URL("./worker.js", import.meta.url), { type: "module" });
+//   worker.postMessage({ id: 1, method: "gg18_keygen_client_run", args: [addr, t, n, delay, ...] });
+//   worker.onmessage = ({ data }) => { ... };
+//
+// `args` are those of the method without the trailing progress object. The
+// worker answers with { id, round, rounds } when a round starts,
+// { id, waiting } while it waits for a party, { id, step, computeMs,
+// networkMs } when a step is done, and finally { id, result } or { id, error }.
+// When the manager rejects the token it posts { id, refreshToken: true } and
+// waits for the page to answer { id, token } (a null token gives up).
+import init, * as tss from "./pkg/tss_wasm.js";
+
+const METHODS = [
+  "gg18_keygen_client_run",
+  "gg18_sign_client_run",
+  "eddsa_keygen_client_run",
+  "eddsa_sign_client_run",
+];
+
+const ready = init();
+
+// resolves the token asked for by the session with the same id
+const pendingTokens = new Map();
+
+self.onmessage = async (event) => {
+  const { id, method, args } = event.data;
+  if ("token" in event.data) {
+    const resolve = pendingTokens.get(id);
+    pendingTokens.delete(id);
+    if (resolve) {
+      resolve(event.data.token);
+    }
+    return;
+  }
+  if (!METHODS.includes(method)) {
+    self.postMessage({ id, error: `unknown method ${method}` });
+    return;
+  }
+  const progress = {
+    onRound: (round, rounds) => self.postMessage({ id, round, rounds }),
+    onWaiting: (party) => self.postMessage({ id, waiting: party }),
+    onTiming: (step, computeMs, networkMs) => self.postMessage({ id, step, computeMs, networkMs }),
+    refreshToken: () =>
+      new Promise((resolve) => {
+        pendingTokens.set(id, resolve);
+        self.postMessage({ id, refreshToken: true });
+      }),
+  };
+  try {
+    await ready;
+    const result = await tss[method](...args, progress);
+    self.postMessage({ id, result: JSON.parse(result) });
+  } catch (error) {
+    self.postMessage({ id, error: String(error) });
+  }
+};